@@ -99,10 +99,8 @@ impl<B: Batchable> BatchDriver<B> {
                 if signed_duration.is_negative() {
                     return TimeTo::Past(*timer);
                 }
-                TimeTo::Future(std::time::Duration::new(
-                    signed_duration.abs().whole_seconds() as u64,
-                    0,
-                ))
+                // The duration is positive, hence convertible into a std duration
+                TimeTo::Future(signed_duration.try_into().unwrap_or_default())
             }
         }
     }
@@ -68,6 +68,13 @@ impl JsonWriter {
         }
     }
 
+    pub fn write_bool(&mut self, value: bool) -> Result<(), JsonWriterError> {
+        self.maybe_separate();
+        let () = serde_json::to_writer(&mut self.buffer, &value)?;
+        self.needs_separator = true;
+        Ok(())
+    }
+
     pub fn write_open_obj(&mut self) {
         self.maybe_separate();
         self.buffer.push(b'{');
@@ -78,6 +85,16 @@ impl JsonWriter {
         self.needs_separator = true;
     }
 
+    pub fn write_open_array(&mut self) {
+        self.maybe_separate();
+        self.buffer.push(b'[');
+    }
+
+    pub fn write_close_array(&mut self) {
+        self.buffer.push(b']');
+        self.needs_separator = true;
+    }
+
     pub fn into_string(self) -> Result<String, JsonWriterError> {
         Ok(String::from_utf8(self.buffer)?)
     }
@@ -150,6 +167,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn write_bool_message() -> anyhow::Result<()> {
+        let mut jw = JsonWriter::with_capacity(128);
+        jw.write_open_obj();
+        jw.write_key("door_open")?;
+        jw.write_bool(true)?;
+        jw.write_key("alarm")?;
+        jw.write_bool(false)?;
+        jw.write_close_obj();
+        assert_eq!(jw.into_string()?, r#"{"door_open":true,"alarm":false}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn write_array_of_messages() -> anyhow::Result<()> {
+        let mut jw = JsonWriter::with_capacity(128);
+        jw.write_open_array();
+        jw.write_open_obj();
+        jw.write_key("temperature")?;
+        jw.write_f64(20.0)?;
+        jw.write_close_obj();
+        jw.write_open_obj();
+        jw.write_key("temperature")?;
+        jw.write_f64(21.0)?;
+        jw.write_close_obj();
+        jw.write_close_array();
+        assert_eq!(
+            jw.into_string()?,
+            r#"[{"temperature":20.0},{"temperature":21.0}]"#
+        );
+        Ok(())
+    }
+
     #[test]
     fn write_multivalue_message() -> anyhow::Result<()> {
         let mut jw = JsonWriter::with_capacity(128);
@@ -1,7 +1,9 @@
 use log::error;
 use std::{
     ffi::OsStr,
+    path::Path,
     process::{Output, Stdio},
+    time::Duration,
 };
 use tokio::{
     fs::File,
@@ -27,6 +29,31 @@ impl LoggingChild {
 
         outcome
     }
+
+    /// Wait for the child to complete, giving up after the given `timeout`.
+    ///
+    /// On timeout, an `std::io::Error` of kind `TimedOut` is returned.
+    /// The child is then only killed if spawned with [`LoggedCommand::kill_on_drop`].
+    pub async fn wait_with_output_and_timeout(
+        self,
+        logger: &mut BufWriter<File>,
+        timeout: Duration,
+    ) -> Result<Output, std::io::Error> {
+        // With `kill_on_drop`, the child is killed when the timed out future is dropped.
+        let outcome = match tokio::time::timeout(timeout, self.inner_child.wait_with_output()).await
+        {
+            Ok(outcome) => outcome,
+            Err(_elapsed) => Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("timed out after {}s", timeout.as_secs()),
+            )),
+        };
+        if let Err(err) = LoggedCommand::log_outcome(&self.command_line, &outcome, logger).await {
+            error!("Fail to log the command execution: {}", err);
+        }
+
+        outcome
+    }
 }
 
 /// A command which execution is logged.
@@ -75,6 +102,36 @@ impl LoggedCommand {
         self
     }
 
+    /// Set the working directory of the command, which is `/tmp` by default.
+    pub fn current_dir(&mut self, dir: impl AsRef<Path>) -> &mut LoggedCommand {
+        self.command.current_dir(dir);
+        self
+    }
+
+    /// Set an environment variable, on top of the environment inherited from the current process.
+    pub fn env(&mut self, key: impl AsRef<OsStr>, val: impl AsRef<OsStr>) -> &mut LoggedCommand {
+        self.command.env(key, val);
+        self
+    }
+
+    /// Run the command as the user with the given id.
+    pub fn uid(&mut self, uid: u32) -> &mut LoggedCommand {
+        self.command.uid(uid);
+        self
+    }
+
+    /// Run the command as the group with the given id.
+    pub fn gid(&mut self, gid: u32) -> &mut LoggedCommand {
+        self.command.gid(gid);
+        self
+    }
+
+    /// Kill the spawned child when dropped, notably when [`LoggingChild::wait_with_output_and_timeout`] times out.
+    pub fn kill_on_drop(&mut self) -> &mut LoggedCommand {
+        self.command.kill_on_drop(true);
+        self
+    }
+
     /// Execute the command and log its exit status, stdout and stderr
     ///
     /// If the command has been executed the outcome is returned (successful or not).
@@ -234,4 +291,60 @@ error: No such file or directory (os error 2)
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn on_timeout_the_child_is_killed_and_the_timeout_is_logged() -> Result<(), anyhow::Error>
+    {
+        // Prepare a log file
+        let tmp_dir = TempDir::new()?;
+        let log_file_path = tmp_dir.path().join("operation.log");
+        let log_file = File::create(log_file_path.clone()).await?;
+        let mut logger = BufWriter::new(log_file);
+
+        // Prepare a command that runs longer than the timeout
+        let mut command = LoggedCommand::new("sleep");
+        command.arg("10").kill_on_drop();
+
+        // Execute the command with a timeout
+        let child = command.spawn()?;
+        let outcome = child
+            .wait_with_output_and_timeout(&mut logger, Duration::from_secs(1))
+            .await;
+        assert_eq!(outcome.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+
+        // The timeout must be logged
+        let log_content = String::from_utf8(std::fs::read(&log_file_path)?)?;
+        assert_eq!(
+            log_content,
+            r#"----- $ sleep "10"
+error: timed out after 1s
+"#
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn the_command_is_run_with_the_given_working_dir_and_env() -> Result<(), anyhow::Error> {
+        // Prepare a log file
+        let tmp_dir = TempDir::new()?;
+        let log_file_path = tmp_dir.path().join("operation.log");
+        let log_file = File::create(log_file_path.clone()).await?;
+        let mut logger = BufWriter::new(log_file);
+
+        // Prepare a command that uses its working directory and environment
+        let mut command = LoggedCommand::new("sh");
+        command
+            .arg("-c")
+            .arg("echo $PWD $GREETING")
+            .current_dir(tmp_dir.path())
+            .env("GREETING", "hello");
+
+        let output = command.execute(&mut logger).await?;
+
+        assert_eq!(
+            String::from_utf8(output.stdout)?,
+            format!("{} hello\n", tmp_dir.path().display())
+        );
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use crate::TopicFilter;
+use crate::{Message, TopicFilter};
 
 /// Configuration of an MQTT connection
 #[derive(Debug, Clone)]
@@ -41,6 +41,11 @@ pub struct Config {
     ///
     /// Default: `1024 * 1024`.
     pub max_packet_size: usize,
+
+    /// Message published by the broker on behalf of the client if the connection is lost
+    ///
+    /// Default: None
+    pub last_will_message: Option<Message>,
 }
 
 /// By default a client connects the local MQTT broker.
@@ -54,6 +59,7 @@ impl Default for Config {
             clean_session: false,
             queue_capacity: 1024,
             max_packet_size: 1024 * 1024,
+            last_will_message: None,
         }
     }
 }
@@ -120,6 +126,14 @@ impl Config {
         }
     }
 
+    /// Set the last will message, published by the broker if the connection is lost
+    pub fn with_last_will_message(self, message: Message) -> Self {
+        Self {
+            last_will_message: Some(message),
+            ..self
+        }
+    }
+
     /// Wrap this config into an internal set of options for `rumqttc`.
     pub(crate) fn mqtt_options(&self) -> rumqttc::MqttOptions {
         let id = match &self.session_name {
@@ -140,6 +154,15 @@ impl Config {
 
         mqtt_options.set_max_packet_size(self.max_packet_size, self.max_packet_size);
 
+        if let Some(message) = &self.last_will_message {
+            mqtt_options.set_last_will(rumqttc::LastWill::new(
+                &message.topic.name,
+                message.payload_bytes(),
+                message.qos,
+                message.retain,
+            ));
+        }
+
         mqtt_options
     }
 }
@@ -1,4 +1,4 @@
-use crate::{Config, ErrChannel, Message, MqttError, PubChannel, SubChannel};
+use crate::{Config, ErrChannel, Message, MqttError, PubChannel, SubChannel, TopicFilter};
 use futures::channel::mpsc;
 use futures::channel::oneshot;
 use futures::{SinkExt, StreamExt};
@@ -21,6 +21,13 @@ pub struct Connection {
 
     /// A channel to notify that all the published messages have been actually published.
     pub pub_done: oneshot::Receiver<()>,
+
+    /// The channel of the topic filters to subscribe to,
+    /// on top of the subscriptions given by the config on connect.
+    pub subscriptions: mpsc::UnboundedSender<TopicFilter>,
+
+    /// The channel of the topic filters to unsubscribe from.
+    pub unsubscriptions: mpsc::UnboundedSender<TopicFilter>,
 }
 
 impl Connection {
@@ -74,6 +81,8 @@ impl Connection {
         let (published_sender, published_receiver) = mpsc::unbounded();
         let (error_sender, error_receiver) = mpsc::unbounded();
         let (pub_done_sender, pub_done_receiver) = oneshot::channel();
+        let (subscriptions_sender, subscriptions_receiver) = mpsc::unbounded();
+        let (unsubscriptions_sender, unsubscriptions_receiver) = mpsc::unbounded();
 
         let (mqtt_client, event_loop) =
             Connection::open(config, received_sender.clone(), error_sender.clone()).await?;
@@ -82,6 +91,12 @@ impl Connection {
             received_sender,
             error_sender.clone(),
         ));
+        tokio::spawn(Connection::subscriber_loop(
+            mqtt_client.clone(),
+            subscriptions_receiver,
+            unsubscriptions_receiver,
+            error_sender.clone(),
+        ));
         tokio::spawn(Connection::sender_loop(
             mqtt_client,
             published_receiver,
@@ -94,6 +109,8 @@ impl Connection {
             published: published_sender,
             errors: error_receiver,
             pub_done: pub_done_receiver,
+            subscriptions: subscriptions_sender,
+            unsubscriptions: unsubscriptions_sender,
         })
     }
 
@@ -218,6 +235,38 @@ impl Connection {
         let _ = done.send(());
     }
 
+    async fn subscriber_loop(
+        mqtt_client: AsyncClient,
+        subscriptions_receiver: mpsc::UnboundedReceiver<TopicFilter>,
+        unsubscriptions_receiver: mpsc::UnboundedReceiver<TopicFilter>,
+        mut error_sender: mpsc::UnboundedSender<MqttError>,
+    ) {
+        let mut changes = futures::stream::select(
+            subscriptions_receiver.map(SubscriptionChange::Subscribe),
+            unsubscriptions_receiver.map(SubscriptionChange::Unsubscribe),
+        );
+        while let Some(change) = changes.next().await {
+            match change {
+                SubscriptionChange::Subscribe(topic_filter) => {
+                    let subscriptions = topic_filter.filters();
+                    if subscriptions.is_empty() {
+                        continue;
+                    }
+                    if let Err(err) = mqtt_client.subscribe_many(subscriptions).await {
+                        let _ = error_sender.send(err.into()).await;
+                    }
+                }
+                SubscriptionChange::Unsubscribe(topic_filter) => {
+                    for pattern in topic_filter.patterns {
+                        if let Err(err) = mqtt_client.unsubscribe(pattern).await {
+                            let _ = error_sender.send(err.into()).await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     pub(crate) fn pause_on_error(err: &ConnectionError) -> bool {
         match &err {
             rumqttc::ConnectionError::Io(_) => true,
@@ -236,3 +285,8 @@ impl Connection {
         sleep(Duration::from_secs(1)).await;
     }
 }
+
+enum SubscriptionChange {
+    Subscribe(TopicFilter),
+    Unsubscribe(TopicFilter),
+}
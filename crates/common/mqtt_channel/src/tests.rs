@@ -44,6 +44,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn the_last_will_message_is_given_to_the_broker_on_connect() {
+        let topic = Topic::new_unchecked("a/health/topic");
+        let last_will = Message::new(&topic, r#"{"status":"down"}"#).with_retain();
+        let mqtt_config = Config::default().with_last_will_message(last_will);
+
+        let will = mqtt_config.mqtt_options().last_will().expect("a last will");
+        assert_eq!(will.topic, "a/health/topic");
+        assert_eq!(&will.message[..], br#"{"status":"down"}"#);
+        assert!(will.retain);
+    }
+
     #[derive(Debug, Clone, Eq, PartialEq)]
     enum MaybeMessage {
         Next(Message),
@@ -120,6 +132,74 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn subscribing_to_new_topics_after_connect() -> Result<(), anyhow::Error> {
+        // Given an MQTT broker
+        let broker = mqtt_tests::test_mqtt_broker();
+        let mqtt_config = Config::default().with_port(broker.port);
+
+        // A client subscribes to a topic on connect
+        let first_topic = "a/first/topic";
+        let mqtt_config = mqtt_config
+            .with_session_name("test_client_subscribing_later")
+            .with_subscriptions(first_topic.try_into()?);
+        let mut con = Connection::new(&mqtt_config).await?;
+
+        // Then subscribes to a new topic
+        let second_topic = "a/second/topic";
+        con.subscriptions.send(second_topic.try_into()?).await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Messages published on either topic ...
+        broker.publish(first_topic, "msg 1").await?;
+        broker.publish(second_topic, "msg 2").await?;
+
+        // ... must be received by the client
+        assert_eq!(
+            MaybeMessage::Next(message(first_topic, "msg 1")),
+            next_message(&mut con.received).await
+        );
+        assert_eq!(
+            MaybeMessage::Next(message(second_topic, "msg 2")),
+            next_message(&mut con.received).await
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn unsubscribing_from_topics_after_connect() -> Result<(), anyhow::Error> {
+        // Given an MQTT broker
+        let broker = mqtt_tests::test_mqtt_broker();
+        let mqtt_config = Config::default().with_port(broker.port);
+
+        // A client subscribes to two topics on connect
+        let first_topic = "a/first/topic";
+        let second_topic = "a/second/topic";
+        let mqtt_config = mqtt_config
+            .with_session_name("test_client_unsubscribing_later")
+            .with_subscriptions(vec![first_topic, second_topic].try_into()?);
+        let mut con = Connection::new(&mqtt_config).await?;
+
+        // Then unsubscribes from the first one
+        con.unsubscriptions.send(first_topic.try_into()?).await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Messages published on the first topic ...
+        broker.publish(first_topic, "msg 1").await?;
+        broker.publish(second_topic, "msg 2").await?;
+
+        // ... must no longer be received by the client
+        assert_eq!(
+            MaybeMessage::Next(message(second_topic, "msg 2")),
+            next_message(&mut con.received).await
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     async fn publishing_messages() -> Result<(), anyhow::Error> {
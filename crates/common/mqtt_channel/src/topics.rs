@@ -91,6 +91,11 @@ impl TopicFilter {
         self.patterns.push(pattern);
     }
 
+    /// Remove the given pattern from this topic filter.
+    pub fn remove(&mut self, pattern: &str) {
+        self.patterns.retain(|p| p != pattern);
+    }
+
     /// Add all the other topics to this one.
     pub fn add_all(&mut self, other: TopicFilter) {
         for pattern in other.patterns {
@@ -0,0 +1,49 @@
+use std::convert::{TryFrom, TryInto};
+
+/// Represents a number of items, e.g. the maximum size of a batch.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Count(pub usize);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid count: '{input}'. Expecting a positive integer.")]
+pub struct InvalidCount {
+    input: String,
+}
+
+impl TryFrom<String> for Count {
+    type Error = InvalidCount;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input
+            .as_str()
+            .parse::<usize>()
+            .map_err(|_| InvalidCount { input })
+            .map(Count)
+    }
+}
+
+impl TryInto<String> for Count {
+    type Error = std::convert::Infallible;
+
+    fn try_into(self) -> Result<String, Self::Error> {
+        Ok(format!("{}", self.0))
+    }
+}
+
+impl From<Count> for usize {
+    fn from(val: Count) -> Self {
+        val.0
+    }
+}
+
+#[cfg(test)]
+use assert_matches::*;
+#[test]
+fn conversion_from_valid_count_succeeds() {
+    assert_matches!(Count::try_from("100".to_string()), Ok(Count(100)));
+}
+
+#[test]
+fn conversion_from_negative_integer_fails() {
+    assert_matches!(Count::try_from("-1".to_string()), Err(InvalidCount { .. }));
+}
@@ -0,0 +1,51 @@
+use std::convert::TryInto;
+
+/// Represents a list of glob patterns, e.g. to select metrics by name.
+///
+/// Set from a string as a comma-separated list of patterns.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct GlobPatterns(pub Vec<String>);
+
+impl From<String> for GlobPatterns {
+    fn from(input: String) -> Self {
+        GlobPatterns(
+            input
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+}
+
+impl TryInto<String> for GlobPatterns {
+    type Error = std::convert::Infallible;
+
+    fn try_into(self) -> Result<String, Self::Error> {
+        Ok(self.0.join(","))
+    }
+}
+
+impl From<GlobPatterns> for Vec<String> {
+    fn from(val: GlobPatterns) -> Self {
+        val.0
+    }
+}
+
+#[test]
+fn conversion_from_comma_separated_patterns() {
+    assert_eq!(
+        GlobPatterns::from("cpu/*, memory/percent-*,,".to_string()),
+        GlobPatterns(vec!["cpu/*".into(), "memory/percent-*".into()])
+    );
+}
+
+#[test]
+fn conversion_into_comma_separated_patterns() {
+    let patterns: String = GlobPatterns(vec!["cpu/*".into(), "df-*/*".into()])
+        .try_into()
+        .unwrap();
+    assert_eq!(patterns, "cpu/*,df-*/*");
+}
@@ -0,0 +1,70 @@
+use std::convert::{TryFrom, TryInto};
+use std::time::Duration;
+
+/// Represents a duration expressed as a number of milliseconds.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Milliseconds(pub u64);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid number of milliseconds: '{input}'.")]
+pub struct InvalidMilliseconds {
+    input: String,
+}
+
+impl TryFrom<String> for Milliseconds {
+    type Error = InvalidMilliseconds;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_| InvalidMilliseconds { input })
+            .map(Milliseconds)
+    }
+}
+
+impl TryInto<String> for Milliseconds {
+    type Error = std::convert::Infallible;
+
+    fn try_into(self) -> Result<String, Self::Error> {
+        Ok(format!("{}", self.0))
+    }
+}
+
+impl From<Milliseconds> for u64 {
+    fn from(val: Milliseconds) -> Self {
+        val.0
+    }
+}
+
+impl From<Milliseconds> for Duration {
+    fn from(val: Milliseconds) -> Self {
+        Duration::from_millis(val.0)
+    }
+}
+
+#[cfg(test)]
+use assert_matches::*;
+#[test]
+fn conversion_from_valid_milliseconds_succeeds() {
+    assert_matches!(
+        Milliseconds::try_from("500".to_string()),
+        Ok(Milliseconds(500))
+    );
+}
+
+#[test]
+fn conversion_from_decimal_milliseconds_fails() {
+    assert_matches!(
+        Milliseconds::try_from("0.5".to_string()),
+        Err(InvalidMilliseconds { .. })
+    );
+}
+
+#[test]
+fn conversion_from_milliseconds_to_duration() {
+    assert_eq!(
+        Duration::from(Milliseconds(1500)),
+        Duration::from_millis(1500)
+    );
+}
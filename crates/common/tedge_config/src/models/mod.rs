@@ -1,8 +1,16 @@
 pub mod connect_url;
+pub mod count;
 pub mod file_path;
 pub mod flag;
+pub mod glob_patterns;
 pub mod ipaddress;
+pub mod milliseconds;
 pub mod port;
+pub mod seconds;
 pub mod templates_set;
+pub mod text_measurement_fallback;
 
-pub use self::{connect_url::*, file_path::*, flag::*, ipaddress::*, port::*, templates_set::*};
+pub use self::{
+    connect_url::*, count::*, file_path::*, flag::*, glob_patterns::*, ipaddress::*,
+    milliseconds::*, port::*, seconds::*, templates_set::*, text_measurement_fallback::*,
+};
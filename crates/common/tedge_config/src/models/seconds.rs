@@ -0,0 +1,64 @@
+use std::convert::{TryFrom, TryInto};
+use std::time::Duration;
+
+/// Represents a duration expressed as a number of seconds.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Seconds(pub u64);
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid number of seconds: '{input}'.")]
+pub struct InvalidSeconds {
+    input: String,
+}
+
+impl TryFrom<String> for Seconds {
+    type Error = InvalidSeconds;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_| InvalidSeconds { input })
+            .map(Seconds)
+    }
+}
+
+impl TryInto<String> for Seconds {
+    type Error = std::convert::Infallible;
+
+    fn try_into(self) -> Result<String, Self::Error> {
+        Ok(format!("{}", self.0))
+    }
+}
+
+impl From<Seconds> for u64 {
+    fn from(val: Seconds) -> Self {
+        val.0
+    }
+}
+
+impl From<Seconds> for Duration {
+    fn from(val: Seconds) -> Self {
+        Duration::from_secs(val.0)
+    }
+}
+
+#[cfg(test)]
+use assert_matches::*;
+#[test]
+fn conversion_from_valid_seconds_succeeds() {
+    assert_matches!(Seconds::try_from("3600".to_string()), Ok(Seconds(3600)));
+}
+
+#[test]
+fn conversion_from_negative_integer_fails() {
+    assert_matches!(
+        Seconds::try_from("-1".to_string()),
+        Err(InvalidSeconds { .. })
+    );
+}
+
+#[test]
+fn conversion_from_seconds_to_duration() {
+    assert_eq!(Duration::from(Seconds(60)), Duration::from_secs(60));
+}
@@ -0,0 +1,75 @@
+use std::convert::{TryFrom, TryInto};
+
+/// How the measurements with a text value, which are not series for Cumulocity, are sent.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextMeasurementFallback {
+    /// The text measurements are dropped.
+    Skip,
+
+    /// The text measurements are sent as custom fragments of the Cumulocity measurement.
+    Fragment,
+}
+
+impl Default for TextMeasurementFallback {
+    fn default() -> Self {
+        TextMeasurementFallback::Skip
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid text measurement fallback: '{input}'. Supported values are: skip, fragment")]
+pub struct InvalidTextMeasurementFallback {
+    input: String,
+}
+
+impl TryFrom<String> for TextMeasurementFallback {
+    type Error = InvalidTextMeasurementFallback;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        match input.as_str() {
+            "skip" => Ok(TextMeasurementFallback::Skip),
+            "fragment" => Ok(TextMeasurementFallback::Fragment),
+            _ => Err(InvalidTextMeasurementFallback { input }),
+        }
+    }
+}
+
+impl TryInto<String> for TextMeasurementFallback {
+    type Error = std::convert::Infallible;
+
+    fn try_into(self) -> Result<String, Self::Error> {
+        match self {
+            TextMeasurementFallback::Skip => Ok("skip".into()),
+            TextMeasurementFallback::Fragment => Ok("fragment".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_string_to_text_measurement_fallback() {
+        assert_eq!(
+            TextMeasurementFallback::try_from("fragment".to_string()).unwrap(),
+            TextMeasurementFallback::Fragment
+        );
+        assert_eq!(
+            TextMeasurementFallback::try_from("skip".to_string()).unwrap(),
+            TextMeasurementFallback::Skip
+        );
+    }
+
+    #[test]
+    fn return_error_for_unexpected_string_input() {
+        assert!(TextMeasurementFallback::try_from("drop".to_string()).is_err());
+    }
+
+    #[test]
+    fn convert_text_measurement_fallback_to_string() {
+        let output: String = TextMeasurementFallback::Fragment.try_into().unwrap();
+        assert_eq!(output, "fragment");
+    }
+}
@@ -120,6 +120,189 @@ impl ConfigSetting for C8ySmartRestTemplates {
     type Value = TemplatesSet;
 }
 
+///
+/// Interval in seconds at which the c8y mapper refreshes the device inventory
+/// (hardware, firmware and agent fragments).
+///
+/// Example: 3600
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yInventoryIntervalSetting;
+
+impl ConfigSetting for C8yInventoryIntervalSetting {
+    const KEY: &'static str = "c8y.inventory.interval";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Interval in seconds at which the device inventory (hardware, firmware, agent) is refreshed. ",
+        "If not set, the inventory is only published on mapper startup. ",
+        "Example: 3600"
+    );
+
+    type Value = Seconds;
+}
+
+///
+/// Interval in seconds within which the device is expected to send data to Cumulocity.
+///
+/// Example: 600
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yAvailabilityIntervalSetting;
+
+impl ConfigSetting for C8yAvailabilityIntervalSetting {
+    const KEY: &'static str = "c8y.availability.interval";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Interval in seconds within which the device is expected to send data to Cumulocity (c8y_RequiredAvailability). ",
+        "Heartbeats are sent when there is no other traffic. ",
+        "If not set, the required availability of the device is left unchanged. ",
+        "Example: 600"
+    );
+
+    type Value = Seconds;
+}
+
+///
+/// Window in milliseconds during which the measurements sent to Cumulocity are batched.
+///
+/// Example: 500
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yBatchWindowSetting;
+
+impl ConfigSetting for C8yBatchWindowSetting {
+    const KEY: &'static str = "c8y.batch.window";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Window in milliseconds during which the measurements are batched into a single Cumulocity request. ",
+        "If not set, each measurement is sent on its own. ",
+        "Example: 500"
+    );
+
+    type Value = Milliseconds;
+}
+
+///
+/// Maximum number of measurements sent to Cumulocity in a batch.
+///
+/// Example: 100
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yBatchMaxSizeSetting;
+
+impl ConfigSetting for C8yBatchMaxSizeSetting {
+    const KEY: &'static str = "c8y.batch.max_size";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Maximum number of measurements sent in a single Cumulocity request, when batching is enabled with c8y.batch.window. ",
+        "Defaults to 100 if not set. ",
+        "Example: 100"
+    );
+
+    type Value = Count;
+}
+
+///
+/// Boolean whether the c8y mapper handles the `c8y_Command` operation, running shell commands on the device.
+///
+/// Example: true
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yCommandEnableSetting;
+
+impl ConfigSetting for C8yCommandEnableSetting {
+    const KEY: &'static str = "c8y.command.enable";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Boolean whether the c8y mapper runs the shell commands sent by Cumulocity (c8y_Command). ",
+        "Disabled if not set. ",
+        "Example: true"
+    );
+
+    type Value = Flag;
+}
+
+///
+/// Shell used to run the `c8y_Command` operations.
+///
+/// Example: /bin/bash
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yCommandShellSetting;
+
+impl ConfigSetting for C8yCommandShellSetting {
+    const KEY: &'static str = "c8y.command.shell";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Shell used to run the c8y_Command operations. ",
+        "Defaults to /bin/sh if not set. ",
+        "Example: /bin/bash"
+    );
+
+    type Value = String;
+}
+
+///
+/// Timeout in seconds of the `c8y_Command` operations.
+///
+/// Example: 60
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yCommandTimeoutSetting;
+
+impl ConfigSetting for C8yCommandTimeoutSetting {
+    const KEY: &'static str = "c8y.command.timeout";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Timeout in seconds after which a c8y_Command operation is killed and marked as failed. ",
+        "Defaults to 60 seconds if not set. ",
+        "Example: 60"
+    );
+
+    type Value = Seconds;
+}
+
+///
+/// Maximum size in bytes of the `c8y_Command` output sent to Cumulocity, at most 16366.
+///
+/// Example: 12288
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yCommandMaxOutputSetting;
+
+impl ConfigSetting for C8yCommandMaxOutputSetting {
+    const KEY: &'static str = "c8y.command.max_output";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Maximum size in bytes of the c8y_Command output sent to Cumulocity, only the end of a longer output being sent. ",
+        "Defaults to 8192 bytes if not set, and cannot exceed 16366 bytes. ",
+        "Example: 12288"
+    );
+
+    type Value = Count;
+}
+
+///
+/// How the measurements with a text value are sent to Cumulocity: `skip` or `fragment`.
+///
+/// Example: fragment
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct C8yTextMeasurementFallbackSetting;
+
+impl ConfigSetting for C8yTextMeasurementFallbackSetting {
+    const KEY: &'static str = "c8y.text_measurement.fallback";
+
+    const DESCRIPTION: &'static str = concat!(
+        "How the measurements with a text value, which are not numeric series for Cumulocity, are sent. ",
+        "Either skip, to drop them, or fragment, to send them as custom fragments of the measurements. ",
+        "Defaults to skip if not set. ",
+        "Example: fragment"
+    );
+
+    type Value = TextMeasurementFallback;
+}
+
 ///
 /// Tenant endpoint URL of Azure IoT tenant.
 ///
@@ -306,6 +489,266 @@ impl ConfigSetting for SoftwarePluginDefaultSetting {
     type Value = String;
 }
 
+///
+/// Interval in seconds at which the system metrics are sampled by the sysmon mapper.
+///
+/// Example: 60
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SysmonIntervalSetting;
+
+impl ConfigSetting for SysmonIntervalSetting {
+    const KEY: &'static str = "sysmon.interval";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Interval in seconds at which the sysmon mapper samples and publishes the system metrics. ",
+        "Defaults to 60 seconds if not set. ",
+        "Example: 60"
+    );
+
+    type Value = Seconds;
+}
+
+///
+/// Boolean whether the sysmon mapper publishes the CPU usage metrics.
+///
+/// Example: false
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SysmonCpuEnableSetting;
+
+impl ConfigSetting for SysmonCpuEnableSetting {
+    const KEY: &'static str = "sysmon.cpu.enable";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Boolean whether the sysmon mapper publishes the CPU usage metrics. ",
+        "Enabled if not set. ",
+        "Example: false"
+    );
+
+    type Value = Flag;
+}
+
+///
+/// Boolean whether the sysmon mapper publishes the memory usage metrics.
+///
+/// Example: false
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SysmonMemoryEnableSetting;
+
+impl ConfigSetting for SysmonMemoryEnableSetting {
+    const KEY: &'static str = "sysmon.memory.enable";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Boolean whether the sysmon mapper publishes the memory usage metrics. ",
+        "Enabled if not set. ",
+        "Example: false"
+    );
+
+    type Value = Flag;
+}
+
+///
+/// Boolean whether the sysmon mapper publishes the disk usage metrics.
+///
+/// Example: false
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SysmonDiskEnableSetting;
+
+impl ConfigSetting for SysmonDiskEnableSetting {
+    const KEY: &'static str = "sysmon.disk.enable";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Boolean whether the sysmon mapper publishes the disk usage metrics. ",
+        "Enabled if not set. ",
+        "Example: false"
+    );
+
+    type Value = Flag;
+}
+
+///
+/// Boolean whether the sysmon mapper publishes the network throughput metrics.
+///
+/// Example: false
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SysmonNetworkEnableSetting;
+
+impl ConfigSetting for SysmonNetworkEnableSetting {
+    const KEY: &'static str = "sysmon.network.enable";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Boolean whether the sysmon mapper publishes the network throughput metrics. ",
+        "Enabled if not set. ",
+        "Example: false"
+    );
+
+    type Value = Flag;
+}
+
+///
+/// Boolean whether the sysmon mapper publishes the temperature metrics.
+///
+/// Example: false
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SysmonTemperatureEnableSetting;
+
+impl ConfigSetting for SysmonTemperatureEnableSetting {
+    const KEY: &'static str = "sysmon.temperature.enable";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Boolean whether the sysmon mapper publishes the temperature metrics. ",
+        "Enabled if not set. ",
+        "Example: false"
+    );
+
+    type Value = Flag;
+}
+
+///
+/// Window in milliseconds during which the collectd measurements are grouped into a single message.
+///
+/// Example: 500
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectdBatchingWindowSetting;
+
+impl ConfigSetting for CollectdBatchingWindowSetting {
+    const KEY: &'static str = "collectd.batching.window";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Window in milliseconds during which the collectd mapper groups the measurements received from collectd. ",
+        "Defaults to 500 milliseconds if not set. ",
+        "Example: 500"
+    );
+
+    type Value = Milliseconds;
+}
+
+///
+/// Maximum delay in milliseconds expected between the collection of a collectd measurement and its reception by the mapper.
+///
+/// Example: 1000
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectdBatchingMaxDelaySetting;
+
+impl ConfigSetting for CollectdBatchingMaxDelaySetting {
+    const KEY: &'static str = "collectd.batching.max_delay";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Maximum delay in milliseconds expected between the collection of a collectd measurement and its reception. ",
+        "Defaults to 400 milliseconds if not set. ",
+        "Example: 1000"
+    );
+
+    type Value = Milliseconds;
+}
+
+///
+/// Largest jump in milliseconds expected in the timestamps of the collectd measurements.
+///
+/// Example: 0
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectdBatchingLeapLimitSetting;
+
+impl ConfigSetting for CollectdBatchingLeapLimitSetting {
+    const KEY: &'static str = "collectd.batching.leap_limit";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Largest jump in milliseconds expected in the timestamps of the collectd measurements. ",
+        "Defaults to 0 if not set. ",
+        "Example: 0"
+    );
+
+    type Value = Milliseconds;
+}
+
+///
+/// MQTT topic filter on which the collectd measurements are received.
+///
+/// Example: collectd/#
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectdSourceTopicSetting;
+
+impl ConfigSetting for CollectdSourceTopicSetting {
+    const KEY: &'static str = "collectd.source.topic";
+
+    const DESCRIPTION: &'static str = concat!(
+        "MQTT topic filter on which the collectd mapper receives the collectd measurements. ",
+        "Defaults to collectd/# if not set. ",
+        "Example: collectd/#"
+    );
+
+    type Value = String;
+}
+
+///
+/// MQTT topic on which the collectd measurements are published as thin-edge JSON.
+///
+/// Example: tedge/measurements/child1
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectdTargetTopicSetting;
+
+impl ConfigSetting for CollectdTargetTopicSetting {
+    const KEY: &'static str = "collectd.target.topic";
+
+    const DESCRIPTION: &'static str = concat!(
+        "MQTT topic on which the collectd mapper publishes the measurements as thin-edge JSON. ",
+        "Defaults to tedge/measurements if not set. ",
+        "Example: tedge/measurements/child1"
+    );
+
+    type Value = String;
+}
+
+///
+/// Glob patterns on `<plugin>/<key>` of the only collectd metrics forwarded by the mapper.
+///
+/// Example: cpu/*,memory/*
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectdMetricsAllowSetting;
+
+impl ConfigSetting for CollectdMetricsAllowSetting {
+    const KEY: &'static str = "collectd.metrics.allow";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Comma-separated glob patterns on <plugin>/<key> of the only collectd metrics forwarded by the mapper. ",
+        "All the metrics are forwarded if not set. ",
+        "Example: cpu/*,memory/*"
+    );
+
+    type Value = GlobPatterns;
+}
+
+///
+/// Glob patterns on `<plugin>/<key>` of the collectd metrics dropped by the mapper.
+///
+/// Example: df-*/*,interface-lo/*
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectdMetricsDenySetting;
+
+impl ConfigSetting for CollectdMetricsDenySetting {
+    const KEY: &'static str = "collectd.metrics.deny";
+
+    const DESCRIPTION: &'static str = concat!(
+        "Comma-separated glob patterns on <plugin>/<key> of the collectd metrics dropped by the mapper, ",
+        "even if allowed. ",
+        "Example: df-*/*,interface-lo/*"
+    );
+
+    type Value = GlobPatterns;
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct TmpPathSetting;
 
@@ -163,6 +163,242 @@ impl ConfigSettingAccessor<C8ySmartRestTemplates> for TEdgeConfig {
     }
 }
 
+impl ConfigSettingAccessor<C8yInventoryIntervalSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yInventoryIntervalSetting) -> ConfigSettingResult<Seconds> {
+        self.data
+            .c8y
+            .inventory_interval
+            .map(Seconds)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yInventoryIntervalSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yInventoryIntervalSetting,
+        value: Seconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.inventory_interval = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yInventoryIntervalSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.inventory_interval = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yAvailabilityIntervalSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yAvailabilityIntervalSetting) -> ConfigSettingResult<Seconds> {
+        self.data
+            .c8y
+            .availability_interval
+            .map(Seconds)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yAvailabilityIntervalSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yAvailabilityIntervalSetting,
+        value: Seconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.availability_interval = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yAvailabilityIntervalSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.availability_interval = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yBatchWindowSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yBatchWindowSetting) -> ConfigSettingResult<Milliseconds> {
+        self.data
+            .c8y
+            .batch_window
+            .map(Milliseconds)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yBatchWindowSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yBatchWindowSetting,
+        value: Milliseconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.batch_window = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yBatchWindowSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.batch_window = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yBatchMaxSizeSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yBatchMaxSizeSetting) -> ConfigSettingResult<Count> {
+        self.data
+            .c8y
+            .batch_max_size
+            .map(Count)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yBatchMaxSizeSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yBatchMaxSizeSetting,
+        value: Count,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.batch_max_size = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yBatchMaxSizeSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.batch_max_size = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yCommandEnableSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yCommandEnableSetting) -> ConfigSettingResult<Flag> {
+        self.data
+            .c8y
+            .command_enable
+            .map(Flag)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yCommandEnableSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yCommandEnableSetting,
+        value: Flag,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.command_enable = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yCommandEnableSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.command_enable = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yCommandShellSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yCommandShellSetting) -> ConfigSettingResult<String> {
+        self.data
+            .c8y
+            .command_shell
+            .clone()
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yCommandShellSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yCommandShellSetting,
+        value: String,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.command_shell = Some(value);
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yCommandShellSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.command_shell = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yCommandTimeoutSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yCommandTimeoutSetting) -> ConfigSettingResult<Seconds> {
+        self.data
+            .c8y
+            .command_timeout
+            .map(Seconds)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yCommandTimeoutSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yCommandTimeoutSetting,
+        value: Seconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.command_timeout = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yCommandTimeoutSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.command_timeout = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yCommandMaxOutputSetting> for TEdgeConfig {
+    fn query(&self, _setting: C8yCommandMaxOutputSetting) -> ConfigSettingResult<Count> {
+        self.data
+            .c8y
+            .command_max_output
+            .map(Count)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yCommandMaxOutputSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yCommandMaxOutputSetting,
+        value: Count,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.command_max_output = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yCommandMaxOutputSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.command_max_output = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<C8yTextMeasurementFallbackSetting> for TEdgeConfig {
+    fn query(
+        &self,
+        _setting: C8yTextMeasurementFallbackSetting,
+    ) -> ConfigSettingResult<TextMeasurementFallback> {
+        self.data
+            .c8y
+            .text_measurement_fallback
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: C8yTextMeasurementFallbackSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: C8yTextMeasurementFallbackSetting,
+        value: TextMeasurementFallback,
+    ) -> ConfigSettingResult<()> {
+        self.data.c8y.text_measurement_fallback = Some(value);
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: C8yTextMeasurementFallbackSetting) -> ConfigSettingResult<()> {
+        self.data.c8y.text_measurement_fallback = None;
+        Ok(())
+    }
+}
+
 impl ConfigSettingAccessor<DeviceCertPathSetting> for TEdgeConfig {
     fn query(&self, _setting: DeviceCertPathSetting) -> ConfigSettingResult<FilePath> {
         Ok(self
@@ -512,6 +748,344 @@ impl ConfigSettingAccessor<SoftwarePluginDefaultSetting> for TEdgeConfig {
     }
 }
 
+impl ConfigSettingAccessor<SysmonIntervalSetting> for TEdgeConfig {
+    fn query(&self, _setting: SysmonIntervalSetting) -> ConfigSettingResult<Seconds> {
+        self.data
+            .sysmon
+            .interval
+            .map(Seconds)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: SysmonIntervalSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: SysmonIntervalSetting,
+        value: Seconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.sysmon.interval = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: SysmonIntervalSetting) -> ConfigSettingResult<()> {
+        self.data.sysmon.interval = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<SysmonCpuEnableSetting> for TEdgeConfig {
+    fn query(&self, _setting: SysmonCpuEnableSetting) -> ConfigSettingResult<Flag> {
+        self.data
+            .sysmon
+            .cpu_enable
+            .map(Flag)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: SysmonCpuEnableSetting::KEY,
+            })
+    }
+
+    fn update(&mut self, _setting: SysmonCpuEnableSetting, value: Flag) -> ConfigSettingResult<()> {
+        self.data.sysmon.cpu_enable = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: SysmonCpuEnableSetting) -> ConfigSettingResult<()> {
+        self.data.sysmon.cpu_enable = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<SysmonMemoryEnableSetting> for TEdgeConfig {
+    fn query(&self, _setting: SysmonMemoryEnableSetting) -> ConfigSettingResult<Flag> {
+        self.data
+            .sysmon
+            .memory_enable
+            .map(Flag)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: SysmonMemoryEnableSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: SysmonMemoryEnableSetting,
+        value: Flag,
+    ) -> ConfigSettingResult<()> {
+        self.data.sysmon.memory_enable = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: SysmonMemoryEnableSetting) -> ConfigSettingResult<()> {
+        self.data.sysmon.memory_enable = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<SysmonDiskEnableSetting> for TEdgeConfig {
+    fn query(&self, _setting: SysmonDiskEnableSetting) -> ConfigSettingResult<Flag> {
+        self.data
+            .sysmon
+            .disk_enable
+            .map(Flag)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: SysmonDiskEnableSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: SysmonDiskEnableSetting,
+        value: Flag,
+    ) -> ConfigSettingResult<()> {
+        self.data.sysmon.disk_enable = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: SysmonDiskEnableSetting) -> ConfigSettingResult<()> {
+        self.data.sysmon.disk_enable = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<SysmonNetworkEnableSetting> for TEdgeConfig {
+    fn query(&self, _setting: SysmonNetworkEnableSetting) -> ConfigSettingResult<Flag> {
+        self.data
+            .sysmon
+            .network_enable
+            .map(Flag)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: SysmonNetworkEnableSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: SysmonNetworkEnableSetting,
+        value: Flag,
+    ) -> ConfigSettingResult<()> {
+        self.data.sysmon.network_enable = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: SysmonNetworkEnableSetting) -> ConfigSettingResult<()> {
+        self.data.sysmon.network_enable = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<SysmonTemperatureEnableSetting> for TEdgeConfig {
+    fn query(&self, _setting: SysmonTemperatureEnableSetting) -> ConfigSettingResult<Flag> {
+        self.data
+            .sysmon
+            .temperature_enable
+            .map(Flag)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: SysmonTemperatureEnableSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: SysmonTemperatureEnableSetting,
+        value: Flag,
+    ) -> ConfigSettingResult<()> {
+        self.data.sysmon.temperature_enable = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: SysmonTemperatureEnableSetting) -> ConfigSettingResult<()> {
+        self.data.sysmon.temperature_enable = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<CollectdBatchingWindowSetting> for TEdgeConfig {
+    fn query(&self, _setting: CollectdBatchingWindowSetting) -> ConfigSettingResult<Milliseconds> {
+        self.data.collectd.batching_window.map(Milliseconds).ok_or(
+            ConfigSettingError::ConfigNotSet {
+                key: CollectdBatchingWindowSetting::KEY,
+            },
+        )
+    }
+
+    fn update(
+        &mut self,
+        _setting: CollectdBatchingWindowSetting,
+        value: Milliseconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.collectd.batching_window = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: CollectdBatchingWindowSetting) -> ConfigSettingResult<()> {
+        self.data.collectd.batching_window = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<CollectdBatchingMaxDelaySetting> for TEdgeConfig {
+    fn query(
+        &self,
+        _setting: CollectdBatchingMaxDelaySetting,
+    ) -> ConfigSettingResult<Milliseconds> {
+        self.data
+            .collectd
+            .batching_max_delay
+            .map(Milliseconds)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: CollectdBatchingMaxDelaySetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: CollectdBatchingMaxDelaySetting,
+        value: Milliseconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.collectd.batching_max_delay = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: CollectdBatchingMaxDelaySetting) -> ConfigSettingResult<()> {
+        self.data.collectd.batching_max_delay = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<CollectdBatchingLeapLimitSetting> for TEdgeConfig {
+    fn query(
+        &self,
+        _setting: CollectdBatchingLeapLimitSetting,
+    ) -> ConfigSettingResult<Milliseconds> {
+        self.data
+            .collectd
+            .batching_leap_limit
+            .map(Milliseconds)
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: CollectdBatchingLeapLimitSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: CollectdBatchingLeapLimitSetting,
+        value: Milliseconds,
+    ) -> ConfigSettingResult<()> {
+        self.data.collectd.batching_leap_limit = Some(value.into());
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: CollectdBatchingLeapLimitSetting) -> ConfigSettingResult<()> {
+        self.data.collectd.batching_leap_limit = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<CollectdSourceTopicSetting> for TEdgeConfig {
+    fn query(&self, _setting: CollectdSourceTopicSetting) -> ConfigSettingResult<String> {
+        self.data
+            .collectd
+            .source_topic
+            .clone()
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: CollectdSourceTopicSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: CollectdSourceTopicSetting,
+        value: String,
+    ) -> ConfigSettingResult<()> {
+        self.data.collectd.source_topic = Some(value);
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: CollectdSourceTopicSetting) -> ConfigSettingResult<()> {
+        self.data.collectd.source_topic = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<CollectdTargetTopicSetting> for TEdgeConfig {
+    fn query(&self, _setting: CollectdTargetTopicSetting) -> ConfigSettingResult<String> {
+        self.data
+            .collectd
+            .target_topic
+            .clone()
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: CollectdTargetTopicSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: CollectdTargetTopicSetting,
+        value: String,
+    ) -> ConfigSettingResult<()> {
+        self.data.collectd.target_topic = Some(value);
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: CollectdTargetTopicSetting) -> ConfigSettingResult<()> {
+        self.data.collectd.target_topic = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<CollectdMetricsAllowSetting> for TEdgeConfig {
+    fn query(&self, _setting: CollectdMetricsAllowSetting) -> ConfigSettingResult<GlobPatterns> {
+        self.data
+            .collectd
+            .metrics_allow
+            .clone()
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: CollectdMetricsAllowSetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: CollectdMetricsAllowSetting,
+        value: GlobPatterns,
+    ) -> ConfigSettingResult<()> {
+        self.data.collectd.metrics_allow = Some(value);
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: CollectdMetricsAllowSetting) -> ConfigSettingResult<()> {
+        self.data.collectd.metrics_allow = None;
+        Ok(())
+    }
+}
+
+impl ConfigSettingAccessor<CollectdMetricsDenySetting> for TEdgeConfig {
+    fn query(&self, _setting: CollectdMetricsDenySetting) -> ConfigSettingResult<GlobPatterns> {
+        self.data
+            .collectd
+            .metrics_deny
+            .clone()
+            .ok_or(ConfigSettingError::ConfigNotSet {
+                key: CollectdMetricsDenySetting::KEY,
+            })
+    }
+
+    fn update(
+        &mut self,
+        _setting: CollectdMetricsDenySetting,
+        value: GlobPatterns,
+    ) -> ConfigSettingResult<()> {
+        self.data.collectd.metrics_deny = Some(value);
+        Ok(())
+    }
+
+    fn unset(&mut self, _setting: CollectdMetricsDenySetting) -> ConfigSettingResult<()> {
+        self.data.collectd.metrics_deny = None;
+        Ok(())
+    }
+}
+
 /// Generic extension trait implementation for all `ConfigSetting`s of `TEdgeConfig`
 /// that provide `TryFrom`/`TryInto` implementations for `String`.
 impl<T, E, F> ConfigSettingAccessorStringExt<T> for TEdgeConfig
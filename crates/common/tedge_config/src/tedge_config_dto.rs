@@ -23,6 +23,12 @@ pub(crate) struct TEdgeConfigDto {
     #[serde(default)]
     pub(crate) software: SoftwareConfigDto,
 
+    #[serde(default)]
+    pub(crate) sysmon: SysmonConfigDto,
+
+    #[serde(default)]
+    pub(crate) collectd: CollectdConfigDto,
+
     #[serde(default)]
     pub(crate) tmp: PathConfigDto,
 
@@ -75,6 +81,33 @@ pub(crate) struct CumulocityConfigDto {
 
     /// Set of c8y templates used for subscriptions.
     pub(crate) smartrest_templates: Option<TemplatesSet>,
+
+    /// Interval in seconds at which the device inventory is refreshed.
+    pub(crate) inventory_interval: Option<u64>,
+
+    /// Interval in seconds within which the device is expected to send data.
+    pub(crate) availability_interval: Option<u64>,
+
+    /// Window in milliseconds during which the measurements are batched.
+    pub(crate) batch_window: Option<u64>,
+
+    /// Maximum number of measurements in a batch.
+    pub(crate) batch_max_size: Option<usize>,
+
+    /// Boolean whether the `c8y_Command` operation is enabled.
+    pub(crate) command_enable: Option<bool>,
+
+    /// Shell used to run the `c8y_Command` operations.
+    pub(crate) command_shell: Option<String>,
+
+    /// Timeout in seconds of the `c8y_Command` operations.
+    pub(crate) command_timeout: Option<u64>,
+
+    /// Maximum size in bytes of the `c8y_Command` output.
+    pub(crate) command_max_output: Option<usize>,
+
+    /// How the measurements with a text value are sent.
+    pub(crate) text_measurement_fallback: Option<TextMeasurementFallback>,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -105,6 +138,57 @@ pub(crate) struct SoftwareConfigDto {
     pub(crate) default_plugin_type: Option<String>,
 }
 
+/// Represents the system monitoring configurations defined in the
+/// [sysmon] section of the thin edge configuration TOML file
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SysmonConfigDto {
+    /// Interval in seconds at which the system metrics are sampled.
+    pub(crate) interval: Option<u64>,
+
+    /// Boolean whether the CPU usage metrics are published.
+    pub(crate) cpu_enable: Option<bool>,
+
+    /// Boolean whether the memory usage metrics are published.
+    pub(crate) memory_enable: Option<bool>,
+
+    /// Boolean whether the disk usage metrics are published.
+    pub(crate) disk_enable: Option<bool>,
+
+    /// Boolean whether the network throughput metrics are published.
+    pub(crate) network_enable: Option<bool>,
+
+    /// Boolean whether the temperature metrics are published.
+    pub(crate) temperature_enable: Option<bool>,
+}
+
+/// Represents the collectd mapper configurations defined in the
+/// [collectd] section of the thin edge configuration TOML file
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CollectdConfigDto {
+    /// Window in milliseconds during which the collectd measurements are grouped into a single message.
+    pub(crate) batching_window: Option<u64>,
+
+    /// Maximum delay in milliseconds expected between the collection of a collectd measurement and its reception by the mapper.
+    pub(crate) batching_max_delay: Option<u64>,
+
+    /// Largest jump in milliseconds expected in the timestamps of the collectd measurements.
+    pub(crate) batching_leap_limit: Option<u64>,
+
+    /// MQTT topic filter on which the collectd measurements are received.
+    pub(crate) source_topic: Option<String>,
+
+    /// MQTT topic on which the collectd measurements are published as thin-edge JSON.
+    pub(crate) target_topic: Option<String>,
+
+    /// Glob patterns on `<plugin>/<key>` of the only collectd metrics forwarded by the mapper.
+    pub(crate) metrics_allow: Option<GlobPatterns>,
+
+    /// Glob patterns on `<plugin>/<key>` of the collectd metrics dropped by the mapper.
+    pub(crate) metrics_deny: Option<GlobPatterns>,
+}
+
 #[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct PathConfigDto {
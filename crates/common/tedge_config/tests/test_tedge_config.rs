@@ -378,6 +378,227 @@ url = "your-tenant.cumulocity.com"
     Ok(())
 }
 
+#[test]
+fn test_parse_config_with_c8y_inventory_interval() -> Result<(), TEdgeConfigError> {
+    let toml_conf = r#"
+[c8y]
+url = "your-tenant.cumulocity.com"
+inventory_interval = 3600
+"#;
+
+    let (_tempdir, config_location) = create_temp_tedge_config(toml_conf)?;
+    let mut config =
+        TEdgeConfigRepository::new_with_defaults(config_location, dummy_tedge_config_defaults())
+            .load()?;
+
+    assert_eq!(config.query(C8yInventoryIntervalSetting)?, Seconds(3600));
+
+    config.update(C8yInventoryIntervalSetting, Seconds(60))?;
+    assert_eq!(config.query(C8yInventoryIntervalSetting)?, Seconds(60));
+
+    config.unset(C8yInventoryIntervalSetting)?;
+    assert!(config
+        .query_optional(C8yInventoryIntervalSetting)?
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn test_parse_config_with_c8y_availability_interval() -> Result<(), TEdgeConfigError> {
+    let toml_conf = r#"
+[c8y]
+url = "your-tenant.cumulocity.com"
+availability_interval = 600
+"#;
+
+    let (_tempdir, config_location) = create_temp_tedge_config(toml_conf)?;
+    let mut config =
+        TEdgeConfigRepository::new_with_defaults(config_location, dummy_tedge_config_defaults())
+            .load()?;
+
+    assert_eq!(config.query(C8yAvailabilityIntervalSetting)?, Seconds(600));
+
+    config.update(C8yAvailabilityIntervalSetting, Seconds(60))?;
+    assert_eq!(config.query(C8yAvailabilityIntervalSetting)?, Seconds(60));
+
+    config.unset(C8yAvailabilityIntervalSetting)?;
+    assert!(config
+        .query_optional(C8yAvailabilityIntervalSetting)?
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn test_parse_config_with_c8y_batch_settings() -> Result<(), TEdgeConfigError> {
+    let toml_conf = r#"
+[c8y]
+url = "your-tenant.cumulocity.com"
+batch_window = 500
+batch_max_size = 50
+"#;
+
+    let (_tempdir, config_location) = create_temp_tedge_config(toml_conf)?;
+    let mut config =
+        TEdgeConfigRepository::new_with_defaults(config_location, dummy_tedge_config_defaults())
+            .load()?;
+
+    assert_eq!(config.query(C8yBatchWindowSetting)?, Milliseconds(500));
+    assert_eq!(config.query(C8yBatchMaxSizeSetting)?, Count(50));
+
+    config.update(C8yBatchMaxSizeSetting, Count(200))?;
+    assert_eq!(config.query(C8yBatchMaxSizeSetting)?, Count(200));
+
+    config.unset(C8yBatchWindowSetting)?;
+    config.unset(C8yBatchMaxSizeSetting)?;
+    assert!(config.query_optional(C8yBatchWindowSetting)?.is_none());
+    assert!(config.query_optional(C8yBatchMaxSizeSetting)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_parse_config_with_c8y_command_settings() -> Result<(), TEdgeConfigError> {
+    let toml_conf = r#"
+[c8y]
+url = "your-tenant.cumulocity.com"
+command_enable = true
+command_shell = "/bin/bash"
+command_timeout = 30
+command_max_output = 16384
+"#;
+
+    let (_tempdir, config_location) = create_temp_tedge_config(toml_conf)?;
+    let mut config =
+        TEdgeConfigRepository::new_with_defaults(config_location, dummy_tedge_config_defaults())
+            .load()?;
+
+    assert_eq!(config.query(C8yCommandEnableSetting)?, Flag(true));
+    assert_eq!(config.query(C8yCommandShellSetting)?, "/bin/bash");
+    assert_eq!(config.query(C8yCommandTimeoutSetting)?, Seconds(30));
+    assert_eq!(config.query(C8yCommandMaxOutputSetting)?, Count(16384));
+
+    config.unset(C8yCommandEnableSetting)?;
+    config.unset(C8yCommandShellSetting)?;
+    config.unset(C8yCommandTimeoutSetting)?;
+    config.unset(C8yCommandMaxOutputSetting)?;
+    assert!(config.query_optional(C8yCommandEnableSetting)?.is_none());
+    assert!(config.query_optional(C8yCommandShellSetting)?.is_none());
+    assert!(config.query_optional(C8yCommandTimeoutSetting)?.is_none());
+    assert!(config.query_optional(C8yCommandMaxOutputSetting)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_parse_config_with_sysmon_settings() -> Result<(), TEdgeConfigError> {
+    let toml_conf = r#"
+[sysmon]
+interval = 10
+cpu_enable = true
+temperature_enable = false
+"#;
+
+    let (_tempdir, config_location) = create_temp_tedge_config(toml_conf)?;
+    let mut config =
+        TEdgeConfigRepository::new_with_defaults(config_location, dummy_tedge_config_defaults())
+            .load()?;
+
+    assert_eq!(config.query(SysmonIntervalSetting)?, Seconds(10));
+    assert_eq!(config.query(SysmonCpuEnableSetting)?, Flag(true));
+    assert_eq!(config.query(SysmonTemperatureEnableSetting)?, Flag(false));
+    assert!(config.query_optional(SysmonDiskEnableSetting)?.is_none());
+
+    config.update(SysmonNetworkEnableSetting, Flag(false))?;
+    assert_eq!(config.query(SysmonNetworkEnableSetting)?, Flag(false));
+
+    config.unset(SysmonIntervalSetting)?;
+    config.unset(SysmonTemperatureEnableSetting)?;
+    assert!(config.query_optional(SysmonIntervalSetting)?.is_none());
+    assert!(config
+        .query_optional(SysmonTemperatureEnableSetting)?
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn test_parse_config_with_collectd_settings() -> Result<(), TEdgeConfigError> {
+    let toml_conf = r#"
+[collectd]
+batching_window = 1000
+source_topic = "collectd/#"
+target_topic = "tedge/measurements/child1"
+metrics_allow = ["cpu/*", "memory/*"]
+"#;
+
+    let (_tempdir, config_location) = create_temp_tedge_config(toml_conf)?;
+    let mut config =
+        TEdgeConfigRepository::new_with_defaults(config_location, dummy_tedge_config_defaults())
+            .load()?;
+
+    assert_eq!(
+        config.query(CollectdBatchingWindowSetting)?,
+        Milliseconds(1000)
+    );
+    assert_eq!(config.query(CollectdSourceTopicSetting)?, "collectd/#");
+    assert_eq!(
+        config.query(CollectdTargetTopicSetting)?,
+        "tedge/measurements/child1"
+    );
+    assert_eq!(
+        config.query(CollectdMetricsAllowSetting)?,
+        GlobPatterns(vec!["cpu/*".into(), "memory/*".into()])
+    );
+    assert!(config.query_optional(CollectdMetricsDenySetting)?.is_none());
+
+    config.update_string(CollectdMetricsDenySetting, "cpu/*idle, df-*/*".into())?;
+    assert_eq!(
+        config.query_string(CollectdMetricsDenySetting)?,
+        "cpu/*idle,df-*/*"
+    );
+
+    config.unset(CollectdBatchingWindowSetting)?;
+    config.unset(CollectdMetricsAllowSetting)?;
+    assert!(config
+        .query_optional(CollectdBatchingWindowSetting)?
+        .is_none());
+    assert!(config
+        .query_optional(CollectdMetricsAllowSetting)?
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn test_parse_config_with_text_measurement_fallback() -> Result<(), TEdgeConfigError> {
+    let toml_conf = r#"
+[c8y]
+url = "your-tenant.cumulocity.com"
+text_measurement_fallback = "fragment"
+"#;
+
+    let (_tempdir, config_location) = create_temp_tedge_config(toml_conf)?;
+    let mut config =
+        TEdgeConfigRepository::new_with_defaults(config_location, dummy_tedge_config_defaults())
+            .load()?;
+
+    assert_eq!(
+        config.query(C8yTextMeasurementFallbackSetting)?,
+        TextMeasurementFallback::Fragment
+    );
+
+    config.update_string(C8yTextMeasurementFallbackSetting, "skip".into())?;
+    assert_eq!(
+        config.query(C8yTextMeasurementFallbackSetting)?,
+        TextMeasurementFallback::Skip
+    );
+    assert!(config
+        .update_string(C8yTextMeasurementFallbackSetting, "drop".into())
+        .is_err());
+
+    config.unset(C8yTextMeasurementFallbackSetting)?;
+    assert!(config
+        .query_optional(C8yTextMeasurementFallbackSetting)?
+        .is_none());
+    Ok(())
+}
+
 #[test]
 fn test_parse_config_with_only_az_configuration() -> Result<(), TEdgeConfigError> {
     let toml_conf = r#"
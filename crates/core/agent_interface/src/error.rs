@@ -94,6 +94,41 @@ pub enum SoftwareError {
     FromCSV { reason: String },
 }
 
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum FirmwareError {
+    #[error("No firmware plugin found in {plugin_dir:?}")]
+    NoPlugin { plugin_dir: String },
+
+    #[error("Failed to prepare the firmware update: {reason}")]
+    Prepare { reason: String },
+
+    #[error("Failed to download {url:?}: {reason}")]
+    Download { reason: String, url: String },
+
+    #[error("Failed to install firmware {name:?}: {reason}")]
+    Install { name: String, reason: String },
+
+    #[error("Failed to reboot the device: {reason}")]
+    Reboot { reason: String },
+
+    #[error("Failed to verify firmware {name:?}: {reason}")]
+    Verify { name: String, reason: String },
+
+    #[error("Firmware plugin error: {reason}")]
+    Plugin { reason: String },
+
+    #[error("I/O error: {reason:?}")]
+    IoError { reason: String },
+}
+
+impl From<std::io::Error> for FirmwareError {
+    fn from(err: std::io::Error) -> Self {
+        FirmwareError::IoError {
+            reason: format!("{}", err),
+        }
+    }
+}
+
 impl From<serde_json::Error> for SoftwareError {
     fn from(err: serde_json::Error) -> Self {
         SoftwareError::ParseError {
@@ -6,9 +6,12 @@ pub mod topic;
 pub use download::*;
 pub use error::*;
 pub use messages::{
-    control_filter_topic, health_check_topics, software_filter_topic, Jsonify, OperationStatus,
-    RestartOperationRequest, RestartOperationResponse, SoftwareListRequest, SoftwareListResponse,
-    SoftwareRequestResponse, SoftwareUpdateRequest, SoftwareUpdateResponse,
+    control_filter_topic, device_filter_topic, firmware_filter_topic, health_check_topics,
+    software_filter_topic, ConfigUpdateRequest, ConfigUpdateResponse, DeviceProfileConfiguration,
+    DeviceProfileFirmware, DeviceProfileRequest, DeviceProfileResponse, FirmwareUpdateRequest,
+    FirmwareUpdateResponse, Jsonify, OperationStatus, RestartOperationRequest,
+    RestartOperationResponse, SoftwareListRequest, SoftwareListResponse, SoftwareRequestResponse,
+    SoftwareUpdateRequest, SoftwareUpdateResponse,
 };
 pub use software::*;
 
@@ -37,6 +40,22 @@ mod tests {
             SoftwareUpdateResponse::topic_name(),
             "tedge/commands/res/software/update"
         );
+        assert_eq!(
+            FirmwareUpdateRequest::topic_name(),
+            "tedge/commands/req/firmware/update"
+        );
+        assert_eq!(
+            FirmwareUpdateResponse::topic_name(),
+            "tedge/commands/res/firmware/update"
+        );
+        assert_eq!(
+            DeviceProfileRequest::topic_name(),
+            "tedge/commands/req/device/profile"
+        );
+        assert_eq!(
+            DeviceProfileResponse::topic_name(),
+            "tedge/commands/res/device/profile"
+        );
     }
 
     #[test]
@@ -37,6 +37,14 @@ pub const fn control_filter_topic() -> &'static str {
     "tedge/commands/req/control/#"
 }
 
+pub const fn firmware_filter_topic() -> &'static str {
+    "tedge/commands/req/firmware/#"
+}
+
+pub const fn device_filter_topic() -> &'static str {
+    "tedge/commands/req/device/#"
+}
+
 /// Message payload definition for SoftwareList request.
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(deny_unknown_fields)]
@@ -528,6 +536,327 @@ impl RestartOperationResponse {
     }
 }
 
+/// Message payload definition for firmware update request.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareUpdateRequest {
+    pub id: String,
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(flatten)]
+    pub url: Option<DownloadInfo>,
+}
+
+impl<'a> Jsonify<'a> for FirmwareUpdateRequest {}
+
+impl FirmwareUpdateRequest {
+    pub fn new(name: &str, version: Option<String>, url: Option<DownloadInfo>) -> Self {
+        let id = nanoid!();
+        FirmwareUpdateRequest {
+            id,
+            name: name.to_string(),
+            version,
+            url,
+        }
+    }
+
+    pub fn topic_name() -> &'static str {
+        "tedge/commands/req/firmware/update"
+    }
+}
+
+/// Message payload definition for firmware update response.
+///
+/// A successful response also describes the installed firmware,
+/// the device having possibly been restarted since the request.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct FirmwareUpdateResponse {
+    pub id: String,
+    pub status: OperationStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl<'a> Jsonify<'a> for FirmwareUpdateResponse {}
+
+impl FirmwareUpdateResponse {
+    pub fn new(req: &FirmwareUpdateRequest) -> Self {
+        Self::new_with_id(&req.id)
+    }
+
+    pub fn new_with_id(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            status: OperationStatus::Executing,
+            reason: None,
+            name: None,
+            version: None,
+            url: None,
+        }
+    }
+
+    pub fn with_status(self, status: OperationStatus) -> Self {
+        Self { status, ..self }
+    }
+
+    pub fn with_firmware(self, name: &str, version: Option<String>, url: Option<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            version,
+            url,
+            ..self
+        }
+    }
+
+    pub fn with_error(self, reason: &str) -> Self {
+        Self {
+            status: OperationStatus::Failed,
+            reason: Some(reason.into()),
+            ..self
+        }
+    }
+
+    pub fn topic_name() -> &'static str {
+        "tedge/commands/res/firmware/update"
+    }
+
+    pub fn status(&self) -> OperationStatus {
+        self.status
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
+/// Firmware to be installed by a device profile.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfileFirmware {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(flatten)]
+    pub url: Option<DownloadInfo>,
+}
+
+/// Configuration file to be updated by a device profile.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfileConfiguration {
+    #[serde(rename = "type")]
+    pub config_type: String,
+
+    #[serde(flatten)]
+    pub url: DownloadInfo,
+}
+
+/// Message payload definition for device profile request.
+///
+/// The firmware, software and configuration updates are applied in that order.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfileRequest {
+    pub id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<DeviceProfileFirmware>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub software: Vec<SoftwareRequestResponseSoftwareList>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub configuration: Vec<DeviceProfileConfiguration>,
+}
+
+impl<'a> Jsonify<'a> for DeviceProfileRequest {}
+
+impl Default for DeviceProfileRequest {
+    fn default() -> DeviceProfileRequest {
+        let id = nanoid!();
+        DeviceProfileRequest {
+            id,
+            firmware: None,
+            software: vec![],
+            configuration: vec![],
+        }
+    }
+}
+
+impl DeviceProfileRequest {
+    pub fn topic_name() -> &'static str {
+        "tedge/commands/req/device/profile"
+    }
+
+    pub fn firmware_request(&self) -> Option<FirmwareUpdateRequest> {
+        self.firmware
+            .as_ref()
+            .map(|firmware| FirmwareUpdateRequest {
+                id: self.id.clone(),
+                name: firmware.name.clone(),
+                version: firmware.version.clone(),
+                url: firmware.url.clone(),
+            })
+    }
+
+    pub fn software_request(&self) -> Option<SoftwareUpdateRequest> {
+        if self.software.is_empty() {
+            None
+        } else {
+            Some(SoftwareUpdateRequest {
+                id: self.id.clone(),
+                update_list: self.software.clone(),
+            })
+        }
+    }
+
+    pub fn config_requests(&self) -> Vec<ConfigUpdateRequest> {
+        self.configuration
+            .iter()
+            .enumerate()
+            .map(|(i, config)| ConfigUpdateRequest {
+                id: format!("{}-{}", self.id, i),
+                config_type: config.config_type.clone(),
+                url: config.url.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Message payload definition for device profile response.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct DeviceProfileResponse {
+    pub id: String,
+    pub status: OperationStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl<'a> Jsonify<'a> for DeviceProfileResponse {}
+
+impl DeviceProfileResponse {
+    pub fn new(req: &DeviceProfileRequest) -> Self {
+        Self::new_with_id(&req.id)
+    }
+
+    pub fn new_with_id(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            status: OperationStatus::Executing,
+            reason: None,
+        }
+    }
+
+    pub fn with_status(self, status: OperationStatus) -> Self {
+        Self { status, ..self }
+    }
+
+    pub fn with_error(self, reason: &str) -> Self {
+        Self {
+            status: OperationStatus::Failed,
+            reason: Some(reason.into()),
+            ..self
+        }
+    }
+
+    pub fn topic_name() -> &'static str {
+        "tedge/commands/res/device/profile"
+    }
+
+    pub fn status(&self) -> OperationStatus {
+        self.status
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
+/// Message payload definition for configuration update request.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigUpdateRequest {
+    pub id: String,
+
+    #[serde(rename = "type")]
+    pub config_type: String,
+
+    #[serde(flatten)]
+    pub url: DownloadInfo,
+}
+
+impl<'a> Jsonify<'a> for ConfigUpdateRequest {}
+
+impl ConfigUpdateRequest {
+    pub fn topic_name() -> &'static str {
+        "tedge/commands/req/config/update"
+    }
+}
+
+/// Message payload definition for configuration update response.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ConfigUpdateResponse {
+    pub id: String,
+    pub status: OperationStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl<'a> Jsonify<'a> for ConfigUpdateResponse {}
+
+impl ConfigUpdateResponse {
+    pub fn new(req: &ConfigUpdateRequest) -> Self {
+        Self {
+            id: req.id.clone(),
+            status: OperationStatus::Executing,
+            reason: None,
+        }
+    }
+
+    pub fn with_status(self, status: OperationStatus) -> Self {
+        Self { status, ..self }
+    }
+
+    pub fn with_error(self, reason: &str) -> Self {
+        Self {
+            status: OperationStatus::Failed,
+            reason: Some(reason.into()),
+            ..self
+        }
+    }
+
+    pub fn topic_name() -> &'static str {
+        "tedge/commands/res/config/update"
+    }
+
+    pub fn status(&self) -> OperationStatus {
+        self.status
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -651,4 +980,107 @@ mod tests {
             .expect("Fail to parse the json request");
         assert_eq!(parsed_request, request);
     }
+
+    #[test]
+    fn serde_firmware_update_request() {
+        let request = FirmwareUpdateRequest {
+            id: "1234".to_string(),
+            name: "raspberrypi-bootloader".to_string(),
+            version: Some("1.20140107-1".to_string()),
+            url: Some("https://example.com/firmware.bin".into()),
+        };
+
+        let expected_json = r#"{"id":"1234","name":"raspberrypi-bootloader","version":"1.20140107-1","url":"https://example.com/firmware.bin"}"#;
+
+        let actual_json = request.to_json().expect("Fail to serialize the request");
+        assert_eq!(actual_json, expected_json);
+
+        let parsed_request =
+            FirmwareUpdateRequest::from_json(&actual_json).expect("Fail to parse the json request");
+        assert_eq!(parsed_request, request);
+    }
+
+    #[test]
+    fn serde_firmware_update_failed_response() {
+        let request = FirmwareUpdateRequest::new("firmware", None, None);
+        let response = FirmwareUpdateResponse::new(&request).with_error("Verification failed");
+
+        let expected_json = format!(
+            r#"{{"id":"{}","status":"failed","reason":"Verification failed"}}"#,
+            request.id
+        );
+
+        let actual_json = response.to_json().expect("Fail to serialize the response");
+        assert_eq!(actual_json, expected_json);
+
+        let parsed_response = FirmwareUpdateResponse::from_json(&actual_json)
+            .expect("Fail to parse the json response");
+        assert_eq!(parsed_response, response);
+    }
+
+    #[test]
+    fn serde_firmware_update_successful_response() {
+        let response = FirmwareUpdateResponse::new_with_id("1234")
+            .with_status(OperationStatus::Successful)
+            .with_firmware(
+                "raspberrypi-bootloader",
+                Some("1.20140107-1".into()),
+                Some("https://example.com/firmware.bin".into()),
+            );
+
+        let expected_json = r#"{"id":"1234","status":"successful","name":"raspberrypi-bootloader","version":"1.20140107-1","url":"https://example.com/firmware.bin"}"#;
+
+        let actual_json = response.to_json().expect("Fail to serialize the response");
+        assert_eq!(actual_json, expected_json);
+
+        let parsed_response = FirmwareUpdateResponse::from_json(&actual_json)
+            .expect("Fail to parse the json response");
+        assert_eq!(parsed_response, response);
+    }
+
+    #[test]
+    fn serde_device_profile_request() {
+        let json_request = r#"{
+            "id": "1234",
+            "firmware": {"name": "bootloader", "version": "1.0", "url": "https://example.com/bootloader.bin"},
+            "software": [{"type": "apt", "modules": [{"name": "collectd", "version": "5.12", "action": "install"}]}],
+            "configuration": [{"type": "mosquitto.conf", "url": "https://example.com/mosquitto.conf"}]
+        }"#;
+
+        let request =
+            DeviceProfileRequest::from_json(json_request).expect("Fail to parse the json request");
+
+        let firmware = request.firmware_request().expect("a firmware request");
+        assert_eq!(firmware.id, "1234");
+        assert_eq!(firmware.name, "bootloader");
+        assert_eq!(firmware.version, Some("1.0".into()));
+
+        let software = request.software_request().expect("a software request");
+        assert_eq!(software.id, "1234");
+        assert_eq!(software.modules_types(), vec!["apt".to_string()]);
+
+        let configs = request.config_requests();
+        assert_eq!(
+            configs,
+            vec![ConfigUpdateRequest {
+                id: "1234-0".into(),
+                config_type: "mosquitto.conf".into(),
+                url: "https://example.com/mosquitto.conf".into(),
+            }]
+        );
+        assert_eq!(
+            configs[0].to_json().unwrap(),
+            r#"{"id":"1234-0","type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}"#
+        );
+    }
+
+    #[test]
+    fn a_device_profile_request_can_be_empty() {
+        let request =
+            DeviceProfileRequest::from_json(r#"{"id":"1234"}"#).expect("Fail to parse the request");
+
+        assert_eq!(request.firmware_request(), None);
+        assert_eq!(request.software_request(), None);
+        assert!(request.config_requests().is_empty());
+    }
 }
@@ -5,6 +5,8 @@ pub enum ResponseTopic {
     SoftwareListResponse,
     SoftwareUpdateResponse,
     RestartResponse,
+    FirmwareUpdateResponse,
+    DeviceProfileResponse,
 }
 
 impl ResponseTopic {
@@ -13,6 +15,8 @@ impl ResponseTopic {
             Self::SoftwareListResponse => r#"tedge/commands/res/software/list"#,
             Self::SoftwareUpdateResponse => r#"tedge/commands/res/software/update"#,
             Self::RestartResponse => r#"tedge/commands/res/control/restart"#,
+            Self::FirmwareUpdateResponse => r#"tedge/commands/res/firmware/update"#,
+            Self::DeviceProfileResponse => r#"tedge/commands/res/device/profile"#,
         }
     }
 }
@@ -25,6 +29,8 @@ impl TryFrom<String> for ResponseTopic {
             r#"tedge/commands/res/software/list"# => Ok(ResponseTopic::SoftwareListResponse),
             r#"tedge/commands/res/software/update"# => Ok(ResponseTopic::SoftwareUpdateResponse),
             r#"tedge/commands/res/control/restart"# => Ok(ResponseTopic::RestartResponse),
+            r#"tedge/commands/res/firmware/update"# => Ok(ResponseTopic::FirmwareUpdateResponse),
+            r#"tedge/commands/res/device/profile"# => Ok(ResponseTopic::DeviceProfileResponse),
             err => Err(TopicError::UnknownTopic {
                 topic: err.to_string(),
             }),
@@ -45,6 +51,8 @@ pub enum RequestTopic {
     SoftwareListRequest,
     SoftwareUpdateRequest,
     RestartRequest,
+    FirmwareUpdateRequest,
+    DeviceProfileRequest,
 }
 
 impl RequestTopic {
@@ -53,6 +61,8 @@ impl RequestTopic {
             Self::SoftwareListRequest => r#"tedge/commands/req/software/list"#,
             Self::SoftwareUpdateRequest => r#"tedge/commands/req/software/update"#,
             Self::RestartRequest => r#"tedge/commands/req/control/restart"#,
+            Self::FirmwareUpdateRequest => r#"tedge/commands/req/firmware/update"#,
+            Self::DeviceProfileRequest => r#"tedge/commands/req/device/profile"#,
         }
     }
 }
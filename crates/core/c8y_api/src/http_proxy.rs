@@ -35,6 +35,15 @@ pub trait C8YHttpProxy: Send + Sync {
         c8y_event: C8yCreateEvent,
     ) -> Result<String, SMCumulocityMapperError>;
 
+    /// Create measurements over HTTP, when too large to be sent over MQTT.
+    ///
+    /// The measurements are given in Cumulocity JSON,
+    /// with an `externalSource` if they are related to a child device.
+    async fn send_measurements(
+        &mut self,
+        c8y_measurements: serde_json::Value,
+    ) -> Result<(), SMCumulocityMapperError>;
+
     async fn send_software_list_http(
         &mut self,
         c8y_software_list: &C8yUpdateSoftwareListResponse,
@@ -90,13 +99,24 @@ impl C8yEndPoint {
     }
 
     fn get_url_for_get_id(&self) -> String {
+        self.get_url_for_external_id(&self.device_id)
+    }
+
+    fn get_url_for_external_id(&self, external_id: &str) -> String {
         let mut url_get_id = self.get_base_url();
         url_get_id.push_str("/identity/externalIds/c8y_Serial/");
-        url_get_id.push_str(&self.device_id);
+        url_get_id.push_str(external_id);
 
         url_get_id
     }
 
+    fn get_url_for_create_measurements(&self) -> String {
+        let mut url_create_measurements = self.get_base_url();
+        url_create_measurements.push_str("/measurement/measurements/");
+
+        url_create_measurements
+    }
+
     fn get_url_for_create_event(&self) -> String {
         let mut url_create_event = self.get_base_url();
         url_create_event.push_str("/event/events/");
@@ -255,8 +275,15 @@ impl JwtAuthHttpProxy {
     }
 
     async fn try_get_internal_id(&mut self) -> Result<String, SMCumulocityMapperError> {
-        let token = self.get_jwt_token().await?;
         let url_get_id = self.end_point.get_url_for_get_id();
+        self.try_get_internal_id_from_url(url_get_id).await
+    }
+
+    async fn try_get_internal_id_from_url(
+        &mut self,
+        url_get_id: String,
+    ) -> Result<String, SMCumulocityMapperError> {
+        let token = self.get_jwt_token().await?;
 
         let internal_id = self
             .http_con
@@ -353,6 +380,46 @@ impl C8YHttpProxy for JwtAuthHttpProxy {
         self.send_event_internal(c8y_event).await
     }
 
+    async fn send_measurements(
+        &mut self,
+        mut c8y_measurements: serde_json::Value,
+    ) -> Result<(), SMCumulocityMapperError> {
+        if let Some(measurements) = c8y_measurements.as_object_mut() {
+            // The external source of a child device is only understood over MQTT
+            let external_id = measurements
+                .remove("externalSource")
+                .and_then(|source| source.get("externalId").cloned())
+                .and_then(|id| id.as_str().map(str::to_string));
+            let source_id = match external_id {
+                Some(child_id) => {
+                    let url_get_id = self.end_point.get_url_for_external_id(&child_id);
+                    self.try_get_internal_id_from_url(url_get_id).await?
+                }
+                None => self.end_point.c8y_internal_id.clone(),
+            };
+            measurements
+                .entry("source")
+                .or_insert_with(|| serde_json::json!({ "id": source_id }));
+        }
+
+        let token = self.get_jwt_token().await?;
+        let create_measurements_url = self.end_point.get_url_for_create_measurements();
+
+        let request = self
+            .http_con
+            .post(create_measurements_url)
+            .json(&c8y_measurements)
+            .bearer_auth(token.token())
+            .header("Accept", "application/json")
+            .timeout(Duration::from_millis(10000))
+            .build()?;
+
+        let response = self.http_con.execute(request).await?;
+        let _ = response.error_for_status_ref()?;
+
+        Ok(())
+    }
+
     async fn send_software_list_http(
         &mut self,
         c8y_software_list: &C8yUpdateSoftwareListResponse,
@@ -565,6 +632,80 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn send_measurements() -> anyhow::Result<()> {
+        let device_id = "test-device";
+
+        // Mock endpoint to return C8Y internal id
+        let _get_internal_id_mock = mock("GET", "/identity/externalIds/c8y_Serial/test-device")
+            .with_status(200)
+            .with_body(
+                json!({ "externalId": device_id, "managedObject": { "id": "123" } }).to_string(),
+            )
+            .create();
+
+        let _get_child_id_mock = mock("GET", "/identity/externalIds/c8y_Serial/child1")
+            .with_status(200)
+            .with_body(
+                json!({ "externalId": "child1", "managedObject": { "id": "789" } }).to_string(),
+            )
+            .create();
+
+        let create_measurements_mock = mock("POST", "/measurement/measurements/")
+            .match_body(Matcher::Json(json!({
+                "type": "ThinEdgeMeasurement",
+                "temperature": {"temperature": {"value": 23.0}},
+                "source": {"id": "123"}
+            })))
+            .with_status(201)
+            .create();
+
+        let create_child_measurements_mock = mock("POST", "/measurement/measurements/")
+            .match_body(Matcher::Json(json!({
+                "type": "ThinEdgeMeasurement",
+                "pressure": {"pressure": {"value": 1.0}},
+                "source": {"id": "789"}
+            })))
+            .with_status(201)
+            .create();
+
+        // An JwtAuthHttpProxy ...
+        let mut jwt_token_retriver = Box::new(MockC8yJwtTokenRetriever::new());
+        jwt_token_retriver
+            .expect_get_jwt_token()
+            .returning(|| Ok(SmartRestJwtResponse::default()));
+
+        let http_client = reqwest::ClientBuilder::new().build().unwrap();
+        let mut http_proxy = JwtAuthHttpProxy::new(
+            jwt_token_retriver,
+            http_client,
+            mockito::server_url().as_str(),
+            device_id,
+        );
+        http_proxy.init().await?;
+
+        // ... creates the measurements of the device
+        http_proxy
+            .send_measurements(json!({
+                "type": "ThinEdgeMeasurement",
+                "temperature": {"temperature": {"value": 23.0}}
+            }))
+            .await?;
+        create_measurements_mock.assert();
+
+        // ... and of its child devices, using their internal id
+        http_proxy
+            .send_measurements(json!({
+                "type": "ThinEdgeMeasurement",
+                "externalSource": {"externalId": "child1", "type": "c8y_Serial"},
+                "pressure": {"pressure": {"value": 1.0}}
+            }))
+            .await?;
+        create_child_measurements_mock.assert();
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn upload_config_file() -> anyhow::Result<()> {
         let device_id = "test-device";
@@ -1,14 +1,20 @@
 use std::collections::HashMap;
 
 use agent_interface::{
-    Jsonify, SoftwareListResponse, SoftwareModule, SoftwareType, SoftwareVersion,
+    DeviceProfileConfiguration, DeviceProfileFirmware, DeviceProfileRequest, Jsonify,
+    SoftwareListResponse, SoftwareModule, SoftwareType, SoftwareVersion,
 };
 
-use c8y_smartrest::error::SMCumulocityMapperError;
+use c8y_smartrest::error::{SMCumulocityMapperError, SmartRestDeserializerError};
+use c8y_smartrest::smartrest_deserializer::{
+    SmartRestUpdateSoftware, SmartRestUpdateSoftwareModule,
+};
 use download::DownloadInfo;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use thin_edge_json::alarm::{AlarmSeverity, ThinEdgeAlarm};
 use thin_edge_json::event::ThinEdgeEvent;
+use thin_edge_json::location::{ThinEdgeLocation, ThinEdgeLocationData};
 use time::OffsetDateTime;
 
 const EMPTY_STRING: &str = "";
@@ -161,6 +167,216 @@ impl TryFrom<ThinEdgeEvent> for C8yCreateEvent {
 
 impl<'a> Jsonify<'a> for C8yCreateEvent {}
 
+/// Standard `c8y_Position` fragment, set on the device and attached to its `c8y_LocationUpdate` events.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct C8yPosition {
+    pub lat: f64,
+    pub lng: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+}
+
+impl From<&ThinEdgeLocationData> for C8yPosition {
+    fn from(location: &ThinEdgeLocationData) -> Self {
+        C8yPosition {
+            lat: location.lat,
+            lng: location.lng,
+            alt: location.alt,
+            accuracy: location.accuracy,
+        }
+    }
+}
+
+impl C8yPosition {
+    /// The inventory update setting the `c8y_Position` of a device.
+    pub fn to_inventory_json(&self) -> Value {
+        json!({ "c8y_Position": self })
+    }
+}
+
+impl From<ThinEdgeLocation> for C8yCreateEvent {
+    fn from(location: ThinEdgeLocation) -> Self {
+        let position = C8yPosition::from(&location.data);
+        let mut extras = HashMap::new();
+        extras.insert("c8y_Position".into(), json!(position));
+        if let Some(child_id) = location.child_id {
+            extras.insert(
+                "externalSource".into(),
+                json!({"externalId": child_id, "type": "c8y_Serial"}),
+            );
+        }
+
+        Self {
+            source: None,
+            event_type: "c8y_LocationUpdate".into(),
+            time: location.data.time.unwrap_or_else(OffsetDateTime::now_utc),
+            text: "Location updated".into(),
+            extras,
+        }
+    }
+}
+
+/// A Cumulocity alarm, as sent over MQTT when it carries custom fragments that SmartREST cannot express.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct C8yCreateAlarm {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<C8yManagedObject>,
+
+    #[serde(rename = "type")]
+    pub alarm_type: String,
+
+    pub severity: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
+
+    pub text: String,
+
+    #[serde(flatten)]
+    pub extras: HashMap<String, Value>,
+}
+
+impl From<ThinEdgeAlarm> for C8yCreateAlarm {
+    fn from(alarm: ThinEdgeAlarm) -> Self {
+        let severity = match alarm.severity {
+            AlarmSeverity::Critical => "CRITICAL",
+            AlarmSeverity::Major => "MAJOR",
+            AlarmSeverity::Minor => "MINOR",
+            AlarmSeverity::Warning => "WARNING",
+        };
+        let alarm_type = alarm.name;
+        let (text, time, extras) = match alarm.data {
+            None => (
+                alarm_type.clone(),
+                OffsetDateTime::now_utc(),
+                HashMap::new(),
+            ),
+            Some(alarm_data) => (
+                alarm_data.text.unwrap_or_else(|| alarm_type.clone()),
+                alarm_data.time.unwrap_or_else(OffsetDateTime::now_utc),
+                alarm_data.extras,
+            ),
+        };
+
+        Self {
+            source: None,
+            alarm_type,
+            severity: severity.into(),
+            time,
+            text,
+            extras,
+        }
+    }
+}
+
+/// A `c8y_DeviceProfile` operation, as received on `c8y/devicecontrol/notifications`.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct C8yDeviceProfileOperation {
+    #[serde(rename = "c8y_DeviceProfile")]
+    pub device_profile: C8yDeviceProfile,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+pub struct C8yDeviceProfile {
+    #[serde(default)]
+    pub firmware: Option<C8yDeviceProfileFirmware>,
+
+    #[serde(default)]
+    pub software: Vec<C8yDeviceProfileSoftware>,
+
+    #[serde(default)]
+    pub configuration: Vec<C8yDeviceProfileConfiguration>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct C8yDeviceProfileFirmware {
+    pub name: String,
+
+    #[serde(default)]
+    pub version: String,
+
+    #[serde(default)]
+    pub url: String,
+}
+
+/// A software module of a device profile, the version following the `version::type` convention.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct C8yDeviceProfileSoftware {
+    pub name: String,
+    pub version: Option<String>,
+    pub url: Option<String>,
+    pub action: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct C8yDeviceProfileConfiguration {
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(rename = "type")]
+    pub config_type: String,
+
+    pub url: String,
+}
+
+impl C8yDeviceProfileOperation {
+    pub fn to_thin_edge_json(&self) -> Result<DeviceProfileRequest, SmartRestDeserializerError> {
+        let profile = &self.device_profile;
+
+        let firmware = profile
+            .firmware
+            .as_ref()
+            .map(|firmware| DeviceProfileFirmware {
+                name: firmware.name.clone(),
+                version: non_empty(&firmware.version).map(String::from),
+                url: non_empty(&firmware.url).map(DownloadInfo::new),
+            });
+
+        // The software modules are given as in a `c8y_SoftwareUpdate` operation
+        let software = SmartRestUpdateSoftware {
+            update_list: profile
+                .software
+                .iter()
+                .map(|module| SmartRestUpdateSoftwareModule {
+                    software: module.name.clone(),
+                    version: module.version.clone(),
+                    url: module.url.clone(),
+                    action: module.action.clone(),
+                })
+                .collect(),
+            ..SmartRestUpdateSoftware::default()
+        }
+        .to_thin_edge_json()?;
+
+        let configuration = profile
+            .configuration
+            .iter()
+            .map(|config| DeviceProfileConfiguration {
+                config_type: config.config_type.clone(),
+                url: DownloadInfo::new(&config.url),
+            })
+            .collect();
+
+        Ok(DeviceProfileRequest {
+            firmware,
+            software: software.update_list,
+            configuration,
+            ..DeviceProfileRequest::default()
+        })
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    if value.trim().is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
 fn combine_version_and_type(
     version: &Option<SoftwareVersion>,
     module_type: &Option<SoftwareType>,
@@ -197,6 +413,7 @@ mod tests {
     use anyhow::Result;
     use assert_matches::assert_matches;
     use test_case::test_case;
+    use thin_edge_json::alarm::ThinEdgeAlarmData;
     use thin_edge_json::event::ThinEdgeEventData;
     use time::macros::datetime;
 
@@ -449,4 +666,154 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn location_translation() {
+        let location = ThinEdgeLocation {
+            child_id: None,
+            data: ThinEdgeLocationData {
+                lat: 52.52,
+                lng: 13.405,
+                alt: Some(34.0),
+                accuracy: None,
+                time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+            },
+        };
+
+        let position = C8yPosition::from(&location.data);
+        assert_eq!(
+            position.to_inventory_json(),
+            json!({"c8y_Position": {"lat": 52.52, "lng": 13.405, "alt": 34.0}})
+        );
+
+        let event = C8yCreateEvent::from(location);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "type": "c8y_LocationUpdate",
+                "time": "2021-04-23T19:00:00+05:00",
+                "text": "Location updated",
+                "c8y_Position": {"lat": 52.52, "lng": 13.405, "alt": 34.0}
+            })
+        );
+    }
+
+    #[test]
+    fn child_location_translation() {
+        let location = ThinEdgeLocation {
+            child_id: Some("child1".into()),
+            data: ThinEdgeLocationData {
+                lat: 52.52,
+                lng: 13.405,
+                alt: None,
+                accuracy: Some(5.0),
+                time: None,
+            },
+        };
+
+        let event = C8yCreateEvent::from(location);
+        assert_eq!(
+            event.extras.get("externalSource"),
+            Some(&json!({"externalId": "child1", "type": "c8y_Serial"}))
+        );
+        assert_eq!(
+            event.extras.get("c8y_Position"),
+            Some(&json!({"lat": 52.52, "lng": 13.405, "accuracy": 5.0}))
+        );
+    }
+
+    #[test]
+    fn alarm_with_custom_fragments_translation() {
+        let alarm = ThinEdgeAlarm {
+            name: "temperature_alarm".into(),
+            severity: AlarmSeverity::Major,
+            data: Some(ThinEdgeAlarmData {
+                text: Some("Temperature high".into()),
+                time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+                extras: HashMap::from([("threshold".to_string(), json!(80))]),
+            }),
+        };
+
+        let c8y_alarm = C8yCreateAlarm::from(alarm);
+        assert_eq!(
+            serde_json::to_value(&c8y_alarm).unwrap(),
+            json!({
+                "type": "temperature_alarm",
+                "severity": "MAJOR",
+                "time": "2021-04-23T19:00:00+05:00",
+                "text": "Temperature high",
+                "threshold": 80
+            })
+        );
+    }
+
+    #[test]
+    fn alarm_without_text_is_named_after_its_type() {
+        let alarm = ThinEdgeAlarm {
+            name: "temperature_alarm".into(),
+            severity: AlarmSeverity::Warning,
+            data: Some(ThinEdgeAlarmData {
+                text: None,
+                time: None,
+                extras: HashMap::from([("threshold".to_string(), json!(80))]),
+            }),
+        };
+
+        let c8y_alarm = C8yCreateAlarm::from(alarm);
+        assert_eq!(c8y_alarm.text, "temperature_alarm");
+        assert_eq!(c8y_alarm.severity, "WARNING");
+    }
+
+    #[test]
+    fn from_c8y_device_profile_to_thin_edge_json() {
+        let operation = r#"{
+            "id": "123",
+            "deviceId": "456",
+            "c8y_DeviceProfile": {
+                "firmware": {"name": "bootloader", "version": "1.0", "url": ""},
+                "software": [
+                    {"name": "collectd", "version": "5.12::apt", "url": "", "action": "install"},
+                    {"name": "nodered", "version": "", "url": "", "action": "delete"}
+                ],
+                "configuration": [
+                    {"name": "mosquitto", "type": "mosquitto.conf", "url": "https://example.com/mosquitto.conf"}
+                ]
+            }
+        }"#;
+
+        let operation: C8yDeviceProfileOperation = serde_json::from_str(operation).unwrap();
+        let request = operation.to_thin_edge_json().unwrap();
+
+        assert_eq!(
+            request.firmware,
+            Some(DeviceProfileFirmware {
+                name: "bootloader".into(),
+                version: Some("1.0".into()),
+                url: None,
+            })
+        );
+        let software = request.software_request().unwrap();
+        assert_eq!(
+            software.modules_types(),
+            vec!["apt".to_string(), "default".to_string()]
+        );
+        assert_eq!(
+            request.configuration,
+            vec![DeviceProfileConfiguration {
+                config_type: "mosquitto.conf".into(),
+                url: DownloadInfo::new("https://example.com/mosquitto.conf"),
+            }]
+        );
+    }
+
+    #[test]
+    fn device_profile_with_unknown_software_action_is_rejected() {
+        let operation = r#"{"c8y_DeviceProfile": {
+            "software": [{"name": "collectd", "version": "5.12", "action": "upgrade"}]
+        }}"#;
+
+        let operation: C8yDeviceProfileOperation = serde_json::from_str(operation).unwrap();
+
+        assert!(operation.to_thin_edge_json().is_err());
+    }
 }
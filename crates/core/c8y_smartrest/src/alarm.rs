@@ -38,6 +38,7 @@ mod tests {
     use super::*;
     use assert_matches::assert_matches;
     use serde::Deserialize;
+    use std::collections::HashMap;
     use test_case::test_case;
     use thin_edge_json::alarm::ThinEdgeAlarmData;
     use time::macros::datetime;
@@ -49,6 +50,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: Some("I raised it".into()),
                 time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+                extras: HashMap::new(),
             }),
         },
         "301,temperature_alarm,\"I raised it\",2021-04-23T19:00:00+05:00"
@@ -61,6 +63,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: Some("I raised it".into()),
                 time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+                extras: HashMap::new(),
             }),
         },
         "302,temperature_alarm,\"I raised it\",2021-04-23T19:00:00+05:00"
@@ -73,6 +76,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: None,
                 time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+                extras: HashMap::new(),
             }),
         },
         "303,temperature_alarm,\"\",2021-04-23T19:00:00+05:00"
@@ -85,6 +89,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: Some("I, raised, it".into()),
                 time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+                extras: HashMap::new(),
             }),
         },
         "304,temperature_alarm,\"I, raised, it\",2021-04-23T19:00:00+05:00"
@@ -121,6 +126,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: Some("I raised it".into()),
                 time: None,
+                extras: HashMap::new(),
             }),
         };
 
@@ -2,9 +2,11 @@ use std::{
     collections::{HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use serde::Deserialize;
+use tracing::warn;
 
 use crate::error::OperationsError;
 
@@ -19,6 +21,10 @@ pub struct OnMessageExec {
     on_message: Option<String>,
     topic: Option<String>,
     user: Option<String>,
+    group: Option<String>,
+    working_dir: Option<PathBuf>,
+    env: Option<HashMap<String, String>>,
+    timeout: Option<u64>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq)]
@@ -41,6 +47,33 @@ impl Operation {
     pub fn topic(&self) -> Option<String> {
         self.exec().and_then(|exec| exec.topic.clone())
     }
+
+    /// The user the command is run as, instead of the mapper user.
+    pub fn user(&self) -> Option<&str> {
+        self.exec().and_then(|exec| exec.user.as_deref())
+    }
+
+    /// The group the command is run as, instead of the primary group of the user.
+    pub fn group(&self) -> Option<&str> {
+        self.exec().and_then(|exec| exec.group.as_deref())
+    }
+
+    /// The working directory of the command.
+    pub fn working_dir(&self) -> Option<&Path> {
+        self.exec().and_then(|exec| exec.working_dir.as_deref())
+    }
+
+    /// The environment variables set for the command, on top of the mapper environment.
+    pub fn env(&self) -> Option<&HashMap<String, String>> {
+        self.exec().and_then(|exec| exec.env.as_ref())
+    }
+
+    /// The maximum duration of the command, after which the operation is killed and marked as failed.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.exec()
+            .and_then(|exec| exec.timeout)
+            .map(Duration::from_secs)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -59,14 +92,31 @@ impl Default for Operations {
 }
 
 impl Operations {
+    /// Add an operation, replacing any previous operation with the same name.
     pub fn add(&mut self, operation: Operation) {
-        if let Some(detail) = operation.exec() {
-            if let Some(on_message) = &detail.on_message {
-                self.operations_by_trigger
-                    .insert(on_message.clone(), self.operations.len());
-            }
-        }
+        self.operations.retain(|op| op.name != operation.name);
         self.operations.push(operation);
+        self.index_operations_by_trigger();
+    }
+
+    /// Remove the operation with the given name, if any.
+    pub fn remove(&mut self, operation_name: &str) {
+        self.operations.retain(|op| op.name != operation_name);
+        self.index_operations_by_trigger();
+    }
+
+    fn index_operations_by_trigger(&mut self) {
+        self.operations_by_trigger = self
+            .operations
+            .iter()
+            .enumerate()
+            .filter_map(|(index, operation)| {
+                operation
+                    .exec()
+                    .and_then(|exec| exec.on_message.clone())
+                    .map(|on_message| (on_message, index))
+            })
+            .collect();
     }
 
     pub fn try_new(dir: impl AsRef<Path>, cloud_name: &str) -> Result<Self, OperationsError> {
@@ -106,25 +156,55 @@ fn get_operations(dir: impl AsRef<Path>, cloud_name: &str) -> Result<Operations,
         .collect::<Result<Vec<PathBuf>, _>>()?
         .into_iter()
         .filter(|path| path.is_file())
+        .filter(|path| {
+            let valid = is_valid_operation_path(path);
+            if !valid {
+                warn!("Ignoring {}: not named after an operation", path.display());
+            }
+            valid
+        })
         .collect::<Vec<PathBuf>>();
 
     for path in dir_entries {
-        let mut details = match fs::read(&path) {
-            Ok(bytes) => toml::from_slice::<Operation>(bytes.as_slice())
-                .map_err(|e| OperationsError::TomlError(path.to_path_buf(), e))?,
-
-            Err(err) => return Err(OperationsError::FromIo(err)),
-        };
+        operations.add(get_operation(&path)?);
+    }
+    Ok(operations)
+}
 
-        details.name = path
-            .file_name()
-            .and_then(|filename| filename.to_str())
-            .ok_or_else(|| OperationsError::InvalidOperationName(path.to_owned()))?
-            .to_owned();
+/// Check that a file of the operations directory is named after an operation,
+/// skipping the hidden, backup and temporary files created by editors or package managers.
+///
+/// An operation name starts with a letter, followed by letters, digits, `_` or `-`.
+pub fn is_valid_operation_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|filename| filename.to_str())
+        .map(is_valid_operation_name)
+        .unwrap_or(false)
+}
 
-        operations.add(details);
+fn is_valid_operation_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }
+        _ => false,
     }
-    Ok(operations)
+}
+
+/// Read the definition of an operation from the given file, named after the operation.
+pub fn get_operation(path: &Path) -> Result<Operation, OperationsError> {
+    let bytes = fs::read(path)?;
+    let mut operation = toml::from_slice::<Operation>(bytes.as_slice())
+        .map_err(|e| OperationsError::TomlError(path.to_path_buf(), e))?;
+
+    operation.name = path
+        .file_name()
+        .and_then(|filename| filename.to_str())
+        .ok_or_else(|| OperationsError::InvalidOperationName(path.to_owned()))?
+        .to_owned();
+
+    Ok(operation)
 }
 
 #[cfg(test)]
@@ -218,4 +298,125 @@ mod tests {
 
         assert_eq!(operations.operations.len(), ops_count);
     }
+
+    #[test]
+    fn adding_an_operation_replaces_the_operation_with_the_same_name() {
+        let test_operations = TestOperations::builder().with_operations(2).build();
+        let mut operations = get_operations(test_operations.temp_dir(), "").unwrap();
+
+        let path = test_operations.temp_dir().path().join("operation0");
+        fs::write(
+            &path,
+            br#"[exec]
+                command = "echo"
+                on_message = "512""#,
+        )
+        .unwrap();
+        operations.add(get_operation(&path).unwrap());
+
+        assert_eq!(operations.operations.len(), 2);
+        assert_eq!(
+            operations
+                .matching_smartrest_template("512")
+                .map(|op| op.name.as_str()),
+            Some("operation0")
+        );
+    }
+
+    #[test_case("c8y_Restart", true)]
+    #[test_case("/etc/tedge/operations/c8y/c8y_Command", true)]
+    #[test_case("custom-op2", true)]
+    #[test_case(".c8y_Restart.swp", false)]
+    #[test_case("c8y_Restart~", false)]
+    #[test_case("c8y_Restart.dpkg-new", false)]
+    #[test_case("4913", false)]
+    fn operation_name_validity(name: &str, expected: bool) {
+        assert_eq!(is_valid_operation_path(Path::new(name)), expected);
+    }
+
+    #[test]
+    fn temporary_files_are_not_read_as_operations() {
+        let test_operations = TestOperations::builder().with_operations(1).build();
+        fs::write(
+            test_operations.temp_dir().path().join(".operation0.swp"),
+            "",
+        )
+        .unwrap();
+
+        let operations = get_operations(test_operations.temp_dir(), "").unwrap();
+        assert_eq!(operations.get_operations_list(), vec!["operation0"]);
+    }
+
+    #[test]
+    fn removing_an_operation() {
+        let test_operations = TestOperations::builder().with_operations(2).build();
+        let mut operations = get_operations(test_operations.temp_dir(), "").unwrap();
+
+        operations.remove("operation0");
+        operations.remove("unknown");
+
+        assert_eq!(operations.get_operations_list(), vec!["operation1"]);
+        assert_eq!(
+            operations
+                .matching_smartrest_template("511")
+                .map(|op| op.name.as_str()),
+            Some("operation1")
+        );
+    }
+
+    #[test]
+    fn operation_timeout_is_read_from_the_operation_file() {
+        let test_operations = TestOperations::builder().with_operations(1).build();
+        let path = test_operations
+            .temp_dir()
+            .path()
+            .join("operation_with_timeout");
+        fs::write(
+            &path,
+            br#"[exec]
+                command = "echo"
+                on_message = "511"
+                timeout = 10"#,
+        )
+        .unwrap();
+
+        let operation = get_operation(&path).unwrap();
+        assert_eq!(operation.timeout(), Some(Duration::from_secs(10)));
+
+        let operation =
+            get_operation(&test_operations.temp_dir().path().join("operation0")).unwrap();
+        assert_eq!(operation.timeout(), None);
+    }
+
+    #[test]
+    fn operation_execution_settings_are_read_from_the_operation_file() {
+        let test_operations = TestOperations::builder().with_operations(0).build();
+        let path = test_operations.temp_dir().path().join("operation_as_user");
+        fs::write(
+            &path,
+            br#"[exec]
+                command = "echo"
+                on_message = "511"
+                user = "operator"
+                group = "operators"
+                working_dir = "/var/lib/operator"
+
+                [exec.env]
+                LANG = "C"
+            "#,
+        )
+        .unwrap();
+
+        let operation = get_operation(&path).unwrap();
+        assert_eq!(operation.user(), Some("operator"));
+        assert_eq!(operation.group(), Some("operators"));
+        assert_eq!(
+            operation.working_dir(),
+            Some(Path::new("/var/lib/operator"))
+        );
+        assert_eq!(
+            operation.env(),
+            Some(&HashMap::from([("LANG".to_string(), "C".to_string())]))
+        );
+    }
 }
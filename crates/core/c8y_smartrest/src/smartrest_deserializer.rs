@@ -234,6 +234,37 @@ pub struct SmartRestRestartRequest {
 
 impl SmartRestRequestGeneric for SmartRestRestartRequest {}
 
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct SmartRestCommandRequest {
+    pub message_id: String,
+    pub device: String,
+    pub command: String,
+}
+
+impl SmartRestRequestGeneric for SmartRestCommandRequest {}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct SmartRestFirmwareRequest {
+    pub message_id: String,
+    pub device: String,
+    pub name: String,
+    pub version: String,
+    pub url: String,
+}
+
+impl SmartRestRequestGeneric for SmartRestFirmwareRequest {}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct SmartRestRemoteAccessConnect {
+    pub message_id: String,
+    pub device: String,
+    pub host: String,
+    pub port: u16,
+    pub key: String,
+}
+
+impl SmartRestRequestGeneric for SmartRestRemoteAccessConnect {}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct SmartRestConfigUploadRequest {
     pub message_id: String,
@@ -602,4 +633,45 @@ mod tests {
         };
         assert_eq!(request, expected_output);
     }
+
+    #[test_case("511,deviceId,uptime", "uptime")]
+    #[test_case(r#"511,deviceId,"ls -l /tmp, /var""#, "ls -l /tmp, /var")]
+    #[test_case(r#"511,deviceId,"echo ""hello""""#, r#"echo "hello""#)]
+    fn deserialize_smartrest_command_request(smartrest: &str, expected_command: &str) {
+        let request = SmartRestCommandRequest::from_smartrest(smartrest).unwrap();
+        let expected_output = SmartRestCommandRequest {
+            message_id: "511".to_string(),
+            device: "deviceId".to_string(),
+            command: expected_command.to_string(),
+        };
+        assert_eq!(request, expected_output);
+    }
+
+    #[test]
+    fn deserialize_smartrest_firmware_request() {
+        let smartrest = "515,deviceId,raspberrypi-bootloader,1.20140107-1,https://test.cumulocity.com/inventory/binaries/70208";
+        let request = SmartRestFirmwareRequest::from_smartrest(smartrest).unwrap();
+        let expected_output = SmartRestFirmwareRequest {
+            message_id: "515".to_string(),
+            device: "deviceId".to_string(),
+            name: "raspberrypi-bootloader".to_string(),
+            version: "1.20140107-1".to_string(),
+            url: "https://test.cumulocity.com/inventory/binaries/70208".to_string(),
+        };
+        assert_eq!(request, expected_output);
+    }
+
+    #[test]
+    fn deserialize_smartrest_remote_access_connect() {
+        let smartrest = "530,deviceId,127.0.0.1,22,eb0e5a0c-62a6-4a5c-a8ec-1e4b8b3d6a0f";
+        let request = SmartRestRemoteAccessConnect::from_smartrest(smartrest).unwrap();
+        let expected_output = SmartRestRemoteAccessConnect {
+            message_id: "530".to_string(),
+            device: "deviceId".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 22,
+            key: "eb0e5a0c-62a6-4a5c-a8ec-1e4b8b3d6a0f".to_string(),
+        };
+        assert_eq!(request, expected_output);
+    }
 }
@@ -14,6 +14,10 @@ pub enum CumulocitySupportedOperations {
     C8yRestartRequest,
     C8yUploadConfigFile,
     C8yDownloadConfigFile,
+    C8yCommand,
+    C8yFirmware,
+    C8yDeviceProfile,
+    C8yRemoteAccessConnect,
 }
 
 impl From<CumulocitySupportedOperations> for &'static str {
@@ -24,6 +28,10 @@ impl From<CumulocitySupportedOperations> for &'static str {
             CumulocitySupportedOperations::C8yRestartRequest => "c8y_Restart",
             CumulocitySupportedOperations::C8yUploadConfigFile => "c8y_UploadConfigFile",
             CumulocitySupportedOperations::C8yDownloadConfigFile => "c8y_DownloadConfigFile",
+            CumulocitySupportedOperations::C8yCommand => "c8y_Command",
+            CumulocitySupportedOperations::C8yFirmware => "c8y_Firmware",
+            CumulocitySupportedOperations::C8yDeviceProfile => "c8y_DeviceProfile",
+            CumulocitySupportedOperations::C8yRemoteAccessConnect => "c8y_RemoteAccessConnect",
         }
     }
 }
@@ -13,9 +13,9 @@
 //! let output = from_thin_edge_json(single_value_thin_edge_json);
 //! ```
 
-use crate::serializer;
+use crate::serializer::{self, TextMeasurementFallback};
 use clock::{Clock, WallClock};
-use thin_edge_json::parser::*;
+use thin_edge_json::{cbor::ThinEdgeCborParserError, parser::*, units::MeasurementUnits};
 use time::{self, OffsetDateTime};
 
 #[derive(thiserror::Error, Debug)]
@@ -25,6 +25,29 @@ pub enum CumulocityJsonError {
 
     #[error(transparent)]
     ThinEdgeJsonParserError(#[from] ThinEdgeJsonParserError),
+
+    #[error(transparent)]
+    ThinEdgeCborParserError(#[from] ThinEdgeCborParserError),
+}
+
+/// Cumulocity measurements translated from thin-edge JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum C8yMeasurements {
+    /// A single measurement, translated from a thin-edge JSON object
+    Single(String),
+
+    /// A `{"measurements":[...]}` collection, translated from an array of thin-edge JSON objects
+    ///
+    /// To be published on `c8y/measurement/measurements/createBulk`, as the measurement batches.
+    Collection(String),
+}
+
+impl C8yMeasurements {
+    pub fn into_string(self) -> String {
+        match self {
+            C8yMeasurements::Single(payload) | C8yMeasurements::Collection(payload) => payload,
+        }
+    }
 }
 
 /// Converts from thin-edge measurement JSON to C8Y measurement JSON
@@ -44,6 +67,42 @@ pub fn from_thin_edge_json_with_child(
     Ok(c8y_vec)
 }
 
+/// Converts from thin-edge Json to c8y_json, adding the known units to the measurements
+/// and sending the text measurements as specified by `text_fallback`
+///
+/// An array of thin-edge JSON objects is translated in one pass into a collection of measurements.
+pub fn from_thin_edge_json_with_settings(
+    input: &str,
+    maybe_child_id: Option<&str>,
+    units: &MeasurementUnits,
+    text_fallback: TextMeasurementFallback,
+) -> Result<C8yMeasurements, CumulocityJsonError> {
+    let timestamp = WallClock.now();
+    from_thin_edge_json_with_timestamp_and_settings(
+        input,
+        timestamp,
+        maybe_child_id,
+        units,
+        text_fallback,
+    )
+}
+
+/// Converts from CBOR-encoded thin-edge measurements to c8y_json,
+/// as `from_thin_edge_json_with_settings` does for thin-edge JSON
+pub fn from_thin_edge_cbor_with_settings(
+    input: &[u8],
+    maybe_child_id: Option<&str>,
+    units: &MeasurementUnits,
+    text_fallback: TextMeasurementFallback,
+) -> Result<C8yMeasurements, CumulocityJsonError> {
+    let timestamp = WallClock.now();
+    let mut serializer = serializer::C8yJsonSerializer::new(timestamp, maybe_child_id)
+        .with_units(units)
+        .with_text_fallback(text_fallback);
+    let () = thin_edge_json::cbor::parse_slice(input, &mut serializer)?;
+    into_c8y_measurements(serializer)
+}
+
 fn from_thin_edge_json_with_timestamp(
     input: &str,
     timestamp: OffsetDateTime,
@@ -54,6 +113,31 @@ fn from_thin_edge_json_with_timestamp(
     Ok(serializer.into_string()?)
 }
 
+fn from_thin_edge_json_with_timestamp_and_settings(
+    input: &str,
+    timestamp: OffsetDateTime,
+    maybe_child_id: Option<&str>,
+    units: &MeasurementUnits,
+    text_fallback: TextMeasurementFallback,
+) -> Result<C8yMeasurements, CumulocityJsonError> {
+    let mut serializer = serializer::C8yJsonSerializer::new(timestamp, maybe_child_id)
+        .with_units(units)
+        .with_text_fallback(text_fallback);
+    let () = parse_str(input, &mut serializer)?;
+    into_c8y_measurements(serializer)
+}
+
+fn into_c8y_measurements(
+    mut serializer: serializer::C8yJsonSerializer,
+) -> Result<C8yMeasurements, CumulocityJsonError> {
+    let payload = serializer.into_string()?;
+    if serializer.is_collection() {
+        Ok(C8yMeasurements::Collection(payload))
+    } else {
+        Ok(C8yMeasurements::Single(payload))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,9 +208,9 @@ mod tests {
 
         let output = from_thin_edge_json(single_value_thin_edge_json);
 
-        assert_eq!(
-            expected_output.split_whitespace().collect::<String>(),
-            output.unwrap().split_whitespace().collect::<String>()
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(output.unwrap().as_str()).unwrap(),
+            serde_json::from_str::<serde_json::Value>(expected_output).unwrap()
         );
     }
 
@@ -201,11 +285,9 @@ mod tests {
 
         let output = from_thin_edge_json(input);
 
-        let actual_output = output.unwrap().split_whitespace().collect::<String>();
-
-        assert_eq!(
-            expected_output.split_whitespace().collect::<String>(),
-            actual_output
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(output.unwrap().as_str()).unwrap(),
+            serde_json::from_str::<serde_json::Value>(expected_output).unwrap()
         );
     }
 
@@ -233,10 +315,8 @@ mod tests {
 
         let output = from_thin_edge_json(input.as_str()).unwrap();
         assert_eq!(
-            expected_output.split_whitespace().collect::<String>(),
-            output
-                .split_whitespace()
-                .collect::<String>()
+            serde_json::from_str::<serde_json::Value>(&output).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&expected_output).unwrap()
         );
         }
     }
@@ -284,4 +364,121 @@ mod tests {
             expected_output
         );
     }
+
+    #[test]
+    fn check_translation_with_measurement_type() {
+        let thin_edge_json = r#"{"type": "c8y_Environment", "temperature": 23.0}"#;
+
+        let timestamp = datetime!(2021-04-08 0:00:0 +05:00);
+        let output = from_thin_edge_json_with_timestamp(thin_edge_json, timestamp, None);
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(output.unwrap().as_str()).unwrap(),
+            json!({
+                "type": "c8y_Environment",
+                "time": "2021-04-08T00:00:00+05:00",
+                "temperature": {"temperature": {"value": 23.0}}
+            })
+        );
+    }
+
+    #[test]
+    fn check_translation_with_units() {
+        let thin_edge_json =
+            r#"{"temperature": 23.0, "current": {"L1": 9.5, "L2": 10.3}, "pressure": 220.0}"#;
+        let units = MeasurementUnits::try_from(r#"{"temperature": "°C", "current": "A"}"#).unwrap();
+
+        let timestamp = datetime!(2021-04-08 0:00:0 +05:00);
+        let output = from_thin_edge_json_with_timestamp_and_settings(
+            thin_edge_json,
+            timestamp,
+            None,
+            &units,
+            TextMeasurementFallback::Skip,
+        )
+        .map(C8yMeasurements::into_string);
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(output.unwrap().as_str()).unwrap(),
+            json!({
+                "type": "ThinEdgeMeasurement",
+                "time": "2021-04-08T00:00:00+05:00",
+                "temperature": {"temperature": {"value": 23.0, "unit": "°C"}},
+                "current": {
+                    "L1": {"value": 9.5, "unit": "A"},
+                    "L2": {"value": 10.3, "unit": "A"}
+                },
+                "pressure": {"pressure": {"value": 220.0}}
+            })
+        );
+    }
+
+    #[test]
+    fn check_translation_of_an_array_into_a_collection() {
+        let thin_edge_json = r#"[
+            {"time": "2021-04-08T00:00:01+05:00", "temperature": 23.0},
+            {"type": "c8y_Vibration", "acceleration": {"x": 0.1, "z": 9.8}}
+        ]"#;
+
+        let timestamp = datetime!(2021-04-08 0:00:02 +05:00);
+        let output = from_thin_edge_json_with_timestamp_and_settings(
+            thin_edge_json,
+            timestamp,
+            Some("child1"),
+            &MeasurementUnits::default(),
+            TextMeasurementFallback::Skip,
+        )
+        .unwrap();
+
+        let payload = match output {
+            C8yMeasurements::Collection(payload) => payload,
+            C8yMeasurements::Single(_) => panic!("Expected a collection of measurements"),
+        };
+        let source = json!({"externalId": "child1", "type": "c8y_Serial"});
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&payload).unwrap(),
+            json!({"measurements": [
+                {
+                    "externalSource": source,
+                    "type": "ThinEdgeMeasurement",
+                    "time": "2021-04-08T00:00:01+05:00",
+                    "temperature": {"temperature": {"value": 23.0}}
+                },
+                {
+                    "externalSource": source,
+                    "type": "c8y_Vibration",
+                    "time": "2021-04-08T00:00:02+05:00",
+                    "acceleration": {"x": {"value": 0.1}, "z": {"value": 9.8}}
+                }
+            ]})
+        );
+    }
+
+    #[test]
+    fn check_translation_of_cbor_measurements() {
+        let thin_edge_cbor = thin_edge_json::cbor::to_vec(&json!({
+            "time": "2021-04-08T00:00:01+05:00",
+            "temperature": 23.0,
+            "door_open": true
+        }))
+        .unwrap();
+
+        let output = from_thin_edge_cbor_with_settings(
+            &thin_edge_cbor,
+            None,
+            &MeasurementUnits::default(),
+            TextMeasurementFallback::Skip,
+        )
+        .unwrap();
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&output.into_string()).unwrap(),
+            json!({
+                "type": "ThinEdgeMeasurement",
+                "time": "2021-04-08T00:00:01+05:00",
+                "temperature": {"temperature": {"value": 23.0}},
+                "door_open": {"door_open": {"value": 1.0}}
+            })
+        );
+    }
 }
@@ -1,12 +1,25 @@
 use json_writer::{JsonWriter, JsonWriterError};
-use thin_edge_json::measurement::MeasurementVisitor;
+use thin_edge_json::{measurement::MeasurementVisitor, units::MeasurementUnits};
 use time::{format_description, OffsetDateTime};
 
-pub struct C8yJsonSerializer {
+const DEFAULT_MEASUREMENT_TYPE: &str = "ThinEdgeMeasurement";
+const DEFAULT_CAPACITY: usize = 1024; // XXX: Choose a capacity based on expected JSON length.
+
+pub use tedge_config::TextMeasurementFallback;
+
+/// Serializes measurements into a Cumulocity measurement.
+///
+/// A series of frames is serialized as a `{"measurements":[...]}` collection of Cumulocity measurements.
+pub struct C8yJsonSerializer<'a> {
     json: JsonWriter,
-    is_within_group: bool,
+    child_id: Option<String>,
+    group: Option<String>,
+    is_collection: bool,
     timestamp_present: bool,
     default_timestamp: OffsetDateTime,
+    measurement_type: Option<String>,
+    units: Option<&'a MeasurementUnits>,
+    text_fallback: TextMeasurementFallback,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -23,6 +36,9 @@ pub enum MeasurementStreamError {
     #[error("Unexpected time stamp within a group")]
     UnexpectedTimestamp,
 
+    #[error("Unexpected measurement type within a group")]
+    UnexpectedType,
+
     #[error("Unexpected end of data")]
     UnexpectedEndOfData,
 
@@ -31,41 +47,82 @@ pub enum MeasurementStreamError {
 
     #[error("Unexpected start of group")]
     UnexpectedStartOfGroup,
+
+    #[error("Unexpected start of frame within a group")]
+    UnexpectedStartOfFrame,
 }
 
-impl C8yJsonSerializer {
+impl<'a> C8yJsonSerializer<'a> {
     pub fn new(default_timestamp: OffsetDateTime, maybe_child_id: Option<&str>) -> Self {
-        let capa = 1024; // XXX: Choose a capacity based on expected JSON length.
-        let mut json = JsonWriter::with_capacity(capa);
+        let mut serializer = Self {
+            json: JsonWriter::with_capacity(DEFAULT_CAPACITY),
+            child_id: maybe_child_id.map(str::to_string),
+            group: None,
+            is_collection: false,
+            timestamp_present: false,
+            default_timestamp,
+            measurement_type: None,
+            units: None,
+            text_fallback: TextMeasurementFallback::default(),
+        };
+        let _ = serializer.start_measurement();
+        serializer
+    }
+
+    /// Add the `unit` of the measurements, when known.
+    pub fn with_units(self, units: &'a MeasurementUnits) -> Self {
+        Self {
+            units: Some(units),
+            ..self
+        }
+    }
 
-        json.write_open_obj();
-        let _ = json.write_key("type");
-        let _ = json.write_str("ThinEdgeMeasurement");
+    /// Set how the text measurements are sent.
+    pub fn with_text_fallback(self, text_fallback: TextMeasurementFallback) -> Self {
+        Self {
+            text_fallback,
+            ..self
+        }
+    }
 
-        if let Some(child_id) = maybe_child_id {
+    /// True if the frames visited so far are serialized as a collection of measurements.
+    pub fn is_collection(&self) -> bool {
+        self.is_collection
+    }
+
+    fn start_measurement(&mut self) -> Result<(), C8yJsonSerializationError> {
+        self.json.write_open_obj();
+
+        if let Some(child_id) = self.child_id.as_deref() {
             // In case the measurement is addressed to a child-device use fragment
             // "externalSource" to tell c8Y identity API to use child-device
             // object referenced by "externalId", instead of root device object
             // referenced by MQTT client's Device ID.
-            let _ = json.write_key("externalSource");
-            let _ = json.write_open_obj();
-            let _ = json.write_key("externalId");
-            let _ = json.write_str(child_id);
-            let _ = json.write_key("type");
-            let _ = json.write_str("c8y_Serial");
-            let _ = json.write_close_obj();
+            self.json.write_key("externalSource")?;
+            self.json.write_open_obj();
+            self.json.write_key("externalId")?;
+            self.json.write_str(child_id)?;
+            self.json.write_key("type")?;
+            self.json.write_str("c8y_Serial")?;
+            self.json.write_close_obj();
         }
 
-        Self {
-            json,
-            is_within_group: false,
-            timestamp_present: false,
-            default_timestamp,
-        }
+        self.timestamp_present = false;
+        self.measurement_type = None;
+        Ok(())
     }
 
     fn end(&mut self) -> Result<(), C8yJsonSerializationError> {
-        if self.is_within_group {
+        self.end_measurement()?;
+        if self.is_collection {
+            self.json.write_close_array();
+            self.json.write_close_obj();
+        }
+        Ok(())
+    }
+
+    fn end_measurement(&mut self) -> Result<(), C8yJsonSerializationError> {
+        if self.group.is_some() {
             return Err(MeasurementStreamError::UnexpectedEndOfData.into());
         }
 
@@ -75,14 +132,30 @@ impl C8yJsonSerializer {
 
         assert!(self.timestamp_present);
 
+        // The type is only known once all the input has been visited
+        self.json.write_key("type")?;
+        self.json.write_str(
+            self.measurement_type
+                .as_deref()
+                .unwrap_or(DEFAULT_MEASUREMENT_TYPE),
+        )?;
+
         self.json.write_close_obj();
         Ok(())
     }
 
-    fn write_value_obj(&mut self, value: f64) -> Result<(), C8yJsonSerializationError> {
+    fn write_value_obj(
+        &mut self,
+        value: f64,
+        unit: Option<&str>,
+    ) -> Result<(), C8yJsonSerializationError> {
         self.json.write_open_obj();
         self.json.write_key("value")?;
         self.json.write_f64(value)?;
+        if let Some(unit) = unit {
+            self.json.write_key("unit")?;
+            self.json.write_str(unit)?;
+        }
         self.json.write_close_obj();
         Ok(())
     }
@@ -93,11 +166,30 @@ impl C8yJsonSerializer {
     }
 }
 
-impl MeasurementVisitor for C8yJsonSerializer {
+impl<'a> MeasurementVisitor for C8yJsonSerializer<'a> {
     type Error = C8yJsonSerializationError;
 
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        if self.group.is_some() {
+            return Err(MeasurementStreamError::UnexpectedStartOfFrame.into());
+        }
+
+        if self.is_collection {
+            self.end_measurement()?;
+        } else {
+            // The measurement started on creation is replaced by a collection of measurements
+            self.json = JsonWriter::with_capacity(DEFAULT_CAPACITY);
+            self.json.write_open_obj();
+            self.json.write_key("measurements")?;
+            self.json.write_open_array();
+            self.is_collection = true;
+        }
+
+        self.start_measurement()
+    }
+
     fn visit_timestamp(&mut self, timestamp: OffsetDateTime) -> Result<(), Self::Error> {
-        if self.is_within_group {
+        if self.group.is_some() {
             return Err(MeasurementStreamError::UnexpectedTimestamp.into());
         }
 
@@ -113,38 +205,65 @@ impl MeasurementVisitor for C8yJsonSerializer {
         Ok(())
     }
 
+    fn visit_type(&mut self, measurement_type: &str) -> Result<(), Self::Error> {
+        if self.group.is_some() {
+            return Err(MeasurementStreamError::UnexpectedType.into());
+        }
+
+        self.measurement_type = Some(measurement_type.into());
+        Ok(())
+    }
+
     fn visit_measurement(&mut self, key: &str, value: f64) -> Result<(), Self::Error> {
+        let units = self.units;
+        let unit = units.and_then(|units| units.unit(self.group.as_deref(), key));
         self.json.write_key(key)?;
 
-        if self.is_within_group {
-            self.write_value_obj(value)?;
+        if self.group.is_some() {
+            self.write_value_obj(value, unit)?;
         } else {
             self.json.write_open_obj();
             self.json.write_key(key)?;
-            self.write_value_obj(value)?;
+            self.write_value_obj(value, unit)?;
             self.json.write_close_obj();
         }
         Ok(())
     }
 
+    fn visit_text_measurement(&mut self, key: &str, value: &str) -> Result<(), Self::Error> {
+        match self.text_fallback {
+            TextMeasurementFallback::Skip => {}
+            TextMeasurementFallback::Fragment => {
+                self.json.write_key(key)?;
+                self.json.write_str(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_bool_measurement(&mut self, key: &str, value: bool) -> Result<(), Self::Error> {
+        // Cumulocity only supports numeric series
+        self.visit_measurement(key, if value { 1.0 } else { 0.0 })
+    }
+
     fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error> {
-        if self.is_within_group {
+        if self.group.is_some() {
             return Err(MeasurementStreamError::UnexpectedStartOfGroup.into());
         }
 
         self.json.write_key(group)?;
         self.json.write_open_obj();
-        self.is_within_group = true;
+        self.group = Some(group.into());
         Ok(())
     }
 
     fn visit_end_group(&mut self) -> Result<(), Self::Error> {
-        if !self.is_within_group {
+        if self.group.is_none() {
             return Err(MeasurementStreamError::UnexpectedEndOfGroup.into());
         }
 
         self.json.write_close_obj();
-        self.is_within_group = false;
+        self.group = None;
         Ok(())
     }
 }
@@ -387,4 +506,64 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn serialize_bool_values_as_numbers() -> anyhow::Result<()> {
+        let timestamp = datetime!(2021-06-22 17:03:14.123456789 +05:00);
+
+        let mut serializer = C8yJsonSerializer::new(timestamp, None);
+        serializer.visit_bool_measurement("door_open", true)?;
+        serializer.visit_start_group("alarms")?;
+        serializer.visit_bool_measurement("smoke", false)?;
+        serializer.visit_end_group()?;
+
+        let output = serializer.into_string()?;
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&output)?,
+            json!({
+                "type": "ThinEdgeMeasurement",
+                "time": "2021-06-22T17:03:14.123456789+05:00",
+                "door_open": {"door_open": {"value": 1.0}},
+                "alarms": {"smoke": {"value": 0.0}}
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_text_values_using_the_fallback() -> anyhow::Result<()> {
+        let timestamp = datetime!(2021-06-22 17:03:14.123456789 +05:00);
+
+        let mut serializer = C8yJsonSerializer::new(timestamp, None);
+        serializer.visit_text_measurement("status", "running")?;
+        serializer.visit_measurement("temperature", 25.5)?;
+        let output = serializer.into_string()?;
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&output)?,
+            json!({
+                "type": "ThinEdgeMeasurement",
+                "time": "2021-06-22T17:03:14.123456789+05:00",
+                "temperature": {"temperature": {"value": 25.5}}
+            })
+        );
+
+        let mut serializer = C8yJsonSerializer::new(timestamp, None)
+            .with_text_fallback(TextMeasurementFallback::Fragment);
+        serializer.visit_text_measurement("status", "running")?;
+        serializer.visit_measurement("temperature", 25.5)?;
+        let output = serializer.into_string()?;
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&output)?,
+            json!({
+                "type": "ThinEdgeMeasurement",
+                "time": "2021-06-22T17:03:14.123456789+05:00",
+                "status": "running",
+                "temperature": {"temperature": {"value": 25.5}}
+            })
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,294 @@
+use agent_interface::{DownloadInfo, FirmwareError, FirmwareUpdateRequest};
+use async_trait::async_trait;
+use download::Downloader;
+use logged_command::LoggedCommand;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use tokio::io::BufWriter;
+use tokio::{fs::File, io::AsyncWriteExt};
+use tracing::{error, warn};
+
+/// The firmware counterpart of the software [Plugin](crate::plugin::Plugin).
+///
+/// A firmware update spans a device restart:
+/// the firmware is prepared, downloaded and installed, then the device is rebooted
+/// and the new firmware is only verified once the device is up again.
+#[async_trait]
+pub trait FirmwarePlugin {
+    async fn prepare(&self, logger: &mut BufWriter<File>) -> Result<(), FirmwareError>;
+
+    async fn install(
+        &self,
+        name: &str,
+        version: &Option<String>,
+        file_path: Option<&Path>,
+        logger: &mut BufWriter<File>,
+    ) -> Result<(), FirmwareError>;
+
+    async fn reboot(&self, logger: &mut BufWriter<File>) -> Result<(), FirmwareError>;
+
+    async fn verify(
+        &self,
+        name: &str,
+        version: &Option<String>,
+        logger: &mut BufWriter<File>,
+    ) -> Result<(), FirmwareError>;
+
+    /// Prepare, download and install the requested firmware, leaving the reboot to the caller.
+    async fn update(
+        &self,
+        request: &FirmwareUpdateRequest,
+        logger: &mut BufWriter<File>,
+        download_path: &Path,
+    ) -> Result<(), FirmwareError> {
+        let () = self.prepare(logger).await?;
+
+        let downloader = match &request.url {
+            Some(url) => Some(
+                Self::download_from_url(
+                    &request.name,
+                    &request.version,
+                    url,
+                    logger,
+                    download_path,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        let file_path = downloader.as_ref().map(|downloader| downloader.filename());
+        let result = self
+            .install(&request.name, &request.version, file_path, logger)
+            .await;
+
+        if let Some(downloader) = downloader {
+            Self::cleanup_downloaded_artefacts(downloader, logger).await?;
+        }
+
+        result
+    }
+
+    async fn download_from_url(
+        name: &str,
+        version: &Option<String>,
+        url: &DownloadInfo,
+        logger: &mut BufWriter<File>,
+        download_path: &Path,
+    ) -> Result<Downloader, FirmwareError> {
+        let downloader = Downloader::new(name, version, download_path);
+
+        logger
+            .write_all(
+                format!(
+                    "----- $ Downloading: {} to {} \n",
+                    &url.url(),
+                    &downloader.filename().to_string_lossy().to_string()
+                )
+                .as_bytes(),
+            )
+            .await?;
+
+        if let Err(err) = downloader
+            .download(url)
+            .await
+            .map_err(|err| FirmwareError::Download {
+                reason: err.to_string(),
+                url: url.url().to_string(),
+            })
+        {
+            error!("Download error: {}", &err);
+            logger
+                .write_all(format!("error: {}\n", &err).as_bytes())
+                .await?;
+            return Err(err);
+        }
+
+        Ok(downloader)
+    }
+
+    async fn cleanup_downloaded_artefacts(
+        downloader: Downloader,
+        logger: &mut BufWriter<File>,
+    ) -> Result<(), FirmwareError> {
+        if let Err(err) = downloader.cleanup().await {
+            logger
+                .write_all(format!("warn: {}\n", &err).as_bytes())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// A firmware plugin implemented by an external executable,
+/// called with the `prepare`, `install`, `reboot` and `verify` sub-commands.
+#[derive(Debug)]
+pub struct ExternalFirmwarePlugin {
+    pub path: PathBuf,
+    pub sudo: Option<PathBuf>,
+}
+
+impl ExternalFirmwarePlugin {
+    pub fn new(path: impl Into<PathBuf>) -> ExternalFirmwarePlugin {
+        ExternalFirmwarePlugin {
+            path: path.into(),
+            sudo: Some("sudo".into()),
+        }
+    }
+
+    pub fn with_sudo(self, sudo: Option<PathBuf>) -> Self {
+        Self { sudo, ..self }
+    }
+
+    /// Open the firmware plugin installed in the given directory.
+    ///
+    /// A single firmware plugin is expected: if there are several, the first one in name order is used.
+    pub fn open(
+        plugin_dir: impl AsRef<Path>,
+        sudo: Option<PathBuf>,
+    ) -> Result<ExternalFirmwarePlugin, FirmwareError> {
+        let plugin_dir = plugin_dir.as_ref();
+        let no_plugin = || FirmwareError::NoPlugin {
+            plugin_dir: plugin_dir.to_string_lossy().to_string(),
+        };
+
+        let mut plugins = std::fs::read_dir(plugin_dir)
+            .map_err(|_| no_plugin())?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect::<Vec<PathBuf>>();
+        plugins.sort();
+
+        if plugins.len() > 1 {
+            warn!(
+                "Several firmware plugins found in {:?}, using {:?}",
+                plugin_dir, plugins[0]
+            );
+        }
+
+        let path = plugins.into_iter().next().ok_or_else(no_plugin)?;
+        Ok(ExternalFirmwarePlugin::new(path).with_sudo(sudo))
+    }
+
+    pub fn command(
+        &self,
+        action: &str,
+        maybe_firmware: Option<(&str, &Option<String>)>,
+    ) -> LoggedCommand {
+        let mut command = if let Some(sudo) = &self.sudo {
+            let mut command = LoggedCommand::new(sudo);
+            command.arg(&self.path);
+            command
+        } else {
+            LoggedCommand::new(&self.path)
+        };
+        command.arg(action);
+
+        if let Some((name, version)) = maybe_firmware {
+            command.arg(name);
+            if let Some(version) = version {
+                command.arg("--firmware-version");
+                command.arg(version);
+            }
+        }
+
+        command
+    }
+
+    pub async fn execute(
+        &self,
+        command: LoggedCommand,
+        logger: &mut BufWriter<File>,
+    ) -> Result<Output, FirmwareError> {
+        command
+            .execute(logger)
+            .await
+            .map_err(|err| FirmwareError::Plugin {
+                reason: format!("{}", err),
+            })
+    }
+
+    pub fn content(&self, bytes: Vec<u8>) -> Result<String, FirmwareError> {
+        String::from_utf8(bytes).map_err(|err| FirmwareError::Plugin {
+            reason: format!("{}", err),
+        })
+    }
+}
+
+const PREPARE: &str = "prepare";
+const INSTALL: &str = "install";
+const REBOOT: &str = "reboot";
+const VERIFY: &str = "verify";
+
+#[async_trait]
+impl FirmwarePlugin for ExternalFirmwarePlugin {
+    async fn prepare(&self, logger: &mut BufWriter<File>) -> Result<(), FirmwareError> {
+        let command = self.command(PREPARE, None);
+        let output = self.execute(command, logger).await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(FirmwareError::Prepare {
+                reason: self.content(output.stderr)?,
+            })
+        }
+    }
+
+    async fn install(
+        &self,
+        name: &str,
+        version: &Option<String>,
+        file_path: Option<&Path>,
+        logger: &mut BufWriter<File>,
+    ) -> Result<(), FirmwareError> {
+        let mut command = self.command(INSTALL, Some((name, version)));
+        if let Some(path) = file_path {
+            command.arg("--file");
+            command.arg(path);
+        }
+        let output = self.execute(command, logger).await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(FirmwareError::Install {
+                name: name.to_string(),
+                reason: self.content(output.stderr)?,
+            })
+        }
+    }
+
+    async fn reboot(&self, logger: &mut BufWriter<File>) -> Result<(), FirmwareError> {
+        let command = self.command(REBOOT, None);
+        let output = self.execute(command, logger).await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(FirmwareError::Reboot {
+                reason: self.content(output.stderr)?,
+            })
+        }
+    }
+
+    async fn verify(
+        &self,
+        name: &str,
+        version: &Option<String>,
+        logger: &mut BufWriter<File>,
+    ) -> Result<(), FirmwareError> {
+        let command = self.command(VERIFY, Some((name, version)));
+        let output = self.execute(command, logger).await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(FirmwareError::Verify {
+                name: name.to_string(),
+                reason: self.content(output.stderr)?,
+            })
+        }
+    }
+}
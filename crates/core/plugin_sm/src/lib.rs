@@ -1,3 +1,4 @@
+pub mod firmware_plugin;
 pub mod log_file;
 pub mod operation_logs;
 pub mod plugin;
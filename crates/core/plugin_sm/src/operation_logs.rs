@@ -26,11 +26,13 @@ pub struct OperationLogs {
 pub enum LogKind {
     SoftwareUpdate,
     SoftwareList,
+    FirmwareUpdate,
     Operation(String),
 }
 
 const UPDATE_PREFIX: &str = "software-update";
 const LIST_PREFIX: &str = "software-list";
+const FIRMWARE_UPDATE_PREFIX: &str = "firmware-update";
 
 impl OperationLogs {
     pub fn try_new(log_dir: PathBuf) -> Result<OperationLogs, OperationLogsError> {
@@ -58,6 +60,7 @@ impl OperationLogs {
         let file_prefix = match kind {
             LogKind::SoftwareUpdate => UPDATE_PREFIX,
             LogKind::SoftwareList => LIST_PREFIX,
+            LogKind::FirmwareUpdate => FIRMWARE_UPDATE_PREFIX,
             LogKind::Operation(ref operation_name) => operation_name.as_str(),
         };
 
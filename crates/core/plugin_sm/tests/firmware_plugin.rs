@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+
+    use agent_interface::{FirmwareError, FirmwareUpdateRequest};
+    use plugin_sm::firmware_plugin::{ExternalFirmwarePlugin, FirmwarePlugin};
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use tempfile::TempDir;
+    use tokio::fs::File;
+    use tokio::io::BufWriter;
+
+    #[tokio::test]
+    async fn firmware_update_prepares_then_installs_the_firmware() {
+        let dir = TempDir::new().unwrap();
+        let plugin = dummy_firmware_plugin(dir.path());
+
+        let request = FirmwareUpdateRequest::new("bootloader", Some("1.0".into()), None);
+        let mut logger = dev_null().await;
+        let res = plugin.update(&request, &mut logger, dir.path()).await;
+
+        assert_eq!(res, Ok(()));
+        assert_eq!(
+            plugin_calls(dir.path()),
+            "prepare\ninstall bootloader --firmware-version 1.0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn firmware_verification_reports_plugin_errors() {
+        let dir = TempDir::new().unwrap();
+        let plugin = dummy_firmware_plugin(dir.path());
+
+        let mut logger = dev_null().await;
+        let res = plugin
+            .verify("bootloader", &Some("2.0".into()), &mut logger)
+            .await;
+
+        assert_eq!(
+            res,
+            Err(FirmwareError::Verify {
+                name: "bootloader".into(),
+                reason: "running version 1.0\n".into()
+            })
+        );
+    }
+
+    #[test]
+    fn opening_an_empty_firmware_plugin_directory_fails() {
+        let dir = TempDir::new().unwrap();
+
+        let res = ExternalFirmwarePlugin::open(dir.path(), None);
+
+        assert!(matches!(res, Err(FirmwareError::NoPlugin { .. })));
+    }
+
+    // The dummy plugin records its calls and only verifies the version 1.0 of the firmware.
+    fn dummy_firmware_plugin(dir: &Path) -> ExternalFirmwarePlugin {
+        let plugin_dir = dir.join("firmware-plugins");
+        std::fs::create_dir(&plugin_dir).unwrap();
+
+        let plugin_path = plugin_dir.join("dummy");
+        let script = format!(
+            r#"#!/bin/sh
+echo "$@" >> {calls}
+if [ "$1" = "verify" ] && [ "$4" != "1.0" ]; then
+    echo "running version 1.0" >&2
+    exit 1
+fi
+"#,
+            calls = dir.join("calls").display()
+        );
+        std::fs::write(&plugin_path, script).unwrap();
+        std::fs::set_permissions(&plugin_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        ExternalFirmwarePlugin::open(&plugin_dir, None).unwrap()
+    }
+
+    fn plugin_calls(dir: &Path) -> String {
+        std::fs::read_to_string(dir.join("calls")).unwrap()
+    }
+
+    async fn dev_null() -> BufWriter<File> {
+        let dev_null = File::create("/dev/null").await.unwrap();
+        BufWriter::new(dev_null)
+    }
+}
@@ -46,6 +46,15 @@ impl ConfigKey {
             config_key!(C8yUrlSetting),
             config_key!(C8yRootCertPathSetting),
             config_key!(C8ySmartRestTemplates),
+            config_key!(C8yInventoryIntervalSetting),
+            config_key!(C8yAvailabilityIntervalSetting),
+            config_key!(C8yBatchWindowSetting),
+            config_key!(C8yBatchMaxSizeSetting),
+            config_key!(C8yCommandEnableSetting),
+            config_key!(C8yCommandShellSetting),
+            config_key!(C8yCommandTimeoutSetting),
+            config_key!(C8yCommandMaxOutputSetting),
+            config_key!(C8yTextMeasurementFallbackSetting),
             config_key!(AzureUrlSetting),
             config_key!(AzureRootCertPathSetting),
             config_key!(AzureMapperTimestamp),
@@ -58,6 +67,19 @@ impl ConfigKey {
             config_key!(MqttExternalCertfileSetting),
             config_key!(MqttExternalKeyfileSetting),
             config_key!(SoftwarePluginDefaultSetting),
+            config_key!(SysmonIntervalSetting),
+            config_key!(SysmonCpuEnableSetting),
+            config_key!(SysmonMemoryEnableSetting),
+            config_key!(SysmonDiskEnableSetting),
+            config_key!(SysmonNetworkEnableSetting),
+            config_key!(SysmonTemperatureEnableSetting),
+            config_key!(CollectdBatchingWindowSetting),
+            config_key!(CollectdBatchingMaxDelaySetting),
+            config_key!(CollectdBatchingLeapLimitSetting),
+            config_key!(CollectdSourceTopicSetting),
+            config_key!(CollectdTargetTopicSetting),
+            config_key!(CollectdMetricsAllowSetting),
+            config_key!(CollectdMetricsDenySetting),
             config_key!(TmpPathSetting),
             config_key!(LogPathSetting),
             config_key!(RunPathSetting),
@@ -51,8 +51,11 @@ impl From<BridgeConfigC8yParams> for BridgeConfig {
             // c8y JSON
             r#"inventory/managedObjects/update/# out 2 c8y/ """#.into(),
             r#"measurement/measurements/create out 2 c8y/ """#.into(),
+            r#"measurement/measurements/createBulk out 2 c8y/ """#.into(),
             r#"event/events/create out 2 c8y/ """#.into(),
+            r#"alarm/alarms/create out 2 c8y/ """#.into(),
             r#"error in 2 c8y/ """#.into(),
+            r#"devicecontrol/notifications in 2 c8y/ """#.into(),
             // c8y JWT token retrieval
             r#"s/uat/# out 2 c8y/ """#.into(),
             r#"s/dat/# in 2 c8y/ """#.into(),
@@ -152,8 +155,11 @@ fn test_bridge_config_from_c8y_params() -> anyhow::Result<()> {
             // c8y JSON
             r#"inventory/managedObjects/update/# out 2 c8y/ """#.into(),
             r#"measurement/measurements/create out 2 c8y/ """#.into(),
+            r#"measurement/measurements/createBulk out 2 c8y/ """#.into(),
             r#"event/events/create out 2 c8y/ """#.into(),
+            r#"alarm/alarms/create out 2 c8y/ """#.into(),
             r#"error in 2 c8y/ """#.into(),
+            r#"devicecontrol/notifications in 2 c8y/ """#.into(),
             // c8y JWT token retrieval
             r#"s/uat/# out 2 c8y/ """#.into(),
             r#"s/dat/# in 2 c8y/ """#.into(),
@@ -178,3 +184,45 @@ fn test_bridge_config_from_c8y_params() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_c8y_json_topics_of_the_mapper_are_bridged() -> anyhow::Result<()> {
+    use std::convert::TryFrom;
+    let params = BridgeConfigC8yParams {
+        connect_url: ConnectUrl::try_from("test.test.io")?,
+        mqtt_tls_port: 8883,
+        config_file: "c8y-bridge.conf".into(),
+        remote_clientid: "alpha".into(),
+        bridge_root_cert_path: "./test_root.pem".into(),
+        bridge_certfile: "./test-certificate.pem".into(),
+        bridge_keyfile: "./test-private-key.pem".into(),
+        smartrest_templates: TemplatesSet::default(),
+    };
+
+    let bridge = BridgeConfig::from(params);
+    let is_bridged_out = |topic: &str| {
+        bridge.topics.iter().any(|rule| {
+            let pattern = rule.split_whitespace().next().unwrap_or_default();
+            let is_out = rule.split_whitespace().nth(1) == Some("out");
+            let topic = topic.strip_prefix("c8y/").unwrap_or(topic);
+            is_out
+                && match pattern.strip_suffix('#') {
+                    Some(prefix) => topic.starts_with(prefix),
+                    None => topic == pattern,
+                }
+        })
+    };
+
+    // The c8y JSON topics on which tedge-mapper-c8y publishes
+    for topic in [
+        "c8y/inventory/managedObjects/update/test-device",
+        "c8y/measurement/measurements/create",
+        "c8y/measurement/measurements/createBulk",
+        "c8y/event/events/create",
+        "c8y/alarm/alarms/create",
+    ] {
+        assert!(is_bridged_out(topic), "{topic} is not bridged");
+    }
+
+    Ok(())
+}
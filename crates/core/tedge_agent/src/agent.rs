@@ -2,36 +2,46 @@ use crate::{
     error::AgentError,
     restart_operation_handler::restart_operation,
     state::{
-        AgentStateRepository, RestartOperationStatus, SoftwareOperationVariants, State,
+        AgentStateRepository, DeviceProfileStage, DeviceProfileState, FirmwareOperationState,
+        FirmwareOperationStatus, RestartOperationStatus, SoftwareOperationVariants, State,
         StateRepository, StateStatus,
     },
 };
 use agent_interface::{
-    control_filter_topic, health_check_topics, software_filter_topic, Jsonify, OperationStatus,
-    RestartOperationRequest, RestartOperationResponse, SoftwareError, SoftwareListRequest,
-    SoftwareListResponse, SoftwareRequestResponse, SoftwareType, SoftwareUpdateRequest,
-    SoftwareUpdateResponse,
+    control_filter_topic, device_filter_topic, firmware_filter_topic, health_check_topics,
+    software_filter_topic, ConfigUpdateRequest, ConfigUpdateResponse, DeviceProfileRequest,
+    DeviceProfileResponse, FirmwareError, FirmwareUpdateRequest, FirmwareUpdateResponse, Jsonify,
+    OperationStatus, RestartOperationRequest, RestartOperationResponse, SoftwareError,
+    SoftwareListRequest, SoftwareListResponse, SoftwareRequestResponse, SoftwareType,
+    SoftwareUpdateRequest, SoftwareUpdateResponse,
 };
 use flockfile::{check_another_instance_is_not_running, Flockfile};
 use mqtt_channel::{Connection, Message, PubChannel, StreamExt, SubChannel, Topic, TopicFilter};
 use plugin_sm::{
+    firmware_plugin::{ExternalFirmwarePlugin, FirmwarePlugin},
     operation_logs::{LogKind, OperationLogs},
     plugin_manager::{ExternalPlugins, Plugins},
 };
 use serde_json::json;
 use std::process;
-use std::{convert::TryInto, fmt::Debug, path::PathBuf, sync::Arc};
+use std::{convert::TryInto, fmt::Debug, path::PathBuf, sync::Arc, time::Duration};
 use tedge_config::{
     ConfigRepository, ConfigSettingAccessor, ConfigSettingAccessorStringExt, LogPathSetting,
     MqttBindAddressSetting, MqttPortSetting, RunPathSetting, SoftwarePluginDefaultSetting,
     TEdgeConfigLocation, TmpPathSetting, DEFAULT_LOG_PATH, DEFAULT_RUN_PATH,
 };
 use tedge_utils::file::create_directory_with_user_group;
+use time::OffsetDateTime;
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, instrument, warn};
 
 const SM_PLUGINS: &str = "sm-plugins";
+const FIRMWARE_PLUGINS: &str = "firmware-plugins";
 const AGENT_LOG_PATH: &str = "tedge/agent";
+const DEVICE_PROFILE_STATE_FILE: &str = "current-device-profile";
+
+// The time given to the configuration plugin to apply the configuration updates of a device profile.
+const DEFAULT_CONFIG_UPDATE_TIMEOUT: Duration = Duration::from_secs(600);
 
 #[cfg(not(test))]
 const INIT_COMMAND: &str = "init";
@@ -39,6 +49,12 @@ const INIT_COMMAND: &str = "init";
 #[cfg(test)]
 const INIT_COMMAND: &str = "echo";
 
+#[cfg(not(test))]
+const FIRMWARE_PLUGIN_SUDO: Option<&str> = Some("sudo");
+
+#[cfg(test)]
+const FIRMWARE_PLUGIN_SUDO: Option<&str> = None;
+
 #[derive(Debug)]
 pub struct SmAgentConfig {
     pub errors_topic: Topic,
@@ -52,6 +68,13 @@ pub struct SmAgentConfig {
     pub response_topic_list: Topic,
     pub response_topic_update: Topic,
     pub response_topic_restart: Topic,
+    pub request_topic_firmware_update: Topic,
+    pub response_topic_firmware_update: Topic,
+    pub request_topic_device_profile: Topic,
+    pub response_topic_device_profile: Topic,
+    pub request_topic_config_update: Topic,
+    pub response_topic_config_update: Topic,
+    pub config_update_timeout: Duration,
     pub sm_home: PathBuf,
     pub log_dir: PathBuf,
     pub run_dir: PathBuf,
@@ -65,9 +88,15 @@ impl Default for SmAgentConfig {
 
         let mqtt_config = mqtt_channel::Config::default();
 
-        let mut request_topics: TopicFilter = vec![software_filter_topic(), control_filter_topic()]
-            .try_into()
-            .expect("Invalid topic filter");
+        let mut request_topics: TopicFilter = vec![
+            software_filter_topic(),
+            control_filter_topic(),
+            firmware_filter_topic(),
+            device_filter_topic(),
+            ConfigUpdateResponse::topic_name(),
+        ]
+        .try_into()
+        .expect("Invalid topic filter");
 
         let request_topics_health: TopicFilter = health_check_topics()
             .try_into()
@@ -94,6 +123,24 @@ impl Default for SmAgentConfig {
         let response_topic_restart =
             Topic::new(RestartOperationResponse::topic_name()).expect("Invalid topic");
 
+        let request_topic_firmware_update =
+            Topic::new(FirmwareUpdateRequest::topic_name()).expect("Invalid topic");
+
+        let response_topic_firmware_update =
+            Topic::new(FirmwareUpdateResponse::topic_name()).expect("Invalid topic");
+
+        let request_topic_device_profile =
+            Topic::new(DeviceProfileRequest::topic_name()).expect("Invalid topic");
+
+        let response_topic_device_profile =
+            Topic::new(DeviceProfileResponse::topic_name()).expect("Invalid topic");
+
+        let request_topic_config_update =
+            Topic::new(ConfigUpdateRequest::topic_name()).expect("Invalid topic");
+
+        let response_topic_config_update =
+            Topic::new(ConfigUpdateResponse::topic_name()).expect("Invalid topic");
+
         let sm_home = PathBuf::from("/etc/tedge");
 
         let log_dir = PathBuf::from(&format!("{DEFAULT_LOG_PATH}/{AGENT_LOG_PATH}"));
@@ -116,6 +163,13 @@ impl Default for SmAgentConfig {
             response_topic_update,
             request_topic_restart,
             response_topic_restart,
+            request_topic_firmware_update,
+            response_topic_firmware_update,
+            request_topic_device_profile,
+            response_topic_device_profile,
+            request_topic_config_update,
+            response_topic_config_update,
+            config_update_timeout: DEFAULT_CONFIG_UPDATE_TIMEOUT,
             sm_home,
             log_dir,
             run_dir,
@@ -201,6 +255,7 @@ pub struct SmAgent {
     config: SmAgentConfig,
     operation_logs: OperationLogs,
     persistance_store: AgentStateRepository,
+    device_profile_store: AgentStateRepository,
     _flock: Flockfile,
 }
 
@@ -210,17 +265,26 @@ impl SmAgent {
         info!("{} starting", &name);
 
         let persistance_store = AgentStateRepository::new(config.sm_home.clone());
+        let device_profile_store = AgentStateRepository::new_with_file_name(
+            config.sm_home.clone(),
+            DEVICE_PROFILE_STATE_FILE,
+        );
         let operation_logs = OperationLogs::try_new(config.log_dir.clone())?;
 
         config.mqtt_config = config
             .mqtt_config
             .with_session_name(name)
-            .with_subscriptions(config.request_topics.clone());
+            .with_subscriptions(config.request_topics.clone())
+            .with_last_will_message(Message::new(
+                &config.response_topic_health,
+                json!({"status": "down"}).to_string(),
+            ));
 
         Ok(Self {
             config,
             operation_logs,
             persistance_store,
+            device_profile_store,
             _flock: flock,
         })
     }
@@ -273,6 +337,9 @@ impl SmAgent {
         });
 
         let () = self.process_pending_operation(&mut mqtt.published).await?;
+        let () = self
+            .process_pending_device_profile(&mut mqtt.published, &plugins)
+            .await?;
 
         while let Err(error) = self
             .process_subscribed_messages(&mut mqtt.received, &mut mqtt.published, &plugins)
@@ -290,7 +357,29 @@ impl SmAgent {
         responses: &mut impl PubChannel,
         plugins: &Arc<Mutex<ExternalPlugins>>,
     ) -> Result<(), AgentError> {
-        while let Some(message) = requests.next().await {
+        let mut config_deadline = self.device_profile_deadline().await;
+        loop {
+            let message = match config_deadline {
+                None => requests.next().await,
+                Some(deadline) => tokio::select! {
+                    message = requests.next() => message,
+                    () = tokio::time::sleep(time_until(deadline)) => {
+                        let _success = self
+                            .time_out_device_profile(responses)
+                            .await
+                            .map_err(|err| {
+                                error!("{:?}", err); // log error and discard such that the agent doesn't exit.
+                            });
+                        config_deadline = None;
+                        continue;
+                    }
+                },
+            };
+            let message = match message {
+                Some(message) => message,
+                None => break,
+            };
+
             debug!("Request {:?}", message);
             match &message.topic {
                 topic if self.config.request_topics_health.accept_topic(topic) => {
@@ -360,6 +449,35 @@ impl SmAgent {
                     }
                 }
 
+                topic if topic == &self.config.request_topic_firmware_update => {
+                    let _success = self
+                        .handle_firmware_update_request(responses, &message)
+                        .await
+                        .map_err(|err| {
+                            error!("{:?}", err); // log error and discard such that the agent doesn't exit.
+                        });
+                }
+
+                topic if topic == &self.config.request_topic_device_profile => {
+                    let _success = self
+                        .handle_device_profile_request(responses, plugins, &message)
+                        .await
+                        .map_err(|err| {
+                            error!("{:?}", err); // log error and discard such that the agent doesn't exit.
+                        });
+                    config_deadline = self.device_profile_deadline().await;
+                }
+
+                topic if topic == &self.config.response_topic_config_update => {
+                    let _success = self
+                        .handle_config_update_response(responses, &message)
+                        .await
+                        .map_err(|err| {
+                            error!("{:?}", err); // log error and discard such that the agent doesn't exit.
+                        });
+                    config_deadline = self.device_profile_deadline().await;
+                }
+
                 _ => error!("Unknown operation. Discarded."),
             }
         }
@@ -573,170 +691,823 @@ impl SmAgent {
         Ok(())
     }
 
-    async fn process_pending_operation(
+    async fn handle_firmware_update_request(
         &self,
         responses: &mut impl PubChannel,
+        message: &Message,
     ) -> Result<(), AgentError> {
-        let state: Result<State, _> = self.persistance_store.load().await;
-        let mut status = OperationStatus::Failed;
-
-        if let State {
-            operation_id: Some(id),
-            operation: Some(operation),
-        } = match state {
-            Ok(state) => state,
-            Err(_) => State {
-                operation_id: None,
-                operation: None,
-            },
-        } {
-            let topic = match operation {
-                StateStatus::Software(SoftwareOperationVariants::List) => {
-                    &self.config.response_topic_list
-                }
+        let request = match FirmwareUpdateRequest::from_slice(message.payload_bytes()) {
+            Ok(request) => {
+                let () = self
+                    .persistance_store
+                    .store(&State {
+                        operation_id: Some(request.id.clone()),
+                        operation: Some(StateStatus::Firmware(FirmwareOperationState {
+                            status: FirmwareOperationStatus::Installing,
+                            name: request.name.clone(),
+                            version: request.version.clone(),
+                            url: request.url.as_ref().map(|url| url.url.clone()),
+                        })),
+                    })
+                    .await?;
 
-                StateStatus::Software(SoftwareOperationVariants::Update) => {
-                    &self.config.response_topic_update
-                }
+                request
+            }
 
-                StateStatus::Restart(RestartOperationStatus::Pending) => {
-                    &self.config.response_topic_restart
-                }
+            Err(error) => {
+                error!("Parsing error: {}", error);
+                let () = responses
+                    .publish(Message::new(
+                        &self.config.errors_topic,
+                        format!("{}", error),
+                    ))
+                    .await?;
 
-                StateStatus::Restart(RestartOperationStatus::Restarting) => {
-                    let _state = self.persistance_store.clear().await?;
-                    if restart_operation::has_rebooted(&self.config.run_dir)? {
-                        info!("Device restart successful.");
-                        status = OperationStatus::Successful;
-                    }
-                    &self.config.response_topic_restart
+                return Err(SoftwareError::ParseError {
+                    reason: "Parsing failed".into(),
                 }
+                .into());
+            }
+        };
 
-                StateStatus::UnknownOperation => {
-                    error!("UnknownOperation in store.");
-                    &self.config.errors_topic
-                }
-            };
+        let executing_response = FirmwareUpdateResponse::new(&request);
+        let () = responses
+            .publish(Message::new(
+                &self.config.response_topic_firmware_update,
+                executing_response.to_bytes()?,
+            ))
+            .await?;
 
-            let response = SoftwareRequestResponse::new(&id, status);
+        // On success, the device is rebooting: the operation completes on restart.
+        let restarting = StateStatus::Firmware(FirmwareOperationState {
+            status: FirmwareOperationStatus::Restarting,
+            name: request.name.clone(),
+            version: request.version.clone(),
+            url: request.url.as_ref().map(|url| url.url.clone()),
+        });
+        if let Err(error) = self
+            .install_firmware(&request, &self.persistance_store, &restarting)
+            .await
+        {
+            error!("{}", error);
 
+            let _state = self.persistance_store.clear().await?;
+            let response = FirmwareUpdateResponse::new(&request).with_error(&error.to_string());
             let () = responses
-                .publish(Message::new(topic, response.to_bytes()?))
+                .publish(Message::new(
+                    &self.config.response_topic_firmware_update,
+                    response.to_bytes()?,
+                ))
                 .await?;
         }
 
         Ok(())
     }
-}
-
-fn get_default_plugin(
-    config_location: &TEdgeConfigLocation,
-) -> Result<Option<SoftwareType>, AgentError> {
-    let config_repository = tedge_config::TEdgeConfigRepository::new(config_location.clone());
-    let tedge_config = config_repository.load()?;
-
-    Ok(tedge_config.query_string_optional(SoftwarePluginDefaultSetting)?)
-}
 
-#[cfg(test)]
-mod tests {
+    /// Install the requested firmware and reboot the device,
+    /// persisting the `restarting` state in the given store to be resumed on restart.
+    async fn install_firmware(
+        &self,
+        request: &FirmwareUpdateRequest,
+        store: &AgentStateRepository,
+        restarting: &StateStatus,
+    ) -> Result<(), AgentError> {
+        let plugin = self.firmware_plugin()?;
+        let mut log_file = self
+            .operation_logs
+            .new_log_file(LogKind::FirmwareUpdate)
+            .await?;
 
-    use std::io::Write;
-    use std::path::PathBuf;
+        let () = plugin
+            .update(request, log_file.buffer(), &self.config.download_dir)
+            .await?;
 
-    use assert_json_diff::assert_json_include;
-    use serde_json::Value;
+        let () = store.update(restarting).await?;
+        let () = restart_operation::create_slash_run_file(&self.config.run_dir)?;
 
-    use super::*;
+        let () = plugin.reboot(log_file.buffer()).await?;
 
-    const SLASH_RUN_PATH_TEDGE_AGENT_RESTART: &str = "tedge_agent/tedge_agent_restart";
+        Ok(())
+    }
 
-    #[ignore]
-    #[tokio::test]
-    async fn check_agent_restart_file_is_created() -> Result<(), AgentError> {
-        assert_eq!(INIT_COMMAND, "echo");
+    async fn resume_firmware_update(
+        &self,
+        id: &str,
+        firmware: &FirmwareOperationState,
+    ) -> FirmwareUpdateResponse {
+        let response = FirmwareUpdateResponse::new_with_id(id);
+        match firmware.status {
+            FirmwareOperationStatus::Installing => {
+                response.with_error("The firmware update has been interrupted")
+            }
+            FirmwareOperationStatus::Restarting => match self.verify_firmware(firmware).await {
+                Ok(()) => {
+                    info!("Firmware update successful.");
+                    response
+                        .with_status(OperationStatus::Successful)
+                        .with_firmware(
+                            &firmware.name,
+                            firmware.version.clone(),
+                            firmware.url.clone(),
+                        )
+                }
+                Err(error) => {
+                    error!("{}", error);
+                    response.with_error(&error.to_string())
+                }
+            },
+        }
+    }
 
-        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
-        let agent = SmAgent::try_new(
-            "tedge_agent_test",
-            SmAgentConfig::try_new(tedge_config_location).unwrap(),
-        )
-        .unwrap();
+    async fn verify_firmware(&self, firmware: &FirmwareOperationState) -> Result<(), AgentError> {
+        if !restart_operation::has_rebooted(&self.config.run_dir)? {
+            return Err(FirmwareError::Reboot {
+                reason: "The device has not been restarted".into(),
+            }
+            .into());
+        }
 
-        // calling handle_restart_operation should create a file in /run/tedge_agent_restart
-        let (_, mut output_stream) = mqtt_tests::output_stream();
-        let response_topic_restart =
-            Topic::new(RestartOperationResponse::topic_name()).expect("Invalid topic");
-        let () = agent
-            .handle_restart_operation(&mut output_stream, &response_topic_restart)
+        let plugin = self.firmware_plugin()?;
+        let mut log_file = self
+            .operation_logs
+            .new_log_file(LogKind::FirmwareUpdate)
             .await?;
-        assert!(
-            std::path::Path::new(&dir.path().join(SLASH_RUN_PATH_TEDGE_AGENT_RESTART)).exists()
-        );
 
-        // removing the file
-        let () =
-            std::fs::remove_file(&dir.path().join(SLASH_RUN_PATH_TEDGE_AGENT_RESTART)).unwrap();
+        let () = plugin
+            .verify(&firmware.name, &firmware.version, log_file.buffer())
+            .await?;
 
         Ok(())
     }
 
-    fn message(t: &str, p: &str) -> Message {
-        let topic = Topic::new(t).expect("a valid topic");
-        let payload = p.as_bytes();
-        Message::new(&topic, payload)
-    }
+    async fn handle_device_profile_request(
+        &self,
+        responses: &mut impl PubChannel,
+        plugins: &Arc<Mutex<ExternalPlugins>>,
+        message: &Message,
+    ) -> Result<(), AgentError> {
+        let request = match DeviceProfileRequest::from_slice(message.payload_bytes()) {
+            Ok(request) => {
+                let state = DeviceProfileState::new(DeviceProfileStage::Firmware, &request)?;
+                let () = self
+                    .device_profile_store
+                    .store(&State {
+                        operation_id: Some(request.id.clone()),
+                        operation: Some(StateStatus::DeviceProfile(state)),
+                    })
+                    .await?;
 
-    fn create_temp_tedge_config() -> std::io::Result<(tempfile::TempDir, TEdgeConfigLocation)> {
-        let dir = tempfile::TempDir::new()?;
+                request
+            }
 
-        let dir_path = dir.path().join(".agent");
-        std::fs::create_dir(&dir_path).unwrap();
+            Err(error) => {
+                error!("Parsing error: {}", error);
+                let () = responses
+                    .publish(Message::new(
+                        &self.config.errors_topic,
+                        format!("{}", error),
+                    ))
+                    .await?;
 
-        let () = {
-            let _file = std::fs::File::create(dir.path().join(".agent/current-operation")).unwrap();
+                return Err(SoftwareError::ParseError {
+                    reason: "Parsing failed".into(),
+                }
+                .into());
+            }
         };
 
-        let dir_path = dir.path().join("sm-plugins");
-        std::fs::create_dir(dir_path).unwrap();
-
-        let dir_path = dir.path().join("lock");
-        std::fs::create_dir(dir_path).unwrap();
-
-        let dir_path = dir.path().join("logs");
-        std::fs::create_dir(dir_path).unwrap();
+        let executing_response = DeviceProfileResponse::new(&request);
+        let () = responses
+            .publish(Message::new(
+                &self.config.response_topic_device_profile,
+                executing_response.to_bytes()?,
+            ))
+            .await?;
 
-        let toml_conf = &format!(
-            r#"
-            [logs]
-            path = '{}'
-            [run]
-            path = '{}'"#,
-            &dir.path().join("logs").to_str().unwrap(),
-            &dir.path().to_str().unwrap()
-        );
+        self.apply_device_profile(responses, plugins, &request, DeviceProfileStage::Firmware)
+            .await
+    }
 
-        let config_location = TEdgeConfigLocation::from_custom_root(dir.path());
-        let mut file = std::fs::File::create(config_location.tedge_config_file_path())?;
-        file.write_all(toml_conf.as_bytes())?;
-        Ok((dir, config_location))
+    /// Apply the device profile from the given stage on,
+    /// reporting the final status unless the workflow has to wait
+    /// for a device reboot or for configuration updates.
+    async fn apply_device_profile(
+        &self,
+        responses: &mut impl PubChannel,
+        plugins: &Arc<Mutex<ExternalPlugins>>,
+        request: &DeviceProfileRequest,
+        stage: DeviceProfileStage,
+    ) -> Result<(), AgentError> {
+        match self
+            .run_device_profile(responses, plugins, request, stage)
+            .await
+        {
+            Ok(Some(_waiting_stage)) => Ok(()),
+            Ok(None) => {
+                self.complete_device_profile(responses, &request.id, &[])
+                    .await
+            }
+            Err(error) => {
+                error!("{}", error);
+                self.complete_device_profile(responses, &request.id, &[error.to_string()])
+                    .await
+            }
+        }
     }
 
-    #[tokio::test]
-    /// testing that tedge agent returns an expety software list when there is no sm plugin
-    async fn test_empty_software_list_returned_when_no_sm_plugin() -> Result<(), AgentError> {
-        let (output, mut output_sink) = mqtt_tests::output_stream();
-        let expected_messages = vec![
-            message(
-                r#"tedge/commands/res/software/list"#,
-                r#"{"id":"123","status":"executing"}"#,
+    /// Run the device profile steps from the given stage on: firmware, software then configuration.
+    ///
+    /// Return the stage the workflow is waiting at, if any.
+    async fn run_device_profile(
+        &self,
+        responses: &mut impl PubChannel,
+        plugins: &Arc<Mutex<ExternalPlugins>>,
+        request: &DeviceProfileRequest,
+        stage: DeviceProfileStage,
+    ) -> Result<Option<DeviceProfileStage>, AgentError> {
+        if stage == DeviceProfileStage::Firmware {
+            if let Some(firmware) = request.firmware_request() {
+                let restarting = DeviceProfileState::new(DeviceProfileStage::Restarting, request)?;
+                let () = self
+                    .install_firmware(
+                        &firmware,
+                        &self.device_profile_store,
+                        &StateStatus::DeviceProfile(restarting),
+                    )
+                    .await?;
+                return Ok(Some(DeviceProfileStage::Restarting));
+            }
+        }
+
+        if stage != DeviceProfileStage::Configuration {
+            if let Some(software) = request.software_request() {
+                let state = DeviceProfileState::new(DeviceProfileStage::Software, request)?;
+                let () = self
+                    .device_profile_store
+                    .update(&StateStatus::DeviceProfile(state))
+                    .await?;
+                let () = self.update_software(plugins, &software).await?;
+            }
+        }
+
+        let config_requests = request.config_requests();
+        if config_requests.is_empty() {
+            return Ok(None);
+        }
+
+        let state = DeviceProfileState {
+            pending_configurations: config_requests.iter().map(|req| req.id.clone()).collect(),
+            configuration_deadline: Some(self.new_configuration_deadline()),
+            ..DeviceProfileState::new(DeviceProfileStage::Configuration, request)?
+        };
+        let () = self.request_configuration_updates(responses, state).await?;
+
+        Ok(Some(DeviceProfileStage::Configuration))
+    }
+
+    /// Persist the device profile state, then request the configuration plugin
+    /// to apply the configuration updates still pending.
+    async fn request_configuration_updates(
+        &self,
+        responses: &mut impl PubChannel,
+        state: DeviceProfileState,
+    ) -> Result<(), AgentError> {
+        let config_requests = state.pending_requests()?;
+        let () = self
+            .device_profile_store
+            .update(&StateStatus::DeviceProfile(state))
+            .await?;
+
+        // The configuration files are updated by the configuration plugin
+        for config_request in config_requests {
+            let () = responses
+                .publish(Message::new(
+                    &self.config.request_topic_config_update,
+                    config_request.to_bytes()?,
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn new_configuration_deadline(&self) -> i64 {
+        OffsetDateTime::now_utc().unix_timestamp()
+            + self.config.config_update_timeout.as_secs() as i64
+    }
+
+    /// The unix timestamp at which the pending configuration updates of the device profile time out, if any.
+    async fn device_profile_deadline(&self) -> Option<i64> {
+        match self.device_profile_store.load().await {
+            Ok(State {
+                operation: Some(StateStatus::DeviceProfile(profile)),
+                ..
+            }) => profile.configuration_deadline,
+            _ => None,
+        }
+    }
+
+    /// Fail the device profile, its configuration updates having not been completed in time.
+    async fn time_out_device_profile(
+        &self,
+        responses: &mut impl PubChannel,
+    ) -> Result<(), AgentError> {
+        if let State {
+            operation_id: Some(id),
+            operation: Some(StateStatus::DeviceProfile(mut profile)),
+        } = self.device_profile_store.load().await?
+        {
+            profile.failures.push(format!(
+                "Timeout waiting for the configuration updates: {}",
+                profile.pending_configurations.join(", ")
+            ));
+            let () = self
+                .complete_device_profile(responses, &id, &profile.failures)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn update_software(
+        &self,
+        plugins: &Arc<Mutex<ExternalPlugins>>,
+        request: &SoftwareUpdateRequest,
+    ) -> Result<(), AgentError> {
+        let () = plugins.lock().await.load()?;
+        let () = plugins
+            .lock()
+            .await
+            .update_default(&get_default_plugin(&self.config.config_location)?)?;
+
+        let log_file = self
+            .operation_logs
+            .new_log_file(LogKind::SoftwareUpdate)
+            .await?;
+        let response = plugins
+            .lock()
+            .await
+            .process(request, log_file, &self.config.download_dir)
+            .await;
+
+        match response.status() {
+            OperationStatus::Failed => Err(AgentError::SoftwareUpdateFailed {
+                reason: response.error().unwrap_or_default(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    async fn handle_config_update_response(
+        &self,
+        responses: &mut impl PubChannel,
+        message: &Message,
+    ) -> Result<(), AgentError> {
+        let response = ConfigUpdateResponse::from_slice(message.payload_bytes())?;
+        if response.status() == OperationStatus::Executing {
+            return Ok(());
+        }
+
+        let (id, mut profile) = match self.device_profile_store.load().await? {
+            State {
+                operation_id: Some(id),
+                operation: Some(StateStatus::DeviceProfile(profile)),
+            } if profile.pending_configurations.contains(&response.id) => (id, profile),
+            _ => {
+                warn!("Unexpected configuration update response: {}", response.id);
+                return Ok(());
+            }
+        };
+
+        profile
+            .pending_configurations
+            .retain(|config_id| config_id != &response.id);
+        if response.status() == OperationStatus::Failed {
+            profile.failures.push(
+                response
+                    .error()
+                    .unwrap_or_else(|| format!("Configuration update {} failed", response.id)),
+            );
+        }
+
+        if profile.pending_configurations.is_empty() {
+            self.complete_device_profile(responses, &id, &profile.failures)
+                .await
+        } else {
+            let () = self
+                .device_profile_store
+                .update(&StateStatus::DeviceProfile(profile))
+                .await?;
+            Ok(())
+        }
+    }
+
+    async fn complete_device_profile(
+        &self,
+        responses: &mut impl PubChannel,
+        id: &str,
+        failures: &[String],
+    ) -> Result<(), AgentError> {
+        let _state = self.device_profile_store.clear().await?;
+
+        let response = DeviceProfileResponse::new_with_id(id);
+        let response = if failures.is_empty() {
+            info!("Device profile successfully applied.");
+            response.with_status(OperationStatus::Successful)
+        } else {
+            response.with_error(&failures.join("; "))
+        };
+
+        let () = responses
+            .publish(Message::new(
+                &self.config.response_topic_device_profile,
+                response.to_bytes()?,
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn resume_device_profile(
+        &self,
+        responses: &mut impl PubChannel,
+        plugins: &Arc<Mutex<ExternalPlugins>>,
+        id: &str,
+        profile: &DeviceProfileState,
+    ) -> Result<(), AgentError> {
+        let request = profile.request()?;
+        match profile.stage {
+            DeviceProfileStage::Firmware | DeviceProfileStage::Software => {
+                let reason = "The device profile update has been interrupted".to_string();
+                self.complete_device_profile(responses, id, &[reason]).await
+            }
+
+            DeviceProfileStage::Restarting => {
+                let firmware = FirmwareOperationState {
+                    status: FirmwareOperationStatus::Restarting,
+                    name: request
+                        .firmware
+                        .as_ref()
+                        .map(|f| f.name.clone())
+                        .unwrap_or_default(),
+                    version: request.firmware.as_ref().and_then(|f| f.version.clone()),
+                    url: request
+                        .firmware
+                        .as_ref()
+                        .and_then(|f| f.url.as_ref())
+                        .map(|url| url.url.clone()),
+                };
+                if let Err(error) = self.verify_firmware(&firmware).await {
+                    error!("{}", error);
+                    return self
+                        .complete_device_profile(responses, id, &[error.to_string()])
+                        .await;
+                }
+
+                self.apply_device_profile(
+                    responses,
+                    plugins,
+                    &request,
+                    DeviceProfileStage::Software,
+                )
+                .await
+            }
+
+            DeviceProfileStage::Configuration => {
+                let now = OffsetDateTime::now_utc().unix_timestamp();
+                match profile.configuration_deadline {
+                    _ if profile.pending_configurations.is_empty() => {
+                        self.complete_device_profile(responses, id, &profile.failures)
+                            .await
+                    }
+                    Some(deadline) if deadline <= now => {
+                        self.time_out_device_profile(responses).await
+                    }
+                    deadline => {
+                        // The requests might have been lost while the agent was down
+                        let state = DeviceProfileState {
+                            configuration_deadline: deadline
+                                .or_else(|| Some(self.new_configuration_deadline())),
+                            ..profile.clone()
+                        };
+                        self.request_configuration_updates(responses, state).await
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resume the device profile interrupted by a restart, if any.
+    async fn process_pending_device_profile(
+        &self,
+        responses: &mut impl PubChannel,
+        plugins: &Arc<Mutex<ExternalPlugins>>,
+    ) -> Result<(), AgentError> {
+        match self.device_profile_store.load().await {
+            Ok(State {
+                operation_id: Some(id),
+                operation: Some(StateStatus::DeviceProfile(profile)),
+            }) => {
+                self.resume_device_profile(responses, plugins, &id, &profile)
+                    .await
+            }
+            Ok(_) => Ok(()),
+            Err(_) => {
+                let _state = self.device_profile_store.clear().await?;
+                Ok(())
+            }
+        }
+    }
+
+    fn firmware_plugin(&self) -> Result<ExternalFirmwarePlugin, FirmwareError> {
+        ExternalFirmwarePlugin::open(
+            self.config.sm_home.join(FIRMWARE_PLUGINS),
+            FIRMWARE_PLUGIN_SUDO.map(PathBuf::from),
+        )
+    }
+
+    async fn process_pending_operation(
+        &self,
+        responses: &mut impl PubChannel,
+    ) -> Result<(), AgentError> {
+        let state: Result<State, _> = self.persistance_store.load().await;
+        let mut status = OperationStatus::Failed;
+
+        if let State {
+            operation_id: Some(id),
+            operation: Some(operation),
+        } = match state {
+            Ok(state) => state,
+            Err(_) => State {
+                operation_id: None,
+                operation: None,
+            },
+        } {
+            let topic = match operation {
+                StateStatus::Software(SoftwareOperationVariants::List) => {
+                    &self.config.response_topic_list
+                }
+
+                StateStatus::Software(SoftwareOperationVariants::Update) => {
+                    &self.config.response_topic_update
+                }
+
+                StateStatus::Restart(RestartOperationStatus::Pending) => {
+                    &self.config.response_topic_restart
+                }
+
+                StateStatus::Restart(RestartOperationStatus::Restarting) => {
+                    let _state = self.persistance_store.clear().await?;
+                    if restart_operation::has_rebooted(&self.config.run_dir)? {
+                        info!("Device restart successful.");
+                        status = OperationStatus::Successful;
+                    }
+                    &self.config.response_topic_restart
+                }
+
+                StateStatus::Firmware(firmware) => {
+                    let _state = self.persistance_store.clear().await?;
+                    let response = self.resume_firmware_update(&id, &firmware).await;
+
+                    let () = responses
+                        .publish(Message::new(
+                            &self.config.response_topic_firmware_update,
+                            response.to_bytes()?,
+                        ))
+                        .await?;
+                    return Ok(());
+                }
+
+                // Device profiles are persisted in their own store
+                StateStatus::DeviceProfile(_) | StateStatus::UnknownOperation => {
+                    error!("UnknownOperation in store.");
+                    &self.config.errors_topic
+                }
+            };
+
+            let response = SoftwareRequestResponse::new(&id, status);
+
+            let () = responses
+                .publish(Message::new(topic, response.to_bytes()?))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn time_until(unix_timestamp: i64) -> Duration {
+    let delay = unix_timestamp - OffsetDateTime::now_utc().unix_timestamp();
+    Duration::from_secs(delay.try_into().unwrap_or(0))
+}
+
+fn get_default_plugin(
+    config_location: &TEdgeConfigLocation,
+) -> Result<Option<SoftwareType>, AgentError> {
+    let config_repository = tedge_config::TEdgeConfigRepository::new(config_location.clone());
+    let tedge_config = config_repository.load()?;
+
+    Ok(tedge_config.query_string_optional(SoftwarePluginDefaultSetting)?)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    use assert_json_diff::assert_json_include;
+    use serde_json::Value;
+
+    use super::*;
+
+    const SLASH_RUN_PATH_TEDGE_AGENT_RESTART: &str = "tedge_agent/tedge_agent_restart";
+
+    #[ignore]
+    #[tokio::test]
+    async fn check_agent_restart_file_is_created() -> Result<(), AgentError> {
+        assert_eq!(INIT_COMMAND, "echo");
+
+        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+        let agent = SmAgent::try_new(
+            "tedge_agent_test",
+            SmAgentConfig::try_new(tedge_config_location).unwrap(),
+        )
+        .unwrap();
+
+        // calling handle_restart_operation should create a file in /run/tedge_agent_restart
+        let (_, mut output_stream) = mqtt_tests::output_stream();
+        let response_topic_restart =
+            Topic::new(RestartOperationResponse::topic_name()).expect("Invalid topic");
+        let () = agent
+            .handle_restart_operation(&mut output_stream, &response_topic_restart)
+            .await?;
+        assert!(
+            std::path::Path::new(&dir.path().join(SLASH_RUN_PATH_TEDGE_AGENT_RESTART)).exists()
+        );
+
+        // removing the file
+        let () =
+            std::fs::remove_file(&dir.path().join(SLASH_RUN_PATH_TEDGE_AGENT_RESTART)).unwrap();
+
+        Ok(())
+    }
+
+    fn message(t: &str, p: &str) -> Message {
+        let topic = Topic::new(t).expect("a valid topic");
+        let payload = p.as_bytes();
+        Message::new(&topic, payload)
+    }
+
+    fn create_temp_tedge_config() -> std::io::Result<(tempfile::TempDir, TEdgeConfigLocation)> {
+        let dir = tempfile::TempDir::new()?;
+
+        let dir_path = dir.path().join(".agent");
+        std::fs::create_dir(&dir_path).unwrap();
+
+        let () = {
+            let _file = std::fs::File::create(dir.path().join(".agent/current-operation")).unwrap();
+        };
+
+        let dir_path = dir.path().join("sm-plugins");
+        std::fs::create_dir(dir_path).unwrap();
+
+        let dir_path = dir.path().join("lock");
+        std::fs::create_dir(dir_path).unwrap();
+
+        let dir_path = dir.path().join("logs");
+        std::fs::create_dir(dir_path).unwrap();
+
+        let toml_conf = &format!(
+            r#"
+            [logs]
+            path = '{}'
+            [run]
+            path = '{}'"#,
+            &dir.path().join("logs").to_str().unwrap(),
+            &dir.path().to_str().unwrap()
+        );
+
+        let config_location = TEdgeConfigLocation::from_custom_root(dir.path());
+        let mut file = std::fs::File::create(config_location.tedge_config_file_path())?;
+        file.write_all(toml_conf.as_bytes())?;
+        Ok((dir, config_location))
+    }
+
+    #[tokio::test]
+    /// testing that tedge agent returns an expety software list when there is no sm plugin
+    async fn test_empty_software_list_returned_when_no_sm_plugin() -> Result<(), AgentError> {
+        let (output, mut output_sink) = mqtt_tests::output_stream();
+        let expected_messages = vec![
+            message(
+                r#"tedge/commands/res/software/list"#,
+                r#"{"id":"123","status":"executing"}"#,
+            ),
+            message(
+                r#"tedge/commands/res/software/list"#,
+                r#"{"id":"123","status":"successful","currentSoftwareList":[{"type":"","modules":[]}]}"#,
+            ),
+        ];
+        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+
+        tokio::spawn(async move {
+            let agent = SmAgent::try_new(
+                "tedge_agent_test",
+                SmAgentConfig::try_new(tedge_config_location).unwrap(),
+            )
+            .unwrap();
+
+            let response_topic_restart =
+                Topic::new(SoftwareListResponse::topic_name()).expect("Invalid topic");
+
+            let plugins = Arc::new(Mutex::new(
+                ExternalPlugins::open(
+                    PathBuf::from(&dir.path()).join("sm-plugins"),
+                    get_default_plugin(&agent.config.config_location).unwrap(),
+                    Some("sudo".into()),
+                )
+                .unwrap(),
+            ));
+            let () = agent
+                .handle_software_list_request(
+                    &mut output_sink,
+                    plugins,
+                    &response_topic_restart,
+                    &Message::new(&response_topic_restart, r#"{"id":"123"}"#),
+                )
+                .await
+                .unwrap();
+        });
+
+        let response = output.collect().await;
+        assert_eq!(expected_messages, response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    /// test health check request response contract
+    async fn health_check() -> Result<(), AgentError> {
+        let (responses, mut response_sink) = mqtt_tests::output_stream();
+        let mut requests = mqtt_tests::input_stream(vec![
+            message("tedge/health-check/tedge-agent", ""),
+            message("tedge/health-check", ""),
+        ])
+        .await;
+
+        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+
+        tokio::spawn(async move {
+            let mut agent = SmAgent::try_new(
+                "tedge_agent_test",
+                SmAgentConfig::try_new(tedge_config_location).unwrap(),
+            )
+            .unwrap();
+
+            let plugins = Arc::new(Mutex::new(
+                ExternalPlugins::open(
+                    PathBuf::from(&dir.path()).join("sm-plugins"),
+                    get_default_plugin(&agent.config.config_location).unwrap(),
+                    Some("sudo".into()),
+                )
+                .unwrap(),
+            ));
+            let () = agent
+                .process_subscribed_messages(&mut requests, &mut response_sink, &plugins)
+                .await
+                .unwrap();
+        });
+
+        let responses = responses.collect().await;
+        assert_eq!(responses.len(), 2);
+
+        for response in responses {
+            assert_eq!(response.topic.name, "tedge/health/tedge-agent");
+            let health_status: Value = serde_json::from_slice(response.payload_bytes())?;
+            assert_json_include!(actual: &health_status, expected: json!({"status": "up"}));
+            assert!(health_status["pid"].is_number());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    /// test that a firmware update is verified once the device has been rebooted
+    async fn firmware_update_completes_after_reboot() -> Result<(), AgentError> {
+        let (output, mut output_sink) = mqtt_tests::output_stream();
+        let expected_messages = vec![
+            message(
+                r#"tedge/commands/res/firmware/update"#,
+                r#"{"id":"123","status":"executing"}"#,
             ),
             message(
-                r#"tedge/commands/res/software/list"#,
-                r#"{"id":"123","status":"successful","currentSoftwareList":[{"type":"","modules":[]}]}"#,
+                r#"tedge/commands/res/firmware/update"#,
+                r#"{"id":"123","status":"successful","name":"bootloader","version":"1.0"}"#,
             ),
         ];
         let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+        let calls = create_dummy_firmware_plugin(dir.path());
+        let run_dir = dir.path().to_path_buf();
 
         tokio::spawn(async move {
             let agent = SmAgent::try_new(
@@ -745,23 +1516,159 @@ mod tests {
             )
             .unwrap();
 
-            let response_topic_restart =
-                Topic::new(SoftwareListResponse::topic_name()).expect("Invalid topic");
+            let request_topic =
+                Topic::new(FirmwareUpdateRequest::topic_name()).expect("Invalid topic");
+            let () = agent
+                .handle_firmware_update_request(
+                    &mut output_sink,
+                    &Message::new(
+                        &request_topic,
+                        r#"{"id":"123","name":"bootloader","version":"1.0"}"#,
+                    ),
+                )
+                .await
+                .unwrap();
+
+            // /run is cleared by the reboot
+            let () =
+                std::fs::remove_file(run_dir.join(SLASH_RUN_PATH_TEDGE_AGENT_RESTART)).unwrap();
+
+            let () = agent
+                .process_pending_operation(&mut output_sink)
+                .await
+                .unwrap();
+        });
+
+        let response = output.collect().await;
+        assert_eq!(expected_messages, response);
+        assert_eq!(
+            std::fs::read_to_string(calls).unwrap(),
+            "prepare\ninstall bootloader --firmware-version 1.0\nreboot\nverify bootloader --firmware-version 1.0\n"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    /// test that a firmware update fails if the device has not been rebooted
+    async fn firmware_update_fails_without_reboot() -> Result<(), AgentError> {
+        let (output, mut output_sink) = mqtt_tests::output_stream();
+        let expected_messages = vec![
+            message(
+                r#"tedge/commands/res/firmware/update"#,
+                r#"{"id":"123","status":"executing"}"#,
+            ),
+            message(
+                r#"tedge/commands/res/firmware/update"#,
+                r#"{"id":"123","status":"failed","reason":"Failed to reboot the device: The device has not been restarted"}"#,
+            ),
+        ];
+        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+        let _calls = create_dummy_firmware_plugin(dir.path());
+
+        tokio::spawn(async move {
+            let agent = SmAgent::try_new(
+                "tedge_agent_test",
+                SmAgentConfig::try_new(tedge_config_location).unwrap(),
+            )
+            .unwrap();
+
+            let request_topic =
+                Topic::new(FirmwareUpdateRequest::topic_name()).expect("Invalid topic");
+            let () = agent
+                .handle_firmware_update_request(
+                    &mut output_sink,
+                    &Message::new(&request_topic, r#"{"id":"123","name":"bootloader"}"#),
+                )
+                .await
+                .unwrap();
+
+            let () = agent
+                .process_pending_operation(&mut output_sink)
+                .await
+                .unwrap();
+        });
+
+        let response = output.collect().await;
+        assert_eq!(expected_messages, response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    /// test that a device profile installs the firmware, then waits for the configuration updates
+    async fn device_profile_is_applied_across_reboot_and_configuration_updates(
+    ) -> Result<(), AgentError> {
+        let (output, mut output_sink) = mqtt_tests::output_stream();
+        let expected_messages = vec![
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"executing"}"#,
+            ),
+            message(
+                r#"tedge/commands/req/config/update"#,
+                r#"{"id":"123-0","type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}"#,
+            ),
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"failed","reason":"Unknown configuration type"}"#,
+            ),
+        ];
+        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+        let calls = create_dummy_firmware_plugin(dir.path());
+        let run_dir = dir.path().to_path_buf();
+        let sm_plugins_dir = dir.path().join("sm-plugins");
+
+        tokio::spawn(async move {
+            let agent = SmAgent::try_new(
+                "tedge_agent_test",
+                SmAgentConfig::try_new(tedge_config_location).unwrap(),
+            )
+            .unwrap();
 
             let plugins = Arc::new(Mutex::new(
                 ExternalPlugins::open(
-                    PathBuf::from(&dir.path()).join("sm-plugins"),
+                    &sm_plugins_dir,
                     get_default_plugin(&agent.config.config_location).unwrap(),
                     Some("sudo".into()),
                 )
                 .unwrap(),
             ));
+
+            let request_topic =
+                Topic::new(DeviceProfileRequest::topic_name()).expect("Invalid topic");
             let () = agent
-                .handle_software_list_request(
+                .handle_device_profile_request(
                     &mut output_sink,
-                    plugins,
-                    &response_topic_restart,
-                    &Message::new(&response_topic_restart, r#"{"id":"123"}"#),
+                    &plugins,
+                    &Message::new(
+                        &request_topic,
+                        r#"{"id":"123",
+                            "firmware":{"name":"bootloader","version":"1.0"},
+                            "configuration":[{"type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}]}"#,
+                    ),
+                )
+                .await
+                .unwrap();
+
+            // /run is cleared by the reboot
+            let () =
+                std::fs::remove_file(run_dir.join(SLASH_RUN_PATH_TEDGE_AGENT_RESTART)).unwrap();
+
+            let () = agent
+                .process_pending_device_profile(&mut output_sink, &plugins)
+                .await
+                .unwrap();
+
+            let response_topic =
+                Topic::new(ConfigUpdateResponse::topic_name()).expect("Invalid topic");
+            let () = agent
+                .handle_config_update_response(
+                    &mut output_sink,
+                    &Message::new(
+                        &response_topic,
+                        r#"{"id":"123-0","status":"failed","reason":"Unknown configuration type"}"#,
+                    ),
                 )
                 .await
                 .unwrap();
@@ -769,20 +1676,53 @@ mod tests {
 
         let response = output.collect().await;
         assert_eq!(expected_messages, response);
+        assert_eq!(
+            std::fs::read_to_string(calls).unwrap(),
+            "prepare\ninstall bootloader --firmware-version 1.0\nreboot\nverify bootloader --firmware-version 1.0\n"
+        );
 
         Ok(())
     }
 
     #[tokio::test]
-    /// test health check request response contract
-    async fn health_check() -> Result<(), AgentError> {
-        let (responses, mut response_sink) = mqtt_tests::output_stream();
+    /// test that the device profile state is not overwritten by the operations run meanwhile
+    async fn device_profile_waits_for_configuration_updates_across_other_operations(
+    ) -> Result<(), AgentError> {
+        let (output, mut output_sink) = mqtt_tests::output_stream();
         let mut requests = mqtt_tests::input_stream(vec![
-            message("tedge/health-check/tedge-agent", ""),
-            message("tedge/health-check", ""),
+            message(
+                "tedge/commands/req/device/profile",
+                r#"{"id":"123","configuration":[{"type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}]}"#,
+            ),
+            message("tedge/commands/req/software/list", r#"{"id":"456"}"#),
+            message(
+                "tedge/commands/res/config/update",
+                r#"{"id":"123-0","status":"successful"}"#,
+            ),
         ])
         .await;
-
+        let expected_messages = vec![
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"executing"}"#,
+            ),
+            message(
+                r#"tedge/commands/req/config/update"#,
+                r#"{"id":"123-0","type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}"#,
+            ),
+            message(
+                r#"tedge/commands/res/software/list"#,
+                r#"{"id":"456","status":"executing"}"#,
+            ),
+            message(
+                r#"tedge/commands/res/software/list"#,
+                r#"{"id":"456","status":"successful","currentSoftwareList":[{"type":"","modules":[]}]}"#,
+            ),
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"successful"}"#,
+            ),
+        ];
         let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
 
         tokio::spawn(async move {
@@ -800,22 +1740,202 @@ mod tests {
                 )
                 .unwrap(),
             ));
+
             let () = agent
-                .process_subscribed_messages(&mut requests, &mut response_sink, &plugins)
+                .process_subscribed_messages(&mut requests, &mut output_sink, &plugins)
                 .await
                 .unwrap();
         });
 
-        let responses = responses.collect().await;
-        assert_eq!(responses.len(), 2);
+        let response = output.collect().await;
+        assert_eq!(expected_messages, response);
 
-        for response in responses {
-            assert_eq!(response.topic.name, "tedge/health/tedge-agent");
-            let health_status: Value = serde_json::from_slice(response.payload_bytes())?;
-            assert_json_include!(actual: &health_status, expected: json!({"status": "up"}));
-            assert!(health_status["pid"].is_number());
+        Ok(())
+    }
+
+    #[tokio::test]
+    /// test that the pending configuration updates are requested again when the agent restarts
+    async fn device_profile_configuration_updates_are_resumed_on_restart() -> Result<(), AgentError>
+    {
+        let (output, mut output_sink) = mqtt_tests::output_stream();
+        let expected_messages = vec![
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"executing"}"#,
+            ),
+            message(
+                r#"tedge/commands/req/config/update"#,
+                r#"{"id":"123-0","type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}"#,
+            ),
+            message(
+                r#"tedge/commands/req/config/update"#,
+                r#"{"id":"123-1","type":"tedge.toml","url":"https://example.com/tedge.toml"}"#,
+            ),
+            // On restart, only the configuration update not completed yet is requested again
+            message(
+                r#"tedge/commands/req/config/update"#,
+                r#"{"id":"123-1","type":"tedge.toml","url":"https://example.com/tedge.toml"}"#,
+            ),
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"successful"}"#,
+            ),
+        ];
+        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+        let sm_plugins_dir = dir.path().join("sm-plugins");
+
+        tokio::spawn(async move {
+            let agent = SmAgent::try_new(
+                "tedge_agent_test",
+                SmAgentConfig::try_new(tedge_config_location).unwrap(),
+            )
+            .unwrap();
+
+            let plugins = Arc::new(Mutex::new(
+                ExternalPlugins::open(
+                    &sm_plugins_dir,
+                    get_default_plugin(&agent.config.config_location).unwrap(),
+                    Some("sudo".into()),
+                )
+                .unwrap(),
+            ));
+
+            let () = agent
+                .handle_device_profile_request(
+                    &mut output_sink,
+                    &plugins,
+                    &message(
+                        "tedge/commands/req/device/profile",
+                        r#"{"id":"123",
+                            "configuration":[
+                                {"type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"},
+                                {"type":"tedge.toml","url":"https://example.com/tedge.toml"}]}"#,
+                    ),
+                )
+                .await
+                .unwrap();
+
+            let () = agent
+                .handle_config_update_response(
+                    &mut output_sink,
+                    &message(
+                        "tedge/commands/res/config/update",
+                        r#"{"id":"123-0","status":"successful"}"#,
+                    ),
+                )
+                .await
+                .unwrap();
+
+            let () = agent
+                .process_pending_device_profile(&mut output_sink, &plugins)
+                .await
+                .unwrap();
+
+            let () = agent
+                .handle_config_update_response(
+                    &mut output_sink,
+                    &message(
+                        "tedge/commands/res/config/update",
+                        r#"{"id":"123-1","status":"successful"}"#,
+                    ),
+                )
+                .await
+                .unwrap();
+        });
+
+        let response = output.collect().await;
+        assert_eq!(expected_messages, response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    /// test that a device profile fails when the configuration updates are not completed in time
+    async fn device_profile_fails_when_configuration_updates_time_out() -> Result<(), AgentError> {
+        let (mut output_sink, mut output) = futures::channel::mpsc::unbounded();
+        let (requests_sink, mut requests) = futures::channel::mpsc::unbounded();
+        let expected_messages = vec![
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"executing"}"#,
+            ),
+            message(
+                r#"tedge/commands/req/config/update"#,
+                r#"{"id":"123-0","type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}"#,
+            ),
+            message(
+                r#"tedge/commands/res/device/profile"#,
+                r#"{"id":"123","status":"failed","reason":"Timeout waiting for the configuration updates: 123-0"}"#,
+            ),
+        ];
+        let (dir, tedge_config_location) = create_temp_tedge_config().unwrap();
+        let sm_plugins_dir = dir.path().join("sm-plugins");
+
+        tokio::spawn(async move {
+            let config = SmAgentConfig {
+                config_update_timeout: Duration::ZERO,
+                ..SmAgentConfig::try_new(tedge_config_location).unwrap()
+            };
+            let mut agent = SmAgent::try_new("tedge_agent_test", config).unwrap();
+
+            let plugins = Arc::new(Mutex::new(
+                ExternalPlugins::open(
+                    &sm_plugins_dir,
+                    get_default_plugin(&agent.config.config_location).unwrap(),
+                    Some("sudo".into()),
+                )
+                .unwrap(),
+            ));
+
+            let () = agent
+                .handle_device_profile_request(
+                    &mut output_sink,
+                    &plugins,
+                    &message(
+                        "tedge/commands/req/device/profile",
+                        r#"{"id":"123","configuration":[{"type":"mosquitto.conf","url":"https://example.com/mosquitto.conf"}]}"#,
+                    ),
+                )
+                .await
+                .unwrap();
+
+            // No configuration update response is received
+            let () = agent
+                .process_subscribed_messages(&mut requests, &mut output_sink, &plugins)
+                .await
+                .unwrap();
+        });
+
+        let mut response = vec![];
+        while response.len() < expected_messages.len() {
+            let message = tokio::time::timeout(Duration::from_secs(5), output.next())
+                .await
+                .expect("a response before the timeout");
+            response.extend(message);
         }
+        assert_eq!(expected_messages, response);
+        drop(requests_sink);
 
         Ok(())
     }
+
+    // Install a firmware plugin that records its calls in the returned file.
+    fn create_dummy_firmware_plugin(dir: &std::path::Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::create_dir(dir.join("tedge_agent")).unwrap();
+        let plugin_dir = dir.join("firmware-plugins");
+        std::fs::create_dir(&plugin_dir).unwrap();
+
+        let calls = dir.join("firmware-plugin-calls");
+        let plugin_path = plugin_dir.join("dummy");
+        std::fs::write(
+            &plugin_path,
+            format!("#!/bin/sh\necho \"$@\" >> {}\n", calls.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&plugin_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        calls
+    }
 }
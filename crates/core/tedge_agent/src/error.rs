@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use agent_interface::SoftwareError;
+use agent_interface::{FirmwareError, SoftwareError};
 use flockfile::FlockfileError;
 use mqtt_channel::MqttError;
 use tedge_config::{ConfigSettingError, TEdgeConfigError};
@@ -26,6 +26,9 @@ pub enum AgentError {
     #[error(transparent)]
     FromSoftware(#[from] SoftwareError),
 
+    #[error(transparent)]
+    FromFirmware(#[from] FirmwareError),
+
     #[error(transparent)]
     FromState(#[from] StateError),
 
@@ -50,6 +53,9 @@ pub enum AgentError {
     #[error("Could not convert {timestamp:?} to unix timestamp. Error message: {}")]
     TimestampConversionError { timestamp: i64, error_msg: String },
 
+    #[error("Failed to update the software: {reason}")]
+    SoftwareUpdateFailed { reason: String },
+
     #[error(transparent)]
     FromOperationsLogs(#[from] plugin_sm::operation_logs::OperationLogsError),
 }
@@ -65,4 +71,7 @@ pub enum StateError {
 
     #[error(transparent)]
     FromIo(#[from] std::io::Error),
+
+    #[error(transparent)]
+    FromSerdeJson(#[from] serde_json::Error),
 }
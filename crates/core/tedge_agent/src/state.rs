@@ -1,4 +1,5 @@
 use crate::error::StateError;
+use agent_interface::{ConfigUpdateRequest, DeviceProfileRequest};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, str::FromStr};
@@ -75,11 +76,16 @@ impl StateRepository for AgentStateRepository {
 
 impl AgentStateRepository {
     pub fn new(tedge_root: PathBuf) -> Self {
+        Self::new_with_file_name(tedge_root, "current-operation")
+    }
+
+    /// A repository stored in its own file, so its state is not overwritten by the other operations.
+    pub fn new_with_file_name(tedge_root: PathBuf, file_name: &str) -> Self {
         let mut state_repo_root = tedge_root;
         state_repo_root.push(PathBuf::from_str(".agent").expect("infallible"));
 
         let mut state_repo_path = state_repo_root.clone();
-        state_repo_path.push(PathBuf::from_str("current-operation").expect("infallible"));
+        state_repo_path.push(file_name);
 
         Self {
             state_repo_path,
@@ -93,6 +99,8 @@ impl AgentStateRepository {
 pub enum StateStatus {
     Software(SoftwareOperationVariants),
     Restart(RestartOperationStatus),
+    Firmware(FirmwareOperationState),
+    DeviceProfile(DeviceProfileState),
     UnknownOperation,
 }
 
@@ -109,6 +117,83 @@ pub enum RestartOperationStatus {
     Restarting,
 }
 
+/// The firmware being installed, kept to verify it once the device has been rebooted.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FirmwareOperationState {
+    pub status: FirmwareOperationStatus,
+    pub name: String,
+    pub version: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FirmwareOperationStatus {
+    Installing,
+    Restarting,
+}
+
+/// The device profile being applied, kept to resume the workflow
+/// once the device has been rebooted on a new firmware or the configuration files updated.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceProfileState {
+    pub stage: DeviceProfileStage,
+
+    /// The JSON device profile request
+    pub request: String,
+
+    /// The ids of the configuration update requests not completed yet
+    #[serde(default)]
+    pub pending_configurations: Vec<String>,
+
+    /// The reasons of the configuration updates that failed
+    #[serde(default)]
+    pub failures: Vec<String>,
+
+    /// The unix timestamp after which the pending configuration updates are considered as failed
+    #[serde(default)]
+    pub configuration_deadline: Option<i64>,
+}
+
+impl DeviceProfileState {
+    pub fn new(
+        stage: DeviceProfileStage,
+        request: &DeviceProfileRequest,
+    ) -> Result<Self, StateError> {
+        Ok(DeviceProfileState {
+            stage,
+            request: serde_json::to_string(request)?,
+            pending_configurations: vec![],
+            failures: vec![],
+            configuration_deadline: None,
+        })
+    }
+
+    pub fn request(&self) -> Result<DeviceProfileRequest, StateError> {
+        Ok(serde_json::from_str(&self.request)?)
+    }
+
+    /// The configuration update requests not completed yet
+    pub fn pending_requests(&self) -> Result<Vec<ConfigUpdateRequest>, StateError> {
+        Ok(self
+            .request()?
+            .config_requests()
+            .into_iter()
+            .filter(|request| self.pending_configurations.contains(&request.id))
+            .collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DeviceProfileStage {
+    Firmware,
+    Restarting,
+    Software,
+    Configuration,
+}
+
 #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct State {
@@ -119,7 +204,8 @@ pub struct State {
 #[cfg(test)]
 mod tests {
     use crate::state::{
-        AgentStateRepository, RestartOperationStatus, SoftwareOperationVariants, State,
+        AgentStateRepository, DeviceProfileStage, DeviceProfileState, FirmwareOperationState,
+        FirmwareOperationStatus, RestartOperationStatus, SoftwareOperationVariants, State,
         StateRepository, StateStatus,
     };
 
@@ -222,4 +308,75 @@ mod tests {
 
         assert_eq!(data, "operation_id = \'1234\'\noperation = \'list\'\n");
     }
+
+    #[tokio::test]
+    async fn agent_state_repository_stores_and_loads_firmware_variant() {
+        let temp_dir = tempdir().unwrap();
+        let repo = AgentStateRepository::new(temp_dir.into_path());
+
+        let state = State {
+            operation_id: Some("1234".into()),
+            operation: Some(StateStatus::Firmware(FirmwareOperationState {
+                status: FirmwareOperationStatus::Restarting,
+                name: "bootloader".into(),
+                version: Some("1.0".into()),
+                url: Some("https://example.com/bootloader.bin".into()),
+            })),
+        };
+        repo.store(&state).await.unwrap();
+
+        let data = repo.load().await.unwrap();
+        assert_eq!(data, state);
+    }
+
+    #[tokio::test]
+    async fn agent_state_repository_stores_and_loads_device_profile_variant() {
+        let temp_dir = tempdir().unwrap();
+        let repo = AgentStateRepository::new(temp_dir.into_path());
+
+        let state = State {
+            operation_id: Some("1234".into()),
+            operation: Some(StateStatus::DeviceProfile(DeviceProfileState {
+                stage: DeviceProfileStage::Configuration,
+                request: r#"{"id":"1234"}"#.into(),
+                pending_configurations: vec!["1234-0".into()],
+                failures: vec![],
+                configuration_deadline: Some(1654000000),
+            })),
+        };
+        repo.store(&state).await.unwrap();
+
+        let data = repo.load().await.unwrap();
+        assert_eq!(data, state);
+    }
+
+    #[tokio::test]
+    async fn agent_state_repositories_with_different_file_names_are_independent() {
+        let temp_dir = tempdir().unwrap();
+        let operations = AgentStateRepository::new(temp_dir.path().to_path_buf());
+        let profiles =
+            AgentStateRepository::new_with_file_name(temp_dir.path().to_path_buf(), "profile");
+
+        let profile = State {
+            operation_id: Some("1234".into()),
+            operation: Some(StateStatus::DeviceProfile(DeviceProfileState {
+                stage: DeviceProfileStage::Configuration,
+                request: r#"{"id":"1234"}"#.into(),
+                pending_configurations: vec!["1234-0".into()],
+                failures: vec![],
+                configuration_deadline: None,
+            })),
+        };
+        profiles.store(&profile).await.unwrap();
+        operations
+            .store(&State {
+                operation_id: Some("5678".into()),
+                operation: Some(StateStatus::Software(SoftwareOperationVariants::List)),
+            })
+            .await
+            .unwrap();
+        operations.clear().await.unwrap();
+
+        assert_eq!(profiles.load().await.unwrap(), profile);
+    }
 }
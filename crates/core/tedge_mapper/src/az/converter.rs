@@ -1,9 +1,16 @@
-use crate::core::{converter::*, error::*, size_threshold::SizeThreshold};
+use crate::core::{
+    converter::*,
+    error::*,
+    measurements::{MeasurementTopic, TEDGE_MEASUREMENTS_CBOR_TOPIC, TEDGE_MEASUREMENTS_TOPIC},
+    size_threshold::SizeThreshold,
+};
 
 use async_trait::async_trait;
 use clock::Clock;
 use mqtt_channel::{Message, TopicFilter};
-use thin_edge_json::serialize::ThinEdgeJsonSerializer;
+use thin_edge_json::{location::ThinEdgeLocation, serialize::ThinEdgeJsonSerializer};
+
+const TEDGE_LOCATION_TOPIC: &str = "tedge/location";
 
 pub struct AzureConverter {
     pub(crate) add_timestamp: bool,
@@ -28,7 +35,15 @@ impl AzureConverter {
     }
 
     pub fn in_topic_filter() -> TopicFilter {
-        make_valid_topic_filter_or_panic("tedge/measurements")
+        let mut topic_filter = make_valid_topic_filter_or_panic(TEDGE_MEASUREMENTS_TOPIC);
+        topic_filter.add_all(make_valid_topic_filter_or_panic(
+            TEDGE_MEASUREMENTS_CBOR_TOPIC,
+        ));
+        topic_filter.add_all(make_valid_topic_filter_or_panic(TEDGE_LOCATION_TOPIC));
+        topic_filter.add_all(make_valid_topic_filter_or_panic(&format!(
+            "{TEDGE_LOCATION_TOPIC}/+"
+        )));
+        topic_filter
     }
 }
 
@@ -42,9 +57,31 @@ impl Converter for AzureConverter {
 
     async fn try_convert(&mut self, input: &Message) -> Result<Vec<Message>, Self::Error> {
         let () = self.size_threshold.validate(input)?;
+
+        // Locations are only validated, then forwarded as is,
+        // the child id being added to the locations of the child devices
+        if input.topic.name.starts_with(TEDGE_LOCATION_TOPIC) {
+            let location = ThinEdgeLocation::try_from(&input.topic.name, input.payload_str()?)?;
+            let payload = match location.child_id {
+                Some(child_id) => {
+                    let mut payload: serde_json::Value =
+                        serde_json::from_slice(input.payload_bytes())?;
+                    payload["child_id"] = child_id.into();
+                    payload.to_string().into_bytes()
+                }
+                None => input.payload_bytes().to_vec(),
+            };
+            return Ok(vec![Message::new(&self.mapper_config.out_topic, payload)]);
+        }
+
         let default_timestamp = self.add_timestamp.then(|| self.clock.now());
         let mut serializer = ThinEdgeJsonSerializer::new_with_timestamp(default_timestamp);
-        let () = thin_edge_json::parser::parse_str(input.payload_str()?, &mut serializer)?;
+        let cbor = MeasurementTopic::parse(&input.topic.name).map_or(false, |topic| topic.cbor);
+        if cbor {
+            let () = thin_edge_json::cbor::parse_slice(input.payload_bytes(), &mut serializer)?;
+        } else {
+            let () = thin_edge_json::parser::parse_str(input.payload_str()?, &mut serializer)?;
+        }
 
         let payload = serializer.into_string()?;
         Ok(vec![(Message::new(&self.mapper_config.out_topic, payload))])
@@ -189,6 +226,108 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn text_and_bool_measurements_are_passed_through() {
+        let mut converter =
+            AzureConverter::new(false, Box::new(TestClock), SizeThreshold(255 * 1024));
+
+        let input = r#"{"status": "running", "door_open": true, "temperature": 23.0}"#;
+        let output = converter.convert(&new_tedge_message(input)).await;
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&extract_first_message_payload(output))
+                .unwrap(),
+            json!({"status": "running", "door_open": true, "temperature": 23.0})
+        );
+    }
+
+    #[tokio::test]
+    async fn arrays_of_measurements_are_passed_through() {
+        let mut converter =
+            AzureConverter::new(true, Box::new(TestClock), SizeThreshold(255 * 1024));
+
+        let input = r#"[
+            {"time": "2013-06-22T17:03:14.000+02:00", "temperature": 23.0},
+            {"temperature": 24.0}
+        ]"#;
+        let output = converter.convert(&new_tedge_message(input)).await;
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&extract_first_message_payload(output))
+                .unwrap(),
+            json!([
+                {"time": "2013-06-22T17:03:14+02:00", "temperature": 23.0},
+                {"time": "2021-04-08T00:00:00+05:00", "temperature": 24.0}
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn cbor_measurements_are_translated_to_json() {
+        let mut converter =
+            AzureConverter::new(false, Box::new(TestClock), SizeThreshold(255 * 1024));
+
+        let input =
+            thin_edge_json::cbor::to_vec(&json!({"temperature": 23.0, "door_open": true})).unwrap();
+        let measurements = Message::new(&Topic::new_unchecked("tedge/measurements/cbor"), input);
+        let output = converter.convert(&measurements).await;
+
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&extract_first_message_payload(output))
+                .unwrap(),
+            json!({"temperature": 23.0, "door_open": true})
+        );
+    }
+
+    #[tokio::test]
+    async fn locations_are_passed_through() {
+        let mut converter =
+            AzureConverter::new(true, Box::new(TestClock), SizeThreshold(255 * 1024));
+
+        let input = r#"{"lat": 52.52, "lng": 13.405, "alt": 34.0}"#;
+        let location = Message::new(&Topic::new_unchecked("tedge/location"), input);
+        let output = converter.convert(&location).await;
+
+        assert_eq!(output[0].topic.name, "az/messages/events/");
+        assert_eq!(extract_first_message_payload(output), input);
+    }
+
+    #[tokio::test]
+    async fn child_locations_are_passed_through_with_their_child_id() {
+        let mut converter =
+            AzureConverter::new(true, Box::new(TestClock), SizeThreshold(255 * 1024));
+
+        let location = Message::new(
+            &Topic::new_unchecked("tedge/location/child1"),
+            r#"{"lat": 52.52, "lng": 13.405}"#,
+        );
+        let output = converter.convert(&location).await;
+
+        assert_eq!(output[0].topic.name, "az/messages/events/");
+        assert_json_eq!(
+            serde_json::from_str::<serde_json::Value>(&extract_first_message_payload(output))
+                .unwrap(),
+            json!({"lat": 52.52, "lng": 13.405, "child_id": "child1"})
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_locations_are_rejected() {
+        let mut converter =
+            AzureConverter::new(false, Box::new(TestClock), SizeThreshold(255 * 1024));
+
+        let location = Message::new(
+            &Topic::new_unchecked("tedge/location"),
+            r#"{"lat": 152.52, "lng": 13.405}"#,
+        );
+        let result = converter.try_convert(&location).await;
+
+        assert_matches!(
+            result,
+            Err(ConversionError::FromThinEdgeJsonLocationDeserialization(_))
+        )
+    }
+
     #[tokio::test]
     async fn exceeding_threshold_returns_error() {
         let mut converter = AzureConverter::new(false, Box::new(TestClock), SizeThreshold(1));
@@ -2,7 +2,12 @@ use std::path::Path;
 
 use crate::{
     az::converter::AzureConverter,
-    core::{component::TEdgeComponent, mapper::create_mapper, size_threshold::SizeThreshold},
+    core::{
+        component::TEdgeComponent,
+        deadband::{create_deadband_directories, with_deadband_filter},
+        mapper::create_mapper,
+        size_threshold::SizeThreshold,
+    },
 };
 
 use async_trait::async_trait;
@@ -37,6 +42,7 @@ impl TEdgeComponent for AzureMapper {
             "tedge",
             0o775,
         )?;
+        create_deadband_directories(cfg_dir)?;
 
         self.init_session(AzureConverter::in_topic_filter()).await?;
         Ok(())
@@ -45,7 +51,7 @@ impl TEdgeComponent for AzureMapper {
     async fn start(
         &self,
         tedge_config: TEdgeConfig,
-        _config_dir: &Path,
+        config_dir: &Path,
     ) -> Result<(), anyhow::Error> {
         let add_timestamp = tedge_config.query(AzureMapperTimestamp)?.is_set();
         let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
@@ -54,6 +60,7 @@ impl TEdgeComponent for AzureMapper {
         let size_threshold = SizeThreshold(255 * 1024);
 
         let converter = Box::new(AzureConverter::new(add_timestamp, clock, size_threshold));
+        let converter = with_deadband_filter(converter, config_dir, AZURE_MAPPER_NAME)?;
 
         let mut mapper = create_mapper(AZURE_MAPPER_NAME, mqtt_host, mqtt_port, converter).await?;
 
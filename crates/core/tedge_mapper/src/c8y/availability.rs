@@ -0,0 +1,249 @@
+use super::health::HealthStatus;
+use clock::{Clock, Timestamp};
+use mqtt_channel::{Message, Topic};
+use std::{collections::HashSet, time::Duration};
+
+const SMARTREST_PUBLISH_TOPIC: &str = "c8y/s/us";
+const C8Y_TOPIC_PREFIX: &str = "c8y/";
+
+/// Keeps the device available in Cumulocity.
+///
+/// The `c8y_RequiredAvailability` of the device is set on startup from the availability interval.
+/// Then, whenever no message has been sent to Cumulocity for a while,
+/// a heartbeat is sent so the device is not marked as unavailable.
+///
+/// No heartbeat is sent while one of the tedge components reports itself as down
+/// on `tedge/health/<component>`: the device then only remains available
+/// as long as it sends other data.
+pub struct Availability {
+    interval: Duration,
+    clock: Box<dyn Clock>,
+    last_activity: Timestamp,
+    last_heartbeat: Option<Timestamp>,
+    unhealthy_components: HashSet<String>,
+}
+
+impl std::fmt::Debug for Availability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Availability")
+            .field("interval", &self.interval)
+            .field("last_activity", &self.last_activity)
+            .field("last_heartbeat", &self.last_heartbeat)
+            .field("unhealthy_components", &self.unhealthy_components)
+            .finish()
+    }
+}
+
+impl Availability {
+    pub fn new(interval: Duration, clock: Box<dyn Clock>) -> Self {
+        let last_activity = clock.now();
+        Availability {
+            interval,
+            clock,
+            last_activity,
+            last_heartbeat: None,
+            unhealthy_components: HashSet::new(),
+        }
+    }
+
+    /// The period at which the need for a heartbeat is checked.
+    pub fn check_interval(&self) -> Duration {
+        self.interval / 2
+    }
+
+    /// The message setting the `c8y_RequiredAvailability` of the device.
+    ///
+    /// Cumulocity expects this interval in minutes.
+    pub fn required_availability_message(&self) -> Message {
+        let minutes = (self.interval.as_secs() + 59) / 60;
+        Message::new(
+            &Topic::new_unchecked(SMARTREST_PUBLISH_TOPIC),
+            format!("117,{}", minutes.max(1)),
+        )
+    }
+
+    /// Record the messages sent to Cumulocity, postponing the next heartbeat.
+    pub fn record_activity(&mut self, messages: &[Message]) {
+        if messages
+            .iter()
+            .any(|message| message.topic.name.starts_with(C8Y_TOPIC_PREFIX))
+        {
+            self.last_activity = self.clock.now();
+        }
+    }
+
+    /// Update the health of a component, as received on `tedge/health/<component>`.
+    ///
+    /// A component is unhealthy if its status is not `up`.
+    /// A cleared status removes the component from the monitored ones.
+    pub fn update_health(&mut self, component: &str, status: Option<HealthStatus>) {
+        match status {
+            None | Some(HealthStatus::Up) => {
+                self.unhealthy_components.remove(component);
+            }
+            Some(_) => {
+                self.unhealthy_components.insert(component.to_string());
+            }
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.unhealthy_components.is_empty()
+    }
+
+    /// Return a heartbeat if nothing has been sent over the last check period
+    /// and all the components are healthy.
+    ///
+    /// Checked at least every `check_interval`, this ensures that Cumulocity receives a message
+    /// once per availability interval when the device is idle.
+    /// A tenth of the check period is tolerated for the delays of the periodic checks,
+    /// so no heartbeat is sent on the check that follows the previous heartbeat.
+    /// When checked more often, no heartbeat is sent sooner than an interval after the previous one.
+    pub fn heartbeat(&mut self) -> Option<Message> {
+        let now = self.clock.now();
+        let tolerance = self.check_interval() / 10;
+        let idle_period = self.check_interval() + tolerance;
+        if !self.is_healthy() || now - self.last_activity < idle_period {
+            return None;
+        }
+        if let Some(last_heartbeat) = self.last_heartbeat {
+            if now - last_heartbeat < self.interval - tolerance {
+                return None;
+            }
+        }
+
+        self.last_activity = now;
+        self.last_heartbeat = Some(now);
+        Some(self.required_availability_message())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use test_case::test_case;
+    use time::macros::datetime;
+
+    #[derive(Clone)]
+    struct TestClock(Arc<Mutex<Timestamp>>);
+
+    impl TestClock {
+        fn new() -> Self {
+            TestClock(Arc::new(Mutex::new(datetime!(2022-05-10 12:00:00 UTC))))
+        }
+
+        fn advance(&self, seconds: i64) {
+            let mut now = self.0.lock().unwrap();
+            *now += time::Duration::seconds(seconds);
+        }
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> Timestamp {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn availability(interval_secs: u64, clock: &TestClock) -> Availability {
+        Availability::new(Duration::from_secs(interval_secs), Box::new(clock.clone()))
+    }
+
+    #[test_case(Duration::from_secs(30), "117,1")]
+    #[test_case(Duration::from_secs(60), "117,1")]
+    #[test_case(Duration::from_secs(90), "117,2")]
+    #[test_case(Duration::from_secs(3600), "117,60")]
+    fn required_availability_is_given_in_minutes(interval: Duration, expected: &str) {
+        let availability = Availability::new(interval, Box::new(TestClock::new()));
+        let message = availability.required_availability_message();
+
+        assert_eq!(message.topic.name, "c8y/s/us");
+        assert_eq!(message.payload_str().unwrap(), expected);
+    }
+
+    #[test]
+    fn no_heartbeat_is_sent_when_there_is_some_traffic() {
+        let clock = TestClock::new();
+        let mut availability = availability(400, &clock);
+
+        clock.advance(150);
+        availability.record_activity(&[Message::new(
+            &Topic::new_unchecked("c8y/measurement/measurements/create"),
+            "{}",
+        )]);
+        clock.advance(200);
+        assert!(availability.heartbeat().is_none());
+
+        clock.advance(50);
+        let heartbeat = availability.heartbeat().expect("a heartbeat");
+        assert_eq!(heartbeat.payload_str().unwrap(), "117,7");
+
+        // The heartbeat itself postpones the next one
+        assert!(availability.heartbeat().is_none());
+    }
+
+    #[test]
+    fn an_idle_device_sends_a_heartbeat_once_per_interval() {
+        let clock = TestClock::new();
+        let mut availability = availability(400, &clock);
+
+        clock.advance(200);
+        assert!(availability.heartbeat().is_none());
+
+        clock.advance(200);
+        assert!(availability.heartbeat().is_some());
+
+        // Next check: the previous heartbeat is still recent enough
+        clock.advance(200);
+        assert!(availability.heartbeat().is_none());
+
+        clock.advance(200);
+        assert!(availability.heartbeat().is_some());
+    }
+
+    #[test]
+    fn frequent_checks_do_not_send_more_than_one_heartbeat_per_interval() {
+        let clock = TestClock::new();
+        let mut availability = availability(400, &clock);
+
+        // Checked every 40 seconds, as when the ticks are also used for the inventory updates
+        let mut heartbeats = vec![];
+        for i in 1..=30 {
+            clock.advance(40);
+            if availability.heartbeat().is_some() {
+                heartbeats.push(i * 40);
+            }
+        }
+
+        assert_eq!(heartbeats, vec![240, 640, 1040]);
+    }
+
+    #[test]
+    fn local_messages_are_not_considered_as_traffic() {
+        let clock = TestClock::new();
+        let mut availability = availability(200, &clock);
+
+        clock.advance(150);
+        availability.record_activity(&[Message::new(
+            &Topic::new_unchecked("tedge/errors"),
+            "some error",
+        )]);
+        assert!(availability.heartbeat().is_some());
+    }
+
+    #[test]
+    fn no_heartbeat_is_sent_while_a_component_is_down() {
+        let clock = TestClock::new();
+        let mut availability = availability(200, &clock);
+
+        availability.update_health("tedge-agent", Some(HealthStatus::Up));
+        availability.update_health("mosquitto-c8y-bridge", Some(HealthStatus::Down));
+        clock.advance(150);
+        assert!(!availability.is_healthy());
+        assert!(availability.heartbeat().is_none());
+
+        availability.update_health("mosquitto-c8y-bridge", Some(HealthStatus::Up));
+        assert!(availability.is_healthy());
+        assert!(availability.heartbeat().is_some());
+    }
+}
@@ -1,61 +1,133 @@
-use crate::core::{converter::*, error::*, size_threshold::SizeThreshold};
+use crate::core::{
+    converter::*,
+    error::*,
+    measurements::{MeasurementTopic, TEDGE_MEASUREMENTS_CBOR_TOPIC, TEDGE_MEASUREMENTS_TOPIC},
+    size_threshold::SizeThreshold,
+};
 use agent_interface::{
     topic::{RequestTopic, ResponseTopic},
-    Auth, DownloadInfo, Jsonify, OperationStatus, RestartOperationRequest,
-    RestartOperationResponse, SoftwareListRequest, SoftwareListResponse, SoftwareUpdateResponse,
+    Auth, DeviceProfileResponse, DownloadInfo, FirmwareUpdateRequest, FirmwareUpdateResponse,
+    Jsonify, OperationStatus, RestartOperationRequest, RestartOperationResponse,
+    SoftwareListRequest, SoftwareListResponse, SoftwareUpdateResponse,
 };
 use async_trait::async_trait;
 use c8y_api::{
     http_proxy::C8YHttpProxy,
-    json_c8y::{C8yCreateEvent, C8yUpdateSoftwareListResponse},
+    json_c8y::{
+        C8yCreateAlarm, C8yCreateEvent, C8yDeviceProfileOperation, C8yPosition,
+        C8yUpdateSoftwareListResponse,
+    },
 };
 use c8y_smartrest::smartrest_deserializer::SmartRestRequestGeneric;
 use c8y_smartrest::{
     alarm,
     error::SmartRestDeserializerError,
-    operations::Operations,
-    smartrest_deserializer::{SmartRestRestartRequest, SmartRestUpdateSoftware},
+    operations::{get_operation, is_valid_operation_path, Operation, Operations},
+    smartrest_deserializer::{
+        SmartRestCommandRequest, SmartRestFirmwareRequest, SmartRestRestartRequest,
+        SmartRestUpdateSoftware,
+    },
     smartrest_serializer::{
         CumulocitySupportedOperations, SmartRestGetPendingOperations, SmartRestSerializer,
         SmartRestSetOperationToExecuting, SmartRestSetOperationToFailed,
         SmartRestSetOperationToSuccessful, SmartRestSetSupportedOperations,
     },
 };
-use c8y_translator::json;
+use c8y_translator::{
+    json::{self, C8yMeasurements},
+    serializer::TextMeasurementFallback,
+};
+use clock::Clock;
 
-use logged_command::LoggedCommand;
-use mqtt_channel::{Message, Topic, TopicFilter};
-use plugin_sm::operation_logs::OperationLogs;
+use futures::{channel::mpsc, SinkExt};
+use logged_command::{LoggedCommand, LoggingChild};
+use mqtt_channel::{Message, Topic, TopicFilter, UnboundedReceiver, UnboundedSender};
+use plugin_sm::{log_file::LogFile, operation_logs::OperationLogs};
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
     fs::File,
     io::Read,
     path::{Path, PathBuf},
+    process::Output,
+    time::{Duration, Instant},
 };
 use tedge_config::{get_tedge_config, ConfigSettingAccessor, LogPathSetting};
-use thin_edge_json::{alarm::ThinEdgeAlarm, event::ThinEdgeEvent};
+use thin_edge_json::{
+    alarm::ThinEdgeAlarm, event::ThinEdgeEvent, location::ThinEdgeLocation, units::MeasurementUnits,
+};
 use time::format_description::well_known::Rfc3339;
 use tracing::{debug, info, log::error};
+use users::{get_group_by_name, get_user_by_name};
 
 use super::{
+    availability::Availability,
     error::CumulocityMapperError,
     fragments::{C8yAgentFragment, C8yDeviceDataFragment},
+    health::{component_health, TEDGE_HEALTH_TOPIC},
+    inventory::DeviceInventory,
     mapper::CumulocityMapper,
+    measurement_batcher::{
+        collection_measurements, collection_messages, MeasurementBatchConfig, MeasurementBatcher,
+        C8Y_JSON_MQTT_MEASUREMENTS_BULK_TOPIC, DEFAULT_BATCH_MAX_SIZE,
+    },
+    service_monitor::ServiceMonitor,
     topic::{C8yTopic, MapperSubscribeTopic},
 };
 
-const C8Y_CLOUD: &str = "c8y";
 const INVENTORY_FRAGMENTS_FILE_LOCATION: &str = "/etc/tedge/device/inventory.json";
-const SUPPORTED_OPERATIONS_DIRECTORY: &str = "/etc/tedge/operations";
 const INVENTORY_MANAGED_OBJECTS_TOPIC: &str = "c8y/inventory/managedObjects/update";
 const SMARTREST_PUBLISH_TOPIC: &str = "c8y/s/us";
 const TEDGE_ALARMS_TOPIC: &str = "tedge/alarms/";
 const INTERNAL_ALARMS_TOPIC: &str = "c8y-internal/alarms/";
 const TEDGE_EVENTS_TOPIC: &str = "tedge/events/";
+const TEDGE_LOCATION_TOPIC: &str = "tedge/location";
+const TEDGE_MEASUREMENTS_META_TOPIC: &str = "tedge/measurements/meta";
 const C8Y_JSON_MQTT_EVENTS_TOPIC: &str = "c8y/event/events/create";
+const C8Y_JSON_MQTT_ALARMS_TOPIC: &str = "c8y/alarm/alarms/create";
 const TEDGE_AGENT_LOG_DIR: &str = "tedge/agent";
 
+/// The tedge topics the mapper subscribes to, on top of the c8y topics.
+const TEDGE_SUBSCRIPTIONS: &[&str] = &[
+    "tedge/measurements",
+    "tedge/measurements/+",
+    "tedge/measurements/cbor/+",
+    "tedge/alarms/+/+",
+    "c8y-internal/alarms/+/+",
+    "tedge/events/+",
+    "tedge/location",
+    "tedge/location/+",
+    "tedge/health/+",
+];
+
 const CREATE_EVENT_SMARTREST_CODE: u16 = 400;
+const OPERATION_FAILURE_REASON_MAX_LEN: usize = 256;
+const SMARTREST_MAX_SIZE: usize = 16 * 1024;
+const DEFAULT_SHELL: &str = "/bin/sh";
+const DEFAULT_SHELL_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+// Leave room in a 16KB SmartREST message for the `503,c8y_Command,` prefix and the escaped quotes.
+const DEFAULT_SHELL_COMMAND_MAX_OUTPUT: usize = 8 * 1024;
+/// The largest command output, once escaped, that fits in a `503,c8y_Command,"<output>"` SmartREST message.
+pub const SHELL_COMMAND_MAX_OUTPUT_LIMIT: usize =
+    SMARTREST_MAX_SIZE - r#"503,c8y_Command,"""#.len();
+
+/// Settings of the `c8y_Command` operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellCommandConfig {
+    pub shell: String,
+    pub timeout: Duration,
+    /// Maximum size in bytes of the command output sent to Cumulocity, once its quotes are escaped.
+    pub max_output: usize,
+}
+
+impl Default for ShellCommandConfig {
+    fn default() -> Self {
+        ShellCommandConfig {
+            shell: DEFAULT_SHELL.into(),
+            timeout: DEFAULT_SHELL_COMMAND_TIMEOUT,
+            max_output: DEFAULT_SHELL_COMMAND_MAX_OUTPUT,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct CumulocityConverter<Proxy>
@@ -69,8 +141,20 @@ where
     device_type: String,
     alarm_converter: AlarmConverter,
     operations: Operations,
+    operations_dir: Option<PathBuf>,
+    shell_command: Option<ShellCommandConfig>,
     operation_logs: OperationLogs,
+    operation_outcomes: UnboundedSender<Message>,
+    operation_outcomes_receiver: Option<UnboundedReceiver<Message>>,
     http_proxy: Proxy,
+    device_inventory: DeviceInventory,
+    inventory_interval: Option<Duration>,
+    last_inventory_update: Instant,
+    availability: Option<Availability>,
+    service_monitor: ServiceMonitor,
+    measurement_batcher: Option<MeasurementBatcher>,
+    measurement_units: MeasurementUnits,
+    text_measurement_fallback: TextMeasurementFallback,
 }
 
 impl<Proxy> CumulocityConverter<Proxy>
@@ -84,15 +168,10 @@ where
         operations: Operations,
         http_proxy: Proxy,
     ) -> Result<Self, CumulocityMapperError> {
-        let mut topic_filter: TopicFilter = vec![
-            "tedge/measurements",
-            "tedge/measurements/+",
-            "tedge/alarms/+/+",
-            "c8y-internal/alarms/+/+",
-            "tedge/events/+",
-        ]
-        .try_into()
-        .expect("topics that mapper should subscribe to");
+        let mut topic_filter: TopicFilter = TEDGE_SUBSCRIPTIONS
+            .to_vec()
+            .try_into()
+            .expect("topics that mapper should subscribe to");
 
         let () = topic_filter.add_all(CumulocityMapper::subscriptions(&operations).unwrap());
 
@@ -103,6 +182,7 @@ where
         };
 
         let alarm_converter = AlarmConverter::new();
+        let service_monitor = ServiceMonitor::new(&device_name);
 
         let children: HashSet<String> = HashSet::new();
 
@@ -112,6 +192,7 @@ where
         let log_dir = PathBuf::from(&format!("{}/{TEDGE_AGENT_LOG_DIR}", logs_path));
 
         let operation_logs = OperationLogs::try_new(log_dir)?;
+        let (operation_outcomes, operation_outcomes_receiver) = mpsc::unbounded();
 
         Ok(CumulocityConverter {
             size_threshold,
@@ -121,8 +202,20 @@ where
             device_type,
             alarm_converter,
             operations,
+            operations_dir: None,
+            shell_command: None,
             operation_logs,
+            operation_outcomes,
+            operation_outcomes_receiver: Some(operation_outcomes_receiver),
             http_proxy,
+            device_inventory: DeviceInventory::default(),
+            inventory_interval: None,
+            last_inventory_update: Instant::now(),
+            availability: None,
+            service_monitor,
+            measurement_batcher: None,
+            measurement_units: MeasurementUnits::default(),
+            text_measurement_fallback: TextMeasurementFallback::default(),
         })
     }
 
@@ -135,15 +228,10 @@ where
         http_proxy: Proxy,
         logs_path: PathBuf,
     ) -> Result<Self, CumulocityMapperError> {
-        let mut topic_filter: TopicFilter = vec![
-            "tedge/measurements",
-            "tedge/measurements/+",
-            "tedge/alarms/+/+",
-            "c8y-internal/alarms/+/+",
-            "tedge/events/+",
-        ]
-        .try_into()
-        .expect("topics that mapper should subscribe to");
+        let mut topic_filter: TopicFilter = TEDGE_SUBSCRIPTIONS
+            .to_vec()
+            .try_into()
+            .expect("topics that mapper should subscribe to");
 
         let () = topic_filter.add_all(CumulocityMapper::subscriptions(&operations).unwrap());
 
@@ -154,6 +242,7 @@ where
         };
 
         let alarm_converter = AlarmConverter::new();
+        let service_monitor = ServiceMonitor::new(&device_name);
 
         let children: HashSet<String> = HashSet::new();
 
@@ -163,6 +252,7 @@ where
         ));
 
         let operation_logs = OperationLogs::try_new(log_dir)?;
+        let (operation_outcomes, operation_outcomes_receiver) = mpsc::unbounded();
 
         Ok(CumulocityConverter {
             size_threshold,
@@ -172,52 +262,229 @@ where
             device_type,
             alarm_converter,
             operations,
+            operations_dir: None,
+            shell_command: None,
             operation_logs,
+            operation_outcomes,
+            operation_outcomes_receiver: Some(operation_outcomes_receiver),
             http_proxy,
+            device_inventory: DeviceInventory::default(),
+            inventory_interval: None,
+            last_inventory_update: Instant::now(),
+            availability: None,
+            service_monitor,
+            measurement_batcher: None,
+            measurement_units: MeasurementUnits::default(),
+            text_measurement_fallback: TextMeasurementFallback::default(),
         })
     }
 
-    fn try_convert_measurement(
+    /// Refresh the device inventory every `interval`, on top of the publication on startup.
+    pub fn with_inventory_interval(self, interval: Option<Duration>) -> Self {
+        Self {
+            inventory_interval: interval,
+            ..self
+        }
+    }
+
+    /// Keep the device available in Cumulocity, sending heartbeats when there is no traffic for a while.
+    ///
+    /// See [Availability].
+    pub fn with_availability_interval(
+        self,
+        interval: Option<Duration>,
+        clock: Box<dyn Clock>,
+    ) -> Self {
+        Self {
+            availability: interval.map(|interval| Availability::new(interval, clock)),
+            ..self
+        }
+    }
+
+    /// Batch the measurements received within a window into a single Cumulocity request.
+    ///
+    /// The batches are published asynchronously, along with the operation outcomes.
+    pub fn with_measurement_batching(self, config: Option<MeasurementBatchConfig>) -> Self {
+        let measurement_batcher = config.map(|config| {
+            MeasurementBatcher::spawn(
+                config,
+                SizeThreshold(self.size_threshold.0),
+                self.operation_outcomes.clone(),
+            )
+        });
+        Self {
+            measurement_batcher,
+            ..self
+        }
+    }
+
+    /// Watch the operations defined in `dir`, to add, update and remove operations at runtime.
+    pub fn with_operations_dir(self, dir: PathBuf) -> Self {
+        Self {
+            operations_dir: Some(dir),
+            ..self
+        }
+    }
+
+    /// Enable the `c8y_Command` operation, running the shell commands sent by Cumulocity.
+    pub fn with_shell_command(self, config: Option<ShellCommandConfig>) -> Self {
+        Self {
+            shell_command: config,
+            ..self
+        }
+    }
+
+    /// Set how the measurements with a text value are sent to Cumulocity.
+    pub fn with_text_measurement_fallback(self, fallback: TextMeasurementFallback) -> Self {
+        Self {
+            text_measurement_fallback: fallback,
+            ..self
+        }
+    }
+
+    fn supported_operations_message(&self) -> Result<Message, ConversionError> {
+        let mut operations = self.operations.get_operations_list();
+        if self.shell_command.is_some() {
+            let c8y_command: &str = CumulocitySupportedOperations::C8yCommand.into();
+            if !operations.iter().any(|op| op == c8y_command) {
+                operations.push(c8y_command.to_string());
+            }
+        }
+        create_supported_operations_fragments_message(&operations)
+    }
+
+    fn try_reload_operation(&mut self, path: &Path) -> Result<Message, ConversionError> {
+        let previous_topics = self.operations.topics_for_operations();
+        if path.is_file() {
+            let operation = get_operation(path)?;
+            info!("Operation {} updated", operation.name);
+            self.operations.add(operation);
+        } else if let Some(operation_name) = path.file_name().and_then(|name| name.to_str()) {
+            info!("Operation {operation_name} removed");
+            self.operations.remove(operation_name);
+        }
+
+        let topics = self.operations.topics_for_operations();
+        let topic_filter = &mut self.mapper_config.in_topic_filter;
+        for topic in topics.iter() {
+            if !topic_filter.patterns.contains(topic) {
+                let () = topic_filter.add(topic)?;
+            }
+        }
+        for topic in previous_topics.difference(&topics) {
+            if !is_default_subscription(topic) {
+                topic_filter.remove(topic);
+            }
+        }
+
+        self.supported_operations_message()
+    }
+
+    /// Return the message creating a child device, if not already created.
+    fn register_child(&mut self, child_id: &str) -> Option<Message> {
+        if self.children.insert(child_id.to_string()) {
+            Some(Message::new(
+                &Topic::new_unchecked(SMARTREST_PUBLISH_TOPIC),
+                format!("101,{child_id},{child_id},thin-edge.io-child"),
+            ))
+        } else {
+            None
+        }
+    }
+
+    async fn try_convert_measurement(
         &mut self,
         input: &Message,
     ) -> Result<Vec<Message>, ConversionError> {
         let mut vec: Vec<Message> = Vec::new();
 
-        let maybe_child_id = get_child_id_from_topic(&input.topic.name)?;
-        let c8y_json_payload = match maybe_child_id {
-            Some(child_id) => {
-                // Need to check if the input Thin Edge JSON is valid before adding a child ID to list
-                let c8y_json_child_payload =
-                    json::from_thin_edge_json_with_child(input.payload_str()?, child_id.as_str())?;
+        let topic = parse_measurement_topic(&input.topic.name)?;
+        let maybe_child_id = topic.device.map(String::from);
+        // Need to check if the input Thin Edge JSON is valid before adding a child ID to list
+        let c8y_measurements = if topic.cbor {
+            json::from_thin_edge_cbor_with_settings(
+                input.payload_bytes(),
+                maybe_child_id.as_deref(),
+                &self.measurement_units,
+                self.text_measurement_fallback,
+            )?
+        } else {
+            json::from_thin_edge_json_with_settings(
+                input.payload_str()?,
+                maybe_child_id.as_deref(),
+                &self.measurement_units,
+                self.text_measurement_fallback,
+            )?
+        };
+        if let Some(child_id) = maybe_child_id {
+            vec.extend(self.register_child(&child_id));
+        }
 
-                if !self.children.contains(child_id.as_str()) {
-                    self.children.insert(child_id.clone());
-                    vec.push(Message::new(
-                        &Topic::new_unchecked(SMARTREST_PUBLISH_TOPIC),
-                        format!("101,{child_id},{child_id},thin-edge.io-child"),
-                    ));
+        match c8y_measurements {
+            C8yMeasurements::Single(c8y_json_payload) => {
+                if c8y_json_payload.len() < self.size_threshold.0 {
+                    match &mut self.measurement_batcher {
+                        Some(batcher) => {
+                            batcher.add(serde_json::from_str(&c8y_json_payload)?).await
+                        }
+                        None => vec.push(Message::new(
+                            &self.mapper_config.out_topic,
+                            c8y_json_payload,
+                        )),
+                    }
+                } else {
+                    // If the translated measurements are larger than the MQTT size limit, use HTTP to send them
+                    let c8y_measurements = serde_json::from_str(&c8y_json_payload)?;
+                    let () = self.http_proxy.send_measurements(c8y_measurements).await?;
+                }
+            }
+            C8yMeasurements::Collection(c8y_json_payload) => {
+                if c8y_json_payload.len() < self.size_threshold.0
+                    && self.measurement_batcher.is_none()
+                {
+                    let topic = Topic::new_unchecked(C8Y_JSON_MQTT_MEASUREMENTS_BULK_TOPIC);
+                    vec.push(Message::new(&topic, c8y_json_payload));
+                } else {
+                    vec.extend(
+                        self.send_measurement_collection(collection_measurements(
+                            &c8y_json_payload,
+                        )?)
+                        .await?,
+                    );
                 }
-                c8y_json_child_payload
             }
-            None => json::from_thin_edge_json(input.payload_str()?)?,
-        };
-
-        if c8y_json_payload.len() < self.size_threshold.0 {
-            vec.push(Message::new(
-                &self.mapper_config.out_topic,
-                c8y_json_payload,
-            ));
-        } else {
-            return Err(ConversionError::TranslatedSizeExceededThreshold {
-                payload: input.payload_str()?[0..50].into(),
-                topic: input.topic.name.clone(),
-                actual_size: c8y_json_payload.len(),
-                threshold: self.size_threshold.0,
-            });
         }
         Ok(vec)
     }
 
+    /// Send measurements using the batcher, if enabled, or as collections of measurements.
+    ///
+    /// The measurements that are too large to be sent over MQTT are sent over HTTP.
+    async fn send_measurement_collection(
+        &mut self,
+        measurements: Vec<serde_json::Value>,
+    ) -> Result<Vec<Message>, ConversionError> {
+        if let Some(batcher) = &mut self.measurement_batcher {
+            for measurement in measurements {
+                batcher.add(measurement).await;
+            }
+            return Ok(vec![]);
+        }
+
+        let threshold = self.size_threshold.0;
+        let (small, large): (Vec<_>, Vec<_>) = measurements
+            .into_iter()
+            .partition(|measurement| measurement.to_string().len() < threshold);
+        for measurement in large {
+            let () = self.http_proxy.send_measurements(measurement).await?;
+        }
+        Ok(collection_messages(
+            small,
+            DEFAULT_BATCH_MAX_SIZE,
+            &self.size_threshold,
+        ))
+    }
+
     async fn try_convert_event(
         &mut self,
         input: &Message,
@@ -249,32 +516,47 @@ where
         }
     }
 
-    fn serialize_to_smartrest(c8y_event: &C8yCreateEvent) -> Result<String, ConversionError> {
-        Ok(format!(
-            "{},{},\"{}\",{}",
-            CREATE_EVENT_SMARTREST_CODE,
-            c8y_event.event_type,
-            c8y_event.text,
-            c8y_event.time.format(&Rfc3339)?
-        ))
-    }
-}
+    /// Set the `c8y_Position` of the device, or of a child device, and create a `c8y_LocationUpdate` event.
+    fn try_convert_location(&mut self, input: &Message) -> Result<Vec<Message>, ConversionError> {
+        let location = ThinEdgeLocation::try_from(input.topic.name.as_str(), input.payload_str()?)?;
+        let mut vec: Vec<Message> = Vec::new();
 
-#[async_trait]
-impl<Proxy> Converter for CumulocityConverter<Proxy>
-where
-    Proxy: C8YHttpProxy,
-{
-    type Error = ConversionError;
+        let device_id = match &location.child_id {
+            Some(child_id) => {
+                vec.extend(self.register_child(child_id));
+                child_id.clone()
+            }
+            None => self.device_name.clone(),
+        };
 
-    fn get_mapper_config(&self) -> &MapperConfig {
-        &self.mapper_config
+        let position = C8yPosition::from(&location.data);
+        let inventory_topic =
+            Topic::new_unchecked(&format!("{INVENTORY_MANAGED_OBJECTS_TOPIC}/{device_id}"));
+        vec.push(Message::new(
+            &inventory_topic,
+            position.to_inventory_json().to_string(),
+        ));
+
+        let c8y_event = C8yCreateEvent::from(location);
+        vec.push(Message::new(
+            &Topic::new_unchecked(C8Y_JSON_MQTT_EVENTS_TOPIC),
+            serde_json::to_string(&c8y_event)?,
+        ));
+
+        Ok(vec)
     }
-    async fn try_convert(&mut self, message: &Message) -> Result<Vec<Message>, ConversionError> {
+
+    async fn try_convert_message(
+        &mut self,
+        message: &Message,
+    ) -> Result<Vec<Message>, ConversionError> {
         match &message.topic {
+            topic if topic.name == TEDGE_MEASUREMENTS_META_TOPIC => {
+                self.measurement_units = MeasurementUnits::try_from(message.payload_str()?)?;
+                Ok(vec![])
+            }
             topic if topic.name.starts_with("tedge/measurements") => {
-                let () = self.size_threshold.validate(message)?;
-                self.try_convert_measurement(message)
+                self.try_convert_measurement(message).await
             }
             topic if topic.name.starts_with("tedge/alarms") => {
                 let () = self.size_threshold.validate(message)?;
@@ -287,6 +569,20 @@ where
             topic if topic.name.starts_with(TEDGE_EVENTS_TOPIC) => {
                 self.try_convert_event(message).await
             }
+            topic if topic.name.starts_with(TEDGE_LOCATION_TOPIC) => {
+                let () = self.size_threshold.validate(message)?;
+                self.try_convert_location(message)
+            }
+            topic if topic.name.starts_with(TEDGE_HEALTH_TOPIC) => {
+                let (component, status) = match component_health(message) {
+                    Some(health) => health,
+                    None => return Ok(vec![]),
+                };
+                if let Some(availability) = &mut self.availability {
+                    availability.update_health(component, status);
+                }
+                Ok(self.service_monitor.process_health(component, status))
+            }
             topic => match topic.clone().try_into() {
                 Ok(MapperSubscribeTopic::ResponseTopic(ResponseTopic::SoftwareListResponse)) => {
                     debug!("Software list");
@@ -306,12 +602,26 @@ where
                 Ok(MapperSubscribeTopic::ResponseTopic(ResponseTopic::RestartResponse)) => {
                     Ok(publish_restart_operation_status(message.payload_str()?).await?)
                 }
+                Ok(MapperSubscribeTopic::ResponseTopic(ResponseTopic::FirmwareUpdateResponse)) => {
+                    Ok(publish_firmware_operation_status(
+                        &self.device_name,
+                        message.payload_str()?,
+                    )?)
+                }
+                Ok(MapperSubscribeTopic::ResponseTopic(ResponseTopic::DeviceProfileResponse)) => {
+                    Ok(publish_device_profile_status(message.payload_str()?)?)
+                }
+                Ok(MapperSubscribeTopic::C8yTopic(C8yTopic::OperationNotifications)) => {
+                    parse_c8y_json_operation(message, &mut self.http_proxy).await
+                }
                 Ok(MapperSubscribeTopic::C8yTopic(_)) => {
                     parse_c8y_topics(
                         message,
                         &self.operations,
+                        self.shell_command.as_ref(),
                         &mut self.http_proxy,
                         &self.operation_logs,
+                        &self.operation_outcomes,
                     )
                     .await
                 }
@@ -322,26 +632,58 @@ where
         }
     }
 
+    fn serialize_to_smartrest(c8y_event: &C8yCreateEvent) -> Result<String, ConversionError> {
+        Ok(format!(
+            "{},{},\"{}\",{}",
+            CREATE_EVENT_SMARTREST_CODE,
+            c8y_event.event_type,
+            c8y_event.text,
+            c8y_event.time.format(&Rfc3339)?
+        ))
+    }
+}
+
+#[async_trait]
+impl<Proxy> Converter for CumulocityConverter<Proxy>
+where
+    Proxy: C8YHttpProxy,
+{
+    type Error = ConversionError;
+
+    fn get_mapper_config(&self) -> &MapperConfig {
+        &self.mapper_config
+    }
+    async fn try_convert(&mut self, message: &Message) -> Result<Vec<Message>, ConversionError> {
+        let messages_or_err = self.try_convert_message(message).await;
+        if let (Some(availability), Ok(messages)) = (&mut self.availability, &messages_or_err) {
+            availability.record_activity(messages);
+        }
+        messages_or_err
+    }
     fn try_init_messages(&self) -> Result<Vec<Message>, ConversionError> {
         let inventory_fragments_message =
             self.wrap_error(create_inventory_fragments_message(&self.device_name));
-        let supported_operations_message =
-            self.wrap_error(create_supported_operations_fragments_message());
+        let supported_operations_message = self.wrap_error(self.supported_operations_message());
         let device_data_message = self.wrap_error(create_device_data_fragments(
             &self.device_name,
             &self.device_type,
+            &self.device_inventory,
         ));
 
         let pending_operations_message = self.wrap_error(create_get_pending_operations_message());
         let software_list_message = self.wrap_error(create_get_software_list_message());
 
-        Ok(vec![
+        let mut messages = vec![
             inventory_fragments_message,
             supported_operations_message,
             device_data_message,
             pending_operations_message,
             software_list_message,
-        ])
+        ];
+        if let Some(availability) = &self.availability {
+            messages.push(availability.required_availability_message());
+        }
+        Ok(messages)
     }
 
     fn sync_messages(&mut self) -> Vec<Message> {
@@ -349,19 +691,85 @@ where
         self.alarm_converter = AlarmConverter::Synced;
         sync_messages
     }
+
+    fn tick_interval(&self) -> Option<Duration> {
+        let heartbeat_interval = self
+            .availability
+            .as_ref()
+            .map(|availability| availability.check_interval());
+        match (self.inventory_interval, heartbeat_interval) {
+            (Some(inventory), Some(heartbeat)) => Some(inventory.min(heartbeat)),
+            (inventory, heartbeat) => inventory.or(heartbeat),
+        }
+    }
+
+    fn tick_messages(&mut self) -> Vec<Message> {
+        let mut messages = vec![];
+
+        // The ticks can be more frequent than the inventory updates, when also used for the heartbeats.
+        // Allow half a tick of jitter so an update is not postponed by a full tick.
+        if let (Some(inventory_interval), Some(tick_interval)) =
+            (self.inventory_interval, self.tick_interval())
+        {
+            if self.last_inventory_update.elapsed() + tick_interval / 2 >= inventory_interval {
+                self.last_inventory_update = Instant::now();
+                messages.push(self.wrap_error(create_device_data_fragments(
+                    &self.device_name,
+                    &self.device_type,
+                    &self.device_inventory,
+                )));
+            }
+        }
+
+        if let Some(availability) = &mut self.availability {
+            availability.record_activity(&messages);
+            messages.extend(availability.heartbeat());
+        }
+
+        messages
+    }
+
+    fn watched_directory(&self) -> Option<PathBuf> {
+        self.operations_dir.clone()
+    }
+
+    fn file_event_messages(&mut self, path: &Path) -> Vec<Message> {
+        if !is_valid_operation_path(path) {
+            debug!("Ignoring {}: not an operation file", path.display());
+            return vec![];
+        }
+        let message_or_err = self.try_reload_operation(path);
+        vec![self.wrap_error(message_or_err)]
+    }
+
+    fn take_async_messages(&mut self) -> Option<UnboundedReceiver<Message>> {
+        self.operation_outcomes_receiver.take()
+    }
+}
+
+/// Check if a topic is subscribed by the mapper, whatever the custom operations.
+fn is_default_subscription(topic: &str) -> bool {
+    TEDGE_SUBSCRIPTIONS.contains(&topic)
+        || CumulocityMapper::subscriptions(&Operations::default())
+            .map(|topic_filter| topic_filter.patterns.iter().any(|pattern| pattern == topic))
+            .unwrap_or(false)
 }
 
 async fn parse_c8y_topics(
     message: &Message,
     operations: &Operations,
+    shell_command: Option<&ShellCommandConfig>,
     http_proxy: &mut impl C8YHttpProxy,
     operation_logs: &OperationLogs,
+    operation_outcomes: &UnboundedSender<Message>,
 ) -> Result<Vec<Message>, ConversionError> {
     match process_smartrest(
         message.payload_str()?,
         operations,
+        shell_command,
         http_proxy,
         operation_logs,
+        operation_outcomes,
     )
     .await
     {
@@ -389,6 +797,41 @@ async fn parse_c8y_topics(
     }
 }
 
+/// Process the JSON operations received on `c8y/devicecontrol/notifications`.
+///
+/// Only the `c8y_DeviceProfile` operations are handled this way,
+/// the other operations being received as SmartREST messages.
+async fn parse_c8y_json_operation(
+    message: &Message,
+    http_proxy: &mut impl C8YHttpProxy,
+) -> Result<Vec<Message>, ConversionError> {
+    let operation: serde_json::Value = serde_json::from_str(message.payload_str()?)?;
+    if operation.get("c8y_DeviceProfile").is_none() {
+        return Ok(vec![]);
+    }
+
+    match forward_device_profile_request(operation, http_proxy).await {
+        Ok(msgs) => Ok(msgs),
+        Err(err) => {
+            error!("{err}");
+            let topic = C8yTopic::SmartRestResponse.to_topic()?;
+            let executing = SmartRestSetOperationToExecuting::new(
+                CumulocitySupportedOperations::C8yDeviceProfile,
+            )
+            .to_smartrest()?;
+            let failed = SmartRestSetOperationToFailed::new(
+                CumulocitySupportedOperations::C8yDeviceProfile,
+                err.to_string(),
+            )
+            .to_smartrest()?;
+            Ok(vec![
+                Message::new(&topic, executing),
+                Message::new(&topic, failed),
+            ])
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum AlarmConverter {
     Syncing {
@@ -426,9 +869,24 @@ impl AlarmConverter {
                 //Regular conversion phase
                 let tedge_alarm =
                     ThinEdgeAlarm::try_from(input.topic.name.as_str(), input.payload_str()?)?;
-                let smartrest_alarm = alarm::serialize_alarm(tedge_alarm)?;
-                let c8y_alarm_topic = Topic::new_unchecked(SMARTREST_PUBLISH_TOPIC);
-                vec.push(Message::new(&c8y_alarm_topic, smartrest_alarm));
+                let has_custom_fragments = tedge_alarm
+                    .data
+                    .as_ref()
+                    .map_or(false, |data| !data.extras.is_empty());
+                if has_custom_fragments {
+                    // SmartREST cannot carry custom fragments, hence use Cumulocity JSON.
+                    // Such an alarm is still cleared with SmartREST, as clearing only relies on the alarm type.
+                    let c8y_alarm = C8yCreateAlarm::from(tedge_alarm);
+                    let c8y_alarm_topic = Topic::new_unchecked(C8Y_JSON_MQTT_ALARMS_TOPIC);
+                    vec.push(Message::new(
+                        &c8y_alarm_topic,
+                        serde_json::to_string(&c8y_alarm)?,
+                    ));
+                } else {
+                    let smartrest_alarm = alarm::serialize_alarm(tedge_alarm)?;
+                    let c8y_alarm_topic = Topic::new_unchecked(SMARTREST_PUBLISH_TOPIC);
+                    vec.push(Message::new(&c8y_alarm_topic, smartrest_alarm));
+                }
 
                 // Persist a copy of the alarm to an internal topic for reconciliation on next restart
                 let alarm_id = input
@@ -529,9 +987,26 @@ impl AlarmConverter {
 fn create_device_data_fragments(
     device_name: &str,
     device_type: &str,
+    device_inventory: &DeviceInventory,
 ) -> Result<Message, ConversionError> {
     let device_data = C8yDeviceDataFragment::from_type(device_type)?;
-    let ops_msg = device_data.to_json()?;
+    let mut ops_msg = device_data.to_json()?;
+
+    // The fragments explicitly set in the inventory file take precedence over the collected ones
+    let user_fragments = read_json_from_file(INVENTORY_FRAGMENTS_FILE_LOCATION).ok();
+    if let (Some(data), serde_json::Value::Object(collected)) =
+        (ops_msg.as_object_mut(), device_inventory.to_json()?)
+    {
+        for (fragment, value) in collected {
+            if user_fragments
+                .as_ref()
+                .and_then(|json| json.get(&fragment))
+                .is_none()
+            {
+                data.insert(fragment, value);
+            }
+        }
+    }
 
     let topic = Topic::new_unchecked(&format!("{INVENTORY_MANAGED_OBJECTS_TOPIC}/{device_name}",));
     Ok(Message::new(&topic, ops_msg.to_string()))
@@ -551,9 +1026,9 @@ fn create_get_pending_operations_message() -> Result<Message, ConversionError> {
     Ok(Message::new(&topic, payload))
 }
 
-fn create_supported_operations_fragments_message() -> Result<Message, ConversionError> {
-    let ops = Operations::try_new(SUPPORTED_OPERATIONS_DIRECTORY, C8Y_CLOUD)?;
-    let ops = ops.get_operations_list();
+fn create_supported_operations_fragments_message(
+    ops: &[String],
+) -> Result<Message, ConversionError> {
     let ops = ops.iter().map(|op| op as &str).collect::<Vec<&str>>();
 
     let ops_msg = SmartRestSetSupportedOperations::new(&ops);
@@ -601,6 +1076,76 @@ async fn publish_restart_operation_status(
     }
 }
 
+/// Map the status of a firmware update to SmartREST,
+/// updating the `c8y_Firmware` fragment of the device once the new firmware is installed.
+fn publish_firmware_operation_status(
+    device_name: &str,
+    json_response: &str,
+) -> Result<Vec<Message>, CumulocityMapperError> {
+    let response = FirmwareUpdateResponse::from_json(json_response)?;
+    let topic = C8yTopic::SmartRestResponse.to_topic()?;
+    let mut messages = vec![];
+
+    let smartrest_set_operation = match response.status() {
+        OperationStatus::Executing => {
+            SmartRestSetOperationToExecuting::new(CumulocitySupportedOperations::C8yFirmware)
+                .to_smartrest()?
+        }
+        OperationStatus::Successful => {
+            if let Some(name) = &response.name {
+                let firmware = serde_json::json!({
+                    "c8y_Firmware": {
+                        "name": name,
+                        "version": response.version.as_deref().unwrap_or_default(),
+                        "url": response.url.as_deref().unwrap_or_default(),
+                    }
+                });
+                let inventory_topic = Topic::new_unchecked(&format!(
+                    "{INVENTORY_MANAGED_OBJECTS_TOPIC}/{device_name}"
+                ));
+                messages.push(Message::new(&inventory_topic, firmware.to_string()));
+            }
+            SmartRestSetOperationToSuccessful::new(CumulocitySupportedOperations::C8yFirmware)
+                .to_smartrest()?
+        }
+        OperationStatus::Failed => SmartRestSetOperationToFailed::new(
+            CumulocitySupportedOperations::C8yFirmware,
+            response
+                .error()
+                .unwrap_or_else(|| "Firmware update failed".into()),
+        )
+        .to_smartrest()?,
+    };
+    messages.insert(0, Message::new(&topic, smartrest_set_operation));
+    Ok(messages)
+}
+
+fn publish_device_profile_status(
+    json_response: &str,
+) -> Result<Vec<Message>, CumulocityMapperError> {
+    let response = DeviceProfileResponse::from_json(json_response)?;
+    let topic = C8yTopic::SmartRestResponse.to_topic()?;
+
+    let smartrest_set_operation = match response.status() {
+        OperationStatus::Executing => {
+            SmartRestSetOperationToExecuting::new(CumulocitySupportedOperations::C8yDeviceProfile)
+                .to_smartrest()?
+        }
+        OperationStatus::Successful => {
+            SmartRestSetOperationToSuccessful::new(CumulocitySupportedOperations::C8yDeviceProfile)
+                .to_smartrest()?
+        }
+        OperationStatus::Failed => SmartRestSetOperationToFailed::new(
+            CumulocitySupportedOperations::C8yDeviceProfile,
+            response
+                .error()
+                .unwrap_or_else(|| "Device profile update failed".into()),
+        )
+        .to_smartrest()?,
+    };
+    Ok(vec![Message::new(&topic, smartrest_set_operation)])
+}
+
 async fn publish_operation_status(
     json_response: &str,
     http_proxy: &mut impl C8YHttpProxy,
@@ -653,55 +1198,267 @@ async fn validate_and_publish_software_list(
     Ok(vec![])
 }
 
+/// Spawn the command of a custom operation, returning the `501` executing status.
+///
+/// The final status, `503` or `502`, is sent on `operation_outcomes` once the command completes.
 async fn execute_operation(
     payload: &str,
     command: &str,
-    operation_name: &str,
+    operation: &Operation,
     operation_logs: &OperationLogs,
-) -> Result<(), CumulocityMapperError> {
+    operation_outcomes: &UnboundedSender<Message>,
+) -> Result<Vec<Message>, CumulocityMapperError> {
     let command = command.to_owned();
     let payload = payload.to_string();
+    let operation_name = operation.name.as_str();
+    let execute_failed = |error_message: String| CumulocityMapperError::ExecuteFailed {
+        error_message,
+        command: command.to_string(),
+        operation_name: operation_name.to_string(),
+    };
 
     let mut logged = LoggedCommand::new(&command);
     logged.arg(&payload);
 
+    if let Some(working_dir) = operation.working_dir() {
+        logged.current_dir(working_dir);
+    }
+    if let Some(env) = operation.env() {
+        for (key, value) in env {
+            logged.env(key, value);
+        }
+    }
+    let (uid, primary_gid) = match operation.user() {
+        Some(user) => {
+            let user = get_user_by_name(user)
+                .ok_or_else(|| execute_failed(format!("Unknown user: {user}")))?;
+            (Some(user.uid()), Some(user.primary_group_id()))
+        }
+        None => (None, None),
+    };
+    let gid = match operation.group() {
+        Some(group) => Some(
+            get_group_by_name(group)
+                .ok_or_else(|| execute_failed(format!("Unknown group: {group}")))?
+                .gid(),
+        ),
+        None => primary_gid,
+    };
+    if let Some(uid) = uid {
+        logged.uid(uid);
+    }
+    if let Some(gid) = gid {
+        logged.gid(gid);
+    }
+    if operation.timeout().is_some() {
+        logged.kill_on_drop();
+    }
+
+    let child = logged.spawn().map_err(|e| execute_failed(e.to_string()));
+
+    let log_file = operation_logs
+        .new_log_file(plugin_sm::operation_logs::LogKind::Operation(
+            operation_name.to_string(),
+        ))
+        .await?;
+
+    match child {
+        Ok(child) => {
+            let topic = C8yTopic::SmartRestResponse.to_topic()?;
+            let executing = Message::new(&topic, format!("501,{operation_name}"));
+
+            let operation_name = operation_name.to_string();
+            report_operation_outcome(
+                child,
+                log_file,
+                operation.timeout(),
+                operation_outcomes,
+                move |outcome| match outcome {
+                    Ok(output) if output.status.success() => format!("503,{operation_name}"),
+                    Ok(output) => {
+                        let reason = operation_failure_reason(&output);
+                        format!("502,{operation_name},\"{reason}\"")
+                    }
+                    Err(err) => format!(
+                        "502,{operation_name},\"{}\"",
+                        err.to_string().replace('"', "\"\"")
+                    ),
+                },
+            );
+            Ok(vec![executing])
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Wait in the background for the command of an operation to complete,
+/// then send on `operation_outcomes` the SmartREST status derived from the outcome.
+fn report_operation_outcome(
+    child: LoggingChild,
+    mut log_file: LogFile,
+    timeout: Option<Duration>,
+    operation_outcomes: &UnboundedSender<Message>,
+    status: impl FnOnce(Result<Output, std::io::Error>) -> String + Send + 'static,
+) {
+    let mut operation_outcomes = operation_outcomes.clone();
+    tokio::spawn(async move {
+        let logger = log_file.buffer();
+        let outcome = match timeout {
+            Some(timeout) => child.wait_with_output_and_timeout(logger, timeout).await,
+            None => child.wait_with_output(logger).await,
+        };
+        let topic = C8yTopic::SmartRestResponse.to_topic().unwrap(); // never fail
+        let _ = operation_outcomes
+            .send(Message::new(&topic, status(outcome)))
+            .await;
+    });
+}
+
+/// Run with the configured shell the command sent by Cumulocity in a `c8y_Command` operation,
+/// returning the `501` executing status.
+///
+/// The final status is sent on `operation_outcomes` once the command completes:
+/// `503` with the command output on exit code 0, `502` with the command output otherwise.
+async fn execute_shell_command(
+    smartrest: &str,
+    config: &ShellCommandConfig,
+    operation_logs: &OperationLogs,
+    operation_outcomes: &UnboundedSender<Message>,
+) -> Result<Vec<Message>, CumulocityMapperError> {
+    let operation_name: &str = CumulocitySupportedOperations::C8yCommand.into();
+    let request = SmartRestCommandRequest::from_smartrest(smartrest)?;
+
+    let mut logged = LoggedCommand::new(&config.shell);
+    // The command is always run with a timeout
+    logged.arg("-c").arg(&request.command).kill_on_drop();
+
     let child = logged
         .spawn()
         .map_err(|e| CumulocityMapperError::ExecuteFailed {
             error_message: e.to_string(),
-            command: command.to_string(),
+            command: request.command.clone(),
             operation_name: operation_name.to_string(),
-        });
+        })?;
 
-    let mut log_file = operation_logs
+    let log_file = operation_logs
         .new_log_file(plugin_sm::operation_logs::LogKind::Operation(
             operation_name.to_string(),
         ))
         .await?;
 
-    match child {
-        Ok(child) => {
-            tokio::spawn(async move {
-                let logger = log_file.buffer();
-                let _result = child.wait_with_output(logger).await.unwrap();
-            });
-            Ok(())
+    let topic = C8yTopic::SmartRestResponse.to_topic()?;
+    let executing = Message::new(&topic, format!("501,{operation_name}"));
+
+    let max_output = config.max_output;
+    report_operation_outcome(
+        child,
+        log_file,
+        Some(config.timeout),
+        operation_outcomes,
+        move |outcome| match outcome {
+            Ok(output) if output.status.success() => {
+                format!(
+                    "503,{operation_name},\"{}\"",
+                    command_output(&output, max_output)
+                )
+            }
+            Ok(output) => {
+                let output_or_status = match command_output(&output, max_output) {
+                    combined_output if combined_output.is_empty() => output.status.to_string(),
+                    combined_output => combined_output,
+                };
+                format!("502,{operation_name},\"{output_or_status}\"")
+            }
+            Err(err) => format!(
+                "502,{operation_name},\"{}\"",
+                err.to_string().replace('"', "\"\"")
+            ),
+        },
+    );
+    Ok(vec![executing])
+}
+
+/// The combined stdout and stderr of a command, with its quotes escaped,
+/// truncated to its last `max_len` bytes, and never more than `SHELL_COMMAND_MAX_OUTPUT_LIMIT`.
+///
+/// As for the operation failure reasons, the end of a long output is kept,
+/// this being where the final result or error of a command is usually printed.
+fn command_output(output: &Output, max_len: usize) -> String {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined_output = format!("{stdout}{stderr}");
+
+    escaped_tail(
+        combined_output.trim_end(),
+        max_len.min(SHELL_COMMAND_MAX_OUTPUT_LIMIT),
+    )
+}
+
+/// The reason of a failed operation: the tail of the stderr, or the exit status if nothing has been printed on stderr.
+fn operation_failure_reason(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        return output.status.to_string();
+    }
+
+    tail(stderr, OPERATION_FAILURE_REASON_MAX_LEN)
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .replace('"', "\"\"")
+}
+
+/// The end of a text with its quotes doubled, cut on a char boundary to fit in `max_len` bytes once escaped.
+fn escaped_tail(text: &str, max_len: usize) -> String {
+    let mut escaped_len = 0;
+    let mut tail_start = text.len();
+    for (index, c) in text.char_indices().rev() {
+        escaped_len += if c == '"' { 2 } else { c.len_utf8() };
+        if escaped_len > max_len {
+            break;
         }
-        Err(err) => Err(err),
+        tail_start = index;
     }
+    text[tail_start..].replace('"', "\"\"")
+}
+
+/// The last `max_len` bytes of a text, cut on a char boundary.
+fn tail(text: &str, max_len: usize) -> &str {
+    let mut tail_start = text.len().saturating_sub(max_len);
+    while !text.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+    &text[tail_start..]
 }
 
 async fn process_smartrest(
     payload: &str,
     operations: &Operations,
+    shell_command: Option<&ShellCommandConfig>,
     http_proxy: &mut impl C8YHttpProxy,
     operation_logs: &OperationLogs,
+    operation_outcomes: &UnboundedSender<Message>,
 ) -> Result<Vec<Message>, CumulocityMapperError> {
     let message_id: &str = &payload[..3];
-    match message_id {
-        "528" => forward_software_request(payload, http_proxy).await,
-        "510" => forward_restart_request(payload),
-        template => forward_operation_request(payload, template, operations, operation_logs).await,
+    match (message_id, shell_command) {
+        ("528", _) => forward_software_request(payload, http_proxy).await,
+        ("510", _) => forward_restart_request(payload),
+        ("515", _) => forward_firmware_request(payload, http_proxy).await,
+        ("511", Some(config)) => {
+            execute_shell_command(payload, config, operation_logs, operation_outcomes).await
+        }
+        (template, _) => {
+            forward_operation_request(
+                payload,
+                template,
+                operations,
+                operation_logs,
+                operation_outcomes,
+            )
+            .await
+        }
     }
 }
 
@@ -748,20 +1505,76 @@ fn forward_restart_request(smartrest: &str) -> Result<Vec<Message>, CumulocityMa
     Ok(vec![Message::new(&topic, request.to_json()?)])
 }
 
+async fn forward_firmware_request(
+    smartrest: &str,
+    http_proxy: &mut impl C8YHttpProxy,
+) -> Result<Vec<Message>, CumulocityMapperError> {
+    let topic = Topic::new(RequestTopic::FirmwareUpdateRequest.as_str())?;
+    let firmware = SmartRestFirmwareRequest::from_smartrest(smartrest)?;
+
+    let version = Some(firmware.version).filter(|version| !version.is_empty());
+    let url = if firmware.url.is_empty() {
+        None
+    } else if http_proxy.url_is_in_my_tenant_domain(&firmware.url) {
+        let token = http_proxy.get_jwt_token().await?;
+        Some(DownloadInfo::new(&firmware.url).with_auth(Auth::new_bearer(&token.token())))
+    } else {
+        Some(DownloadInfo::new(&firmware.url))
+    };
+
+    let request = FirmwareUpdateRequest::new(&firmware.name, version, url);
+    Ok(vec![Message::new(&topic, request.to_json()?)])
+}
+
+async fn forward_device_profile_request(
+    operation: serde_json::Value,
+    http_proxy: &mut impl C8YHttpProxy,
+) -> Result<Vec<Message>, CumulocityMapperError> {
+    let topic = Topic::new(RequestTopic::DeviceProfileRequest.as_str())?;
+    let operation: C8yDeviceProfileOperation = serde_json::from_value(operation)?;
+    let mut request = operation.to_thin_edge_json()?;
+
+    // The configuration files are downloaded by the configuration plugin, which adds the credentials itself
+    let token = http_proxy.get_jwt_token().await?;
+    let firmware_url = request
+        .firmware
+        .iter_mut()
+        .filter_map(|firmware| firmware.url.as_mut());
+    let software_urls = request
+        .software
+        .iter_mut()
+        .flat_map(|modules| modules.modules.iter_mut())
+        .filter_map(|module| module.url.as_mut());
+    for url in firmware_url.chain(software_urls) {
+        if http_proxy.url_is_in_my_tenant_domain(url.url()) {
+            url.auth = Some(Auth::new_bearer(&token.token()));
+        }
+    }
+
+    Ok(vec![Message::new(&topic, request.to_json()?)])
+}
+
 async fn forward_operation_request(
     payload: &str,
     template: &str,
     operations: &Operations,
     operation_logs: &OperationLogs,
+    operation_outcomes: &UnboundedSender<Message>,
 ) -> Result<Vec<Message>, CumulocityMapperError> {
     match operations.matching_smartrest_template(template) {
-        Some(operation) => {
-            if let Some(command) = operation.command() {
-                execute_operation(payload, command.as_str(), &operation.name, operation_logs)
-                    .await?;
+        Some(operation) => match operation.command() {
+            Some(command) => {
+                execute_operation(
+                    payload,
+                    command.as_str(),
+                    operation,
+                    operation_logs,
+                    operation_outcomes,
+                )
+                .await
             }
-            Ok(vec![])
-        }
+            None => Ok(vec![]),
+        },
         None => Err(CumulocityMapperError::UnknownOperation(
             template.to_string(),
         )),
@@ -807,35 +1620,244 @@ fn get_inventory_fragments(file_path: &str) -> Result<serde_json::Value, Convers
     }
 }
 
-pub fn get_child_id_from_topic(topic: &str) -> Result<Option<String>, ConversionError> {
-    match topic.strip_prefix("tedge/measurements/").map(String::from) {
-        Some(maybe_id) if maybe_id.is_empty() => {
-            Err(ConversionError::InvalidChildId { id: maybe_id })
-        }
-        option => Ok(option),
-    }
+/// Parse a measurement topic, rejecting the topics with an invalid child id.
+pub fn parse_measurement_topic(topic: &str) -> Result<MeasurementTopic<'_>, ConversionError> {
+    MeasurementTopic::parse(topic).ok_or_else(|| {
+        let id = topic
+            .strip_prefix(TEDGE_MEASUREMENTS_CBOR_TOPIC)
+            .or_else(|| topic.strip_prefix(TEDGE_MEASUREMENTS_TOPIC))
+            .map_or(topic, |suffix| suffix.trim_start_matches('/'));
+        ConversionError::InvalidChildId { id: id.into() }
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::c8y::error::CumulocityMapperError;
+    use c8y_smartrest::operations::Operation;
+    use futures::{channel::mpsc, StreamExt};
+    use mqtt_channel::Message;
     use plugin_sm::operation_logs::OperationLogs;
+    use std::time::Duration;
     use tempfile::TempDir;
 
     #[tokio::test]
     async fn test_execute_operation_is_not_blocked() {
         let log_dir = TempDir::new().unwrap();
         let operation_logs = OperationLogs::try_new(log_dir.path().to_path_buf()).unwrap();
+        let (outcomes, _outcomes_receiver) = mpsc::unbounded();
 
         let now = std::time::Instant::now();
-        let () = super::execute_operation("5", "sleep", "sleep_one", &operation_logs)
+        let _ = super::execute_operation(
+            "5",
+            "sleep",
+            &operation("sleep_one", ""),
+            &operation_logs,
+            &outcomes,
+        )
+        .await
+        .unwrap();
+        let _ = super::execute_operation(
+            "5",
+            "sleep",
+            &operation("sleep_two", ""),
+            &operation_logs,
+            &outcomes,
+        )
+        .await
+        .unwrap();
+
+        // a result between now and elapsed that is not 0 probably means that the operations are
+        // blocking and that you probably removed a tokio::spawn handle (;
+        assert_eq!(now.elapsed().as_secs(), 0);
+    }
+
+    #[test_case::test_case("true", "", "", "503,custom_op"; "successful operation")]
+    #[test_case::test_case(
+        "ls",
+        "dummy-file",
+        "",
+        "502,custom_op,\"ls: cannot access 'dummy-file': No such file or directory\"";
+        "failed operation"
+    )]
+    #[test_case::test_case(
+        "sleep",
+        "5",
+        "timeout = 1",
+        "502,custom_op,\"timed out after 1s\"";
+        "timed out operation"
+    )]
+    #[test_case::test_case(
+        "printenv",
+        "GREETING",
+        "[exec.env]\nGREETING = \"hello\"",
+        "503,custom_op";
+        "operation with env"
+    )]
+    #[test_case::test_case(
+        "ls",
+        "etc",
+        "working_dir = \"/\"",
+        "503,custom_op";
+        "operation with working dir"
+    )]
+    #[tokio::test]
+    async fn the_outcome_of_an_operation_is_sent_once_complete(
+        command: &str,
+        payload: &str,
+        exec: &str,
+        expected_outcome: &str,
+    ) {
+        let log_dir = TempDir::new().unwrap();
+        let operation_logs = OperationLogs::try_new(log_dir.path().to_path_buf()).unwrap();
+        let (outcomes, mut outcomes_receiver) = mpsc::unbounded::<Message>();
+
+        let messages = super::execute_operation(
+            payload,
+            command,
+            &operation("custom_op", exec),
+            &operation_logs,
+            &outcomes,
+        )
+        .await
+        .unwrap();
+        assert_eq!(messages[0].payload_str().unwrap(), "501,custom_op");
+
+        let outcome = tokio::time::timeout(Duration::from_secs(5), outcomes_receiver.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(outcome.topic.name, "c8y/s/us");
+        assert_eq!(outcome.payload_str().unwrap(), expected_outcome);
+    }
+
+    #[test_case::test_case("user = \"unknown-user\"", "Unknown user: unknown-user")]
+    #[test_case::test_case("group = \"unknown-group\"", "Unknown group: unknown-group")]
+    #[tokio::test]
+    async fn an_operation_with_an_unknown_user_or_group_is_rejected(
+        exec: &str,
+        expected_error: &str,
+    ) {
+        let log_dir = TempDir::new().unwrap();
+        let operation_logs = OperationLogs::try_new(log_dir.path().to_path_buf()).unwrap();
+        let (outcomes, _outcomes_receiver) = mpsc::unbounded::<Message>();
+
+        let result = super::execute_operation(
+            "",
+            "true",
+            &operation("custom_op", exec),
+            &operation_logs,
+            &outcomes,
+        )
+        .await;
+
+        match result {
+            Err(CumulocityMapperError::ExecuteFailed { error_message, .. }) => {
+                assert_eq!(error_message, expected_error)
+            }
+            _ => panic!("Expected an execution failure"),
+        }
+    }
+
+    fn operation(name: &str, exec: &str) -> Operation {
+        let mut operation: Operation = toml::from_str(&format!("[exec]\n{exec}")).unwrap();
+        operation.name = name.to_string();
+        operation
+    }
+
+    #[test_case::test_case("511,device,echo hello", "503,c8y_Command,\"hello\""; "successful command")]
+    #[test_case::test_case(
+        r#"511,device,"echo '""hello"", world'""#,
+        "503,c8y_Command,\"\"\"hello\"\", world\"";
+        "command with quotes and commas"
+    )]
+    #[test_case::test_case(
+        "511,device,ls dummy-file",
+        "502,c8y_Command,\"ls: cannot access 'dummy-file': No such file or directory\"";
+        "failed command"
+    )]
+    #[test_case::test_case("511,device,exit 3", "502,c8y_Command,\"exit status: 3\""; "silent failure")]
+    #[test_case::test_case(
+        "511,device,sleep 5",
+        "502,c8y_Command,\"timed out after 1s\"";
+        "timed out command"
+    )]
+    #[tokio::test]
+    async fn the_output_of_a_shell_command_is_sent_once_complete(
+        smartrest: &str,
+        expected_outcome: &str,
+    ) {
+        let log_dir = TempDir::new().unwrap();
+        let operation_logs = OperationLogs::try_new(log_dir.path().to_path_buf()).unwrap();
+        let (outcomes, mut outcomes_receiver) = mpsc::unbounded::<Message>();
+        let config = super::ShellCommandConfig {
+            timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
+
+        let messages = super::execute_shell_command(smartrest, &config, &operation_logs, &outcomes)
             .await
             .unwrap();
-        let () = super::execute_operation("5", "sleep", "sleep_two", &operation_logs)
+        assert_eq!(messages[0].payload_str().unwrap(), "501,c8y_Command");
+
+        let outcome = tokio::time::timeout(Duration::from_secs(5), outcomes_receiver.next())
             .await
+            .unwrap()
             .unwrap();
+        assert_eq!(outcome.payload_str().unwrap(), expected_outcome);
+    }
 
-        // a result between now and elapsed that is not 0 probably means that the operations are
-        // blocking and that you probably removed a tokio::spawn handle (;
-        assert_eq!(now.elapsed().as_secs(), 0);
+    #[test]
+    fn the_output_of_a_shell_command_is_truncated() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let max_output = super::DEFAULT_SHELL_COMMAND_MAX_OUTPUT;
+        let output = std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: "é".repeat(max_output).into_bytes(),
+            stderr: vec![],
+        };
+
+        let combined_output = super::command_output(&output, max_output);
+        assert!(combined_output.len() <= max_output);
+        assert_eq!(combined_output.len(), max_output / 2 * 2);
+    }
+
+    #[test]
+    fn the_end_of_a_long_shell_command_output_is_kept() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let output = std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: b"first line\nsecond line\n".to_vec(),
+            stderr: b"final error\n".to_vec(),
+        };
+
+        assert_eq!(super::command_output(&output, 16), "line\nfinal error");
+    }
+
+    #[test]
+    fn the_output_of_a_shell_command_is_truncated_once_escaped() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let output = std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: r#"say "hello" and "bye""#.as_bytes().to_vec(),
+            stderr: vec![],
+        };
+        assert_eq!(super::command_output(&output, 8), r#" ""bye"""#);
+        assert_eq!(super::command_output(&output, 7), r#"""bye"""#);
+        assert_eq!(super::command_output(&output, 6), r#"bye"""#);
+
+        let output = std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: "\"".repeat(super::SMARTREST_MAX_SIZE).into_bytes(),
+            stderr: vec![],
+        };
+        let combined_output = super::command_output(&output, usize::MAX);
+        assert!(combined_output.len() <= super::SHELL_COMMAND_MAX_OUTPUT_LIMIT);
+        assert!(
+            format!("503,c8y_Command,\"{combined_output}\"").len() <= super::SMARTREST_MAX_SIZE
+        );
     }
 }
@@ -13,6 +13,16 @@ pub struct C8yAgent {
     url: String,
 }
 
+impl C8yAgent {
+    pub fn new(version: String) -> Self {
+        C8yAgent {
+            name: DEFAULT_AGENT_FRAGMENT_NAME.into(),
+            version,
+            url: DEFAULT_AGENT_FRAGMENT_URL.into(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct C8yAgentFragment {
     #[serde(rename = "c8y_Agent")]
@@ -21,11 +31,7 @@ pub struct C8yAgentFragment {
 
 impl C8yAgentFragment {
     pub fn new() -> Result<Self, ConversionError> {
-        let c8y_agent = C8yAgent {
-            name: DEFAULT_AGENT_FRAGMENT_NAME.into(),
-            version: get_tedge_version()?,
-            url: DEFAULT_AGENT_FRAGMENT_URL.into(),
-        };
+        let c8y_agent = C8yAgent::new(get_tedge_version()?);
         Ok(Self { c8y_agent })
     }
 
@@ -0,0 +1,75 @@
+use mqtt_channel::Message;
+
+pub(crate) const TEDGE_HEALTH_TOPIC: &str = "tedge/health/";
+
+/// The health status of a tedge component, as published on `tedge/health/<component>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Up,
+    Down,
+    Unknown,
+}
+
+impl HealthStatus {
+    /// Parse a health status, be it the JSON status published by the tedge daemons
+    /// (`{"status":"up"}`, or `{"status":"down"}` as last will)
+    /// or the `1`/`0` status published by the mosquitto bridges.
+    ///
+    /// Return `None` for an empty payload, i.e. a cleared retained status.
+    pub fn from_payload(payload: &[u8]) -> Option<HealthStatus> {
+        if payload.is_empty() {
+            return None;
+        }
+
+        let status = match serde_json::from_slice::<serde_json::Value>(payload) {
+            Ok(serde_json::Value::Object(health)) => {
+                match health.get("status").and_then(|status| status.as_str()) {
+                    Some("up") => HealthStatus::Up,
+                    Some("down") => HealthStatus::Down,
+                    _ => HealthStatus::Unknown,
+                }
+            }
+            Ok(serde_json::Value::Number(status)) => match status.as_u64() {
+                Some(1) => HealthStatus::Up,
+                Some(0) => HealthStatus::Down,
+                _ => HealthStatus::Unknown,
+            },
+            _ => HealthStatus::Unknown,
+        };
+        Some(status)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthStatus::Up => "up",
+            HealthStatus::Down => "down",
+            HealthStatus::Unknown => "unknown",
+        }
+    }
+}
+
+/// Extract the component name and its status from a `tedge/health/<component>` message.
+pub fn component_health(message: &Message) -> Option<(&str, Option<HealthStatus>)> {
+    let component = message.topic.name.strip_prefix(TEDGE_HEALTH_TOPIC)?;
+    Some((
+        component,
+        HealthStatus::from_payload(message.payload_bytes()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(r#"{"status":"up","pid":1234}"#, Some(HealthStatus::Up))]
+    #[test_case(r#"{"status":"down"}"#, Some(HealthStatus::Down))]
+    #[test_case(r#"{"pid":1234}"#, Some(HealthStatus::Unknown))]
+    #[test_case("1", Some(HealthStatus::Up))]
+    #[test_case("0", Some(HealthStatus::Down))]
+    #[test_case("down", Some(HealthStatus::Unknown))]
+    #[test_case("", None)]
+    fn parse_health_status(payload: &str, expected: Option<HealthStatus>) {
+        assert_eq!(HealthStatus::from_payload(payload.as_bytes()), expected);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::core::error::ConversionError;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+use super::fragments::{get_tedge_version, C8yAgent};
+
+const OS_RELEASE_FILE: &str = "etc/os-release";
+const KERNEL_RELEASE_FILE: &str = "proc/sys/kernel/osrelease";
+const CPU_INFO_FILE: &str = "proc/cpuinfo";
+const MEM_INFO_FILE: &str = "proc/meminfo";
+const DMI_DIRECTORY: &str = "sys/class/dmi/id";
+
+/// Standard `c8y_Hardware` fragment, extended with the CPU model, the memory and the disk size.
+///
+/// The memory and disk sizes are given in MB.
+#[derive(Debug, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct C8yHardware {
+    model: String,
+    serial_number: String,
+    revision: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_memory: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_disk: Option<u64>,
+}
+
+/// `c8y_OperatingSystem` fragment, describing the operating system and its kernel.
+///
+/// The standard `c8y_Firmware` fragment is not used, being owned by the `c8y_Firmware` operation.
+#[derive(Debug, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct C8yOperatingSystem {
+    name: String,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kernel: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct C8yInventoryFragments {
+    #[serde(rename = "c8y_Hardware")]
+    pub c8y_hardware: C8yHardware,
+
+    #[serde(rename = "c8y_OperatingSystem")]
+    pub c8y_operating_system: C8yOperatingSystem,
+
+    #[serde(rename = "c8y_Agent")]
+    pub c8y_agent: C8yAgent,
+}
+
+/// Collects the hardware and OS inventory of the device.
+///
+/// All the information is read from the standard Linux pseudo-files
+/// (`/etc/os-release`, `/proc/cpuinfo`, `/proc/meminfo`, DMI data under `/sys`),
+/// looked up relative to a root directory so alternate roots can be used.
+/// Any missing piece of information is simply left empty.
+#[derive(Debug, Clone)]
+pub struct DeviceInventory {
+    root: PathBuf,
+}
+
+impl Default for DeviceInventory {
+    fn default() -> Self {
+        DeviceInventory::new("/")
+    }
+}
+
+impl DeviceInventory {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        DeviceInventory {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn collect(&self) -> Result<C8yInventoryFragments, ConversionError> {
+        Ok(C8yInventoryFragments {
+            c8y_hardware: self.hardware(),
+            c8y_operating_system: self.operating_system(),
+            c8y_agent: C8yAgent::new(get_tedge_version()?),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<serde_json::Value, ConversionError> {
+        Ok(serde_json::to_value(self.collect()?)?)
+    }
+
+    fn hardware(&self) -> C8yHardware {
+        let cpu_info = self.read_key_values(CPU_INFO_FILE, ':');
+
+        let model = self
+            .read_dmi("product_name")
+            .or_else(|| cpu_info.get("Model").cloned())
+            .or_else(|| cpu_info.get("Hardware").cloned())
+            .unwrap_or_default();
+        let serial_number = cpu_info
+            .get("Serial")
+            .cloned()
+            .or_else(|| self.read_dmi("product_serial"))
+            .unwrap_or_default();
+        let revision = cpu_info
+            .get("Revision")
+            .cloned()
+            .or_else(|| self.read_dmi("product_version"))
+            .unwrap_or_default();
+
+        C8yHardware {
+            model,
+            serial_number,
+            revision,
+            cpu: cpu_info.get("model name").cloned(),
+            total_memory: self.total_memory(),
+            total_disk: self.total_disk(),
+        }
+    }
+
+    fn operating_system(&self) -> C8yOperatingSystem {
+        let os_release = self.read_key_values(OS_RELEASE_FILE, '=');
+
+        let name = os_release
+            .get("PRETTY_NAME")
+            .or_else(|| os_release.get("NAME"))
+            .cloned()
+            .unwrap_or_default();
+        let version = os_release
+            .get("VERSION_ID")
+            .or_else(|| os_release.get("VERSION"))
+            .cloned()
+            .unwrap_or_default();
+
+        C8yOperatingSystem {
+            name,
+            version,
+            kernel: self.read_trimmed(KERNEL_RELEASE_FILE),
+        }
+    }
+
+    /// Total memory in MB, as given by `MemTotal` in `/proc/meminfo`.
+    fn total_memory(&self) -> Option<u64> {
+        self.read_key_values(MEM_INFO_FILE, ':')
+            .get("MemTotal")
+            .and_then(|value| value.trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kilobytes| kilobytes / 1024)
+    }
+
+    /// Total size in MB of the file system mounted on the root directory.
+    fn total_disk(&self) -> Option<u64> {
+        match nix::sys::statvfs::statvfs(&self.root) {
+            Ok(stat) => {
+                // The integer types of the statvfs fields are platform dependent
+                #[allow(clippy::useless_conversion)]
+                let bytes = u64::from(stat.blocks()) * u64::from(stat.fragment_size());
+                Some(bytes / (1024 * 1024))
+            }
+            Err(err) => {
+                debug!("Fail to get the disk size of {:?}: {}", self.root, err);
+                None
+            }
+        }
+    }
+
+    fn read_dmi(&self, key: &str) -> Option<String> {
+        self.read_trimmed(&format!("{DMI_DIRECTORY}/{key}"))
+    }
+
+    fn read_trimmed(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.root.join(path))
+            .ok()
+            .map(|content| content.trim().to_string())
+            .filter(|content| !content.is_empty())
+    }
+
+    /// Read a file made of `key <separator> value` lines.
+    ///
+    /// Quotes around values are removed and the first occurrence of a key wins.
+    fn read_key_values(&self, path: &str, separator: char) -> HashMap<String, String> {
+        let mut key_values = HashMap::new();
+        if let Some(content) = self.read_trimmed(path) {
+            for line in content.lines() {
+                if let Some((key, value)) = line.split_once(separator) {
+                    let value = value.trim().trim_matches('"').to_string();
+                    if !value.is_empty() {
+                        key_values.entry(key.trim().to_string()).or_insert(value);
+                    }
+                }
+            }
+        }
+        key_values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_file(root: &TempDir, path: &str, content: &str) {
+        let path = root.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn collect_inventory_of_a_raspberry_pi() {
+        let root = TempDir::new().unwrap();
+        create_file(
+            &root,
+            OS_RELEASE_FILE,
+            r#"PRETTY_NAME="Raspbian GNU/Linux 11 (bullseye)"
+NAME="Raspbian GNU/Linux"
+VERSION_ID="11"
+"#,
+        );
+        create_file(&root, KERNEL_RELEASE_FILE, "5.10.103-v7l+\n");
+        create_file(
+            &root,
+            CPU_INFO_FILE,
+            "processor\t: 0\nmodel name\t: ARMv7 Processor rev 3 (v7l)\n\n\
+             processor\t: 1\nmodel name\t: ARMv7 Processor rev 3 (v7l)\n\n\
+             Hardware\t: BCM2711\nRevision\t: c03112\nSerial\t\t: 100000002a5b7c1e\n\
+             Model\t\t: Raspberry Pi 4 Model B Rev 1.2\n",
+        );
+        create_file(&root, MEM_INFO_FILE, "MemTotal:        3884292 kB\n");
+
+        let inventory = DeviceInventory::new(root.path());
+
+        assert_eq!(
+            inventory.hardware(),
+            C8yHardware {
+                model: "Raspberry Pi 4 Model B Rev 1.2".into(),
+                serial_number: "100000002a5b7c1e".into(),
+                revision: "c03112".into(),
+                cpu: Some("ARMv7 Processor rev 3 (v7l)".into()),
+                total_memory: Some(3793),
+                total_disk: inventory.total_disk(),
+            }
+        );
+        assert_eq!(
+            inventory.operating_system(),
+            C8yOperatingSystem {
+                name: "Raspbian GNU/Linux 11 (bullseye)".into(),
+                version: "11".into(),
+                kernel: Some("5.10.103-v7l+".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn collect_inventory_from_dmi() {
+        let root = TempDir::new().unwrap();
+        create_file(
+            &root,
+            OS_RELEASE_FILE,
+            "NAME=Ubuntu\nVERSION=\"20.04.4 LTS\"\n",
+        );
+        create_file(&root, "sys/class/dmi/id/product_name", "ThinkPad T14\n");
+        create_file(&root, "sys/class/dmi/id/product_serial", "PF2ABC42\n");
+        create_file(&root, "sys/class/dmi/id/product_version", "\n");
+
+        let inventory = DeviceInventory::new(root.path());
+        let hardware = inventory.hardware();
+        assert_eq!(hardware.model, "ThinkPad T14");
+        assert_eq!(hardware.serial_number, "PF2ABC42");
+        assert_eq!(hardware.revision, "");
+        assert_eq!(hardware.total_memory, None);
+
+        let operating_system = inventory.operating_system();
+        assert_eq!(operating_system.name, "Ubuntu");
+        assert_eq!(operating_system.version, "20.04.4 LTS");
+        assert_eq!(operating_system.kernel, None);
+    }
+
+    #[test]
+    fn the_firmware_fragment_is_not_published() {
+        let root = TempDir::new().unwrap();
+        create_file(
+            &root,
+            OS_RELEASE_FILE,
+            "NAME=Ubuntu\nVERSION_ID=\"22.04\"\n",
+        );
+
+        let json = DeviceInventory::new(root.path()).to_json().unwrap();
+        assert_eq!(json["c8y_OperatingSystem"]["name"], "Ubuntu");
+        assert!(json.get("c8y_Firmware").is_none());
+    }
+
+    #[test]
+    fn missing_information_is_left_empty() {
+        let root = TempDir::new().unwrap();
+        let inventory = DeviceInventory::new(root.path());
+
+        let json = serde_json::to_value(inventory.hardware()).unwrap();
+        assert_eq!(json["model"], "");
+        assert_eq!(json["serialNumber"], "");
+        assert!(json.get("cpu").is_none());
+        assert!(json.get("totalMemory").is_none());
+    }
+}
@@ -1,18 +1,31 @@
-use std::path::Path;
+use std::{path::Path, time::Duration};
 
 use crate::{
-    c8y::converter::CumulocityConverter,
-    core::{component::TEdgeComponent, mapper::create_mapper, size_threshold::SizeThreshold},
+    c8y::{
+        converter::{CumulocityConverter, ShellCommandConfig, SHELL_COMMAND_MAX_OUTPUT_LIMIT},
+        measurement_batcher::MeasurementBatchConfig,
+    },
+    core::{
+        component::TEdgeComponent,
+        deadband::{create_deadband_directories, with_deadband_filter},
+        mapper::create_mapper,
+        size_threshold::SizeThreshold,
+    },
 };
 
 use agent_interface::topic::ResponseTopic;
 use async_trait::async_trait;
 use c8y_api::http_proxy::{C8YHttpProxy, JwtAuthHttpProxy};
 use c8y_smartrest::operations::Operations;
+use c8y_translator::serializer::TextMeasurementFallback;
+use clock::WallClock;
 use mqtt_channel::TopicFilter;
 use tedge_config::{
-    ConfigSettingAccessor, DeviceIdSetting, DeviceTypeSetting, MqttBindAddressSetting,
-    MqttPortSetting, TEdgeConfig,
+    C8yAvailabilityIntervalSetting, C8yBatchMaxSizeSetting, C8yBatchWindowSetting,
+    C8yCommandEnableSetting, C8yCommandMaxOutputSetting, C8yCommandShellSetting,
+    C8yCommandTimeoutSetting, C8yInventoryIntervalSetting, C8yTextMeasurementFallbackSetting,
+    ConfigSetting, ConfigSettingAccessor, DeviceIdSetting, DeviceTypeSetting,
+    MqttBindAddressSetting, MqttPortSetting, TEdgeConfig,
 };
 use tedge_utils::file::*;
 use tracing::{info, info_span, Instrument};
@@ -34,6 +47,9 @@ impl CumulocityMapper {
         topic_filter.add(ResponseTopic::SoftwareUpdateResponse.as_str())?;
         topic_filter.add(C8yTopic::SmartRestRequest.as_str())?;
         topic_filter.add(ResponseTopic::RestartResponse.as_str())?;
+        topic_filter.add(ResponseTopic::FirmwareUpdateResponse.as_str())?;
+        topic_filter.add(C8yTopic::OperationNotifications.as_str())?;
+        topic_filter.add(ResponseTopic::DeviceProfileResponse.as_str())?;
 
         for topic in operations.topics_for_operations() {
             topic_filter.add(&topic)?
@@ -70,14 +86,34 @@ impl TEdgeComponent for CumulocityMapper {
         let device_type = tedge_config.query(DeviceTypeSetting)?;
         let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
         let mqtt_host = tedge_config.query(MqttBindAddressSetting)?.to_string();
+        let inventory_interval = tedge_config
+            .query_optional(C8yInventoryIntervalSetting)?
+            .map(Duration::from)
+            .filter(|interval| !interval.is_zero());
+        let availability_interval = tedge_config
+            .query_optional(C8yAvailabilityIntervalSetting)?
+            .map(Duration::from)
+            .filter(|interval| !interval.is_zero());
+        let shell_command = shell_command_config(&tedge_config)?;
+        let measurement_batching = measurement_batch_config(&tedge_config)?;
+        let text_measurement_fallback = text_measurement_fallback(&tedge_config)?;
 
-        let converter = Box::new(CumulocityConverter::new(
-            size_threshold,
-            device_name,
-            device_type,
-            operations,
-            http_proxy,
-        )?);
+        let converter = Box::new(
+            CumulocityConverter::new(
+                size_threshold,
+                device_name,
+                device_type,
+                operations,
+                http_proxy,
+            )?
+            .with_inventory_interval(inventory_interval)
+            .with_availability_interval(availability_interval, Box::new(WallClock))
+            .with_shell_command(shell_command)
+            .with_measurement_batching(measurement_batching)
+            .with_text_measurement_fallback(text_measurement_fallback)
+            .with_operations_dir(cfg_dir.join("operations").join("c8y")),
+        );
+        let converter = with_deadband_filter(converter, cfg_dir, CUMULOCITY_MAPPER_NAME)?;
 
         let mut mapper =
             create_mapper(CUMULOCITY_MAPPER_NAME, mqtt_host, mqtt_port, converter).await?;
@@ -91,6 +127,64 @@ impl TEdgeComponent for CumulocityMapper {
     }
 }
 
+/// The settings of the `c8y_Command` operation, if enabled.
+fn shell_command_config(
+    tedge_config: &TEdgeConfig,
+) -> Result<Option<ShellCommandConfig>, anyhow::Error> {
+    let enabled = tedge_config
+        .query_optional(C8yCommandEnableSetting)?
+        .map(|flag| flag.is_set())
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+
+    let mut config = ShellCommandConfig::default();
+    if let Some(shell) = tedge_config.query_optional(C8yCommandShellSetting)? {
+        config.shell = shell;
+    }
+    if let Some(timeout) = tedge_config.query_optional(C8yCommandTimeoutSetting)? {
+        config.timeout = timeout.into();
+    }
+    if let Some(max_output) = tedge_config.query_optional(C8yCommandMaxOutputSetting)? {
+        let max_output = max_output.into();
+        if max_output > SHELL_COMMAND_MAX_OUTPUT_LIMIT {
+            anyhow::bail!(
+                "Invalid {}: it must be at most {} bytes, to fit in a SmartREST message",
+                C8yCommandMaxOutputSetting::KEY,
+                SHELL_COMMAND_MAX_OUTPUT_LIMIT
+            )
+        }
+        config.max_output = max_output;
+    }
+    Ok(Some(config))
+}
+
+/// The settings of the measurement batching, if enabled.
+fn measurement_batch_config(
+    tedge_config: &TEdgeConfig,
+) -> Result<Option<MeasurementBatchConfig>, anyhow::Error> {
+    let window = match tedge_config.query_optional(C8yBatchWindowSetting)? {
+        Some(window) if window.0 > 0 => Duration::from(window),
+        _ => return Ok(None),
+    };
+
+    let mut config = MeasurementBatchConfig::new(window);
+    if let Some(max_size) = tedge_config.query_optional(C8yBatchMaxSizeSetting)? {
+        config.max_size = max_size.into();
+    }
+    Ok(Some(config))
+}
+
+/// How the text measurements are sent, skipped by default.
+fn text_measurement_fallback(
+    tedge_config: &TEdgeConfig,
+) -> Result<TextMeasurementFallback, anyhow::Error> {
+    Ok(tedge_config
+        .query_optional(C8yTextMeasurementFallbackSetting)?
+        .unwrap_or_default())
+}
+
 fn create_directories(config_dir: &str) -> Result<(), anyhow::Error> {
     create_directory_with_user_group(
         &format!("{config_dir}/operations/c8y"),
@@ -110,6 +204,7 @@ fn create_directories(config_dir: &str) -> Result<(), anyhow::Error> {
         "tedge",
         0o644,
     )?;
+    create_deadband_directories(Path::new(config_dir))?;
     Ok(())
 }
 
@@ -0,0 +1,264 @@
+use crate::core::size_threshold::SizeThreshold;
+use batcher::{BatchConfigBuilder, BatchDriver, BatchDriverInput, BatchDriverOutput, Batcher};
+use futures::SinkExt;
+use mqtt_channel::{Message, Topic, UnboundedSender};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tracing::error;
+
+/// Forwarded to Cumulocity by the `measurement/measurements/createBulk` rule of the c8y bridge.
+pub const C8Y_JSON_MQTT_MEASUREMENTS_BULK_TOPIC: &str = "c8y/measurement/measurements/createBulk";
+pub const DEFAULT_BATCH_MAX_SIZE: usize = 100;
+// The measurements are timestamped on reception, so they are only delayed by the channel to the batcher.
+const BATCH_DELIVERY_JITTER: u32 = 200;
+const BATCH_CHANNEL_SIZE: usize = 100;
+
+/// Settings of the measurement batching.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasurementBatchConfig {
+    pub window: Duration,
+    pub max_size: usize,
+}
+
+impl MeasurementBatchConfig {
+    pub fn new(window: Duration) -> Self {
+        MeasurementBatchConfig {
+            window,
+            max_size: DEFAULT_BATCH_MAX_SIZE,
+        }
+    }
+}
+
+/// A translated measurement, waiting to be sent to Cumulocity.
+#[derive(Debug)]
+pub struct C8yMeasurement {
+    sequence: u64,
+    received_at: OffsetDateTime,
+    measurement: Value,
+}
+
+impl batcher::Batchable for C8yMeasurement {
+    // All the measurements received within a window are batched together, even those of the same type.
+    type Key = u64;
+
+    fn key(&self) -> Self::Key {
+        self.sequence
+    }
+
+    fn event_time(&self) -> OffsetDateTime {
+        self.received_at
+    }
+}
+
+/// Groups the measurements received within a window into `{"measurements":[...]}` collections,
+/// published on `c8y/measurement/measurements/createBulk`.
+///
+/// The batches are processed in the background and sent on the output given on spawn.
+#[derive(Debug)]
+pub struct MeasurementBatcher {
+    input: Sender<BatchDriverInput<C8yMeasurement>>,
+    sequence: u64,
+}
+
+impl MeasurementBatcher {
+    pub fn spawn(
+        config: MeasurementBatchConfig,
+        size_threshold: SizeThreshold,
+        output: UnboundedSender<Message>,
+    ) -> Self {
+        let window = u32::try_from(config.window.as_millis()).unwrap_or(u32::MAX);
+        let batch_config = BatchConfigBuilder::new()
+            .event_jitter(window)
+            .delivery_jitter(BATCH_DELIVERY_JITTER)
+            .message_leap_limit(0)
+            .build();
+        let (input, batch_input) = mpsc::channel(BATCH_CHANNEL_SIZE);
+        let (batch_output, batches) = mpsc::channel(BATCH_CHANNEL_SIZE);
+
+        let driver = BatchDriver::new(Batcher::new(batch_config), batch_input, batch_output);
+        tokio::spawn(async move {
+            if let Err(err) = driver.run().await {
+                error!("Error in measurement batcher: {}", err);
+            }
+        });
+        tokio::spawn(publish_batches(
+            batches,
+            config.max_size.max(1),
+            size_threshold,
+            output,
+        ));
+
+        MeasurementBatcher { input, sequence: 0 }
+    }
+
+    pub async fn add(&mut self, measurement: Value) {
+        self.sequence += 1;
+        let measurement = C8yMeasurement {
+            sequence: self.sequence,
+            received_at: OffsetDateTime::now_utc(),
+            measurement,
+        };
+        if let Err(err) = self.input.send(BatchDriverInput::Event(measurement)).await {
+            error!("Failed to batch a measurement: {}", err);
+        }
+    }
+}
+
+async fn publish_batches(
+    mut batches: Receiver<BatchDriverOutput<C8yMeasurement>>,
+    max_size: usize,
+    size_threshold: SizeThreshold,
+    mut output: UnboundedSender<Message>,
+) {
+    while let Some(BatchDriverOutput::Batch(batch)) = batches.recv().await {
+        for message in batch_messages(batch, max_size, &size_threshold) {
+            let _ = output.send(message).await;
+        }
+    }
+}
+
+/// Split a batch into collections of at most `max_size` measurements,
+/// each small enough to be sent over MQTT.
+fn batch_messages(
+    mut batch: Vec<C8yMeasurement>,
+    max_size: usize,
+    size_threshold: &SizeThreshold,
+) -> Vec<Message> {
+    batch.sort_by_key(|measurement| measurement.sequence);
+    let measurements = batch
+        .into_iter()
+        .map(|C8yMeasurement { measurement, .. }| measurement)
+        .collect();
+    collection_messages(measurements, max_size, size_threshold)
+}
+
+/// Split measurements into collections of at most `max_size` measurements,
+/// each small enough to be sent over MQTT.
+pub fn collection_messages(
+    measurements: Vec<Value>,
+    max_size: usize,
+    size_threshold: &SizeThreshold,
+) -> Vec<Message> {
+    // Leave room for the `{"measurements":[]}` envelope
+    let max_payload_size = size_threshold.0.saturating_sub(20);
+
+    let mut collections = vec![];
+    let mut collection: Vec<Value> = vec![];
+    let mut collection_size = 0;
+    for measurement in measurements {
+        let measurement_size = measurement.to_string().len() + 1;
+        if !collection.is_empty()
+            && (collection.len() >= max_size
+                || collection_size + measurement_size > max_payload_size)
+        {
+            collections.push(std::mem::take(&mut collection));
+            collection_size = 0;
+        }
+        collection_size += measurement_size;
+        collection.push(measurement);
+    }
+    if !collection.is_empty() {
+        collections.push(collection);
+    }
+
+    let topic = Topic::new_unchecked(C8Y_JSON_MQTT_MEASUREMENTS_BULK_TOPIC);
+    collections
+        .into_iter()
+        .map(|measurements| {
+            Message::new(&topic, json!({ "measurements": measurements }).to_string())
+        })
+        .collect()
+}
+
+/// The measurements of a `{"measurements":[...]}` collection.
+pub fn collection_measurements(payload: &str) -> Result<Vec<Value>, serde_json::Error> {
+    #[derive(Deserialize)]
+    struct Collection {
+        measurements: Vec<Value>,
+    }
+
+    Ok(serde_json::from_str::<Collection>(payload)?.measurements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurements(count: u64) -> Vec<C8yMeasurement> {
+        (1..=count)
+            .rev()
+            .map(|sequence| C8yMeasurement {
+                sequence,
+                received_at: OffsetDateTime::now_utc(),
+                measurement: json!({"type": "ThinEdgeMeasurement", "n": {"n": {"value": sequence}}}),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn measurements_are_sent_in_reception_order() {
+        let messages = batch_messages(measurements(3), 10, &SizeThreshold(16184));
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(
+            messages[0].topic.name,
+            "c8y/measurement/measurements/createBulk"
+        );
+        let payload: Value = serde_json::from_str(messages[0].payload_str().unwrap()).unwrap();
+        assert_eq!(
+            payload,
+            json!({"measurements": [
+                {"type": "ThinEdgeMeasurement", "n": {"n": {"value": 1}}},
+                {"type": "ThinEdgeMeasurement", "n": {"n": {"value": 2}}},
+                {"type": "ThinEdgeMeasurement", "n": {"n": {"value": 3}}},
+            ]})
+        );
+    }
+
+    #[test]
+    fn batches_are_split_by_max_size() {
+        let messages = batch_messages(measurements(5), 2, &SizeThreshold(16184));
+
+        let sizes: Vec<usize> = messages
+            .iter()
+            .map(|message| {
+                let payload: Value = serde_json::from_str(message.payload_str().unwrap()).unwrap();
+                payload["measurements"].as_array().unwrap().len()
+            })
+            .collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn batches_are_split_to_fit_in_mqtt_messages() {
+        let messages = batch_messages(measurements(5), 100, &SizeThreshold(150));
+
+        assert!(messages.len() > 1);
+        for message in messages {
+            assert!(message.payload_bytes().len() < 150);
+        }
+    }
+
+    #[tokio::test]
+    async fn measurements_received_within_a_window_are_batched() {
+        let (output, mut batches) = futures::channel::mpsc::unbounded();
+        let config = MeasurementBatchConfig::new(Duration::from_millis(200));
+        let mut batcher = MeasurementBatcher::spawn(config, SizeThreshold(16184), output);
+
+        batcher.add(json!({"type": "ThinEdgeMeasurement"})).await;
+        batcher.add(json!({"type": "ThinEdgeMeasurement"})).await;
+
+        let message = tokio::time::timeout(
+            Duration::from_secs(2),
+            futures::StreamExt::next(&mut batches),
+        )
+        .await
+        .expect("a batch")
+        .expect("a message");
+        let payload: Value = serde_json::from_str(message.payload_str().unwrap()).unwrap();
+        assert_eq!(payload["measurements"].as_array().unwrap().len(), 2);
+    }
+}
@@ -1,7 +1,12 @@
+mod availability;
 pub mod converter;
 pub mod error;
 mod fragments;
+mod health;
+mod inventory;
 pub mod mapper;
+mod measurement_batcher;
+mod service_monitor;
 pub mod topic;
 
 #[cfg(test)]
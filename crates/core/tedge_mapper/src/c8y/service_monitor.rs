@@ -0,0 +1,85 @@
+use super::health::HealthStatus;
+use mqtt_channel::{Message, Topic};
+use std::collections::HashMap;
+
+const SMARTREST_PUBLISH_TOPIC: &str = "c8y/s/us";
+const SERVICE_TYPE: &str = "service";
+
+/// Publishes the health of the tedge components as Cumulocity services of the device.
+///
+/// A `c8y_Service` child is created (SmartREST `102`) the first time the status of a component is received,
+/// then the status of this service is only updated (SmartREST `104`) when it changes.
+#[derive(Debug)]
+pub struct ServiceMonitor {
+    device_name: String,
+    statuses: HashMap<String, HealthStatus>,
+}
+
+impl ServiceMonitor {
+    pub fn new(device_name: impl Into<String>) -> Self {
+        ServiceMonitor {
+            device_name: device_name.into(),
+            statuses: HashMap::new(),
+        }
+    }
+
+    pub fn process_health(
+        &mut self,
+        component: &str,
+        status: Option<HealthStatus>,
+    ) -> Vec<Message> {
+        let status = match status {
+            Some(status) => status,
+            None => return vec![],
+        };
+
+        let service_id = format!("{}_{}", self.device_name, component);
+        match self.statuses.insert(component.to_string(), status) {
+            None => {
+                let topic = Topic::new_unchecked(SMARTREST_PUBLISH_TOPIC);
+                let payload = format!(
+                    "102,{service_id},{SERVICE_TYPE},{component},{}",
+                    status.as_str()
+                );
+                vec![Message::new(&topic, payload)]
+            }
+            Some(previous_status) if previous_status != status => {
+                let topic =
+                    Topic::new_unchecked(&format!("{SMARTREST_PUBLISH_TOPIC}/{service_id}"));
+                vec![Message::new(&topic, format!("104,{}", status.as_str()))]
+            }
+            Some(_) => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_service_is_created_then_updated_on_status_change() {
+        let mut monitor = ServiceMonitor::new("test-device");
+
+        let messages = monitor.process_health("tedge-agent", Some(HealthStatus::Up));
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].topic.name, "c8y/s/us");
+        assert_eq!(
+            messages[0].payload_str().unwrap(),
+            "102,test-device_tedge-agent,service,tedge-agent,up"
+        );
+
+        // Nothing is sent as long as the status doesn't change
+        assert!(monitor
+            .process_health("tedge-agent", Some(HealthStatus::Up))
+            .is_empty());
+
+        let messages = monitor.process_health("tedge-agent", Some(HealthStatus::Down));
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].topic.name, "c8y/s/us/test-device_tedge-agent");
+        assert_eq!(messages[0].payload_str().unwrap(), "104,down");
+
+        // A cleared status is ignored
+        assert!(monitor.process_health("tedge-agent", None).is_empty());
+    }
+}
@@ -13,17 +13,27 @@ use c8y_smartrest::{
     error::SMCumulocityMapperError, operations::Operations,
     smartrest_deserializer::SmartRestJwtResponse,
 };
+use c8y_translator::serializer::TextMeasurementFallback;
+use clock::{Clock, Timestamp};
 
-use mqtt_channel::{Message, Topic};
+use mqtt_channel::{Message, StreamExt, Topic};
 use mqtt_tests::test_mqtt_server::MqttProcessHandler;
 use serde_json::json;
 use serial_test::serial;
-use std::{path::Path, time::Duration};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tempfile::TempDir;
 use test_case::test_case;
+use time::macros::datetime;
 use tokio::task::JoinHandle;
 
-use super::converter::{get_child_id_from_topic, CumulocityConverter};
+use super::{
+    converter::{parse_measurement_topic, CumulocityConverter, ShellCommandConfig},
+    measurement_batcher::MeasurementBatchConfig,
+};
 
 const TEST_TIMEOUT_MS: Duration = Duration::from_millis(5000);
 const MQTT_HOST: &str = "127.0.0.1";
@@ -518,6 +528,50 @@ async fn test_sync_alarms() {
     assert!(converter.convert(&internal_alarm_message).await.is_empty());
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn alarms_with_custom_fragments_are_sent_as_c8y_json() {
+    let mut converter = create_c8y_converter();
+    let alarm_topic = "tedge/alarms/major/temperature_alarm";
+    let alarm_payload =
+        r#"{ "text": "Temperature high", "threshold": 80, "sensor": {"id": "t1"} }"#;
+    let alarm_message = Message::new(&Topic::new_unchecked(alarm_topic), alarm_payload);
+
+    // An alarm with custom fragments, raised while the mapper was down, is synced as any other alarm
+    assert!(converter.convert(&alarm_message).await.is_empty());
+    let sync_messages = converter.sync_messages();
+    assert_eq!(sync_messages, vec![alarm_message.clone()]);
+
+    let messages = converter.convert(&sync_messages[0]).await;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].topic.name, "c8y/alarm/alarms/create");
+    let c8y_alarm: serde_json::Value =
+        serde_json::from_str(messages[0].payload_str().unwrap()).unwrap();
+    assert_json_include!(
+        actual: c8y_alarm,
+        expected: json!({
+            "type": "temperature_alarm",
+            "severity": "MAJOR",
+            "text": "Temperature high",
+            "threshold": 80,
+            "sensor": {"id": "t1"}
+        })
+    );
+
+    // The original payload is persisted for the next sync
+    assert_eq!(
+        messages[1].topic.name,
+        "c8y-internal/alarms/major/temperature_alarm"
+    );
+    assert_eq!(messages[1].payload_str().unwrap(), alarm_payload);
+
+    // The alarm is cleared using SmartREST, as any other alarm
+    let clear_message = Message::new(&Topic::new_unchecked(alarm_topic), "");
+    let messages = converter.convert(&clear_message).await;
+    assert_eq!(messages[0].topic.name, "c8y/s/us");
+    assert_eq!(messages[0].payload_str().unwrap(), "306,temperature_alarm");
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 #[serial]
 async fn convert_thin_edge_json_with_child_id() {
@@ -538,17 +592,17 @@ async fn convert_thin_edge_json_with_child_id() {
 
     // Test the first output messages contains SmartREST and C8Y JSON.
     let out_first_messages = converter.convert(&in_message).await;
-    assert_eq!(
-        out_first_messages,
-        vec![
+    assert_messages_eq(
+        &out_first_messages,
+        &[
             expected_smart_rest_message,
-            expected_c8y_json_message.clone()
-        ]
+            expected_c8y_json_message.clone(),
+        ],
     );
 
     // Test the second output messages doesn't contain SmartREST child device creation.
     let out_second_messages = converter.convert(&in_message).await;
-    assert_eq!(out_second_messages, vec![expected_c8y_json_message]);
+    assert_messages_eq(&out_second_messages, &[expected_c8y_json_message]);
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -580,9 +634,9 @@ async fn convert_first_thin_edge_json_invalid_then_valid_with_child_id() {
         &Topic::new_unchecked("c8y/measurement/measurements/create"),
         r#"{"type":"ThinEdgeMeasurement","externalSource":{"externalId":"child1","type":"c8y_Serial"},"temp":{"temp":{"value":1.0}},"time":"2021-11-16T17:45:40.571760714+01:00"}"#,
     );
-    assert_eq!(
-        out_second_messages,
-        vec![expected_smart_rest_message, expected_c8y_json_message]
+    assert_messages_eq(
+        &out_second_messages,
+        &[expected_smart_rest_message, expected_c8y_json_message],
     );
 }
 
@@ -606,12 +660,12 @@ async fn convert_two_thin_edge_json_messages_given_different_child_id() {
         &Topic::new_unchecked("c8y/measurement/measurements/create"),
         r#"{"type":"ThinEdgeMeasurement","externalSource":{"externalId":"child1","type":"c8y_Serial"},"temp":{"temp":{"value":1.0}},"time":"2021-11-16T17:45:40.571760714+01:00"}"#,
     );
-    assert_eq!(
-        out_first_messages,
-        vec![
+    assert_messages_eq(
+        &out_first_messages,
+        &[
             expected_first_smart_rest_message,
-            expected_first_c8y_json_message
-        ]
+            expected_first_c8y_json_message,
+        ],
     );
 
     // Second message from "child2"
@@ -628,24 +682,29 @@ async fn convert_two_thin_edge_json_messages_given_different_child_id() {
         &Topic::new_unchecked("c8y/measurement/measurements/create"),
         r#"{"type":"ThinEdgeMeasurement","externalSource":{"externalId":"child2","type":"c8y_Serial"},"temp":{"temp":{"value":1.0}},"time":"2021-11-16T17:45:40.571760714+01:00"}"#,
     );
-    assert_eq!(
-        out_second_messages,
-        vec![
+    assert_messages_eq(
+        &out_second_messages,
+        &[
             expected_second_smart_rest_message,
-            expected_second_c8y_json_message
-        ]
+            expected_second_c8y_json_message,
+        ],
     );
 }
 
-#[test_case("tedge/measurements/test", Some("test".to_string()); "valid child id")]
-#[test_case("tedge/measurements/", None; "returns an error (empty value)")]
-#[test_case("tedge/measurements", None; "invalid child id (parent topic)")]
-#[test_case("foo/bar", None; "invalid child id (invalid topic)")]
-fn extract_child_id(in_topic: &str, expected_child_id: Option<String>) {
-    match get_child_id_from_topic(in_topic) {
-        Ok(maybe_id) => assert_eq!(maybe_id, expected_child_id),
+#[test_case("tedge/measurements/test", Ok(Some("test")); "valid child id")]
+#[test_case("tedge/measurements/", Err(""); "returns an error (empty value)")]
+#[test_case("tedge/measurements", Ok(None); "parent topic")]
+#[test_case("foo/bar", Err("foo/bar"); "returns an error (invalid topic)")]
+#[test_case("tedge/measurements/cbor", Ok(None); "cbor measurements of the parent")]
+#[test_case("tedge/measurements/cbor/test", Ok(Some("test")); "valid cbor child id")]
+#[test_case("tedge/measurements/cbor/", Err(""); "returns an error (empty cbor value)")]
+#[test_case("tedge/measurements/cbor/meta", Err("meta"); "returns an error (reserved meta id)")]
+#[test_case("tedge/measurements/cbor/cbor", Err("cbor"); "returns an error (reserved cbor id)")]
+fn extract_child_id(in_topic: &str, expected_child_id: Result<Option<&str>, &str>) {
+    match parse_measurement_topic(in_topic) {
+        Ok(topic) => assert_eq!(Ok(topic.device), expected_child_id),
         Err(crate::core::error::ConversionError::InvalidChildId { id }) => {
-            assert_eq!(id, "".to_string())
+            assert_eq!(Err(id.as_str()), expected_child_id)
         }
         _ => {
             panic!("Unexpected error type")
@@ -742,21 +801,8 @@ async fn test_convert_big_measurement() {
     );
     let result = converter.convert(&big_measurement_message).await;
 
-    assert!(result.clone()
-        .into_iter()
-        .nth(0)
-        .unwrap()
-        .payload_str()
-        .unwrap()
-        .contains("The payload {\"temperature0\":0,\"temperature1\":1,\"temperature10\" received on tedge/measurements after translation is"));
-
-    assert!(result
-        .into_iter()
-        .nth(0)
-        .unwrap()
-        .payload_str()
-        .unwrap()
-        .contains("greater than the threshold size of 16384."));
+    // The measurements are sent over HTTP
+    assert!(result.is_empty());
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -772,15 +818,13 @@ async fn test_convert_small_measurement() {
 
     let result = converter.convert(&big_measurement_message).await;
 
-    assert!(result
-        .into_iter()
-        .nth(0)
-        .unwrap()
-        .payload_str()
-        .unwrap()
-        .contains(
-            "{\"type\":\"ThinEdgeMeasurement\",\"temperature0\":{\"temperature0\":{\"value\":0.0}}"
-        ));
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(result[0].payload_str().unwrap()).unwrap(),
+        expected: json!({
+            "type": "ThinEdgeMeasurement",
+            "temperature0": {"temperature0": {"value": 0.0}}
+        })
+    );
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -796,21 +840,12 @@ async fn test_convert_big_measurement_for_child_device() {
 
     let result = converter.convert(&big_measurement_message).await;
 
-    assert!(result.clone()
-        .into_iter()
-        .nth(0)
-        .unwrap()
-        .payload_str()
-        .unwrap()
-        .contains("The payload {\"temperature0\":0,\"temperature1\":1,\"temperature10\" received on tedge/measurements/child1 after translation is"));
-
-    assert!(result
-        .into_iter()
-        .nth(0)
-        .unwrap()
-        .payload_str()
-        .unwrap()
-        .contains("greater than the threshold size of 16384."));
+    // Only the child device is created over MQTT, the measurements being sent over HTTP
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+        result[0].payload_str().unwrap(),
+        "101,child1,child1,thin-edge.io-child"
+    );
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -834,15 +869,640 @@ async fn test_convert_small_measurement_for_child_device() {
         .unwrap()
         .contains("101,child1,child1,thin-edge.io-child"));
 
-    assert!(result.clone()
-        .into_iter()
-        .nth(1)
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(result[1].payload_str().unwrap()).unwrap(),
+        expected: json!({
+            "type": "ThinEdgeMeasurement",
+            "externalSource": {"externalId": "child1", "type": "c8y_Serial"},
+            "temperature0": {"temperature0": {"value": 0.0}}
+        })
+    );
+}
+
+#[test]
+fn operations_are_reloaded_on_file_events() {
+    let ops_dir = TempDir::new().unwrap();
+    let mut converter = create_c8y_converter().with_operations_dir(ops_dir.path().to_path_buf());
+
+    // A new operation is added
+    let op_path = ops_dir.path().join("c8y_Command");
+    std::fs::write(
+        &op_path,
+        r#"[exec]
+            topic = "c8y/s/ds/custom"
+            command = "echo"
+            on_message = "511""#,
+    )
+    .unwrap();
+    let messages = converter.file_event_messages(&op_path);
+    assert_eq!(messages[0].topic, Topic::new_unchecked("c8y/s/us"));
+    assert_eq!(messages[0].payload_str().unwrap(), "114,c8y_Command\n");
+    assert!(converter
+        .get_in_topic_filter()
+        .accept_topic(&Topic::new_unchecked("c8y/s/ds/custom")));
+
+    // The temporary files of an editor are ignored
+    let swap_path = ops_dir.path().join(".c8y_Command.swp");
+    std::fs::write(&swap_path, "binary content").unwrap();
+    assert!(converter.file_event_messages(&swap_path).is_empty());
+    std::fs::remove_file(&swap_path).unwrap();
+    assert!(converter.file_event_messages(&swap_path).is_empty());
+
+    // The operation is removed
+    std::fs::remove_file(&op_path).unwrap();
+    let messages = converter.file_event_messages(&op_path);
+    assert_eq!(messages[0].payload_str().unwrap(), "114\n");
+    assert!(!converter
+        .get_in_topic_filter()
+        .accept_topic(&Topic::new_unchecked("c8y/s/ds/custom")));
+}
+
+#[test]
+fn removing_an_operation_keeps_the_default_subscriptions() {
+    let ops_dir = TempDir::new().unwrap();
+    let mut converter = create_c8y_converter().with_operations_dir(ops_dir.path().to_path_buf());
+
+    let op_path = ops_dir.path().join("c8y_Custom");
+    std::fs::write(
+        &op_path,
+        r#"[exec]
+            topic = "c8y/s/ds"
+            command = "echo"
+            on_message = "511""#,
+    )
+    .unwrap();
+    converter.file_event_messages(&op_path);
+    std::fs::remove_file(&op_path).unwrap();
+    converter.file_event_messages(&op_path);
+
+    assert!(converter
+        .get_in_topic_filter()
+        .accept_topic(&Topic::new_unchecked("c8y/s/ds")));
+}
+
+#[tokio::test]
+async fn locations_are_mapped_to_position_updates_and_events() {
+    let mut converter = create_c8y_converter();
+
+    let location = Message::new(
+        &Topic::new_unchecked("tedge/location"),
+        r#"{"lat": 52.52, "lng": 13.405, "time": "2021-04-23T19:00:00+05:00"}"#,
+    );
+    let messages = converter.convert(&location).await;
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].topic.name,
+        "c8y/inventory/managedObjects/update/test-device"
+    );
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(messages[0].payload_str().unwrap()).unwrap(),
+        expected: json!({"c8y_Position": {"lat": 52.52, "lng": 13.405}})
+    );
+    assert_eq!(messages[1].topic.name, "c8y/event/events/create");
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(messages[1].payload_str().unwrap()).unwrap(),
+        expected: json!({
+            "type": "c8y_LocationUpdate",
+            "time": "2021-04-23T19:00:00+05:00",
+            "c8y_Position": {"lat": 52.52, "lng": 13.405}
+        })
+    );
+}
+
+#[tokio::test]
+async fn child_locations_are_mapped_to_the_child_device() {
+    let mut converter = create_c8y_converter();
+
+    let location = Message::new(
+        &Topic::new_unchecked("tedge/location/child1"),
+        r#"{"lat": 52.52, "lng": 13.405, "accuracy": 10}"#,
+    );
+    let messages = converter.convert(&location).await;
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[0].payload_str().unwrap(),
+        "101,child1,child1,thin-edge.io-child"
+    );
+    assert_eq!(
+        messages[1].topic.name,
+        "c8y/inventory/managedObjects/update/child1"
+    );
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(messages[2].payload_str().unwrap()).unwrap(),
+        expected: json!({
+            "type": "c8y_LocationUpdate",
+            "externalSource": {"externalId": "child1", "type": "c8y_Serial"},
+        })
+    );
+
+    // The child device is only created once
+    assert_eq!(converter.convert(&location).await.len(), 2);
+}
+
+#[tokio::test]
+async fn invalid_locations_are_rejected() {
+    let mut converter = create_c8y_converter();
+
+    let location = Message::new(&Topic::new_unchecked("tedge/location"), r#"{"lat": 52.52}"#);
+    let messages = converter.convert(&location).await;
+
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].topic.name, "tedge/errors");
+}
+
+#[tokio::test]
+async fn measurements_are_sent_with_their_type_and_units() {
+    let mut converter = create_c8y_converter();
+
+    let units = Message::new(
+        &Topic::new_unchecked("tedge/measurements/meta"),
+        r#"{"temperature": "°C", "current": {"L1": "A"}}"#,
+    );
+    assert!(converter.convert(&units).await.is_empty());
+
+    let measurement = Message::new(
+        &Topic::new_unchecked("tedge/measurements"),
+        r#"{"type": "c8y_Environment", "temperature": 23, "current": {"L1": 9.5, "L2": 10}}"#,
+    );
+    let messages = converter.convert(&measurement).await;
+
+    assert_eq!(messages.len(), 1);
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(messages[0].payload_str().unwrap()).unwrap(),
+        expected: json!({
+            "type": "c8y_Environment",
+            "temperature": {"temperature": {"value": 23.0, "unit": "°C"}},
+            "current": {"L1": {"value": 9.5, "unit": "A"}, "L2": {"value": 10.0}}
+        })
+    );
+
+    // Invalid units are reported as errors
+    let invalid_units = Message::new(
+        &Topic::new_unchecked("tedge/measurements/meta"),
+        r#"{"temperature": 42}"#,
+    );
+    let messages = converter.convert(&invalid_units).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].topic.name, "tedge/errors");
+}
+
+#[tokio::test]
+async fn text_and_bool_measurements_are_sent_as_configured() {
+    let measurement = Message::new(
+        &Topic::new_unchecked("tedge/measurements"),
+        r#"{"status": "running", "door_open": true, "temperature": 23}"#,
+    );
+
+    let mut converter = create_c8y_converter();
+    let messages = converter.convert(&measurement).await;
+    let payload: serde_json::Value =
+        serde_json::from_str(messages[0].payload_str().unwrap()).unwrap();
+    assert_json_include!(
+        actual: payload,
+        expected: json!({
+            "door_open": {"door_open": {"value": 1.0}},
+            "temperature": {"temperature": {"value": 23.0}}
+        })
+    );
+    assert!(payload.get("status").is_none());
+
+    let mut converter =
+        create_c8y_converter().with_text_measurement_fallback(TextMeasurementFallback::Fragment);
+    let messages = converter.convert(&measurement).await;
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(messages[0].payload_str().unwrap()).unwrap(),
+        expected: json!({
+            "status": "running",
+            "door_open": {"door_open": {"value": 1.0}}
+        })
+    );
+}
+
+#[tokio::test]
+async fn arrays_of_measurements_are_sent_as_collections() {
+    let mut converter = create_c8y_converter();
+
+    let measurements = Message::new(
+        &Topic::new_unchecked("tedge/measurements/child1"),
+        r#"[
+            {"time": "2021-04-23T19:00:00+05:00", "temperature": 23},
+            {"time": "2021-04-23T19:00:01+05:00", "temperature": 24}
+        ]"#,
+    );
+    let messages = converter.convert(&measurements).await;
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].payload_str().unwrap(),
+        "101,child1,child1,thin-edge.io-child"
+    );
+    assert_eq!(
+        messages[1].topic.name,
+        "c8y/measurement/measurements/createBulk"
+    );
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(messages[1].payload_str().unwrap()).unwrap(),
+        expected: json!({"measurements": [
+            {
+                "externalSource": {"externalId": "child1", "type": "c8y_Serial"},
+                "time": "2021-04-23T19:00:00+05:00",
+                "temperature": {"temperature": {"value": 23.0}}
+            },
+            {
+                "externalSource": {"externalId": "child1", "type": "c8y_Serial"},
+                "time": "2021-04-23T19:00:01+05:00",
+                "temperature": {"temperature": {"value": 24.0}}
+            }
+        ]})
+    );
+}
+
+#[tokio::test]
+async fn cbor_measurements_are_translated() {
+    let mut converter = create_c8y_converter();
+
+    let payload = thin_edge_json::cbor::to_vec(&json!({
+        "time": "2021-04-23T19:00:00+05:00",
+        "temperature": 23,
+        "current": {"L1": 9.5, "L2": 10.3}
+    }))
+    .unwrap();
+    let measurements = Message::new(
+        &Topic::new_unchecked("tedge/measurements/cbor/child1"),
+        payload,
+    );
+    let messages = converter.convert(&measurements).await;
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].payload_str().unwrap(),
+        "101,child1,child1,thin-edge.io-child"
+    );
+    assert_eq!(
+        messages[1].topic.name,
+        "c8y/measurement/measurements/create"
+    );
+    assert_json_include!(
+        actual: serde_json::from_str::<serde_json::Value>(messages[1].payload_str().unwrap()).unwrap(),
+        expected: json!({
+            "externalSource": {"externalId": "child1", "type": "c8y_Serial"},
+            "time": "2021-04-23T19:00:00+05:00",
+            "temperature": {"temperature": {"value": 23.0}},
+            "current": {"L1": {"value": 9.5}, "L2": {"value": 10.3}}
+        })
+    );
+}
+
+#[tokio::test]
+async fn invalid_cbor_measurements_are_reported() {
+    let mut converter = create_c8y_converter();
+
+    let measurements = Message::new(
+        &Topic::new_unchecked("tedge/measurements/cbor"),
+        r#"{"temperature": 23}"#,
+    );
+    let messages = converter.convert(&measurements).await;
+
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].topic.name, "tedge/errors");
+    assert!(messages[0].payload_str().unwrap().contains("Invalid CBOR"));
+}
+
+#[tokio::test]
+async fn large_arrays_of_measurements_are_split() {
+    let mut converter = create_c8y_converter();
+
+    let samples: Vec<String> = (0..1000)
+        .map(|i| format!(r#"{{"temperature": {i}}}"#))
+        .collect();
+    let measurements = Message::new(
+        &Topic::new_unchecked("tedge/measurements"),
+        format!("[{}]", samples.join(",")),
+    );
+    let messages = converter.convert(&measurements).await;
+
+    assert!(messages.len() > 1);
+    let mut count = 0;
+    for message in messages {
+        assert_eq!(
+            message.topic.name,
+            "c8y/measurement/measurements/createBulk"
+        );
+        assert!(message.payload_bytes().len() < 16 * 1024);
+        let payload: serde_json::Value =
+            serde_json::from_str(message.payload_str().unwrap()).unwrap();
+        count += payload["measurements"].as_array().unwrap().len();
+    }
+    assert_eq!(count, 1000);
+}
+
+#[tokio::test]
+async fn heartbeats_are_sent_when_there_is_no_traffic() {
+    let converter = create_c8y_converter();
+    assert_eq!(converter.tick_interval(), None);
+
+    let clock = TestClock::new();
+    let mut converter = converter
+        .with_availability_interval(Some(Duration::from_secs(400)), Box::new(clock.clone()));
+    assert_eq!(converter.tick_interval(), Some(Duration::from_secs(200)));
+
+    // The required availability is set on startup
+    let init_messages = converter.init_messages();
+    assert!(init_messages
+        .iter()
+        .any(|msg| msg.topic.name == "c8y/s/us" && msg.payload_str().unwrap() == "117,7"));
+
+    // No heartbeat is sent as long as some data is sent to Cumulocity
+    clock.advance(150);
+    let measurement = Message::new(
+        &Topic::new_unchecked("tedge/measurements"),
+        r#"{"temperature": 23}"#,
+    );
+    assert!(!converter.convert(&measurement).await.is_empty());
+    clock.advance(200);
+    assert!(converter.tick_messages().is_empty());
+
+    clock.advance(50);
+    let tick_messages = converter.tick_messages();
+    assert_eq!(tick_messages.len(), 1);
+    assert_eq!(tick_messages[0].payload_str().unwrap(), "117,7");
+
+    // Nor while a tedge component is down
+    let down = Message::new(
+        &Topic::new_unchecked("tedge/health/tedge-agent"),
+        r#"{"status":"down"}"#,
+    );
+    let messages = converter.convert(&down).await;
+    assert_eq!(
+        messages[0].payload_str().unwrap(),
+        "102,test-device_tedge-agent,service,tedge-agent,down"
+    );
+    clock.advance(400);
+    assert!(converter.tick_messages().is_empty());
+
+    let up = Message::new(
+        &Topic::new_unchecked("tedge/health/tedge-agent"),
+        r#"{"status":"up"}"#,
+    );
+    let messages = converter.convert(&up).await;
+    assert_eq!(messages[0].topic.name, "c8y/s/us/test-device_tedge-agent");
+    assert_eq!(messages[0].payload_str().unwrap(), "104,up");
+    clock.advance(400);
+    assert_eq!(converter.tick_messages().len(), 1);
+}
+
+#[tokio::test]
+async fn shell_commands_are_only_run_when_enabled() {
+    let mut converter = create_c8y_converter();
+    let command_request = Message::new(
+        &Topic::new_unchecked("c8y/s/ds"),
+        "511,test-device,echo hello",
+    );
+
+    // By default, c8y_Command is neither supported nor executed
+    let init_messages = converter.init_messages();
+    assert!(init_messages
+        .iter()
+        .all(|msg| !msg.payload_str().unwrap().contains("c8y_Command")));
+    assert!(converter.convert(&command_request).await.is_empty());
+
+    // Once enabled, c8y_Command is declared as supported and the commands are executed
+    let mut converter = converter.with_shell_command(Some(ShellCommandConfig::default()));
+    let mut outcomes = converter.take_async_messages().unwrap();
+
+    let init_messages = converter.init_messages();
+    assert!(init_messages
+        .iter()
+        .any(|msg| msg.payload_str().unwrap() == "114,c8y_Command\n"));
+
+    let messages = converter.convert(&command_request).await;
+    assert_eq!(messages[0].payload_str().unwrap(), "501,c8y_Command");
+
+    let outcome = tokio::time::timeout(TEST_TIMEOUT_MS, outcomes.next())
+        .await
         .unwrap()
+        .unwrap();
+    assert_eq!(outcome.payload_str().unwrap(), "503,c8y_Command,\"hello\"");
+}
+
+#[tokio::test]
+async fn measurements_are_batched_when_enabled() {
+    let config = MeasurementBatchConfig::new(Duration::from_millis(200));
+    let mut converter = create_c8y_converter().with_measurement_batching(Some(config));
+    let mut batches = converter.take_async_messages().unwrap();
+
+    let measurement = Message::new(
+        &Topic::new_unchecked("tedge/measurements"),
+        r#"{"temperature": 21.3}"#,
+    );
+    let child_measurement = Message::new(
+        &Topic::new_unchecked("tedge/measurements/child1"),
+        r#"{"temperature": 18.2}"#,
+    );
+
+    // The measurements are not sent right away
+    assert!(converter.convert(&measurement).await.is_empty());
+    let messages = converter.convert(&child_measurement).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0].payload_str().unwrap(),
+        "101,child1,child1,thin-edge.io-child"
+    );
+
+    // But in a single collection, once the batching window is over
+    let batch = tokio::time::timeout(TEST_TIMEOUT_MS, batches.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(batch.topic.name, "c8y/measurement/measurements/createBulk");
+    let batch: serde_json::Value = serde_json::from_str(batch.payload_str().unwrap()).unwrap();
+    assert_json_include!(
+        actual: batch,
+        expected: json!({"measurements": [
+            {"type": "ThinEdgeMeasurement", "temperature": {"temperature": {"value": 21.3}}},
+            {
+                "type": "ThinEdgeMeasurement",
+                "externalSource": {"externalId": "child1", "type": "c8y_Serial"},
+                "temperature": {"temperature": {"value": 18.2}}
+            },
+        ]})
+    );
+}
+
+#[tokio::test]
+async fn firmware_requests_are_forwarded_to_the_agent() {
+    let mut converter = create_c8y_converter();
+    let firmware_request = Message::new(
+        &Topic::new_unchecked("c8y/s/ds"),
+        "515,test-device,bootloader,1.0,https://test.c8y.io/inventory/binaries/123",
+    );
+
+    let messages = converter.convert(&firmware_request).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].topic.name, "tedge/commands/req/firmware/update");
+
+    let request: serde_json::Value =
+        serde_json::from_str(messages[0].payload_str().unwrap()).unwrap();
+    assert_json_include!(
+        actual: request,
+        expected: json!({
+            "name": "bootloader",
+            "version": "1.0",
+            "url": "https://test.c8y.io/inventory/binaries/123",
+            "auth": { "bearer": "fake-token" }
+        })
+    );
+}
+
+#[test_case(r#"{"id":"123","status":"executing"}"#, "501,c8y_Firmware\n")]
+#[test_case(r#"{"id":"123","status":"successful"}"#, "503,c8y_Firmware,\n")]
+#[test_case(
+    r#"{"id":"123","status":"failed","reason":"Failed to verify firmware"}"#,
+    "502,c8y_Firmware,\"Failed to verify firmware\"\n"
+)]
+#[tokio::test]
+async fn firmware_responses_are_mapped_to_operation_status(response: &str, expected: &str) {
+    let mut converter = create_c8y_converter();
+    let firmware_response = Message::new(
+        &Topic::new_unchecked("tedge/commands/res/firmware/update"),
+        response,
+    );
+
+    let messages = converter.convert(&firmware_response).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].topic.name, "c8y/s/us");
+    assert_eq!(messages[0].payload_str().unwrap(), expected);
+}
+
+#[tokio::test]
+async fn the_firmware_inventory_is_updated_once_installed() {
+    let mut converter = create_c8y_converter();
+    let firmware_response = Message::new(
+        &Topic::new_unchecked("tedge/commands/res/firmware/update"),
+        r#"{"id":"123","status":"successful","name":"bootloader","version":"1.0","url":"https://example.com/bootloader.bin"}"#,
+    );
+
+    let messages = converter.convert(&firmware_response).await;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].payload_str().unwrap(), "503,c8y_Firmware,\n");
+    assert_eq!(
+        messages[1].topic.name,
+        "c8y/inventory/managedObjects/update/test-device"
+    );
+    let inventory: serde_json::Value =
+        serde_json::from_str(messages[1].payload_str().unwrap()).unwrap();
+    assert_eq!(
+        inventory,
+        json!({
+            "c8y_Firmware": {
+                "name": "bootloader",
+                "version": "1.0",
+                "url": "https://example.com/bootloader.bin"
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn device_profile_operations_are_forwarded_to_the_agent() {
+    let mut converter = create_c8y_converter();
+    let device_profile_operation = Message::new(
+        &Topic::new_unchecked("c8y/devicecontrol/notifications"),
+        json!({
+            "id": "123",
+            "c8y_DeviceProfile": {
+                "firmware": {"name": "bootloader", "version": "1.0", "url": "https://test.c8y.io/inventory/binaries/123"},
+                "software": [{"name": "collectd", "version": "5.12::apt", "url": "", "action": "install"}],
+                "configuration": [{"name": "mosquitto", "type": "mosquitto.conf", "url": "https://test.c8y.io/inventory/binaries/456"}]
+            }
+        })
+        .to_string(),
+    );
+
+    let messages = converter.convert(&device_profile_operation).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].topic.name, "tedge/commands/req/device/profile");
+
+    let request: serde_json::Value =
+        serde_json::from_str(messages[0].payload_str().unwrap()).unwrap();
+    assert_json_include!(
+        actual: request,
+        expected: json!({
+            "firmware": {
+                "name": "bootloader",
+                "version": "1.0",
+                "url": "https://test.c8y.io/inventory/binaries/123",
+                "auth": { "bearer": "fake-token" }
+            },
+            "software": [{
+                "type": "apt",
+                "modules": [{"name": "collectd", "version": "5.12", "action": "install"}]
+            }],
+            "configuration": [{
+                "type": "mosquitto.conf",
+                "url": "https://test.c8y.io/inventory/binaries/456"
+            }]
+        })
+    );
+}
+
+#[tokio::test]
+async fn other_json_operations_are_ignored() {
+    let mut converter = create_c8y_converter();
+    let restart_operation = Message::new(
+        &Topic::new_unchecked("c8y/devicecontrol/notifications"),
+        json!({"id": "123", "c8y_Restart": {}}).to_string(),
+    );
+
+    let messages = converter.convert(&restart_operation).await;
+    assert!(messages.is_empty());
+}
+
+#[tokio::test]
+async fn invalid_device_profile_operations_are_rejected() {
+    let mut converter = create_c8y_converter();
+    let device_profile_operation = Message::new(
+        &Topic::new_unchecked("c8y/devicecontrol/notifications"),
+        json!({
+            "c8y_DeviceProfile": {
+                "software": [{"name": "collectd", "version": "5.12", "action": "upgrade"}]
+            }
+        })
+        .to_string(),
+    );
+
+    let messages = converter.convert(&device_profile_operation).await;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].payload_str().unwrap(),
+        "501,c8y_DeviceProfile\n"
+    );
+    assert!(messages[1]
         .payload_str()
         .unwrap()
-        .contains(
-            "{\"type\":\"ThinEdgeMeasurement\",\"externalSource\":{\"externalId\":\"child1\",\"type\":\"c8y_Serial\"},\"temperature0\":{\"temperature0\":{\"value\":0.0}},"
-        ));
+        .starts_with("502,c8y_DeviceProfile,"));
+}
+
+#[test_case(r#"{"id":"123","status":"executing"}"#, "501,c8y_DeviceProfile\n")]
+#[test_case(r#"{"id":"123","status":"successful"}"#, "503,c8y_DeviceProfile,\n")]
+#[test_case(
+    r#"{"id":"123","status":"failed","reason":"Failed to update the software"}"#,
+    "502,c8y_DeviceProfile,\"Failed to update the software\"\n"
+)]
+#[tokio::test]
+async fn device_profile_responses_are_mapped_to_operation_status(response: &str, expected: &str) {
+    let mut converter = create_c8y_converter();
+    let device_profile_response = Message::new(
+        &Topic::new_unchecked("tedge/commands/res/device/profile"),
+        response,
+    );
+
+    let messages = converter.convert(&device_profile_response).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].topic.name, "c8y/s/us");
+    assert_eq!(messages[0].payload_str().unwrap(), expected);
 }
 
 fn create_packet(size: usize) -> String {
@@ -855,6 +1515,21 @@ fn create_packet(size: usize) -> String {
     buffer
 }
 
+/// Compare messages, the JSON payloads being compared regardless of the order of their fields.
+fn assert_messages_eq(actual: &[Message], expected: &[Message]) {
+    assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert_eq!(actual.topic, expected.topic);
+        match (
+            serde_json::from_slice::<serde_json::Value>(actual.payload_bytes()),
+            serde_json::from_slice::<serde_json::Value>(expected.payload_bytes()),
+        ) {
+            (Ok(actual_json), Ok(expected_json)) => assert_eq!(actual_json, expected_json),
+            _ => assert_eq!(actual.payload_str().ok(), expected.payload_str().ok()),
+        }
+    }
+}
+
 fn create_thin_edge_measurement(size: usize) -> String {
     let mut map = serde_json::Map::new();
     let data = r#""temperature":25"#;
@@ -904,6 +1579,13 @@ impl C8YHttpProxy for FakeC8YHttpProxy {
         Ok("123".into())
     }
 
+    async fn send_measurements(
+        &mut self,
+        _c8y_measurements: serde_json::Value,
+    ) -> Result<(), SMCumulocityMapperError> {
+        Ok(())
+    }
+
     async fn upload_config_file(
         &mut self,
         _config_path: &Path,
@@ -929,6 +1611,26 @@ async fn start_c8y_mapper(mqtt_port: u16) -> Result<JoinHandle<()>, anyhow::Erro
     Ok(mapper_task)
 }
 
+#[derive(Clone)]
+struct TestClock(Arc<Mutex<Timestamp>>);
+
+impl TestClock {
+    fn new() -> Self {
+        TestClock(Arc::new(Mutex::new(datetime!(2022-05-10 12:00:00 UTC))))
+    }
+
+    fn advance(&self, seconds: i64) {
+        let mut now = self.0.lock().unwrap();
+        *now += time::Duration::seconds(seconds);
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Timestamp {
+        *self.0.lock().unwrap()
+    }
+}
+
 fn create_c8y_converter() -> CumulocityConverter<FakeC8YHttpProxy> {
     let size_threshold = SizeThreshold(16 * 1024);
     let device_name = "test-device".into();
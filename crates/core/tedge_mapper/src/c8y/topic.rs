@@ -7,6 +7,7 @@ use mqtt_channel::Topic;
 pub enum C8yTopic {
     SmartRestRequest,
     SmartRestResponse,
+    OperationNotifications,
     OperationTopic(String),
 }
 
@@ -15,6 +16,7 @@ impl C8yTopic {
         match self {
             Self::SmartRestRequest => r#"c8y/s/ds"#,
             Self::SmartRestResponse => r#"c8y/s/us"#,
+            Self::OperationNotifications => r#"c8y/devicecontrol/notifications"#,
             Self::OperationTopic(name) => name.as_str(),
         }
     }
@@ -31,6 +33,7 @@ impl TryFrom<String> for C8yTopic {
         match value.as_str() {
             r#"c8y/s/ds"# => Ok(C8yTopic::SmartRestRequest),
             r#"c8y/s/us"# => Ok(C8yTopic::SmartRestResponse),
+            r#"c8y/devicecontrol/notifications"# => Ok(C8yTopic::OperationNotifications),
             topic_name => {
                 if topic_name[..3].contains("c8y") {
                     Ok(C8yTopic::OperationTopic(topic_name.to_string()))
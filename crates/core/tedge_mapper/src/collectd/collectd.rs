@@ -3,6 +3,8 @@ use mqtt_channel::Message;
 use thin_edge_json::measurement::MeasurementVisitor;
 use time::{Duration, OffsetDateTime};
 
+use super::filter::MetricFilter;
+
 #[derive(Debug)]
 pub struct CollectdMessage {
     pub metric_group_key: String,
@@ -53,7 +55,11 @@ impl CollectdMessage {
         }
     }
 
-    pub fn parse_from(mqtt_message: &Message) -> Result<Vec<Self>, CollectdError> {
+    /// Parse the measurements of a collectd message, ignoring those rejected by the filter.
+    pub fn parse_from(
+        mqtt_message: &Message,
+        filter: &MetricFilter,
+    ) -> Result<Vec<Self>, CollectdError> {
         let topic = mqtt_message.topic.name.as_str();
         let collectd_topic = match CollectdTopic::from_str(topic) {
             Ok(collectd_topic) => collectd_topic,
@@ -62,6 +68,11 @@ impl CollectdMessage {
             }
         };
 
+        // The filtered-out metrics are dropped before being parsed
+        if !filter.accept(collectd_topic.metric_group_key, collectd_topic.metric_key) {
+            return Ok(vec![]);
+        }
+
         let payload = mqtt_message.payload_str().map_err(|_err| {
             CollectdError::NonUTF8MeasurementPayload(mqtt_message.payload_bytes().into())
         })?;
@@ -200,7 +211,8 @@ mod tests {
         let topic = Topic::new_unchecked("collectd/localhost/temperature/value");
         let mqtt_message = Message::new(&topic, "123456789:32.5");
 
-        let collectd_message = CollectdMessage::parse_from(&mqtt_message).unwrap();
+        let collectd_message =
+            CollectdMessage::parse_from(&mqtt_message, &MetricFilter::default()).unwrap();
 
         let CollectdMessage {
             metric_group_key,
@@ -220,7 +232,8 @@ mod tests {
         let topic = Topic::new("collectd/localhost/temperature/value").unwrap();
         let mqtt_message = Message::new(&topic, "123456789:32.5:45.2");
 
-        let collectd_message = CollectdMessage::parse_from(&mqtt_message).unwrap();
+        let collectd_message =
+            CollectdMessage::parse_from(&mqtt_message, &MetricFilter::default()).unwrap();
 
         let CollectdMessage {
             metric_group_key,
@@ -251,7 +264,8 @@ mod tests {
         let topic = Topic::new("collectd/localhost/temperature/value").unwrap();
         let mqtt_message = Message::new(&topic, "123456789.125:32.5\u{0}");
 
-        let collectd_message = CollectdMessage::parse_from(&mqtt_message).unwrap();
+        let collectd_message =
+            CollectdMessage::parse_from(&mqtt_message, &MetricFilter::default()).unwrap();
 
         let CollectdMessage {
             metric_group_key,
@@ -266,12 +280,34 @@ mod tests {
         assert_eq!(*metric_value, 32.5);
     }
 
+    #[test]
+    fn filtered_out_collectd_messages_are_dropped() {
+        let filter =
+            MetricFilter::new(vec!["cpu/*".into()], vec!["cpu/percent-idle".into()]).unwrap();
+        let message = |topic: &str| Message::new(&Topic::new_unchecked(topic), "123456789:32.5");
+
+        let accepted =
+            CollectdMessage::parse_from(&message("collectd/host/cpu/percent-active"), &filter)
+                .unwrap();
+        let denied =
+            CollectdMessage::parse_from(&message("collectd/host/cpu/percent-idle"), &filter)
+                .unwrap();
+        let not_allowed =
+            CollectdMessage::parse_from(&message("collectd/host/memory/percent-used"), &filter)
+                .unwrap();
+
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].key(), "cpu/percent-active");
+        assert!(denied.is_empty());
+        assert!(not_allowed.is_empty());
+    }
+
     #[test]
     fn invalid_collectd_message_topic() {
         let topic = Topic::new("collectd/less/level").unwrap();
         let mqtt_message = Message::new(&topic, "123456789:32.5");
 
-        let result = CollectdMessage::parse_from(&mqtt_message);
+        let result = CollectdMessage::parse_from(&mqtt_message, &MetricFilter::default());
 
         assert_matches!(result, Err(CollectdError::InvalidMeasurementTopic(_)));
     }
@@ -281,7 +317,8 @@ mod tests {
         let topic = Topic::new("collectd/host/group/key").unwrap();
         let invalid_collectd_message = Message::new(&topic, "123456789");
 
-        let result = CollectdMessage::parse_from(&invalid_collectd_message);
+        let result =
+            CollectdMessage::parse_from(&invalid_collectd_message, &MetricFilter::default());
 
         assert_matches!(result, Err(CollectdError::InvalidMeasurementPayload(_, _)));
     }
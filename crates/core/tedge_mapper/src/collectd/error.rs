@@ -17,6 +17,13 @@ pub enum DeviceMonitorError {
         #[from] thin_edge_json::serialize::ThinEdgeJsonSerializationError,
     ),
 
+    #[error("Invalid collectd metric pattern {pattern:?}: {error}")]
+    InvalidMetricPattern {
+        pattern: String,
+        #[source]
+        error: glob::PatternError,
+    },
+
     #[error(transparent)]
     FromBatchingError(#[from] SendError<thin_edge_json::group::MeasurementGrouper>),
 }
@@ -0,0 +1,74 @@
+use glob::Pattern;
+
+use crate::collectd::error::DeviceMonitorError;
+
+/// Selects the collectd metrics forwarded by the mapper, using glob patterns on `<plugin>/<key>`.
+///
+/// A metric is forwarded if it matches one of the allow patterns, or if there are none,
+/// unless it also matches one of the deny patterns.
+#[derive(Debug, Default)]
+pub struct MetricFilter {
+    allow: Vec<Pattern>,
+    deny: Vec<Pattern>,
+}
+
+impl MetricFilter {
+    pub fn new(allow: Vec<String>, deny: Vec<String>) -> Result<Self, DeviceMonitorError> {
+        Ok(MetricFilter {
+            allow: compile_patterns(allow)?,
+            deny: compile_patterns(deny)?,
+        })
+    }
+
+    pub fn accept(&self, metric_group_key: &str, metric_key: &str) -> bool {
+        let metric = format!("{metric_group_key}/{metric_key}");
+        let allowed =
+            self.allow.is_empty() || self.allow.iter().any(|pattern| pattern.matches(&metric));
+        allowed && !self.deny.iter().any(|pattern| pattern.matches(&metric))
+    }
+}
+
+fn compile_patterns(patterns: Vec<String>) -> Result<Vec<Pattern>, DeviceMonitorError> {
+    patterns
+        .into_iter()
+        .map(|pattern| {
+            Pattern::new(&pattern)
+                .map_err(|error| DeviceMonitorError::InvalidMetricPattern { pattern, error })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use test_case::test_case;
+
+    fn filter(allow: &[&str], deny: &[&str]) -> MetricFilter {
+        let strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+        MetricFilter::new(strings(allow), strings(deny)).unwrap()
+    }
+
+    #[test_case(&[], &[], "cpu/percent-idle", true; "all metrics are accepted by default")]
+    #[test_case(&["cpu/*"], &[], "cpu/percent-idle", true; "allowed metric")]
+    #[test_case(&["cpu/*"], &[], "memory/percent-used", false; "metric not allowed")]
+    #[test_case(&["cpu/*", "memory/*"], &[], "memory/percent-used", true; "metric allowed by any pattern")]
+    #[test_case(&[], &["df-*"], "df-root/percent_bytes-used", false; "a star also matches the separator")]
+    #[test_case(&[], &["interface-lo/*"], "interface-lo/if_octets", false; "denied metric")]
+    #[test_case(&["cpu/*"], &["cpu/*-idle"], "cpu/percent-idle", false; "deny takes precedence")]
+    #[test_case(&["cpu/percent-?dle"], &[], "cpu/percent-idle", true; "single char wildcard")]
+    fn metrics_are_filtered(allow: &[&str], deny: &[&str], metric: &str, accepted: bool) {
+        let (group, key) = metric.split_once('/').unwrap();
+        assert_eq!(filter(allow, deny).accept(group, key), accepted);
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let result = MetricFilter::new(vec!["cpu/[".into()], vec![]);
+
+        assert_matches!(
+            result,
+            Err(DeviceMonitorError::InvalidMetricPattern { pattern, .. }) if pattern == "cpu/["
+        );
+    }
+}
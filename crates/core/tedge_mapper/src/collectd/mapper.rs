@@ -1,12 +1,15 @@
 use std::path::Path;
 
 use crate::{
-    collectd::monitor::{DeviceMonitor, DeviceMonitorConfig},
+    collectd::{
+        filter::MetricFilter,
+        monitor::{DeviceMonitor, DeviceMonitorConfig},
+    },
     core::component::TEdgeComponent,
 };
 use async_trait::async_trait;
-use mqtt_channel::TopicFilter;
-use tedge_config::{ConfigSettingAccessor, MqttBindAddressSetting, MqttPortSetting, TEdgeConfig};
+use mqtt_channel::{Topic, TopicFilter};
+use tedge_config::*;
 use tracing::{info, info_span, Instrument};
 
 const COLLECTD_MAPPER_NAME: &str = "tedge-mapper-collectd";
@@ -25,12 +28,14 @@ impl TEdgeComponent for CollectdMapper {
         COLLECTD_MAPPER_NAME
     }
 
-    async fn init(&self, _cfg_dir: &Path) -> Result<(), anyhow::Error> {
+    async fn init(&self, cfg_dir: &Path) -> Result<(), anyhow::Error> {
         info!("Initialize tedge mapper collectd");
-        self.init_session(TopicFilter::new(
-            DeviceMonitorConfig::default().mqtt_source_topic,
-        )?)
-        .await?;
+        let tedge_config =
+            TEdgeConfigRepository::new(TEdgeConfigLocation::from_custom_root(cfg_dir)).load()?;
+        let source_topic = tedge_config
+            .query_optional(CollectdSourceTopicSetting)?
+            .unwrap_or_else(|| DeviceMonitorConfig::default().mqtt_source_topic);
+        self.init_session(TopicFilter::new(&source_topic)?).await?;
         Ok(())
     }
 
@@ -42,7 +47,7 @@ impl TEdgeComponent for CollectdMapper {
         let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
         let mqtt_host = tedge_config.query(MqttBindAddressSetting)?.to_string();
 
-        let device_monitor_config = DeviceMonitorConfig::default()
+        let device_monitor_config = device_monitor_config(&tedge_config)?
             .with_port(mqtt_port)
             .with_host(mqtt_host);
 
@@ -55,3 +60,38 @@ impl TEdgeComponent for CollectdMapper {
         Ok(())
     }
 }
+
+/// The settings of the `[collectd]` section of tedge.toml, the defaults being used for the unset ones.
+fn device_monitor_config(tedge_config: &TEdgeConfig) -> Result<DeviceMonitorConfig, anyhow::Error> {
+    let mut config = DeviceMonitorConfig::default();
+    if let Some(source_topic) = tedge_config.query_optional(CollectdSourceTopicSetting)? {
+        let _ = TopicFilter::new(&source_topic)?;
+        config = config.with_source_topic(source_topic);
+    }
+    if let Some(target_topic) = tedge_config.query_optional(CollectdTargetTopicSetting)? {
+        let _ = Topic::new(&target_topic)?;
+        config = config.with_target_topic(target_topic);
+    }
+    if let Some(window) = tedge_config.query_optional(CollectdBatchingWindowSetting)? {
+        config = config.with_batching_window(milliseconds(window));
+    }
+    if let Some(delay) = tedge_config.query_optional(CollectdBatchingMaxDelaySetting)? {
+        config = config.with_maximum_message_delay(milliseconds(delay));
+    }
+    if let Some(leap_limit) = tedge_config.query_optional(CollectdBatchingLeapLimitSetting)? {
+        config = config.with_message_leap_limit(milliseconds(leap_limit));
+    }
+
+    let allow = tedge_config
+        .query_optional(CollectdMetricsAllowSetting)?
+        .unwrap_or_default();
+    let deny = tedge_config
+        .query_optional(CollectdMetricsDenySetting)?
+        .unwrap_or_default();
+    let filter = MetricFilter::new(allow.into(), deny.into())?;
+    Ok(config.with_metric_filter(filter))
+}
+
+fn milliseconds(value: Milliseconds) -> u32 {
+    u32::try_from(value.0).unwrap_or(u32::MAX)
+}
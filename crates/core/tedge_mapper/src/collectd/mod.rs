@@ -2,5 +2,6 @@ mod batcher;
 #[allow(clippy::module_inception)]
 mod collectd;
 mod error;
+mod filter;
 pub mod mapper;
 mod monitor;
@@ -5,7 +5,10 @@ use mqtt_channel::{Connection, Message, QoS, SinkExt, StreamExt, Topic, TopicFil
 use serde_json::json;
 use tracing::{error, info, instrument};
 
-use super::{batcher::MessageBatch, collectd::CollectdMessage, error::DeviceMonitorError};
+use super::{
+    batcher::MessageBatch, collectd::CollectdMessage, error::DeviceMonitorError,
+    filter::MetricFilter,
+};
 
 const DEFAULT_HOST: &str = "localhost";
 const DEFAULT_PORT: u16 = 1883;
@@ -24,11 +27,12 @@ pub struct DeviceMonitorConfig {
     host: String,
     port: u16,
     mqtt_client_id: &'static str,
-    pub mqtt_source_topic: &'static str,
-    mqtt_target_topic: &'static str,
+    pub mqtt_source_topic: String,
+    mqtt_target_topic: String,
     batching_window: u32,
     maximum_message_delay: u32,
     message_leap_limit: u32,
+    metric_filter: MetricFilter,
 }
 
 impl Default for DeviceMonitorConfig {
@@ -37,11 +41,12 @@ impl Default for DeviceMonitorConfig {
             host: DEFAULT_HOST.to_string(),
             port: DEFAULT_PORT,
             mqtt_client_id: DEFAULT_MQTT_CLIENT_ID,
-            mqtt_source_topic: DEFAULT_MQTT_SOURCE_TOPIC,
-            mqtt_target_topic: DEFAULT_MQTT_TARGET_TOPIC,
+            mqtt_source_topic: DEFAULT_MQTT_SOURCE_TOPIC.to_string(),
+            mqtt_target_topic: DEFAULT_MQTT_TARGET_TOPIC.to_string(),
             batching_window: DEFAULT_BATCHING_WINDOW,
             maximum_message_delay: DEFAULT_MAXIMUM_MESSAGE_DELAY,
             message_leap_limit: DEFAULT_MESSAGE_LEAP_LIMIT,
+            metric_filter: MetricFilter::default(),
         }
     }
 }
@@ -54,6 +59,48 @@ impl DeviceMonitorConfig {
     pub fn with_host(self, host: String) -> Self {
         Self { host, ..self }
     }
+
+    pub fn with_source_topic(self, mqtt_source_topic: String) -> Self {
+        Self {
+            mqtt_source_topic,
+            ..self
+        }
+    }
+
+    pub fn with_target_topic(self, mqtt_target_topic: String) -> Self {
+        Self {
+            mqtt_target_topic,
+            ..self
+        }
+    }
+
+    pub fn with_batching_window(self, batching_window: u32) -> Self {
+        Self {
+            batching_window,
+            ..self
+        }
+    }
+
+    pub fn with_maximum_message_delay(self, maximum_message_delay: u32) -> Self {
+        Self {
+            maximum_message_delay,
+            ..self
+        }
+    }
+
+    pub fn with_message_leap_limit(self, message_leap_limit: u32) -> Self {
+        Self {
+            message_leap_limit,
+            ..self
+        }
+    }
+
+    pub fn with_metric_filter(self, metric_filter: MetricFilter) -> Self {
+        Self {
+            metric_filter,
+            ..self
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -69,13 +116,13 @@ impl DeviceMonitor {
     }
 
     #[instrument(skip(self), name = "monitor")]
-    pub async fn run(&self) -> Result<(), DeviceMonitorError> {
+    pub async fn run(self) -> Result<(), DeviceMonitorError> {
         let health_check_topics: TopicFilter = vec![COMMON_HEALTH_CHECK_TOPIC, HEALTH_CHECK_TOPIC]
             .try_into()
             .expect("Valid health topics");
         let health_status_topic = Topic::new_unchecked(HEALTH_STATUS_TOPIC);
 
-        let mut input_topic = TopicFilter::new(self.device_monitor_config.mqtt_source_topic)?
+        let mut input_topic = TopicFilter::new(&self.device_monitor_config.mqtt_source_topic)?
             .with_qos(QoS::AtMostOnce);
         input_topic.add_all(health_check_topics.clone());
 
@@ -84,7 +131,11 @@ impl DeviceMonitor {
             self.device_monitor_config.port,
         )
         .with_session_name(self.device_monitor_config.mqtt_client_id)
-        .with_subscriptions(input_topic);
+        .with_subscriptions(input_topic)
+        .with_last_will_message(Message::new(
+            &health_status_topic,
+            json!({"status": "down"}).to_string(),
+        ));
         let mqtt_client = Connection::new(&mqtt_config).await?;
 
         let batch_config = BatchConfigBuilder::new()
@@ -102,6 +153,7 @@ impl DeviceMonitor {
             }
         });
 
+        let metric_filter = self.device_monitor_config.metric_filter;
         let mut collectd_messages = mqtt_client.received;
         let mut output_messages = mqtt_client.published.clone();
         let input_join_handle = tokio::task::spawn(async move {
@@ -115,7 +167,7 @@ impl DeviceMonitor {
                     let health_message = Message::new(&health_status_topic, health_status);
                     let _ = output_messages.send(health_message).await;
                 } else {
-                    match CollectdMessage::parse_from(&message) {
+                    match CollectdMessage::parse_from(&message, &metric_filter) {
                         Ok(collectd_message) => {
                             for msg in collectd_message {
                                 let batch_input = BatchDriverInput::Event(msg);
@@ -137,7 +189,7 @@ impl DeviceMonitor {
             msg_send.send(eof).await
         });
 
-        let output_topic = Topic::new(self.device_monitor_config.mqtt_target_topic)?;
+        let output_topic = Topic::new(&self.device_monitor_config.mqtt_target_topic)?;
         let mut output_messages = mqtt_client.published;
         let output_join_handle = tokio::task::spawn(async move {
             loop {
@@ -0,0 +1,31 @@
+use std::{fs, io, path::Path};
+
+/// Read a configuration file, returning `None` if there is no such file.
+///
+/// A missing file is not an error for the optional configuration files of the mappers,
+/// which fall back to their defaults.
+pub fn read_config_file(path: &Path) -> Result<Option<String>, io::Error> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_missing_file_is_not_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rules.toml");
+        assert_eq!(read_config_file(&path).unwrap(), None);
+
+        fs::write(&path, "[[rules]]").unwrap();
+        assert_eq!(read_config_file(&path).unwrap(), Some("[[rules]]".into()));
+
+        // A directory is not a configuration file
+        assert!(read_config_file(dir.path()).is_err());
+    }
+}
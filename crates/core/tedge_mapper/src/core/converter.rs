@@ -1,6 +1,10 @@
 use async_trait::async_trait;
-use mqtt_channel::{Message, Topic, TopicFilter};
-use std::fmt::Display;
+use mqtt_channel::{Message, Topic, TopicFilter, UnboundedReceiver};
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use tracing::error;
 
 #[derive(Debug)]
@@ -67,6 +71,36 @@ pub trait Converter: Send + Sync {
     fn sync_messages(&mut self) -> Vec<Message> {
         vec![]
     }
+
+    /// The period at which the mapper calls `tick_messages`, if the converter needs to be woken up periodically.
+    fn tick_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// This function will be called by the mapper every `tick_interval`, once the sync window is over.
+    /// The returned messages are published as is, without being converted.
+    /// Typically used to publish data that must be refreshed periodically, independently of the incoming messages.
+    fn tick_messages(&mut self) -> Vec<Message> {
+        vec![]
+    }
+
+    /// The directory watched by the mapper on behalf of the converter, if any.
+    fn watched_directory(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// This function will be called by the mapper each time a file of the `watched_directory` is created, updated or removed.
+    /// The returned messages are published as is, without being converted.
+    /// The converter can update its input topic filter: the mapper then subscribes to the new topics.
+    fn file_event_messages(&mut self, _path: &Path) -> Vec<Message> {
+        vec![]
+    }
+
+    /// The channel of the messages produced asynchronously by the converter, say once a background task completes.
+    /// This channel is taken by the mapper on start, and the messages are published as is, without being converted.
+    fn take_async_messages(&mut self) -> Option<UnboundedReceiver<Message>> {
+        None
+    }
 }
 
 pub fn make_valid_topic_or_panic(topic_name: &str) -> Topic {
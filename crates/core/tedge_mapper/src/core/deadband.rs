@@ -0,0 +1,936 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use clock::{Clock, Timestamp, WallClock};
+use mqtt_channel::{Message, Topic, UnboundedReceiver};
+use serde::{Deserialize, Serialize};
+use tedge_utils::{
+    file::{create_directory_with_user_group, FileError},
+    fs::atomically_write_file_sync,
+};
+use thin_edge_json::{
+    measurement::MeasurementVisitor,
+    serialize::{ThinEdgeJsonSerializationError, ThinEdgeJsonSerializer},
+};
+use time::OffsetDateTime;
+use tracing::warn;
+
+use crate::core::{
+    config::read_config_file,
+    converter::*,
+    error::*,
+    measurements::{is_valid_measurement_path, MeasurementTopic},
+};
+
+const DEADBAND_DIR: &str = "deadband";
+const DEADBAND_FILTERS_FILE: &str = "filters.toml";
+// The state changes at runtime, hence is not stored along the configuration.
+const DEADBAND_STATE_DIR: &str = "/var/tedge/deadband";
+// The period at which the state is saved, unless the wrapped converter has its own tick interval.
+const DEADBAND_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Wrap a converter with the deadband filter defined in `<config-dir>/deadband/filters.toml`, if any.
+///
+/// The last values sent by the mapper are persisted in `/var/tedge/deadband/<mapper-name>.json`,
+/// on each tick of the mapper and on shutdown.
+pub fn with_deadband_filter(
+    converter: Box<dyn Converter<Error = ConversionError>>,
+    config_dir: &Path,
+    mapper_name: &str,
+) -> Result<Box<dyn Converter<Error = ConversionError>>, DeadbandError> {
+    let deadband_dir = config_dir.join(DEADBAND_DIR);
+    match DeadbandConfig::read_from_file(&deadband_dir.join(DEADBAND_FILTERS_FILE))? {
+        Some(config) => {
+            let filter = DeadbandFilter::new(config, Box::new(WallClock))
+                .with_state_file(Path::new(DEADBAND_STATE_DIR).join(format!("{mapper_name}.json")));
+            Ok(Box::new(DeadbandConverter::new(converter, filter)))
+        }
+        None => Ok(converter),
+    }
+}
+
+/// Create the directories of the deadband filters, `<config-dir>/deadband`, and of their state.
+pub fn create_deadband_directories(config_dir: &Path) -> Result<(), FileError> {
+    let config_dir = config_dir.join(DEADBAND_DIR).display().to_string();
+    create_directory_with_user_group(&config_dir, "tedge", "tedge", 0o775)?;
+    create_directory_with_user_group("/var/tedge", "tedge", "tedge", 0o775)?;
+    create_directory_with_user_group(DEADBAND_STATE_DIR, "tedge", "tedge", 0o775)?;
+    Ok(())
+}
+
+/// The deadbands of the measurements, as defined in `/etc/tedge/deadband/filters.toml`:
+///
+/// ```toml
+/// [[deadband]]
+/// measurement = "temperature"
+/// absolute = 0.5
+/// max_silence = 300
+///
+/// [[deadband]]
+/// measurement = "current/L1"
+/// percent = 2.0
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeadbandConfig {
+    #[serde(default)]
+    pub deadband: Vec<DeadbandRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeadbandRule {
+    /// The name of the measurement, or `group/name` for a measurement of a group.
+    pub measurement: String,
+
+    /// A value is dropped if it differs by at most this amount from the last value sent.
+    pub absolute: Option<f64>,
+
+    /// A value is dropped if it differs by at most this percentage of the last value sent.
+    pub percent: Option<f64>,
+
+    /// A value is sent, even if within the deadband, when no value has been sent for this number of seconds.
+    pub max_silence: Option<u64>,
+}
+
+impl DeadbandConfig {
+    /// Read the deadbands from a TOML file, returning `None` if there is no such file.
+    pub fn read_from_file(path: &Path) -> Result<Option<Self>, DeadbandError> {
+        let content = read_config_file(path).map_err(|error| DeadbandError::FromIo {
+            path: path.display().to_string(),
+            error,
+        })?;
+        content.map(|content| Self::parse(&content)).transpose()
+    }
+
+    pub fn parse(content: &str) -> Result<Self, DeadbandError> {
+        let config: DeadbandConfig = toml::from_str(content)?;
+
+        let mut measurements = HashSet::new();
+        for rule in config.deadband.iter() {
+            let () = rule.validate()?;
+            if !measurements.insert(&rule.measurement) {
+                return Err(DeadbandError::DuplicatedMeasurement(
+                    rule.measurement.clone(),
+                ));
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl DeadbandRule {
+    fn validate(&self) -> Result<(), DeadbandError> {
+        if !is_valid_measurement_path(&self.measurement) {
+            return Err(DeadbandError::InvalidMeasurement(self.measurement.clone()));
+        }
+
+        match (self.absolute, self.percent) {
+            (Some(threshold), None) | (None, Some(threshold)) => {
+                if !threshold.is_finite() || threshold < 0.0 {
+                    return Err(DeadbandError::NegativeThreshold(self.measurement.clone()));
+                }
+            }
+            _ => return Err(DeadbandError::InvalidThreshold(self.measurement.clone())),
+        }
+
+        Ok(())
+    }
+
+    fn is_within_deadband(&self, last_sent: f64, value: f64) -> bool {
+        let delta = (value - last_sent).abs();
+        match (self.absolute, self.percent) {
+            (Some(absolute), _) => delta <= absolute,
+            (_, Some(percent)) => delta <= last_sent.abs() * percent / 100.0,
+            _ => false,
+        }
+    }
+
+    fn is_silent_for_too_long(&self, last_sent: &LastSent, now: Timestamp) -> bool {
+        match self.max_silence {
+            Some(max_silence) => now.unix_timestamp() - last_sent.time >= max_silence as i64,
+            None => false,
+        }
+    }
+}
+
+/// The last value sent for a measurement, with the time it was sent as a unix timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct LastSent {
+    value: f64,
+    time: i64,
+}
+
+/// Drops the measurements that are within the deadband of the last value sent,
+/// i.e. that have not changed enough to be worth sending to the cloud.
+///
+/// The last values sent are tracked per measurement topic, hence per child device,
+/// and persisted in a state file, if any, to be restored on restart.
+/// The state is only written by `save_state` and when the filter is dropped, not on each value sent.
+pub struct DeadbandFilter {
+    rules: HashMap<String, DeadbandRule>,
+    last_sent: HashMap<String, HashMap<String, LastSent>>,
+    state_file: Option<PathBuf>,
+    unsaved_changes: bool,
+    clock: Box<dyn Clock>,
+}
+
+impl DeadbandFilter {
+    pub fn new(config: DeadbandConfig, clock: Box<dyn Clock>) -> Self {
+        let rules = config
+            .deadband
+            .into_iter()
+            .map(|rule| (rule.measurement.clone(), rule))
+            .collect();
+        DeadbandFilter {
+            rules,
+            last_sent: HashMap::new(),
+            state_file: None,
+            unsaved_changes: false,
+            clock,
+        }
+    }
+
+    /// Persist the last values sent in this file, restoring them if the file exists.
+    pub fn with_state_file(mut self, state_file: PathBuf) -> Self {
+        let last_sent = match fs::read_to_string(&state_file) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                warn!(
+                    "Ignoring the deadband state {}: {}",
+                    state_file.display(),
+                    err
+                );
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        self.last_sent = last_sent;
+        self.state_file = Some(state_file);
+        self
+    }
+
+    /// Filter a measurement message, returning `None` if all its measurements are dropped.
+    ///
+    /// The messages that are not measurements, or not valid measurements, are returned untouched.
+    /// When some measurements are dropped, the remaining ones are returned as thin-edge JSON,
+    /// CBOR-encoded measurements being then published on the corresponding JSON topic.
+    pub fn filter(&mut self, input: Message) -> Option<Message> {
+        let (topic, cbor) = match MeasurementTopic::parse(&input.topic.name) {
+            Some(topic) => (topic.json_topic(), topic.cbor),
+            None => return Some(input),
+        };
+
+        let mut frames = Frames::default();
+        let parsed = if cbor {
+            thin_edge_json::cbor::parse_slice(input.payload_bytes(), &mut frames).is_ok()
+        } else {
+            match input.payload_str() {
+                Ok(payload) => thin_edge_json::parser::parse_str(payload, &mut frames).is_ok(),
+                Err(_) => false,
+            }
+        };
+        if !parsed {
+            return Some(input);
+        }
+
+        let now = self.clock.now();
+        let mut dropped = false;
+        let mut updated = false;
+        for frame in frames.frames.iter_mut() {
+            frame.items.retain(|item| match item.value {
+                FrameValue::Number(value) if self.rules.contains_key(&item.path()) => {
+                    let send = self.update(&topic, &item.path(), value, now);
+                    updated |= send;
+                    dropped |= !send;
+                    send
+                }
+                _ => true,
+            });
+        }
+        self.unsaved_changes |= updated;
+
+        if !dropped {
+            return Some(input);
+        }
+        frames.frames.retain(|frame| !frame.items.is_empty());
+        if frames.frames.is_empty() {
+            return None;
+        }
+        match frames.to_thin_edge_json() {
+            Ok(payload) => Some(Message::new(&Topic::new_unchecked(&topic), payload)),
+            Err(err) => {
+                warn!("Failed to filter measurements: {}", err);
+                Some(input)
+            }
+        }
+    }
+
+    /// Check if a value has to be sent, updating the last value sent if so.
+    fn update(&mut self, topic: &str, path: &str, value: f64, now: Timestamp) -> bool {
+        let rule = match self.rules.get(path) {
+            Some(rule) => rule,
+            None => return true,
+        };
+        let last_sent = self.last_sent.entry(topic.to_string()).or_default();
+        let send = match last_sent.get(path) {
+            Some(last) => {
+                !rule.is_within_deadband(last.value, value)
+                    || rule.is_silent_for_too_long(last, now)
+            }
+            None => true,
+        };
+        if send {
+            let time = now.unix_timestamp();
+            last_sent.insert(path.to_string(), LastSent { value, time });
+        }
+        send
+    }
+
+    /// Save the last values sent in the state file, if any and if changed since the last save.
+    ///
+    /// The state is written to a temporary file, then renamed, so a crash never leaves a truncated state.
+    pub fn save_state(&mut self) {
+        if !self.unsaved_changes {
+            return;
+        }
+        if let Some(state_file) = &self.state_file {
+            let temp_file = state_file.with_extension("json.tmp");
+            let _ = fs::remove_file(&temp_file);
+            let result = serde_json::to_string(&self.last_sent)
+                .map_err(io::Error::from)
+                .and_then(|state| {
+                    atomically_write_file_sync(&temp_file, state_file, state.as_bytes())
+                });
+            match result {
+                Ok(()) => self.unsaved_changes = false,
+                Err(err) => warn!(
+                    "Failed to save the deadband state {}: {}",
+                    state_file.display(),
+                    err
+                ),
+            }
+        }
+    }
+}
+
+impl Drop for DeadbandFilter {
+    fn drop(&mut self) {
+        self.save_state();
+    }
+}
+
+/// Applies a deadband filter to the measurements, before converting them.
+pub struct DeadbandConverter {
+    converter: Box<dyn Converter<Error = ConversionError>>,
+    filter: DeadbandFilter,
+}
+
+impl DeadbandConverter {
+    pub fn new(
+        converter: Box<dyn Converter<Error = ConversionError>>,
+        filter: DeadbandFilter,
+    ) -> Self {
+        DeadbandConverter { converter, filter }
+    }
+}
+
+#[async_trait]
+impl Converter for DeadbandConverter {
+    type Error = ConversionError;
+
+    fn get_mapper_config(&self) -> &MapperConfig {
+        self.converter.get_mapper_config()
+    }
+
+    fn get_in_topic_filter(&self) -> &mqtt_channel::TopicFilter {
+        self.converter.get_in_topic_filter()
+    }
+
+    async fn try_convert(&mut self, input: &Message) -> Result<Vec<Message>, Self::Error> {
+        match self.filter.filter(input.clone()) {
+            Some(message) => self.converter.try_convert(&message).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    fn try_init_messages(&self) -> Result<Vec<Message>, Self::Error> {
+        self.converter.try_init_messages()
+    }
+
+    fn init_messages(&self) -> Vec<Message> {
+        self.converter.init_messages()
+    }
+
+    fn sync_messages(&mut self) -> Vec<Message> {
+        self.converter.sync_messages()
+    }
+
+    fn tick_interval(&self) -> Option<Duration> {
+        self.converter
+            .tick_interval()
+            .or(Some(DEADBAND_SAVE_INTERVAL))
+    }
+
+    fn tick_messages(&mut self) -> Vec<Message> {
+        self.filter.save_state();
+        self.converter.tick_messages()
+    }
+
+    fn watched_directory(&self) -> Option<PathBuf> {
+        self.converter.watched_directory()
+    }
+
+    fn file_event_messages(&mut self, path: &Path) -> Vec<Message> {
+        self.converter.file_event_messages(path)
+    }
+
+    fn take_async_messages(&mut self) -> Option<UnboundedReceiver<Message>> {
+        self.converter.take_async_messages()
+    }
+}
+
+/// The measurements of a message, collected to be filtered.
+#[derive(Debug, Default)]
+struct Frames {
+    is_array: bool,
+    frames: Vec<Frame>,
+    group: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Frame {
+    timestamp: Option<OffsetDateTime>,
+    measurement_type: Option<String>,
+    items: Vec<FrameItem>,
+}
+
+#[derive(Debug)]
+struct FrameItem {
+    group: Option<String>,
+    name: String,
+    value: FrameValue,
+}
+
+#[derive(Debug)]
+enum FrameValue {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+impl FrameItem {
+    fn path(&self) -> String {
+        match &self.group {
+            Some(group) => format!("{group}/{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl Frames {
+    fn current(&mut self) -> &mut Frame {
+        if self.frames.is_empty() {
+            self.frames.push(Frame::default());
+        }
+        self.frames.last_mut().expect("at least one frame")
+    }
+
+    fn push(&mut self, name: &str, value: FrameValue) {
+        let group = self.group.clone();
+        self.current().items.push(FrameItem {
+            group,
+            name: name.into(),
+            value,
+        });
+    }
+
+    fn to_thin_edge_json(&self) -> Result<String, ThinEdgeJsonSerializationError> {
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        for frame in self.frames.iter() {
+            if self.is_array {
+                let () = serializer.visit_frame()?;
+            }
+            if let Some(timestamp) = frame.timestamp {
+                let () = serializer.visit_timestamp(timestamp)?;
+            }
+            if let Some(measurement_type) = &frame.measurement_type {
+                let () = serializer.visit_type(measurement_type)?;
+            }
+
+            let mut current_group: Option<&str> = None;
+            for item in frame.items.iter() {
+                if item.group.as_deref() != current_group {
+                    if current_group.is_some() {
+                        let () = serializer.visit_end_group()?;
+                    }
+                    if let Some(group) = &item.group {
+                        let () = serializer.visit_start_group(group)?;
+                    }
+                    current_group = item.group.as_deref();
+                }
+                let () = match &item.value {
+                    FrameValue::Number(value) => {
+                        serializer.visit_measurement(&item.name, *value)?
+                    }
+                    FrameValue::Text(value) => {
+                        serializer.visit_text_measurement(&item.name, value)?
+                    }
+                    FrameValue::Bool(value) => {
+                        serializer.visit_bool_measurement(&item.name, *value)?
+                    }
+                };
+            }
+            if current_group.is_some() {
+                let () = serializer.visit_end_group()?;
+            }
+        }
+        serializer.into_string()
+    }
+}
+
+impl MeasurementVisitor for Frames {
+    type Error = Infallible;
+
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        self.is_array = true;
+        self.frames.push(Frame::default());
+        Ok(())
+    }
+
+    fn visit_timestamp(&mut self, value: OffsetDateTime) -> Result<(), Self::Error> {
+        self.current().timestamp = Some(value);
+        Ok(())
+    }
+
+    fn visit_type(&mut self, measurement_type: &str) -> Result<(), Self::Error> {
+        self.current().measurement_type = Some(measurement_type.into());
+        Ok(())
+    }
+
+    fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), Self::Error> {
+        self.push(name, FrameValue::Number(value));
+        Ok(())
+    }
+
+    fn visit_text_measurement(&mut self, name: &str, value: &str) -> Result<(), Self::Error> {
+        self.push(name, FrameValue::Text(value.into()));
+        Ok(())
+    }
+
+    fn visit_bool_measurement(&mut self, name: &str, value: bool) -> Result<(), Self::Error> {
+        self.push(name, FrameValue::Bool(value));
+        Ok(())
+    }
+
+    fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error> {
+        self.group = Some(group.into());
+        Ok(())
+    }
+
+    fn visit_end_group(&mut self) -> Result<(), Self::Error> {
+        self.group = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use test_case::test_case;
+    use time::macros::datetime;
+
+    #[derive(Clone)]
+    struct TestClock(Arc<Mutex<Timestamp>>);
+
+    impl TestClock {
+        fn new() -> Self {
+            TestClock(Arc::new(Mutex::new(datetime!(2021-04-08 00:00:00 +00:00))))
+        }
+
+        fn advance(&self, seconds: i64) {
+            let mut now = self.0.lock().unwrap();
+            *now += time::Duration::seconds(seconds);
+        }
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> Timestamp {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn filter(config: &str, clock: &TestClock) -> DeadbandFilter {
+        DeadbandFilter::new(
+            DeadbandConfig::parse(config).unwrap(),
+            Box::new(clock.clone()),
+        )
+    }
+
+    fn measurements(topic: &str, payload: &str) -> Message {
+        Message::new(&Topic::new_unchecked(topic), payload)
+    }
+
+    fn payload(message: Option<Message>) -> Option<serde_json::Value> {
+        message.map(|message| serde_json::from_str(message.payload_str().unwrap()).unwrap())
+    }
+
+    const TEMPERATURE_DEADBAND: &str = r#"
+        [[deadband]]
+        measurement = "temperature"
+        absolute = 0.5
+        "#;
+
+    #[test]
+    fn parse_config() {
+        let config = DeadbandConfig::parse(
+            r#"
+            [[deadband]]
+            measurement = "temperature"
+            absolute = 0.5
+            max_silence = 300
+
+            [[deadband]]
+            measurement = "current/L1"
+            percent = 2.0
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.deadband,
+            vec![
+                DeadbandRule {
+                    measurement: "temperature".into(),
+                    absolute: Some(0.5),
+                    percent: None,
+                    max_silence: Some(300),
+                },
+                DeadbandRule {
+                    measurement: "current/L1".into(),
+                    absolute: None,
+                    percent: Some(2.0),
+                    max_silence: None,
+                },
+            ]
+        );
+    }
+
+    #[test_case("absolute = 0.5\npercent = 2.0"; "both thresholds")]
+    #[test_case("max_silence = 300"; "no threshold")]
+    fn reject_ambiguous_thresholds(thresholds: &str) {
+        let result = DeadbandConfig::parse(&format!(
+            "[[deadband]]\nmeasurement = \"temperature\"\n{thresholds}"
+        ));
+
+        assert_matches!(result, Err(DeadbandError::InvalidThreshold(_)));
+    }
+
+    #[test]
+    fn reject_negative_thresholds() {
+        let result =
+            DeadbandConfig::parse("[[deadband]]\nmeasurement = \"temperature\"\nabsolute = -1.0");
+
+        assert_matches!(result, Err(DeadbandError::NegativeThreshold(_)));
+    }
+
+    #[test]
+    fn reject_duplicated_measurements() {
+        let result =
+            DeadbandConfig::parse(&format!("{TEMPERATURE_DEADBAND}\n{TEMPERATURE_DEADBAND}"));
+
+        assert_matches!(result, Err(DeadbandError::DuplicatedMeasurement(_)));
+    }
+
+    #[test]
+    fn values_within_an_absolute_deadband_are_dropped() {
+        let clock = TestClock::new();
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock);
+        let topic = "tedge/measurements";
+
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 20.0}"#))
+            .is_some());
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 20.4}"#))
+            .is_none());
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 19.5}"#))
+            .is_none());
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 20.6}"#))
+            .is_some());
+    }
+
+    #[test]
+    fn values_within_a_percent_deadband_are_dropped() {
+        let clock = TestClock::new();
+        let mut filter = filter(
+            r#"
+            [[deadband]]
+            measurement = "current/L1"
+            percent = 10.0
+            "#,
+            &clock,
+        );
+        let topic = "tedge/measurements";
+
+        assert!(filter
+            .filter(measurements(topic, r#"{"current": {"L1": 200.0}}"#))
+            .is_some());
+        assert!(filter
+            .filter(measurements(topic, r#"{"current": {"L1": 219.0}}"#))
+            .is_none());
+        assert!(filter
+            .filter(measurements(topic, r#"{"current": {"L1": 221.0}}"#))
+            .is_some());
+    }
+
+    #[test]
+    fn only_the_measurements_within_the_deadband_are_dropped() {
+        let clock = TestClock::new();
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock);
+        let topic = "tedge/measurements";
+        let _ = filter.filter(measurements(topic, r#"{"temperature": 20.0}"#));
+
+        let filtered = filter.filter(measurements(
+            topic,
+            r#"{"time": "2021-04-08T00:00:00+00:00", "type": "env", "temperature": 20.1, "pressure": 1000, "status": "ok"}"#,
+        ));
+
+        assert_eq!(
+            payload(filtered),
+            Some(json!({
+                "time": "2021-04-08T00:00:00Z",
+                "type": "env",
+                "pressure": 1000.0,
+                "status": "ok"
+            }))
+        );
+    }
+
+    #[test]
+    fn values_are_sent_after_max_silence() {
+        let clock = TestClock::new();
+        let mut filter = filter(
+            r#"
+            [[deadband]]
+            measurement = "temperature"
+            absolute = 0.5
+            max_silence = 60
+            "#,
+            &clock,
+        );
+        let topic = "tedge/measurements";
+
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 20.0}"#))
+            .is_some());
+        clock.advance(59);
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 20.0}"#))
+            .is_none());
+        clock.advance(1);
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 20.0}"#))
+            .is_some());
+        clock.advance(1);
+        assert!(filter
+            .filter(measurements(topic, r#"{"temperature": 20.0}"#))
+            .is_none());
+    }
+
+    #[test]
+    fn last_values_are_tracked_per_device() {
+        let clock = TestClock::new();
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock);
+
+        assert!(filter
+            .filter(measurements(
+                "tedge/measurements",
+                r#"{"temperature": 20.0}"#
+            ))
+            .is_some());
+        assert!(filter
+            .filter(measurements(
+                "tedge/measurements/child1",
+                r#"{"temperature": 20.0}"#
+            ))
+            .is_some());
+        assert!(filter
+            .filter(measurements(
+                "tedge/measurements/child1",
+                r#"{"temperature": 20.0}"#
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn samples_within_the_deadband_are_removed_from_arrays() {
+        let clock = TestClock::new();
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock);
+
+        let filtered = filter.filter(measurements(
+            "tedge/measurements",
+            r#"[{"temperature": 20.0}, {"temperature": 20.1}, {"temperature": 21.0}]"#,
+        ));
+
+        assert_eq!(
+            payload(filtered),
+            Some(json!([{"temperature": 20.0}, {"temperature": 21.0}]))
+        );
+    }
+
+    #[test]
+    fn filtered_cbor_measurements_are_forwarded_as_json() {
+        let clock = TestClock::new();
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock);
+        let _ = filter.filter(measurements(
+            "tedge/measurements/child1",
+            r#"{"temperature": 20.0}"#,
+        ));
+
+        let cbor = thin_edge_json::cbor::to_vec(&json!({"temperature": 20.1, "pressure": 1000.0}))
+            .unwrap();
+        let filtered = filter
+            .filter(Message::new(
+                &Topic::new_unchecked("tedge/measurements/cbor/child1"),
+                cbor,
+            ))
+            .unwrap();
+
+        assert_eq!(filtered.topic.name, "tedge/measurements/child1");
+        assert_eq!(payload(Some(filtered)), Some(json!({"pressure": 1000.0})));
+    }
+
+    #[test]
+    fn other_messages_are_untouched() {
+        let clock = TestClock::new();
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock);
+        let units = measurements("tedge/measurements/meta", r#"{"temperature": "°C"}"#);
+        let invalid = measurements("tedge/measurements", r#"{"temperature": 20.0"#);
+        let alarm = measurements("tedge/alarms/major/temperature", r#"{"temperature": 20.0}"#);
+
+        for message in [units, invalid, alarm] {
+            assert_eq!(filter.filter(message.clone()), Some(message));
+        }
+    }
+
+    #[test]
+    fn last_values_are_restored_on_restart() {
+        let clock = TestClock::new();
+        let state_dir = tempfile::tempdir().unwrap();
+        let state_file = state_dir.path().join("tedge-mapper-c8y.json");
+
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock).with_state_file(state_file.clone());
+        let _ = filter.filter(measurements(
+            "tedge/measurements",
+            r#"{"temperature": 20.0}"#,
+        ));
+        // The state is saved on shutdown
+        drop(filter);
+
+        let mut filter = self::filter(TEMPERATURE_DEADBAND, &clock).with_state_file(state_file);
+        assert!(filter
+            .filter(measurements(
+                "tedge/measurements",
+                r#"{"temperature": 20.1}"#
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn the_state_is_only_saved_on_demand() {
+        let clock = TestClock::new();
+        let state_dir = tempfile::tempdir().unwrap();
+        let state_file = state_dir.path().join("tedge-mapper-c8y.json");
+
+        let mut filter = filter(TEMPERATURE_DEADBAND, &clock).with_state_file(state_file.clone());
+        let _ = filter.filter(measurements(
+            "tedge/measurements",
+            r#"{"temperature": 20.0}"#,
+        ));
+        assert!(!state_file.exists());
+
+        filter.save_state();
+        let state: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&state_file).unwrap()).unwrap();
+        assert_eq!(
+            state["tedge/measurements"]["temperature"]["value"],
+            json!(20.0)
+        );
+
+        // Only the state file is left, the temporary file being renamed
+        let files: Vec<_> = fs::read_dir(state_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec!["tedge-mapper-c8y.json"]);
+    }
+
+    struct EchoConverter {
+        mapper_config: MapperConfig,
+    }
+
+    #[async_trait]
+    impl Converter for EchoConverter {
+        type Error = ConversionError;
+
+        fn get_mapper_config(&self) -> &MapperConfig {
+            &self.mapper_config
+        }
+
+        async fn try_convert(&mut self, input: &Message) -> Result<Vec<Message>, Self::Error> {
+            Ok(vec![input.clone()])
+        }
+    }
+
+    fn echo_converter() -> Box<EchoConverter> {
+        Box::new(EchoConverter {
+            mapper_config: MapperConfig {
+                in_topic_filter: make_valid_topic_filter_or_panic("tedge/measurements"),
+                out_topic: make_valid_topic_or_panic("echo"),
+                errors_topic: make_valid_topic_or_panic("tedge/errors"),
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn the_converter_only_sees_the_filtered_measurements() {
+        let clock = TestClock::new();
+        let mut converter =
+            DeadbandConverter::new(echo_converter(), filter(TEMPERATURE_DEADBAND, &clock));
+
+        let first = measurements("tedge/measurements", r#"{"temperature": 20.0}"#);
+        assert_eq!(converter.convert(&first).await, vec![first]);
+
+        let second = measurements("tedge/measurements", r#"{"temperature": 20.2}"#);
+        assert!(converter.convert(&second).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn the_state_is_saved_periodically() {
+        let clock = TestClock::new();
+        let state_dir = tempfile::tempdir().unwrap();
+        let state_file = state_dir.path().join("tedge-mapper-c8y.json");
+        let filter = filter(TEMPERATURE_DEADBAND, &clock).with_state_file(state_file.clone());
+        let mut converter = DeadbandConverter::new(echo_converter(), filter);
+        assert_eq!(converter.tick_interval(), Some(DEADBAND_SAVE_INTERVAL));
+
+        let measurement = measurements("tedge/measurements", r#"{"temperature": 20.0}"#);
+        let _ = converter.convert(&measurement).await;
+        assert!(!state_file.exists());
+
+        assert!(converter.tick_messages().is_empty());
+        assert!(state_file.exists());
+    }
+}
@@ -24,6 +24,31 @@ pub enum MapperError {
     FromFlockfile(#[from] flockfile::FlockfileError),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum DeadbandError {
+    #[error("Cannot read the deadband configuration from {path}: {error}")]
+    FromIo {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[error("Invalid deadband configuration: {0}")]
+    FromToml(#[from] toml::de::Error),
+
+    #[error("Invalid deadband for {0:?}: expecting either an absolute or a percent threshold")]
+    InvalidThreshold(String),
+
+    #[error("Invalid deadband for {0:?}: the threshold must be a non-negative number")]
+    NegativeThreshold(String),
+
+    #[error("Invalid deadband measurement {0:?}: expecting a name or a group/name path")]
+    InvalidMeasurement(String),
+
+    #[error("The deadband of {0:?} is defined more than once")]
+    DuplicatedMeasurement(String),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ConversionError {
     #[error(transparent)]
@@ -51,9 +76,25 @@ pub enum ConversionError {
         #[from] thin_edge_json::event::error::ThinEdgeJsonDeserializerError,
     ),
 
+    #[error(transparent)]
+    FromThinEdgeJsonLocationDeserialization(
+        #[from] thin_edge_json::location::error::ThinEdgeJsonLocationError,
+    ),
+
+    #[error(transparent)]
+    FromThinEdgeJsonUnitsDeserialization(
+        #[from] thin_edge_json::units::error::ThinEdgeJsonUnitsError,
+    ),
+
     #[error(transparent)]
     FromThinEdgeJsonParser(#[from] thin_edge_json::parser::ThinEdgeJsonParserError),
 
+    #[error(transparent)]
+    FromThinEdgeCborParser(#[from] thin_edge_json::cbor::ThinEdgeCborParserError),
+
+    #[error(transparent)]
+    FromHistory(#[from] crate::history::error::HistoryError),
+
     #[error("The size of the message received on {topic} is {actual_size} which is greater than the threshold size of {threshold}.")]
     SizeThresholdExceeded {
         topic: String,
@@ -91,14 +132,6 @@ pub enum ConversionError {
     #[error(transparent)]
     FromTimeFormatError(#[from] time::error::Format),
 
-    #[error("The payload {payload} received on {topic} after translation is {actual_size} greater than the threshold size of {threshold}.")]
-    TranslatedSizeExceededThreshold {
-        payload: String,
-        topic: String,
-        actual_size: usize,
-        threshold: usize,
-    },
-
     #[error(transparent)]
     FromOperationLogsError(#[from] plugin_sm::operation_logs::OperationLogsError),
 }
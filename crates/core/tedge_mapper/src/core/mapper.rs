@@ -1,12 +1,14 @@
-use std::{process, time::Duration};
+use std::{ffi::OsString, io, path::Path, process, time::Duration};
 
 use crate::core::{converter::*, error::*};
 
+use inotify::{Event, EventStream, Inotify, WatchMask};
 use mqtt_channel::{
     Connection, Message, MqttError, SinkExt, StreamExt, Topic, TopicFilter, UnboundedReceiver,
     UnboundedSender,
 };
 use serde_json::json;
+use tokio::time::{Instant, Interval};
 use tracing::{error, info, instrument};
 
 const SYNC_WINDOW: Duration = Duration::from_secs(3);
@@ -32,14 +34,21 @@ pub async fn create_mapper(
     let mut topic_filter = mapper_config.in_topic_filter.clone();
     topic_filter.add_all(health_check_topics.clone());
 
-    let mqtt_client =
-        Connection::new(&mqtt_config(app_name, &mqtt_host, mqtt_port, topic_filter)?).await?;
+    let health_down_message =
+        Message::new(&health_status_topic, json!({"status": "down"}).to_string());
+    let mqtt_client = Connection::new(
+        &mqtt_config(app_name, &mqtt_host, mqtt_port, topic_filter)?
+            .with_last_will_message(health_down_message),
+    )
+    .await?;
 
     Mapper::subscribe_errors(mqtt_client.errors);
 
     Ok(Mapper::new(
         mqtt_client.received,
         mqtt_client.published,
+        mqtt_client.subscriptions,
+        mqtt_client.unsubscriptions,
         converter,
         health_check_topics,
         health_status_topic,
@@ -63,6 +72,8 @@ pub fn mqtt_config(
 pub struct Mapper {
     input: UnboundedReceiver<Message>,
     output: UnboundedSender<Message>,
+    subscriptions: UnboundedSender<TopicFilter>,
+    unsubscriptions: UnboundedSender<TopicFilter>,
     converter: Box<dyn Converter<Error = ConversionError>>,
     health_check_topics: TopicFilter,
     health_status_topic: Topic,
@@ -72,6 +83,8 @@ impl Mapper {
     pub fn new(
         input: UnboundedReceiver<Message>,
         output: UnboundedSender<Message>,
+        subscriptions: UnboundedSender<TopicFilter>,
+        unsubscriptions: UnboundedSender<TopicFilter>,
         converter: Box<dyn Converter<Error = ConversionError>>,
         health_check_topics: TopicFilter,
         health_status_topic: Topic,
@@ -79,6 +92,8 @@ impl Mapper {
         Self {
             input,
             output,
+            subscriptions,
+            unsubscriptions,
             converter,
             health_check_topics,
             health_status_topic,
@@ -102,6 +117,20 @@ impl Mapper {
 
     #[instrument(skip(self), name = "messages")]
     async fn process_messages(&mut self) -> Result<(), MqttError> {
+        // Watch the files from the start, so changes made during the sync window are not missed
+        let mut file_events =
+            self.converter
+                .watched_directory()
+                .and_then(|dir| match watch_directory(&dir) {
+                    Ok(events) => Some(events),
+                    Err(err) => {
+                        error!("Cannot watch {}: {}", dir.display(), err);
+                        None
+                    }
+                });
+
+        let mut async_messages = self.converter.take_async_messages();
+
         let init_messages = self.converter.init_messages();
         for init_message in init_messages.into_iter() {
             let _ = self.output.send(init_message).await;
@@ -122,13 +151,77 @@ impl Mapper {
         }
 
         // Continue processing messages after the sync period
-        while let Some(message) = self.input.next().await {
-            self.process_message(message).await;
+        let mut ticks = self
+            .converter
+            .tick_interval()
+            .map(|period| tokio::time::interval_at(Instant::now() + period, period));
+        loop {
+            tokio::select! {
+                maybe_message = self.input.next() => match maybe_message {
+                    Some(message) => self.process_message(message).await,
+                    None => break,
+                },
+                _ = next_tick(&mut ticks) => {
+                    for message in self.converter.tick_messages() {
+                        let _ = self.output.send(message).await;
+                    }
+                }
+                event = next_file_event(&mut file_events) => {
+                    self.process_file_event(event).await;
+                }
+                message = next_async_message(&mut async_messages) => {
+                    let _ = self.output.send(message).await;
+                }
+            }
         }
 
         Ok(())
     }
 
+    async fn process_file_event(&mut self, event: Result<Event<OsString>, io::Error>) {
+        let file_name = match event {
+            Ok(Event {
+                name: Some(file_name),
+                ..
+            }) => file_name,
+            Ok(_) => return,
+            Err(err) => {
+                error!("Failed to watch file events: {}", err);
+                return;
+            }
+        };
+        let dir = match self.converter.watched_directory() {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        let subscribed = self.converter.get_in_topic_filter().patterns.clone();
+        for message in self.converter.file_event_messages(&dir.join(file_name)) {
+            let _ = self.output.send(message).await;
+        }
+
+        let patterns = &self.converter.get_in_topic_filter().patterns;
+        let mut new_subscriptions = TopicFilter::empty();
+        for pattern in patterns.iter() {
+            if !subscribed.contains(pattern) {
+                new_subscriptions.add_unchecked(pattern);
+            }
+        }
+        let mut removed_subscriptions = TopicFilter::empty();
+        for pattern in subscribed.iter() {
+            if !patterns.contains(pattern) {
+                removed_subscriptions.add_unchecked(pattern);
+            }
+        }
+
+        if !new_subscriptions.patterns.is_empty() {
+            let _ = self.subscriptions.send(new_subscriptions).await;
+        }
+        if !removed_subscriptions.patterns.is_empty() {
+            let _ = self.unsubscriptions.send(removed_subscriptions).await;
+        }
+    }
+
     async fn process_message(&mut self, message: Message) {
         if self.health_check_topics.accept(&message) {
             let health_status = json!({
@@ -147,6 +240,46 @@ impl Mapper {
     }
 }
 
+fn watch_directory(dir: &Path) -> Result<EventStream<[u8; 1024]>, io::Error> {
+    let mut inotify = Inotify::init()?;
+    let _ = inotify.add_watch(
+        dir,
+        WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::DELETE | WatchMask::MOVED_FROM,
+    )?;
+    inotify.event_stream([0; 1024])
+}
+
+async fn next_tick(ticks: &mut Option<Interval>) {
+    match ticks {
+        Some(ticks) => {
+            let _ = ticks.tick().await;
+        }
+        None => futures::future::pending().await,
+    }
+}
+
+async fn next_file_event(
+    file_events: &mut Option<EventStream<[u8; 1024]>>,
+) -> Result<Event<OsString>, io::Error> {
+    match file_events {
+        Some(events) => match events.next().await {
+            Some(event) => event,
+            None => futures::future::pending().await,
+        },
+        None => futures::future::pending().await,
+    }
+}
+
+async fn next_async_message(messages: &mut Option<UnboundedReceiver<Message>>) -> Message {
+    match messages {
+        Some(messages) => match messages.next().await {
+            Some(message) => message,
+            None => futures::future::pending().await,
+        },
+        None => futures::future::pending().await,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,7 +287,7 @@ mod tests {
     use async_trait::async_trait;
     use mqtt_channel::{Message, Topic, TopicFilter};
     use serde_json::Value;
-    use std::time::Duration;
+    use std::{path::PathBuf, time::Duration};
     use tokio::time::sleep;
 
     #[tokio::test]
@@ -256,8 +389,88 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn tick_messages_are_published_periodically() -> Result<(), anyhow::Error> {
+        // Given an MQTT broker
+        let broker = mqtt_tests::test_mqtt_broker();
+        let mut messages = broker.messages_published_on("tick_topic").await;
+
+        // Given a mapper with a converter that has to be woken up periodically
+        let converter = UppercaseConverter::new().with_tick_interval(Duration::from_millis(200));
+        let mut mapper = create_mapper(
+            "mapper_under_test",
+            "localhost".into(),
+            broker.port,
+            Box::new(converter),
+        )
+        .await?;
+
+        tokio::spawn(async move {
+            let _ = mapper.run().await;
+        });
+
+        // The tick messages are published once the sync window is over
+        let timeout = SYNC_WINDOW + Duration::from_secs(2);
+        mqtt_tests::assert_received_all_expected(&mut messages, timeout, &["tick #1"]).await;
+        mqtt_tests::assert_received_all_expected(&mut messages, timeout, &["tick #2"]).await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn file_events_are_notified_to_the_converter() -> Result<(), anyhow::Error> {
+        // Given an MQTT broker
+        let broker = mqtt_tests::test_mqtt_broker();
+        let mut file_messages = broker.messages_published_on("file_topic").await;
+        let mut out_messages = broker.messages_published_on("out_topic").await;
+
+        // Given a mapper with a converter that watches a directory
+        let dir = tempfile::tempdir()?;
+        let converter = UppercaseConverter::new().with_watched_directory(dir.path().to_path_buf());
+        let mut mapper = create_mapper(
+            "mapper_under_test",
+            "localhost".into(),
+            broker.port,
+            Box::new(converter),
+        )
+        .await?;
+
+        tokio::spawn(async move {
+            let _ = mapper.run().await;
+        });
+
+        sleep(Duration::from_secs(1)).await;
+
+        // When a file is created in that directory
+        std::fs::write(dir.path().join("new_topic"), "")?;
+
+        // The messages returned by the converter are published, once the sync window is over
+        let timeout = SYNC_WINDOW + Duration::from_secs(2);
+        mqtt_tests::assert_received_all_expected(&mut file_messages, timeout, &["new_topic"]).await;
+
+        // And the mapper subscribes to the topics added by the converter to its input topic filter
+        sleep(Duration::from_millis(200)).await;
+        broker.publish("new_topic", "abcde").await?;
+        mqtt_tests::assert_received_all_expected(&mut out_messages, timeout, &["ABCDE"]).await;
+
+        // And unsubscribes from the topics removed by the converter
+        std::fs::remove_file(dir.path().join("new_topic"))?;
+        mqtt_tests::assert_received_all_expected(&mut file_messages, timeout, &["new_topic"]).await;
+        sleep(Duration::from_millis(200)).await;
+        broker.publish("new_topic", "fghij").await?;
+        broker.publish("in_topic", "klmno").await?;
+        mqtt_tests::assert_received(&mut out_messages, timeout, &["KLMNO"]).await;
+
+        Ok(())
+    }
+
     struct UppercaseConverter {
         mapper_config: MapperConfig,
+        tick_interval: Option<Duration>,
+        tick_count: usize,
+        watched_directory: Option<PathBuf>,
     }
 
     impl UppercaseConverter {
@@ -267,7 +480,26 @@ mod tests {
                 out_topic: Topic::new_unchecked("out_topic"),
                 errors_topic: Topic::new_unchecked("err_topic"),
             };
-            UppercaseConverter { mapper_config }
+            UppercaseConverter {
+                mapper_config,
+                tick_interval: None,
+                tick_count: 0,
+                watched_directory: None,
+            }
+        }
+
+        pub fn with_tick_interval(self, interval: Duration) -> UppercaseConverter {
+            UppercaseConverter {
+                tick_interval: Some(interval),
+                ..self
+            }
+        }
+
+        pub fn with_watched_directory(self, dir: PathBuf) -> UppercaseConverter {
+            UppercaseConverter {
+                watched_directory: Some(dir),
+                ..self
+            }
         }
 
         pub fn conversion_error() -> ConversionError {
@@ -296,5 +528,31 @@ mod tests {
                 Err(UppercaseConverter::conversion_error())
             }
         }
+
+        fn tick_interval(&self) -> Option<Duration> {
+            self.tick_interval
+        }
+
+        fn tick_messages(&mut self) -> Vec<Message> {
+            self.tick_count += 1;
+            vec![Message::new(
+                &Topic::new_unchecked("tick_topic"),
+                format!("tick #{}", self.tick_count),
+            )]
+        }
+
+        fn watched_directory(&self) -> Option<PathBuf> {
+            self.watched_directory.clone()
+        }
+
+        fn file_event_messages(&mut self, path: &Path) -> Vec<Message> {
+            let file_name = path.file_name().unwrap().to_str().unwrap();
+            if path.exists() {
+                self.mapper_config.in_topic_filter.add_unchecked(file_name);
+            } else {
+                self.mapper_config.in_topic_filter.remove(file_name);
+            }
+            vec![Message::new(&Topic::new_unchecked("file_topic"), file_name)]
+        }
     }
 }
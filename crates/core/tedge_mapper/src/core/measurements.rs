@@ -0,0 +1,174 @@
+use std::convert::Infallible;
+
+use thin_edge_json::{
+    cbor::{parse_slice, ThinEdgeCborParserError},
+    measurement::MeasurementVisitor,
+    parser::{parse_str, ThinEdgeJsonParserError},
+};
+use time::OffsetDateTime;
+
+pub const TEDGE_MEASUREMENTS_TOPIC: &str = "tedge/measurements";
+pub const TEDGE_MEASUREMENTS_CBOR_TOPIC: &str = "tedge/measurements/cbor";
+
+/// The sub-topics of `tedge/measurements` used for the CBOR measurements and the units.
+const RESERVED_CHILD_IDS: [&str; 2] = ["cbor", "meta"];
+
+/// Check that a measurement path is either `name` or `group/name`.
+pub fn is_valid_measurement_path(path: &str) -> bool {
+    path.split('/').count() <= 2 && !path.split('/').any(str::is_empty)
+}
+
+/// A child id must be neither empty nor one of the reserved sub-topics of `tedge/measurements`.
+pub fn is_valid_child_id(child_id: &str) -> bool {
+    !child_id.is_empty() && !RESERVED_CHILD_IDS.contains(&child_id)
+}
+
+/// Call `f` on each numeric measurement of thin-edge JSON measurements,
+/// with the path of the measurement (`name` or `group/name`), the timestamp of the measurement if any, and its value.
+///
+/// The samples of an array of measurements are given in order.
+pub fn for_each_numeric_measurement<F>(input: &str, f: F) -> Result<(), ThinEdgeJsonParserError>
+where
+    F: FnMut(&str, Option<OffsetDateTime>, f64),
+{
+    let mut visitor = NumericMeasurements {
+        f,
+        group: None,
+        timestamp: None,
+    };
+    parse_str(input, &mut visitor)
+}
+
+/// Same as `for_each_numeric_measurement`, but for CBOR-encoded measurements.
+pub fn for_each_numeric_cbor_measurement<F>(
+    input: &[u8],
+    f: F,
+) -> Result<(), ThinEdgeCborParserError>
+where
+    F: FnMut(&str, Option<OffsetDateTime>, f64),
+{
+    let mut visitor = NumericMeasurements {
+        f,
+        group: None,
+        timestamp: None,
+    };
+    parse_slice(input, &mut visitor)
+}
+
+/// The device and encoding of measurements, as given by their topic.
+pub struct MeasurementTopic<'a> {
+    /// The child device, none for the main device.
+    pub device: Option<&'a str>,
+    pub cbor: bool,
+}
+
+impl<'a> MeasurementTopic<'a> {
+    /// Parse `tedge/measurements[/cbor][/<child-id>]`, returning none for any other topic, notably the units.
+    pub fn parse(topic: &'a str) -> Option<Self> {
+        let levels: Vec<&str> = match topic.strip_prefix(TEDGE_MEASUREMENTS_TOPIC)? {
+            "" => vec![],
+            suffix => suffix.strip_prefix('/')?.split('/').collect(),
+        };
+        match levels.as_slice() {
+            [] => Some(MeasurementTopic {
+                device: None,
+                cbor: false,
+            }),
+            ["meta"] => None,
+            ["cbor"] => Some(MeasurementTopic {
+                device: None,
+                cbor: true,
+            }),
+            ["cbor", child] if is_valid_child_id(child) => Some(MeasurementTopic {
+                device: Some(child),
+                cbor: true,
+            }),
+            [child] if is_valid_child_id(child) => Some(MeasurementTopic {
+                device: Some(child),
+                cbor: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The thin-edge JSON topic of the measurements: `tedge/measurements[/<child-id>]`.
+    pub fn json_topic(&self) -> String {
+        match self.device {
+            Some(child_id) => format!("{TEDGE_MEASUREMENTS_TOPIC}/{child_id}"),
+            None => TEDGE_MEASUREMENTS_TOPIC.to_string(),
+        }
+    }
+}
+
+struct NumericMeasurements<F> {
+    f: F,
+    group: Option<String>,
+    timestamp: Option<OffsetDateTime>,
+}
+
+impl<F> MeasurementVisitor for NumericMeasurements<F>
+where
+    F: FnMut(&str, Option<OffsetDateTime>, f64),
+{
+    type Error = Infallible;
+
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        self.timestamp = None;
+        Ok(())
+    }
+
+    fn visit_timestamp(&mut self, value: OffsetDateTime) -> Result<(), Self::Error> {
+        self.timestamp = Some(value);
+        Ok(())
+    }
+
+    fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), Self::Error> {
+        match &self.group {
+            Some(group) => (self.f)(&format!("{group}/{name}"), self.timestamp, value),
+            None => (self.f)(name, self.timestamp, value),
+        }
+        Ok(())
+    }
+
+    fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error> {
+        self.group = Some(group.into());
+        Ok(())
+    }
+
+    fn visit_end_group(&mut self) -> Result<(), Self::Error> {
+        self.group = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("tedge/measurements", Some((None, false)); "main device")]
+    #[test_case("tedge/measurements/child1", Some((Some("child1"), false)); "child device")]
+    #[test_case("tedge/measurements/cbor", Some((None, true)); "main device cbor")]
+    #[test_case("tedge/measurements/cbor/child1", Some((Some("child1"), true)); "child device cbor")]
+    #[test_case("tedge/measurements/meta", None; "units")]
+    #[test_case("tedge/measurements/cbor/meta", None; "reserved meta child id")]
+    #[test_case("tedge/measurements/cbor/cbor", None; "reserved cbor child id")]
+    #[test_case("tedge/measurements/child1/more", None; "too many levels")]
+    #[test_case("tedge/measurements/", None; "empty child id")]
+    #[test_case("tedge/measurementsfoo", None; "other topic")]
+    fn measurement_topics_are_parsed(topic: &str, expected: Option<(Option<&str>, bool)>) {
+        let parsed = MeasurementTopic::parse(topic).map(|topic| (topic.device, topic.cbor));
+        assert_eq!(parsed, expected);
+    }
+
+    #[test_case("tedge/measurements", "tedge/measurements")]
+    #[test_case("tedge/measurements/child1", "tedge/measurements/child1")]
+    #[test_case("tedge/measurements/cbor", "tedge/measurements")]
+    #[test_case("tedge/measurements/cbor/child1", "tedge/measurements/child1")]
+    fn cbor_measurements_have_a_json_topic(topic: &str, json_topic: &str) {
+        assert_eq!(
+            MeasurementTopic::parse(topic).unwrap().json_topic(),
+            json_topic
+        );
+    }
+}
@@ -1,5 +1,8 @@
 pub mod component;
+pub mod config;
 pub mod converter;
+pub mod deadband;
 pub mod error;
 pub mod mapper;
+pub mod measurements;
 pub mod size_threshold;
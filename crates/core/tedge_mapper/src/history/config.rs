@@ -0,0 +1,151 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use tracing::info;
+
+use crate::{core::config::read_config_file, history::error::HistoryError};
+
+pub const DEFAULT_STORE_DIR: &str = "/var/tedge/history";
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+const DEFAULT_RETENTION: u64 = 7 * 24 * 3600;
+const DEFAULT_SEGMENT: u64 = 3600;
+const DEFAULT_HTTP_PORT: u16 = 8090;
+
+/// The settings of the measurement store, as defined in `/etc/tedge/history/history.toml`:
+///
+/// ```toml
+/// path = "/var/tedge/history"
+/// max_size = 10485760
+/// retention = 604800
+/// segment = 3600
+/// http_enable = true
+/// http_bind = "127.0.0.1:8090"
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// The directory of the store, `/var/tedge/history` by default.
+    pub path: Option<PathBuf>,
+
+    /// The maximum size of the store in bytes, the oldest samples being removed first.
+    #[serde(default = "default_max_size")]
+    pub max_size: u64,
+
+    /// How long the samples are kept, in seconds.
+    #[serde(default = "default_retention")]
+    pub retention: u64,
+
+    /// The time span of a segment file in seconds, samples being removed one segment at a time.
+    #[serde(default = "default_segment")]
+    pub segment: u64,
+
+    #[serde(default = "default_http_enable")]
+    pub http_enable: bool,
+
+    #[serde(default = "default_http_bind")]
+    pub http_bind: SocketAddr,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            path: None,
+            max_size: default_max_size(),
+            retention: default_retention(),
+            segment: default_segment(),
+            http_enable: default_http_enable(),
+            http_bind: default_http_bind(),
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// Read the settings from a TOML file.
+    ///
+    /// A missing file is not an error: the defaults are used.
+    pub fn read_from_file(path: &Path) -> Result<Self, HistoryError> {
+        match read_config_file(path).map_err(|err| HistoryError::from_io(path, err))? {
+            Some(content) => Self::parse(&content),
+            None => {
+                info!(
+                    "Using the default history settings: {} not found",
+                    path.display()
+                );
+                Ok(Self::default())
+            }
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, HistoryError> {
+        let config: HistoryConfig = toml::from_str(content)?;
+        if config.max_size == 0 {
+            return Err(HistoryError::InvalidSetting("max_size"));
+        }
+        if config.retention == 0 {
+            return Err(HistoryError::InvalidSetting("retention"));
+        }
+        if config.segment == 0 {
+            return Err(HistoryError::InvalidSetting("segment"));
+        }
+        Ok(config)
+    }
+
+    /// The directory of the store.
+    pub fn store_dir(&self) -> PathBuf {
+        self.path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_STORE_DIR))
+    }
+}
+
+fn default_max_size() -> u64 {
+    DEFAULT_MAX_SIZE
+}
+
+fn default_retention() -> u64 {
+    DEFAULT_RETENTION
+}
+
+fn default_segment() -> u64 {
+    DEFAULT_SEGMENT
+}
+
+fn default_http_enable() -> bool {
+    true
+}
+
+fn default_http_bind() -> SocketAddr {
+    (Ipv4Addr::LOCALHOST, DEFAULT_HTTP_PORT).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn missing_settings_are_defaulted() {
+        let config = HistoryConfig::parse("max_size = 1024").unwrap();
+
+        assert_eq!(
+            config,
+            HistoryConfig {
+                max_size: 1024,
+                ..HistoryConfig::default()
+            }
+        );
+        assert_eq!(config.store_dir(), PathBuf::from("/var/tedge/history"));
+        assert_eq!(config.http_bind.to_string(), "127.0.0.1:8090");
+    }
+
+    #[test]
+    fn null_settings_are_rejected() {
+        assert_matches!(
+            HistoryConfig::parse("retention = 0"),
+            Err(HistoryError::InvalidSetting("retention"))
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use clock::Clock;
+use mqtt_channel::{Message, TopicFilter};
+use time::OffsetDateTime;
+use tracing::{debug, error};
+
+use crate::{
+    core::{
+        converter::*,
+        error::*,
+        measurements::{
+            for_each_numeric_cbor_measurement, for_each_numeric_measurement, MeasurementTopic,
+        },
+    },
+    history::{
+        error::HistoryError,
+        query::{run_query, QueryRequest},
+        store::{Sample, Store},
+    },
+};
+
+const TEDGE_QUERY_TOPIC: &str = "tedge/query/measurements";
+const TEDGE_QUERY_RESPONSE_TOPIC: &str = "tedge/query/measurements/response";
+
+// The store is also cleaned periodically, when no samples are received.
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct HistoryConverter {
+    pub(crate) store: Arc<Mutex<Store>>,
+    pub(crate) clock: Box<dyn Clock>,
+    pub(crate) mapper_config: MapperConfig,
+}
+
+impl HistoryConverter {
+    pub fn new(store: Arc<Mutex<Store>>, clock: Box<dyn Clock>) -> Self {
+        let mapper_config = MapperConfig {
+            in_topic_filter: Self::in_topic_filter(),
+            out_topic: make_valid_topic_or_panic(TEDGE_QUERY_RESPONSE_TOPIC),
+            errors_topic: make_valid_topic_or_panic("tedge/errors"),
+        };
+        HistoryConverter {
+            store,
+            clock,
+            mapper_config,
+        }
+    }
+
+    pub fn in_topic_filter() -> TopicFilter {
+        let mut topic_filter = make_valid_topic_filter_or_panic("tedge/measurements/#");
+        topic_filter.add_all(make_valid_topic_filter_or_panic(TEDGE_QUERY_TOPIC));
+        topic_filter
+    }
+
+    async fn store_measurements(&mut self, input: &Message) -> Result<(), HistoryError> {
+        let topic = match MeasurementTopic::parse(&input.topic.name) {
+            Some(topic) => topic,
+            None => return Ok(()),
+        };
+
+        let received_at = self.clock.now();
+        let mut samples = vec![];
+        let mut add_sample = |measurement: &str, timestamp: Option<OffsetDateTime>, value| {
+            samples.push(Sample::new(
+                timestamp.unwrap_or(received_at),
+                topic.device,
+                measurement,
+                value,
+            ))
+        };
+
+        // Invalid measurements are already reported on `tedge/errors` by the cloud mappers
+        let parsed = if topic.cbor {
+            for_each_numeric_cbor_measurement(input.payload_bytes(), &mut add_sample)
+                .map_err(|err| err.to_string())
+        } else {
+            input
+                .payload_str()
+                .map_err(|err| err.to_string())
+                .and_then(|payload| {
+                    for_each_numeric_measurement(payload, &mut add_sample)
+                        .map_err(|err| err.to_string())
+                })
+        };
+        if let Err(err) = parsed {
+            debug!("Ignoring invalid measurements: {}", err);
+            return Ok(());
+        }
+
+        // The lock is not taken on the async runtime, as a query can hold it while reading the store files
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut store = store.lock().expect("the store lock is not poisoned");
+            store.append(&samples, received_at)
+        })
+        .await
+        .expect("the append task doesn't panic")
+    }
+
+    async fn answer_query(&mut self, input: &Message) -> Result<Vec<Message>, HistoryError> {
+        let request = serde_json::from_slice::<QueryRequest>(input.payload_bytes())?;
+        // Without a valid id, the requester cannot be answered: the error is published on `tedge/errors`
+        let response_topic = format!(
+            "{}/{}",
+            TEDGE_QUERY_RESPONSE_TOPIC,
+            request.response_topic_level()?
+        );
+        let response_topic = make_valid_topic_or_panic(&response_topic);
+
+        // The other errors are returned to the requester
+        let response = run_query(self.store.clone(), Ok(request))
+            .await
+            .unwrap_or_else(|error| error);
+        Ok(vec![Message::new(&response_topic, response)])
+    }
+}
+
+#[async_trait]
+impl Converter for HistoryConverter {
+    type Error = ConversionError;
+
+    fn get_mapper_config(&self) -> &MapperConfig {
+        &self.mapper_config
+    }
+
+    async fn try_convert(&mut self, input: &Message) -> Result<Vec<Message>, Self::Error> {
+        if input.topic.name == TEDGE_QUERY_TOPIC {
+            return Ok(self.answer_query(input).await?);
+        }
+
+        let () = self.store_measurements(input).await?;
+        Ok(vec![])
+    }
+
+    fn tick_interval(&self) -> Option<Duration> {
+        Some(EVICTION_INTERVAL)
+    }
+
+    fn tick_messages(&mut self) -> Vec<Message> {
+        let store = self.store.clone();
+        let now = self.clock.now();
+        tokio::task::spawn_blocking(move || {
+            let mut store = store.lock().expect("the store lock is not poisoned");
+            if let Err(err) = store.evict(now) {
+                error!("Cannot clean the measurement store: {}", err);
+            }
+        });
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::config::HistoryConfig;
+    use mqtt_channel::Topic;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use time::macros::datetime;
+
+    struct TestClock;
+
+    impl Clock for TestClock {
+        fn now(&self) -> clock::Timestamp {
+            datetime!(2022-05-10 12:00:00 UTC)
+        }
+    }
+
+    fn converter(dir: &TempDir) -> HistoryConverter {
+        let store = Store::open(dir.path(), &HistoryConfig::default()).unwrap();
+        HistoryConverter::new(Arc::new(Mutex::new(store)), Box::new(TestClock))
+    }
+
+    async fn query(
+        converter: &mut HistoryConverter,
+        request: serde_json::Value,
+    ) -> serde_json::Value {
+        let query = Message::new(
+            &Topic::new_unchecked(TEDGE_QUERY_TOPIC),
+            request.to_string(),
+        );
+        let responses = converter.convert(&query).await;
+        assert_eq!(responses.len(), 1);
+        assert_eq!(
+            responses[0].topic.name,
+            format!(
+                "{}/{}",
+                TEDGE_QUERY_RESPONSE_TOPIC,
+                request["id"].as_str().unwrap()
+            )
+        );
+        serde_json::from_str(responses[0].payload_str().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn stored_measurements_are_queried() {
+        let dir = TempDir::new().unwrap();
+        let mut converter = converter(&dir);
+
+        let measurements = [
+            (
+                "tedge/measurements",
+                r#"{"temperature": 20, "status": "ok"}"#.as_bytes().to_vec(),
+            ),
+            (
+                "tedge/measurements",
+                r#"{"time": "2022-05-10T11:00:00Z", "temperature": 18}"#
+                    .as_bytes()
+                    .to_vec(),
+            ),
+            (
+                "tedge/measurements/child1",
+                r#"{"temperature": 30}"#.as_bytes().to_vec(),
+            ),
+            (
+                "tedge/measurements/cbor",
+                thin_edge_json::cbor::to_vec(&json!({"temperature": 22})).unwrap(),
+            ),
+            (
+                "tedge/measurements/meta",
+                r#"{"temperature": "°C"}"#.as_bytes().to_vec(),
+            ),
+        ];
+        for (topic, payload) in measurements {
+            let output = converter
+                .convert(&Message::new(&Topic::new_unchecked(topic), payload))
+                .await;
+            assert!(output.is_empty());
+        }
+
+        let response = query(
+            &mut converter,
+            json!({"id": "1", "measurement": "temperature", "from": "2022-05-10T11:30:00Z"}),
+        )
+        .await;
+        assert_eq!(
+            response,
+            json!({
+                "id": "1",
+                "samples": [
+                    {"time": "2022-05-10T12:00:00Z", "measurement": "temperature", "value": 20.0},
+                    {"time": "2022-05-10T12:00:00Z", "measurement": "temperature", "value": 22.0},
+                ],
+                "truncated": false,
+            })
+        );
+
+        let response = query(&mut converter, json!({"id": "2", "device": "child1"})).await;
+        assert_eq!(response["samples"][0]["value"], json!(30.0));
+    }
+
+    #[tokio::test]
+    async fn invalid_queries_are_answered_with_an_error() {
+        let dir = TempDir::new().unwrap();
+        let mut converter = converter(&dir);
+
+        let response = query(&mut converter, json!({"id": "2", "from": "yesterday"})).await;
+
+        assert_eq!(response["id"], json!("2"));
+        assert!(response["error"]
+            .as_str()
+            .unwrap()
+            .contains("from must be an RFC 3339 timestamp"));
+    }
+
+    #[tokio::test]
+    async fn queries_without_id_are_reported_as_errors() {
+        let dir = TempDir::new().unwrap();
+        let mut converter = converter(&dir);
+
+        for request in [json!({"measurement": "temperature"}), json!({"id": "a/b"})] {
+            let responses = converter
+                .convert(&Message::new(
+                    &Topic::new_unchecked(TEDGE_QUERY_TOPIC),
+                    request.to_string(),
+                ))
+                .await;
+
+            assert_eq!(responses.len(), 1);
+            assert_eq!(responses[0].topic.name, "tedge/errors");
+            assert!(responses[0]
+                .payload_str()
+                .unwrap()
+                .contains("the id must be"));
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_measurements_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let mut converter = converter(&dir);
+
+        let output = converter
+            .convert(&Message::new(
+                &Topic::new_unchecked("tedge/measurements"),
+                r#"{"temperature": "hot""#,
+            ))
+            .await;
+
+        assert!(output.is_empty());
+        let response = query(&mut converter, json!({"id": "3"})).await;
+        assert_eq!(response["samples"], json!([]));
+    }
+}
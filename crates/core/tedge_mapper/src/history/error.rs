@@ -0,0 +1,41 @@
+#[derive(thiserror::Error, Debug)]
+pub enum HistoryError {
+    #[error("I/O error on {path}: {error}")]
+    FromIo {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[error("Invalid history configuration: {0}")]
+    FromToml(#[from] toml::de::Error),
+
+    #[error("Invalid history configuration: {0} must be greater than zero")]
+    InvalidSetting(&'static str),
+
+    #[error("Invalid query: {0}")]
+    FromQueryJson(#[from] serde_json::Error),
+
+    #[error("Invalid query: {0}")]
+    FromQueryString(#[from] serde_urlencoded::de::Error),
+
+    #[error("Invalid query: {field} must be an RFC 3339 timestamp, not {value:?}")]
+    InvalidTime { field: &'static str, value: String },
+
+    #[error("Invalid query: the limit must be between 1 and {max}")]
+    InvalidLimit { max: usize },
+
+    #[error(
+        "Invalid query: the id must be a non-empty topic name level, without '/', '+' nor '#'"
+    )]
+    InvalidQueryId,
+}
+
+impl HistoryError {
+    pub fn from_io(path: &std::path::Path, error: std::io::Error) -> Self {
+        HistoryError::FromIo {
+            path: path.display().to_string(),
+            error,
+        }
+    }
+}
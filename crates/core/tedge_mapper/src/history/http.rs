@@ -0,0 +1,111 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use tracing::info;
+
+use crate::history::{
+    query::{run_query, QueryRequest},
+    store::Store,
+};
+
+const MEASUREMENTS_PATH: &str = "/measurements";
+
+/// Serve the range queries on `GET /measurements?measurement=...&from=...`,
+/// with the same parameters and responses as the MQTT queries.
+pub async fn serve(bind: SocketAddr, store: Arc<Mutex<Store>>) -> Result<(), hyper::Error> {
+    let make_service = make_service_fn(move |_| {
+        let store = store.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let store = store.clone();
+                async move { Ok::<_, Infallible>(handle(store, request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&bind)?.serve(make_service);
+    info!("Serving the measurement queries on http://{}", bind);
+    server.await
+}
+
+async fn handle(store: Arc<Mutex<Store>>, request: Request<Body>) -> Response<Body> {
+    if request.uri().path() != MEASUREMENTS_PATH {
+        return empty_response(StatusCode::NOT_FOUND);
+    }
+    if request.method() != Method::GET {
+        return empty_response(StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    let query = request.uri().query().unwrap_or_default();
+    let request = serde_urlencoded::from_str::<QueryRequest>(query).map_err(Into::into);
+    let (status, response) = match run_query(store, request).await {
+        Ok(response) => (StatusCode::OK, response),
+        Err(response) => (StatusCode::BAD_REQUEST, response),
+    };
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(response))
+        .expect("a valid response")
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .expect("a valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{config::HistoryConfig, store::Sample};
+    use tempfile::TempDir;
+    use time::macros::datetime;
+
+    async fn get(store: &Arc<Mutex<Store>>, uri: &str) -> Response<Body> {
+        handle(
+            store.clone(),
+            Request::get(uri).body(Body::empty()).unwrap(),
+        )
+        .await
+    }
+
+    async fn body(response: Response<Body>) -> serde_json::Value {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn measurements_are_queried_over_http() {
+        let dir = TempDir::new().unwrap();
+        let mut store = Store::open(dir.path(), &HistoryConfig::default()).unwrap();
+        let time = datetime!(2022-05-10 12:00:00 UTC);
+        store
+            .append(&[Sample::new(time, None, "temperature", 20.0)], time)
+            .unwrap();
+        let store = Arc::new(Mutex::new(store));
+
+        let response = get(&store, "/measurements?measurement=temperature").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body(response).await["samples"][0],
+            serde_json::json!({"time": "2022-05-10T12:00:00Z", "measurement": "temperature", "value": 20.0})
+        );
+
+        let response = get(&store, "/measurements?from=yesterday").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(body(response).await["error"].is_string());
+
+        let response = get(&store, "/alarms").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    core::{component::TEdgeComponent, mapper::create_mapper},
+    history::{
+        config::{HistoryConfig, DEFAULT_STORE_DIR},
+        converter::HistoryConverter,
+        http,
+        store::Store,
+    },
+};
+
+use async_trait::async_trait;
+use clock::WallClock;
+use tedge_config::{ConfigSettingAccessor, MqttBindAddressSetting, MqttPortSetting, TEdgeConfig};
+use tedge_utils::file::create_directory_with_user_group;
+use tracing::{error, info, info_span, Instrument};
+
+const HISTORY_MAPPER_NAME: &str = "tedge-mapper-history";
+const HISTORY_CONFIG_FILE: &str = "history/history.toml";
+
+pub struct HistoryMapper {}
+
+impl HistoryMapper {
+    pub fn new() -> HistoryMapper {
+        HistoryMapper {}
+    }
+}
+
+#[async_trait]
+impl TEdgeComponent for HistoryMapper {
+    fn session_name(&self) -> &str {
+        HISTORY_MAPPER_NAME
+    }
+
+    async fn init(&self, cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        info!("Initialize tedge mapper history");
+        let config_dir = cfg_dir.display().to_string();
+        create_directory_with_user_group(
+            &format!("{config_dir}/history"),
+            "tedge",
+            "tedge",
+            0o775,
+        )?;
+        create_directory_with_user_group("/var/tedge", "tedge", "tedge", 0o775)?;
+        create_directory_with_user_group(DEFAULT_STORE_DIR, "tedge", "tedge", 0o775)?;
+
+        self.init_session(HistoryConverter::in_topic_filter())
+            .await?;
+        Ok(())
+    }
+
+    async fn start(&self, tedge_config: TEdgeConfig, cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
+        let mqtt_host = tedge_config.query(MqttBindAddressSetting)?.to_string();
+
+        let config = HistoryConfig::read_from_file(&cfg_dir.join(HISTORY_CONFIG_FILE))?;
+        let store = Store::open(&config.store_dir(), &config)?;
+        let store = Arc::new(Mutex::new(store));
+
+        if config.http_enable {
+            let store = store.clone();
+            tokio::spawn(async move {
+                if let Err(err) = http::serve(config.http_bind, store).await {
+                    error!("The HTTP endpoint for measurement queries failed: {}", err);
+                }
+            });
+        }
+
+        let converter = Box::new(HistoryConverter::new(store, Box::new(WallClock)));
+        let mut mapper =
+            create_mapper(HISTORY_MAPPER_NAME, mqtt_host, mqtt_port, converter).await?;
+
+        mapper
+            .run()
+            .instrument(info_span!(HISTORY_MAPPER_NAME))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+mod config;
+mod converter;
+pub mod error;
+mod http;
+pub mod mapper;
+mod query;
+mod store;
@@ -0,0 +1,259 @@
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+};
+
+use serde::Deserialize;
+use serde_json::json;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::history::{
+    error::HistoryError,
+    store::{QueryResult, Sample, Store},
+};
+
+const DEFAULT_LIMIT: usize = 1000;
+const MAX_LIMIT: usize = 10000;
+
+/// A range query, as received on `tedge/query/measurements` or as the query string of the HTTP endpoint:
+///
+/// ```json
+/// {
+///     "id": "42",
+///     "measurement": "temperature",
+///     "device": "child1",
+///     "from": "2022-05-10T12:00:00Z",
+///     "to": "2022-05-10T13:00:00Z",
+///     "limit": 100
+/// }
+/// ```
+///
+/// All the fields are optional, except the `id` of an MQTT query
+/// which is answered on `tedge/query/measurements/response/<id>`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryRequest {
+    /// An identifier chosen by the requester, returned as is along the response.
+    pub id: Option<String>,
+
+    pub measurement: Option<String>,
+
+    pub device: Option<String>,
+
+    pub from: Option<String>,
+
+    pub to: Option<String>,
+
+    pub limit: Option<usize>,
+}
+
+/// A validated range query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// A measurement name, a `group/name` path or a group name matching all the measurements of the group.
+    /// All the measurements are returned if none.
+    pub measurement: Option<String>,
+
+    /// The child device, or none for the main device.
+    pub device: Option<String>,
+
+    /// The start of the range, inclusive.
+    pub from: Option<OffsetDateTime>,
+
+    /// The end of the range, exclusive.
+    pub to: Option<OffsetDateTime>,
+
+    pub limit: usize,
+}
+
+impl TryFrom<QueryRequest> for Query {
+    type Error = HistoryError;
+
+    fn try_from(request: QueryRequest) -> Result<Self, Self::Error> {
+        let limit = request.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(HistoryError::InvalidLimit { max: MAX_LIMIT });
+        }
+
+        Ok(Query {
+            measurement: request.measurement,
+            device: request.device,
+            from: parse_time("from", request.from)?,
+            to: parse_time("to", request.to)?,
+            limit,
+        })
+    }
+}
+
+impl QueryRequest {
+    /// The id of an MQTT query, used as the last level of the response topic.
+    pub fn response_topic_level(&self) -> Result<&str, HistoryError> {
+        match self.id.as_deref() {
+            Some(id) if !id.is_empty() && !id.contains(&['/', '+', '#'][..]) => Ok(id),
+            _ => Err(HistoryError::InvalidQueryId),
+        }
+    }
+}
+
+impl Query {
+    pub fn matches(&self, sample: &Sample) -> bool {
+        if sample.device != self.device {
+            return false;
+        }
+
+        if let Some(measurement) = &self.measurement {
+            let in_group = sample
+                .measurement
+                .strip_prefix(measurement.as_str())
+                .map_or(false, |name| name.starts_with('/'));
+            if &sample.measurement != measurement && !in_group {
+                return false;
+            }
+        }
+
+        let time = sample.timestamp();
+        self.from.map_or(true, |from| from <= time) && self.to.map_or(true, |to| time < to)
+    }
+}
+
+/// Run a query on the store, returning the JSON response: either the samples or an error.
+///
+/// The store files are read on a blocking thread, the store lock being held only there.
+pub async fn run_query(
+    store: Arc<Mutex<Store>>,
+    request: Result<QueryRequest, HistoryError>,
+) -> Result<String, String> {
+    let id = request.as_ref().ok().and_then(|request| request.id.clone());
+    let result = match request.and_then(Query::try_from) {
+        Ok(query) => tokio::task::spawn_blocking(move || {
+            let store = store.lock().expect("the store lock is not poisoned");
+            store.query(&query)
+        })
+        .await
+        .expect("the query task doesn't panic"),
+        Err(err) => Err(err),
+    };
+
+    let with_id = |mut response: serde_json::Value| {
+        if let Some(id) = &id {
+            response["id"] = id.as_str().into();
+        }
+        response.to_string()
+    };
+    match result {
+        Ok(result) => Ok(with_id(samples_to_json(result))),
+        Err(err) => Err(with_id(json!({ "error": err.to_string() }))),
+    }
+}
+
+fn samples_to_json(result: QueryResult) -> serde_json::Value {
+    let samples: Vec<_> = result
+        .samples
+        .iter()
+        .map(|sample| {
+            json!({
+                "time": sample.timestamp().format(&Rfc3339).unwrap_or_default(),
+                "measurement": sample.measurement,
+                "value": sample.value,
+            })
+        })
+        .collect();
+    json!({
+        "samples": samples,
+        "truncated": result.truncated,
+    })
+}
+
+fn parse_time(
+    field: &'static str,
+    value: Option<String>,
+) -> Result<Option<OffsetDateTime>, HistoryError> {
+    value
+        .map(|value| {
+            OffsetDateTime::parse(&value, &Rfc3339)
+                .map_err(|_| HistoryError::InvalidTime { field, value })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use time::macros::datetime;
+
+    #[test]
+    fn a_query_matches_samples_of_a_device_in_a_time_range() {
+        let query = Query::try_from(QueryRequest {
+            measurement: Some("current".into()),
+            device: Some("child1".into()),
+            from: Some("2022-05-10T12:00:00Z".into()),
+            to: Some("2022-05-10T14:00:00+01:00".into()),
+            ..QueryRequest::default()
+        })
+        .unwrap();
+
+        let t0 = datetime!(2022-05-10 12:00:00 UTC);
+        let t1 = datetime!(2022-05-10 13:00:00 UTC);
+        assert!(query.matches(&Sample::new(t0, Some("child1"), "current", 1.0)));
+        assert!(query.matches(&Sample::new(t0, Some("child1"), "current/L1", 1.0)));
+        assert!(!query.matches(&Sample::new(t0, Some("child1"), "current_L1", 1.0)));
+        assert!(!query.matches(&Sample::new(t0, None, "current", 1.0)));
+        assert!(!query.matches(&Sample::new(t1, Some("child1"), "current", 1.0)));
+    }
+
+    #[test]
+    fn invalid_queries_are_rejected() {
+        let request = QueryRequest {
+            from: Some("yesterday".into()),
+            ..QueryRequest::default()
+        };
+        assert_matches!(
+            Query::try_from(request),
+            Err(HistoryError::InvalidTime { field: "from", .. })
+        );
+
+        let request = QueryRequest {
+            limit: Some(0),
+            ..QueryRequest::default()
+        };
+        assert_matches!(
+            Query::try_from(request),
+            Err(HistoryError::InvalidLimit { .. })
+        );
+    }
+
+    #[test]
+    fn mqtt_queries_must_have_an_id_usable_as_a_topic_level() {
+        let with_id = |id: Option<&str>| QueryRequest {
+            id: id.map(Into::into),
+            ..QueryRequest::default()
+        };
+
+        assert_eq!(with_id(Some("42")).response_topic_level().unwrap(), "42");
+        for id in [None, Some(""), Some("a/b"), Some("+"), Some("#")] {
+            assert_matches!(
+                with_id(id).response_topic_level(),
+                Err(HistoryError::InvalidQueryId)
+            );
+        }
+    }
+
+    #[test]
+    fn query_strings_are_parsed() {
+        let request: QueryRequest = serde_urlencoded::from_str(
+            "measurement=temperature&from=2022-05-10T12%3A00%3A00Z&limit=10",
+        )
+        .unwrap();
+
+        assert_eq!(
+            request,
+            QueryRequest {
+                measurement: Some("temperature".into()),
+                from: Some("2022-05-10T12:00:00Z".into()),
+                limit: Some(10),
+                ..QueryRequest::default()
+            }
+        );
+    }
+}
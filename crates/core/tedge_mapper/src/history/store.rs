@@ -0,0 +1,382 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::history::{config::HistoryConfig, error::HistoryError, query::Query};
+
+const SEGMENT_EXTENSION: &str = "jsonl";
+
+// A segment is closed once it holds this fraction of the store size,
+// so the size cap is enforced with a reasonable granularity.
+const SEGMENTS_PER_STORE: u64 = 8;
+
+/// A numeric sample of a measurement, as stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// The time of the sample, in milliseconds since the epoch.
+    #[serde(rename = "t")]
+    pub time: i64,
+
+    /// The child device of the sample, none for the main device.
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+
+    /// The name of the measurement, or `group/name` for a measurement of a group.
+    #[serde(rename = "m")]
+    pub measurement: String,
+
+    #[serde(rename = "v")]
+    pub value: f64,
+}
+
+impl Sample {
+    pub fn new(
+        time: OffsetDateTime,
+        device: Option<&str>,
+        measurement: &str,
+        value: f64,
+    ) -> Sample {
+        Sample {
+            time: (time.unix_timestamp_nanos() / 1_000_000) as i64,
+            device: device.map(str::to_string),
+            measurement: measurement.to_string(),
+            value,
+        }
+    }
+
+    pub fn timestamp(&self) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp_nanos(self.time as i128 * 1_000_000)
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+    }
+}
+
+/// The samples returned by a query, in chronological order.
+#[derive(Debug, Default, PartialEq)]
+pub struct QueryResult {
+    pub samples: Vec<Sample>,
+
+    /// True when more samples than the query limit were matching.
+    pub truncated: bool,
+}
+
+/// A size-capped store of measurement samples, with a retention period.
+///
+/// The samples are appended as JSON lines to segment files, named after the time the segment was started.
+/// A new segment is started periodically, and the oldest segments are removed
+/// once expired or when the store exceeds its maximum size.
+///
+/// The retention applies to the reception time of the samples, not to their own timestamps.
+pub struct Store {
+    dir: PathBuf,
+    max_size: u64,
+    retention: i64,
+    segment_span: i64,
+    segments: VecDeque<Segment>,
+    current: Option<File>,
+}
+
+#[derive(Debug)]
+struct Segment {
+    start: i64,
+    size: u64,
+}
+
+impl Store {
+    /// Open the store, creating its directory if missing.
+    pub fn open(dir: &Path, config: &HistoryConfig) -> Result<Store, HistoryError> {
+        fs::create_dir_all(dir).map_err(|err| HistoryError::from_io(dir, err))?;
+
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(dir).map_err(|err| HistoryError::from_io(dir, err))? {
+            let path = entry.map_err(|err| HistoryError::from_io(dir, err))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+                continue;
+            }
+            let start = match path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok())
+            {
+                Some(start) => start,
+                None => {
+                    warn!("Ignoring unexpected file {} in the store", path.display());
+                    continue;
+                }
+            };
+            let size = fs::metadata(&path)
+                .map_err(|err| HistoryError::from_io(&path, err))?
+                .len();
+            segments.push(Segment { start, size });
+        }
+        segments.sort_by_key(|segment| segment.start);
+        info!(
+            "Measurement store {} opened with {} segments",
+            dir.display(),
+            segments.len()
+        );
+
+        Ok(Store {
+            dir: dir.to_path_buf(),
+            max_size: config.max_size,
+            retention: config.retention as i64,
+            segment_span: config.segment as i64,
+            segments: segments.into(),
+            current: None,
+        })
+    }
+
+    /// Append samples received at the given time, removing the samples out of retention or exceeding the size cap.
+    pub fn append(&mut self, samples: &[Sample], now: OffsetDateTime) -> Result<(), HistoryError> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = String::new();
+        for sample in samples {
+            lines.push_str(&serde_json::to_string(sample).expect("a sample is serializable"));
+            lines.push('\n');
+        }
+
+        let () = self.rotate_if_needed(now.unix_timestamp(), lines.len() as u64);
+        let segment = self.segments.back_mut().expect("a current segment");
+        let path = self.dir.join(segment_file_name(segment.start));
+        let file = match self.current.as_mut() {
+            Some(file) => file,
+            None => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .map_err(|err| HistoryError::from_io(&path, err))?;
+                self.current.insert(file)
+            }
+        };
+        file.write_all(lines.as_bytes())
+            .map_err(|err| HistoryError::from_io(&path, err))?;
+        segment.size += lines.len() as u64;
+
+        self.evict(now)
+    }
+
+    /// Remove the segments out of retention, and the oldest segments exceeding the size cap.
+    pub fn evict(&mut self, now: OffsetDateTime) -> Result<(), HistoryError> {
+        let expiry = now.unix_timestamp() - self.retention;
+        while let Some(oldest) = self.segments.front() {
+            let expired = oldest.start + self.segment_span <= expiry;
+            let too_large = self.segments.len() > 1 && self.size() > self.max_size;
+            if !expired && !too_large {
+                break;
+            }
+
+            let oldest = self.segments.pop_front().expect("an oldest segment");
+            if self.segments.is_empty() {
+                self.current = None;
+            }
+            let path = self.dir.join(segment_file_name(oldest.start));
+            fs::remove_file(&path).map_err(|err| HistoryError::from_io(&path, err))?;
+        }
+        Ok(())
+    }
+
+    /// The samples matching a query, in chronological order.
+    ///
+    /// Lines that cannot be read back, say the last line of a segment truncated by a crash, are skipped.
+    pub fn query(&self, query: &Query) -> Result<QueryResult, HistoryError> {
+        let mut samples = Vec::new();
+        for segment in self.segments.iter() {
+            let path = self.dir.join(segment_file_name(segment.start));
+            let file = File::open(&path).map_err(|err| HistoryError::from_io(&path, err))?;
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|err| HistoryError::from_io(&path, err))?;
+                if let Ok(sample) = serde_json::from_str::<Sample>(&line) {
+                    if query.matches(&sample) {
+                        samples.push(sample);
+                    }
+                }
+            }
+        }
+
+        samples.sort_by_key(|sample| sample.time);
+        let truncated = samples.len() > query.limit;
+        samples.truncate(query.limit);
+        Ok(QueryResult { samples, truncated })
+    }
+
+    /// The size of the store in bytes.
+    pub fn size(&self) -> u64 {
+        self.segments.iter().map(|segment| segment.size).sum()
+    }
+
+    fn rotate_if_needed(&mut self, now: i64, len: u64) {
+        let segment_max_size = (self.max_size / SEGMENTS_PER_STORE).max(1);
+        // A segment left by a previous run is not reopened, as its last line might be truncated
+        let rotate = match self.segments.back() {
+            None => true,
+            Some(segment) => {
+                segment.start + self.segment_span <= now
+                    || (segment.size > 0 && self.current.is_none())
+                    || (segment.size > 0 && segment.size + len > segment_max_size)
+            }
+        };
+        if rotate {
+            // The segments are named after their start, which must be unique
+            let start = match self.segments.back() {
+                Some(segment) if segment.start >= now => segment.start + 1,
+                _ => now,
+            };
+            self.segments.push_back(Segment { start, size: 0 });
+            self.current = None;
+        }
+    }
+}
+
+fn segment_file_name(start: i64) -> String {
+    format!("{start}.{SEGMENT_EXTENSION}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use time::{macros::datetime, Duration};
+
+    const T0: OffsetDateTime = datetime!(2022-05-10 12:00:00 UTC);
+
+    fn config(max_size: u64) -> HistoryConfig {
+        HistoryConfig {
+            max_size,
+            retention: 3600,
+            segment: 600,
+            ..HistoryConfig::default()
+        }
+    }
+
+    fn all_samples() -> Query {
+        Query {
+            measurement: None,
+            device: None,
+            from: None,
+            to: None,
+            limit: 1000,
+        }
+    }
+
+    fn temperature(time: OffsetDateTime, value: f64) -> Sample {
+        Sample::new(time, None, "temperature", value)
+    }
+
+    #[test]
+    fn samples_are_persisted() {
+        let dir = TempDir::new().unwrap();
+        let samples = vec![
+            temperature(T0, 20.0),
+            temperature(T0 + Duration::SECOND, 21.0),
+        ];
+
+        let mut store = Store::open(dir.path(), &config(1024 * 1024)).unwrap();
+        store.append(&samples, T0).unwrap();
+        drop(store);
+
+        let store = Store::open(dir.path(), &config(1024 * 1024)).unwrap();
+        let result = store.query(&all_samples()).unwrap();
+        assert_eq!(result.samples, samples);
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn samples_out_of_retention_are_removed() {
+        let dir = TempDir::new().unwrap();
+        let mut store = Store::open(dir.path(), &config(1024 * 1024)).unwrap();
+
+        store.append(&[temperature(T0, 20.0)], T0).unwrap();
+        store
+            .append(&[temperature(T0, 21.0)], T0 + Duration::minutes(30))
+            .unwrap();
+        assert_eq!(store.query(&all_samples()).unwrap().samples.len(), 2);
+
+        // The first segment, started at T0 and closed at T0 + 10 min, expires at T0 + 70 min
+        store.evict(T0 + Duration::minutes(70)).unwrap();
+        let samples = store.query(&all_samples()).unwrap().samples;
+        assert_eq!(samples, vec![temperature(T0, 21.0)]);
+
+        store.evict(T0 + Duration::minutes(100)).unwrap();
+        assert!(store.query(&all_samples()).unwrap().samples.is_empty());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        // A new segment is started on the next samples
+        store
+            .append(&[temperature(T0, 22.0)], T0 + Duration::minutes(100))
+            .unwrap();
+        assert_eq!(store.query(&all_samples()).unwrap().samples.len(), 1);
+    }
+
+    #[test]
+    fn the_oldest_samples_are_removed_when_the_store_is_full() {
+        let dir = TempDir::new().unwrap();
+        let mut store = Store::open(dir.path(), &config(1000)).unwrap();
+
+        for i in 0..100 {
+            let time = T0 + Duration::seconds(i);
+            store.append(&[temperature(time, i as f64)], time).unwrap();
+        }
+
+        assert!(store.size() <= 1000);
+        let samples = store.query(&all_samples()).unwrap().samples;
+        assert_eq!(
+            samples.last(),
+            Some(&temperature(T0 + Duration::seconds(99), 99.0))
+        );
+        assert!(samples.len() < 100);
+    }
+
+    #[test]
+    fn query_results_are_sorted_and_limited() {
+        let dir = TempDir::new().unwrap();
+        let mut store = Store::open(dir.path(), &config(1024 * 1024)).unwrap();
+
+        let samples = vec![
+            temperature(T0 + Duration::SECOND * 2, 22.0),
+            temperature(T0, 20.0),
+            temperature(T0 + Duration::SECOND, 21.0),
+        ];
+        store.append(&samples, T0).unwrap();
+
+        let result = store
+            .query(&Query {
+                limit: 2,
+                ..all_samples()
+            })
+            .unwrap();
+        assert_eq!(
+            result,
+            QueryResult {
+                samples: vec![
+                    temperature(T0, 20.0),
+                    temperature(T0 + Duration::SECOND, 21.0)
+                ],
+                truncated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_lines_are_skipped() {
+        let dir = TempDir::new().unwrap();
+        let mut store = Store::open(dir.path(), &config(1024 * 1024)).unwrap();
+        store.append(&[temperature(T0, 20.0)], T0).unwrap();
+
+        let segment = dir.path().join(segment_file_name(T0.unix_timestamp()));
+        let mut file = OpenOptions::new().append(true).open(segment).unwrap();
+        file.write_all(br#"{"t":1652184000000,"m":"temp"#).unwrap();
+
+        let samples = store.query(&all_samples()).unwrap().samples;
+        assert_eq!(samples, vec![temperature(T0, 20.0)]);
+    }
+}
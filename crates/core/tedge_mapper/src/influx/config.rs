@@ -0,0 +1,203 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+};
+
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{core::config::read_config_file, influx::error::InfluxError};
+
+const DEFAULT_PROMETHEUS_PORT: u16 = 9108;
+
+/// The outputs of the influx mapper, as defined in `/etc/tedge/influx/influx.toml`:
+///
+/// ```toml
+/// [influxdb]
+/// url = "http://localhost:8086/api/v2/write?org=acme&bucket=tedge"
+/// token = "my-token"
+///
+/// [prometheus]
+/// enable = true
+/// bind = "127.0.0.1:9108"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InfluxConfig {
+    #[serde(default)]
+    pub influxdb: InfluxDbConfig,
+
+    #[serde(default)]
+    pub prometheus: PrometheusConfig,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InfluxDbConfig {
+    /// The write endpoint of InfluxDB, the line protocol being posted to this url.
+    pub url: Option<String>,
+
+    /// The InfluxDB API token, if required.
+    pub token: Option<String>,
+
+    /// A file the line protocol is appended to, as an alternative to the url.
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrometheusConfig {
+    #[serde(default = "default_prometheus_enable")]
+    pub enable: bool,
+
+    #[serde(default = "default_prometheus_bind")]
+    pub bind: SocketAddr,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        PrometheusConfig {
+            enable: default_prometheus_enable(),
+            bind: default_prometheus_bind(),
+        }
+    }
+}
+
+/// Where the line protocol is written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineOutput {
+    Http { url: Url, token: Option<String> },
+    File(PathBuf),
+}
+
+impl InfluxConfig {
+    /// Read the configuration from a TOML file.
+    ///
+    /// A missing file is not an error: only the Prometheus endpoint is then enabled.
+    pub fn read_from_file(path: &Path) -> Result<Self, InfluxError> {
+        let content = read_config_file(path).map_err(|error| InfluxError::FromIo {
+            path: path.display().to_string(),
+            error,
+        })?;
+        match content {
+            Some(content) => Self::parse(&content),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, InfluxError> {
+        let config: InfluxConfig = toml::from_str(content)?;
+        let output = config.line_output()?;
+        if output.is_none() && !config.prometheus.enable {
+            return Err(InfluxError::NoOutput);
+        }
+        Ok(config)
+    }
+
+    /// The output of the line protocol, if any.
+    pub fn line_output(&self) -> Result<Option<LineOutput>, InfluxError> {
+        match (&self.influxdb.url, &self.influxdb.file) {
+            (Some(_), Some(_)) => Err(InfluxError::ConflictingOutputs),
+            (Some(url), None) => {
+                let invalid_url = |reason: String| InfluxError::InvalidUrl {
+                    url: url.clone(),
+                    reason,
+                };
+                let parsed_url = Url::parse(url).map_err(|err| invalid_url(err.to_string()))?;
+                if !matches!(parsed_url.scheme(), "http" | "https") {
+                    return Err(invalid_url("expecting an http or https url".into()));
+                }
+                Ok(Some(LineOutput::Http {
+                    url: parsed_url,
+                    token: self.influxdb.token.clone(),
+                }))
+            }
+            (None, Some(file)) => Ok(Some(LineOutput::File(file.clone()))),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+fn default_prometheus_enable() -> bool {
+    true
+}
+
+fn default_prometheus_bind() -> SocketAddr {
+    (Ipv4Addr::LOCALHOST, DEFAULT_PROMETHEUS_PORT).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn the_prometheus_endpoint_is_enabled_by_default() {
+        let config = InfluxConfig::parse("").unwrap();
+
+        assert_eq!(config.line_output().unwrap(), None);
+        assert!(config.prometheus.enable);
+        assert_eq!(config.prometheus.bind.to_string(), "127.0.0.1:9108");
+    }
+
+    #[test]
+    fn the_line_protocol_is_written_to_an_url_or_a_file() {
+        let config = InfluxConfig::parse(
+            r#"
+            [influxdb]
+            url = "http://localhost:8086/api/v2/write?org=acme&bucket=tedge"
+            token = "secret"
+            "#,
+        )
+        .unwrap();
+        assert_matches!(
+            config.line_output().unwrap(),
+            Some(LineOutput::Http { url, token: Some(token) })
+                if url.path() == "/api/v2/write" && token == "secret"
+        );
+
+        let config = InfluxConfig::parse(
+            r#"
+            [influxdb]
+            file = "/var/log/tedge/measurements.lp"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.line_output().unwrap(),
+            Some(LineOutput::File("/var/log/tedge/measurements.lp".into()))
+        );
+    }
+
+    #[test]
+    fn invalid_outputs_are_rejected() {
+        assert_matches!(
+            InfluxConfig::parse(
+                r#"
+                [influxdb]
+                url = "http://localhost:8086/api/v2/write"
+                file = "/tmp/measurements.lp"
+                "#
+            ),
+            Err(InfluxError::ConflictingOutputs)
+        );
+        assert_matches!(
+            InfluxConfig::parse(
+                r#"
+                [influxdb]
+                url = "ftp://localhost/measurements"
+                "#
+            ),
+            Err(InfluxError::InvalidUrl { .. })
+        );
+        assert_matches!(
+            InfluxConfig::parse(
+                r#"
+                [prometheus]
+                enable = false
+                "#
+            ),
+            Err(InfluxError::NoOutput)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use clock::Clock;
+use mqtt_channel::{Message, TopicFilter};
+use thin_edge_json::{influx::InfluxLineSerializer, serialize::ThinEdgeJsonSerializationError};
+
+use crate::{
+    core::{
+        converter::*,
+        error::*,
+        measurements::{
+            for_each_numeric_cbor_measurement, for_each_numeric_measurement, MeasurementTopic,
+        },
+    },
+    influx::{prometheus::LatestValues, writer::LineWriter},
+};
+
+pub struct InfluxConverter {
+    pub(crate) writer: Option<LineWriter>,
+    pub(crate) latest_values: Option<Arc<Mutex<LatestValues>>>,
+    pub(crate) clock: Box<dyn Clock>,
+    pub(crate) mapper_config: MapperConfig,
+}
+
+impl InfluxConverter {
+    pub fn new(
+        writer: Option<LineWriter>,
+        latest_values: Option<Arc<Mutex<LatestValues>>>,
+        clock: Box<dyn Clock>,
+    ) -> Self {
+        // Nothing is published on MQTT, but the errors
+        let mapper_config = MapperConfig {
+            in_topic_filter: Self::in_topic_filter(),
+            out_topic: make_valid_topic_or_panic("tedge/errors"),
+            errors_topic: make_valid_topic_or_panic("tedge/errors"),
+        };
+        InfluxConverter {
+            writer,
+            latest_values,
+            clock,
+            mapper_config,
+        }
+    }
+
+    pub fn in_topic_filter() -> TopicFilter {
+        make_valid_topic_filter_or_panic("tedge/measurements/#")
+    }
+}
+
+#[async_trait]
+impl Converter for InfluxConverter {
+    type Error = ConversionError;
+
+    fn get_mapper_config(&self) -> &MapperConfig {
+        &self.mapper_config
+    }
+
+    async fn try_convert(&mut self, input: &Message) -> Result<Vec<Message>, Self::Error> {
+        let topic = match MeasurementTopic::parse(&input.topic.name) {
+            Some(topic) => topic,
+            None => return Ok(vec![]),
+        };
+
+        if let Some(writer) = self.writer.as_ref() {
+            let mut serializer = InfluxLineSerializer::new_with_timestamp(Some(self.clock.now()));
+            if let Some(device) = topic.device {
+                serializer = serializer.with_tag("device", device);
+            }
+            if topic.cbor {
+                let () = thin_edge_json::cbor::parse_slice(input.payload_bytes(), &mut serializer)?;
+            } else {
+                let () = thin_edge_json::parser::parse_str(input.payload_str()?, &mut serializer)?;
+            }
+            let lines = serializer
+                .into_string()
+                .map_err(ThinEdgeJsonSerializationError::from)?;
+            writer.write(lines);
+        }
+
+        if let Some(latest_values) = self.latest_values.as_ref() {
+            let mut latest_values = latest_values
+                .lock()
+                .expect("the latest values lock is not poisoned");
+            let update = |measurement: &str, _, value| {
+                latest_values.update(topic.device, measurement, value)
+            };
+            if topic.cbor {
+                let () = for_each_numeric_cbor_measurement(input.payload_bytes(), update)?;
+            } else {
+                let () = for_each_numeric_measurement(input.payload_str()?, update)?;
+            }
+        }
+
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::influx::config::LineOutput;
+    use assert_matches::assert_matches;
+    use mqtt_channel::Topic;
+    use serde_json::json;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use time::macros::datetime;
+
+    struct TestClock;
+
+    impl Clock for TestClock {
+        fn now(&self) -> clock::Timestamp {
+            datetime!(2022-04-15 05:20:00 UTC)
+        }
+    }
+
+    fn measurements(topic: &str, payload: impl Into<Vec<u8>>) -> Message {
+        Message::new(&Topic::new_unchecked(topic), payload.into())
+    }
+
+    #[tokio::test]
+    async fn measurements_are_written_as_line_protocol() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("measurements.lp");
+        let writer = LineWriter::spawn(LineOutput::File(path.clone())).unwrap();
+        let mut converter = InfluxConverter::new(Some(writer), None, Box::new(TestClock));
+
+        let inputs = [
+            measurements("tedge/measurements", r#"{"temperature": 23}"#),
+            measurements("tedge/measurements/child1", r#"{"current": {"L1": 9.5}}"#),
+            measurements(
+                "tedge/measurements/cbor",
+                thin_edge_json::cbor::to_vec(&json!({"pressure": 98})).unwrap(),
+            ),
+            measurements("tedge/measurements/meta", r#"{"temperature": "°C"}"#),
+        ];
+        for input in inputs.iter() {
+            assert!(converter.convert(input).await.is_empty());
+        }
+
+        let expected = concat!(
+            "temperature value=23 1650000000000000000\n",
+            "current,device=child1 L1=9.5 1650000000000000000\n",
+            "pressure value=98 1650000000000000000\n",
+        );
+        for _ in 0..50 {
+            if std::fs::read_to_string(&path).unwrap_or_default() == expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn the_latest_values_are_updated() {
+        let latest_values = Arc::new(Mutex::new(LatestValues::default()));
+        let mut converter =
+            InfluxConverter::new(None, Some(latest_values.clone()), Box::new(TestClock));
+
+        converter
+            .convert(&measurements(
+                "tedge/measurements",
+                r#"{"temperature": 23}"#,
+            ))
+            .await;
+        converter
+            .convert(&measurements(
+                "tedge/measurements",
+                r#"{"temperature": 24}"#,
+            ))
+            .await;
+
+        assert_eq!(
+            latest_values.lock().unwrap().render(),
+            "# TYPE temperature gauge\ntemperature 24\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_measurements_are_reported() {
+        let latest_values = Arc::new(Mutex::new(LatestValues::default()));
+        let mut converter = InfluxConverter::new(None, Some(latest_values), Box::new(TestClock));
+
+        let result = converter
+            .try_convert(&measurements(
+                "tedge/measurements",
+                r#"{"temperature": "hot""#,
+            ))
+            .await;
+
+        assert_matches!(result, Err(ConversionError::FromThinEdgeJsonParser(_)));
+    }
+}
@@ -0,0 +1,24 @@
+#[derive(thiserror::Error, Debug)]
+pub enum InfluxError {
+    #[error("Cannot read the influx mapper configuration from {path}: {error}")]
+    FromIo {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[error("Invalid influx mapper configuration: {0}")]
+    FromToml(#[from] toml::de::Error),
+
+    #[error("Invalid InfluxDB url {url:?}: {reason}")]
+    InvalidUrl { url: String, reason: String },
+
+    #[error("Invalid influx mapper configuration: the line protocol is written either to an url or to a file, not both")]
+    ConflictingOutputs,
+
+    #[error("Cannot create the InfluxDB client: {0}")]
+    FromReqwest(#[from] reqwest::Error),
+
+    #[error("Invalid influx mapper configuration: neither an InfluxDB url, nor a file, nor the Prometheus endpoint is configured")]
+    NoOutput,
+}
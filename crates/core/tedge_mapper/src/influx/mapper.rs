@@ -0,0 +1,86 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    core::{component::TEdgeComponent, mapper::create_mapper},
+    influx::{
+        config::InfluxConfig, converter::InfluxConverter, prometheus, prometheus::LatestValues,
+        writer::LineWriter,
+    },
+};
+
+use async_trait::async_trait;
+use clock::WallClock;
+use tedge_config::{ConfigSettingAccessor, MqttBindAddressSetting, MqttPortSetting, TEdgeConfig};
+use tedge_utils::file::create_directory_with_user_group;
+use tracing::{error, info, info_span, Instrument};
+
+const INFLUX_MAPPER_NAME: &str = "tedge-mapper-influx";
+const INFLUX_CONFIG_FILE: &str = "influx/influx.toml";
+
+pub struct InfluxMapper {}
+
+impl InfluxMapper {
+    pub fn new() -> InfluxMapper {
+        InfluxMapper {}
+    }
+}
+
+#[async_trait]
+impl TEdgeComponent for InfluxMapper {
+    fn session_name(&self) -> &str {
+        INFLUX_MAPPER_NAME
+    }
+
+    async fn init(&self, cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        info!("Initialize tedge mapper influx");
+        let config_dir = cfg_dir.display().to_string();
+        create_directory_with_user_group(&format!("{config_dir}/influx"), "tedge", "tedge", 0o775)?;
+
+        self.init_session(InfluxConverter::in_topic_filter())
+            .await?;
+        Ok(())
+    }
+
+    async fn start(&self, tedge_config: TEdgeConfig, cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
+        let mqtt_host = tedge_config.query(MqttBindAddressSetting)?.to_string();
+
+        let config = InfluxConfig::read_from_file(&cfg_dir.join(INFLUX_CONFIG_FILE))?;
+        let writer = config
+            .line_output()?
+            .map(|output| {
+                info!("Writing the measurements to {:?}", output);
+                LineWriter::spawn(output)
+            })
+            .transpose()?;
+
+        let latest_values = config.prometheus.enable.then(|| {
+            let latest_values = Arc::new(Mutex::new(LatestValues::default()));
+            let served_values = latest_values.clone();
+            let bind = config.prometheus.bind;
+            tokio::spawn(async move {
+                if let Err(err) = prometheus::serve(bind, served_values).await {
+                    error!("The Prometheus endpoint failed: {}", err);
+                }
+            });
+            latest_values
+        });
+
+        let converter = Box::new(InfluxConverter::new(
+            writer,
+            latest_values,
+            Box::new(WallClock),
+        ));
+        let mut mapper = create_mapper(INFLUX_MAPPER_NAME, mqtt_host, mqtt_port, converter).await?;
+
+        mapper
+            .run()
+            .instrument(info_span!(INFLUX_MAPPER_NAME))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+mod config;
+mod converter;
+mod error;
+pub mod mapper;
+mod prometheus;
+mod writer;
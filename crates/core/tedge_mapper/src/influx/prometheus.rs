@@ -0,0 +1,165 @@
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt::Write,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use tracing::info;
+
+const METRICS_PATH: &str = "/metrics";
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// The latest value of each numeric measurement, per device, exposed as Prometheus gauges.
+#[derive(Debug, Default)]
+pub struct LatestValues {
+    gauges: BTreeMap<String, BTreeMap<Option<String>, f64>>,
+}
+
+impl LatestValues {
+    /// Update the value of a measurement given by its path (`name` or `group/name`).
+    pub fn update(&mut self, device: Option<&str>, measurement: &str, value: f64) {
+        self.gauges
+            .entry(metric_name(measurement))
+            .or_default()
+            .insert(device.map(str::to_string), value);
+    }
+
+    /// Render the values using the Prometheus text exposition format.
+    ///
+    /// The measurements of the child devices are labelled with their device id.
+    pub fn render(&self) -> String {
+        let mut metrics = String::new();
+        for (name, values) in self.gauges.iter() {
+            let _ = writeln!(metrics, "# TYPE {name} gauge");
+            for (device, value) in values.iter() {
+                match device {
+                    Some(device) => {
+                        let device = escape_label_value(device);
+                        let _ = writeln!(metrics, "{name}{{device=\"{device}\"}} {value}");
+                    }
+                    None => {
+                        let _ = writeln!(metrics, "{name} {value}");
+                    }
+                }
+            }
+        }
+        metrics
+    }
+}
+
+/// Serve the latest values on `GET /metrics`.
+pub async fn serve(
+    bind: SocketAddr,
+    latest_values: Arc<Mutex<LatestValues>>,
+) -> Result<(), hyper::Error> {
+    let make_service = make_service_fn(move |_| {
+        let latest_values = latest_values.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let latest_values = latest_values.clone();
+                async move { Ok::<_, Infallible>(handle(&latest_values, request)) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&bind)?.serve(make_service);
+    info!(
+        "Serving the Prometheus metrics on http://{}{}",
+        bind, METRICS_PATH
+    );
+    server.await
+}
+
+fn handle(latest_values: &Mutex<LatestValues>, request: Request<Body>) -> Response<Body> {
+    let status = if request.uri().path() != METRICS_PATH {
+        StatusCode::NOT_FOUND
+    } else if request.method() != Method::GET {
+        StatusCode::METHOD_NOT_ALLOWED
+    } else {
+        let metrics = latest_values
+            .lock()
+            .expect("the latest values lock is not poisoned")
+            .render();
+        return Response::builder()
+            .header(CONTENT_TYPE, METRICS_CONTENT_TYPE)
+            .body(Body::from(metrics))
+            .expect("a valid response");
+    };
+
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .expect("a valid response")
+}
+
+/// A Prometheus metric name for a measurement: `group/name` is named `group_name`.
+fn metric_name(measurement: &str) -> String {
+    let mut name: String = measurement
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_latest_values_are_rendered_as_gauges() {
+        let mut values = LatestValues::default();
+        values.update(None, "temperature", 20.0);
+        values.update(None, "temperature", 21.5);
+        values.update(Some("child1"), "temperature", 30.0);
+        values.update(None, "current/L1", 9.5);
+        values.update(Some("child\"2"), "1st_sensor", 1.0);
+
+        assert_eq!(
+            values.render(),
+            concat!(
+                "# TYPE _1st_sensor gauge\n",
+                "_1st_sensor{device=\"child\\\"2\"} 1\n",
+                "# TYPE current_L1 gauge\n",
+                "current_L1 9.5\n",
+                "# TYPE temperature gauge\n",
+                "temperature 21.5\n",
+                "temperature{device=\"child1\"} 30\n",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn the_metrics_are_served_on_the_metrics_path() {
+        let mut values = LatestValues::default();
+        values.update(None, "temperature", 20.0);
+        let values = Mutex::new(values);
+
+        let response = handle(
+            &values,
+            Request::get("/metrics").body(Body::empty()).unwrap(),
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "# TYPE temperature gauge\ntemperature 20\n");
+
+        let response = handle(&values, Request::get("/").body(Body::empty()).unwrap());
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+use tracing::{error, info, warn};
+
+use crate::influx::{config::LineOutput, error::InfluxError};
+
+// The lines queued while a write is in progress are sent together, up to this size.
+const MAX_BATCH_SIZE: usize = 512 * 1024;
+
+// The number of line protocol chunks queued while InfluxDB is slow or unavailable, the next ones being dropped.
+const LINE_CHANNEL_SIZE: usize = 1024;
+
+// A request to InfluxDB is abandoned after this delay, its lines being dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Writes the line protocol in the background, so the mapper is not slowed down by a slow or unavailable InfluxDB.
+///
+/// The lines that cannot be written, or that cannot be queued while InfluxDB is lagging behind, are dropped with a log.
+pub struct LineWriter {
+    lines: Sender<String>,
+    lagging: AtomicBool,
+}
+
+impl LineWriter {
+    pub fn spawn(output: LineOutput) -> Result<LineWriter, InfluxError> {
+        LineWriter::spawn_with_timeout(output, WRITE_TIMEOUT)
+    }
+
+    fn spawn_with_timeout(
+        output: LineOutput,
+        timeout: Duration,
+    ) -> Result<LineWriter, InfluxError> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        let (lines, receiver) = mpsc::channel(LINE_CHANNEL_SIZE);
+        tokio::spawn(write_lines(output, client, receiver));
+        Ok(LineWriter {
+            lines,
+            lagging: AtomicBool::new(false),
+        })
+    }
+
+    pub fn write(&self, lines: String) {
+        match self.lines.try_send(lines) {
+            Ok(()) => {
+                if self.lagging.swap(false, Ordering::Relaxed) {
+                    info!("The line protocol writer caught up: the measurements are written again");
+                }
+            }
+            Err(TrySendError::Full(_)) => {
+                // Logged once, and not for each measurement dropped
+                if !self.lagging.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "The line protocol writer is lagging behind: the measurements are dropped"
+                    );
+                }
+            }
+            Err(TrySendError::Closed(_)) => error!("The line protocol writer is stopped"),
+        }
+    }
+}
+
+async fn write_lines(output: LineOutput, client: reqwest::Client, mut receiver: Receiver<String>) {
+    while let Some(mut batch) = receiver.recv().await {
+        while batch.len() < MAX_BATCH_SIZE {
+            match receiver.try_recv() {
+                Ok(lines) => batch.push_str(&lines),
+                Err(_) => break,
+            }
+        }
+
+        if let Err(err) = output.write(&client, batch).await {
+            error!("Cannot write the measurements: {}", err);
+        }
+    }
+}
+
+impl LineOutput {
+    async fn write(&self, client: &reqwest::Client, lines: String) -> Result<(), String> {
+        match self {
+            LineOutput::Http { url, token } => {
+                let mut request = client
+                    .post(url.clone())
+                    .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+                    .body(lines);
+                if let Some(token) = token {
+                    request = request.header(AUTHORIZATION, format!("Token {token}"));
+                }
+                request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map(|_| ())
+                    .map_err(|err| err.to_string())
+            }
+            LineOutput::File(path) => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(lines.as_bytes()))
+                .map_err(|err| format!("{}: {}", path.display(), err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::mock;
+    use reqwest::Url;
+    use tempfile::TempDir;
+    use tokio::net::TcpListener;
+
+    /// An HTTP server accepting the connections, but never answering.
+    async fn unresponsive_server() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/v2/write", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+        Url::parse(&url).unwrap()
+    }
+
+    #[tokio::test]
+    async fn lines_are_posted_to_influxdb() {
+        let _influxdb = mock("POST", "/api/v2/write?org=acme&bucket=tedge")
+            .match_header("authorization", "Token secret")
+            .match_body("temperature value=23\n")
+            .with_status(204)
+            .expect(1)
+            .create();
+        let output = LineOutput::Http {
+            url: Url::parse(&format!(
+                "{}/api/v2/write?org=acme&bucket=tedge",
+                mockito::server_url()
+            ))
+            .unwrap(),
+            token: Some("secret".into()),
+        };
+
+        let result = output
+            .write(&reqwest::Client::new(), "temperature value=23\n".into())
+            .await;
+
+        assert_eq!(result, Ok(()));
+        _influxdb.assert();
+    }
+
+    #[tokio::test]
+    async fn lines_are_appended_to_a_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("measurements.lp");
+        let output = LineOutput::File(path.clone());
+
+        let client = reqwest::Client::new();
+        output.write(&client, "a value=1\n".into()).await.unwrap();
+        output.write(&client, "b value=2\n".into()).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "a value=1\nb value=2\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_to_an_unresponsive_influxdb_time_out() {
+        let output = LineOutput::Http {
+            url: unresponsive_server().await,
+            token: None,
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            output.write(&client, "temperature value=23\n".into()),
+        )
+        .await
+        .expect("the write is abandoned on timeout");
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn lines_are_dropped_when_influxdb_lags_behind() {
+        let output = LineOutput::Http {
+            url: unresponsive_server().await,
+            token: None,
+        };
+        let writer = LineWriter::spawn_with_timeout(output, Duration::from_secs(60)).unwrap();
+
+        // The writes never block, even once the queue is full
+        for _ in 0..2 * LINE_CHANNEL_SIZE {
+            writer.write("temperature value=23\n".into());
+        }
+
+        assert!(writer.lagging.load(Ordering::Relaxed));
+    }
+}
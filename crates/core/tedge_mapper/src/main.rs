@@ -2,7 +2,8 @@ use std::{fmt, path::PathBuf};
 
 use crate::{
     az::mapper::AzureMapper, c8y::mapper::CumulocityMapper, collectd::mapper::CollectdMapper,
-    core::component::TEdgeComponent,
+    core::component::TEdgeComponent, history::mapper::HistoryMapper, influx::mapper::InfluxMapper,
+    rules::mapper::RulesMapper, sysmon::mapper::SysmonMapper,
 };
 use clap::Parser;
 use flockfile::check_another_instance_is_not_running;
@@ -13,12 +14,20 @@ mod az;
 mod c8y;
 mod collectd;
 mod core;
+mod history;
+mod influx;
+mod rules;
+mod sysmon;
 
 fn lookup_component(component_name: &MapperName) -> Box<dyn TEdgeComponent> {
     match component_name {
         MapperName::Az => Box::new(AzureMapper::new()),
         MapperName::Collectd => Box::new(CollectdMapper::new()),
         MapperName::C8y => Box::new(CumulocityMapper::new()),
+        MapperName::History => Box::new(HistoryMapper::new()),
+        MapperName::Influx => Box::new(InfluxMapper::new()),
+        MapperName::Rules => Box::new(RulesMapper::new()),
+        MapperName::Sysmon => Box::new(SysmonMapper::new()),
     }
 }
 
@@ -61,6 +70,10 @@ pub enum MapperName {
     Az,
     C8y,
     Collectd,
+    History,
+    Influx,
+    Rules,
+    Sysmon,
 }
 
 impl fmt::Display for MapperName {
@@ -69,6 +82,10 @@ impl fmt::Display for MapperName {
             MapperName::Az => write!(f, "tedge-mapper-az"),
             MapperName::C8y => write!(f, "tedge-mapper-c8y"),
             MapperName::Collectd => write!(f, "tedge-mapper-collectd"),
+            MapperName::History => write!(f, "tedge-mapper-history"),
+            MapperName::Influx => write!(f, "tedge-mapper-influx"),
+            MapperName::Rules => write!(f, "tedge-mapper-rules"),
+            MapperName::Sysmon => write!(f, "tedge-mapper-sysmon"),
         }
     }
 }
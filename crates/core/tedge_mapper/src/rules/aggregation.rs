@@ -0,0 +1,428 @@
+use std::{collections::HashSet, path::Path, time::Duration};
+
+use mqtt_channel::Topic;
+use serde::Deserialize;
+use thin_edge_json::{
+    measurement::MeasurementVisitor,
+    serialize::{ThinEdgeJsonSerializationError, ThinEdgeJsonSerializer},
+};
+use time::OffsetDateTime;
+use tracing::warn;
+
+use crate::{
+    core::measurements::is_valid_measurement_path,
+    rules::{config::read_rules_file, error::RulesError},
+};
+
+const DEFAULT_AGGREGATION_INPUT_TOPIC: &str = "tedge/measurements";
+const DEFAULT_AGGREGATION_OUTPUT_TOPIC: &str = "tedge/aggregates";
+
+/// The aggregation rules, as defined in `/etc/tedge/rules/aggregations.toml`:
+///
+/// ```toml
+/// input_topic = "tedge/raw/measurements"
+/// output_topic = "tedge/measurements"
+///
+/// [[aggregations]]
+/// measurement = "temperature"
+/// window = 60
+/// functions = ["min", "max", "mean"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AggregationRules {
+    /// The topic of the raw measurements.
+    #[serde(default = "default_input_topic")]
+    pub input_topic: String,
+
+    /// The topic on which the aggregates are published as thin-edge JSON.
+    #[serde(default = "default_output_topic")]
+    pub output_topic: String,
+
+    #[serde(default)]
+    pub aggregations: Vec<AggregationRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AggregationRule {
+    /// The name of the measurement, or `group/name` for a measurement of a group.
+    pub measurement: String,
+
+    /// The duration of the aggregation window, in seconds.
+    pub window: u64,
+
+    #[serde(default = "AggregateFunction::all")]
+    pub functions: Vec<AggregateFunction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateFunction {
+    Min,
+    Max,
+    Mean,
+    Last,
+    Count,
+}
+
+/// The summary of the samples of a measurement received within a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub start: OffsetDateTime,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub last: f64,
+    pub count: usize,
+}
+
+fn default_input_topic() -> String {
+    DEFAULT_AGGREGATION_INPUT_TOPIC.into()
+}
+
+fn default_output_topic() -> String {
+    DEFAULT_AGGREGATION_OUTPUT_TOPIC.into()
+}
+
+impl Default for AggregationRules {
+    fn default() -> Self {
+        AggregationRules {
+            input_topic: default_input_topic(),
+            output_topic: default_output_topic(),
+            aggregations: vec![],
+        }
+    }
+}
+
+impl AggregationRules {
+    /// Read the rules from a TOML file.
+    ///
+    /// A missing file is not an error: there are simply no rules.
+    pub fn read_from_file(path: &Path) -> Result<Self, RulesError> {
+        match read_rules_file(path)? {
+            Some(content) => Self::parse(&content),
+            None => {
+                warn!("No aggregation rules defined: {} not found", path.display());
+                Ok(Self::default())
+            }
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, RulesError> {
+        let rules: AggregationRules = toml::from_str(content)?;
+
+        for topic in [&rules.input_topic, &rules.output_topic] {
+            if Topic::new(topic).is_err() {
+                return Err(RulesError::InvalidTopic(topic.clone()));
+            }
+        }
+        if rules.input_topic == rules.output_topic {
+            return Err(RulesError::AggregationLoop(rules.output_topic));
+        }
+
+        let mut measurements = HashSet::new();
+        for rule in rules.aggregations.iter() {
+            let () = rule.validate()?;
+            if !measurements.insert(&rule.measurement) {
+                return Err(RulesError::DuplicatedAggregation(rule.measurement.clone()));
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+impl AggregationRule {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window)
+    }
+
+    fn validate(&self) -> Result<(), RulesError> {
+        if !is_valid_measurement_path(&self.measurement) {
+            return Err(RulesError::InvalidAggregatedMeasurement(
+                self.measurement.clone(),
+            ));
+        }
+
+        if self.window == 0 {
+            return Err(RulesError::InvalidAggregationWindow(
+                self.measurement.clone(),
+            ));
+        }
+
+        if self.functions.is_empty() {
+            return Err(RulesError::NoAggregateFunctions(self.measurement.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Serialize an aggregate as thin-edge JSON.
+    ///
+    /// The aggregates of a measurement `name` are published as a group `name`,
+    /// with a measurement per aggregate function: `{"name": {"min": ..., "max": ...}}`.
+    /// The aggregates of a measurement `group/name` are published in the group `group`,
+    /// with a measurement per aggregate function: `{"group": {"name_min": ..., "name_max": ...}}`.
+    pub fn to_thin_edge_json(
+        &self,
+        aggregate: &Aggregate,
+    ) -> Result<String, ThinEdgeJsonSerializationError> {
+        let (group, prefix) = match self.measurement.split_once('/') {
+            Some((group, name)) => (group, format!("{name}_")),
+            None => (self.measurement.as_str(), String::new()),
+        };
+
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        let () = serializer.visit_timestamp(aggregate.start)?;
+        let () = serializer.visit_start_group(group)?;
+        for function in self.functions.iter() {
+            let name = format!("{prefix}{}", function.name());
+            let () = serializer.visit_measurement(&name, function.apply(aggregate))?;
+        }
+        let () = serializer.visit_end_group()?;
+        serializer.into_string()
+    }
+}
+
+impl AggregateFunction {
+    fn all() -> Vec<AggregateFunction> {
+        vec![
+            AggregateFunction::Min,
+            AggregateFunction::Max,
+            AggregateFunction::Mean,
+            AggregateFunction::Last,
+            AggregateFunction::Count,
+        ]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+            AggregateFunction::Mean => "mean",
+            AggregateFunction::Last => "last",
+            AggregateFunction::Count => "count",
+        }
+    }
+
+    fn apply(self, aggregate: &Aggregate) -> f64 {
+        match self {
+            AggregateFunction::Min => aggregate.min,
+            AggregateFunction::Max => aggregate.max,
+            AggregateFunction::Mean => aggregate.sum / aggregate.count as f64,
+            AggregateFunction::Last => aggregate.last,
+            AggregateFunction::Count => aggregate.count as f64,
+        }
+    }
+}
+
+impl Aggregate {
+    /// Aggregate a sequence of samples, given in order.
+    ///
+    /// Return `None` if there are no samples.
+    pub fn new(samples: impl IntoIterator<Item = (OffsetDateTime, f64)>) -> Option<Aggregate> {
+        let mut samples = samples.into_iter();
+        let (start, value) = samples.next()?;
+        let mut aggregate = Aggregate {
+            start,
+            min: value,
+            max: value,
+            sum: value,
+            last: value,
+            count: 1,
+        };
+        for (time, value) in samples {
+            aggregate.start = aggregate.start.min(time);
+            aggregate.min = aggregate.min.min(value);
+            aggregate.max = aggregate.max.max(value);
+            aggregate.sum += value;
+            aggregate.last = value;
+            aggregate.count += 1;
+        }
+        Some(aggregate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+    use time::macros::datetime;
+
+    #[test]
+    fn parse_rules() {
+        let rules = AggregationRules::parse(
+            r#"
+            input_topic = "tedge/raw/measurements"
+            output_topic = "tedge/measurements"
+
+            [[aggregations]]
+            measurement = "temperature"
+            window = 60
+            functions = ["min", "max"]
+
+            [[aggregations]]
+            measurement = "battery/level"
+            window = 300
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rules,
+            AggregationRules {
+                input_topic: "tedge/raw/measurements".into(),
+                output_topic: "tedge/measurements".into(),
+                aggregations: vec![
+                    AggregationRule {
+                        measurement: "temperature".into(),
+                        window: 60,
+                        functions: vec![AggregateFunction::Min, AggregateFunction::Max],
+                    },
+                    AggregationRule {
+                        measurement: "battery/level".into(),
+                        window: 300,
+                        functions: AggregateFunction::all(),
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn default_topics() {
+        let rules = AggregationRules::parse("").unwrap();
+
+        assert_eq!(rules, AggregationRules::default());
+        assert_eq!(rules.input_topic, "tedge/measurements");
+        assert_eq!(rules.output_topic, "tedge/aggregates");
+    }
+
+    #[test]
+    fn reject_aggregates_published_on_their_input_topic() {
+        let result = AggregationRules::parse(r#"output_topic = "tedge/measurements""#);
+
+        assert_matches!(result, Err(RulesError::AggregationLoop(_)));
+    }
+
+    #[test]
+    fn reject_wildcard_topics() {
+        let result = AggregationRules::parse(r#"input_topic = "tedge/measurements/+""#);
+
+        assert_matches!(result, Err(RulesError::InvalidTopic(_)));
+    }
+
+    #[test]
+    fn reject_empty_window() {
+        let result = AggregationRules::parse(
+            r#"
+            [[aggregations]]
+            measurement = "temperature"
+            window = 0
+            "#,
+        );
+
+        assert_matches!(result, Err(RulesError::InvalidAggregationWindow(_)));
+    }
+
+    #[test]
+    fn reject_unknown_function() {
+        let result = AggregationRules::parse(
+            r#"
+            [[aggregations]]
+            measurement = "temperature"
+            window = 60
+            functions = ["median"]
+            "#,
+        );
+
+        assert_matches!(result, Err(RulesError::FromToml(_)));
+    }
+
+    #[test]
+    fn reject_duplicated_aggregations() {
+        let result = AggregationRules::parse(
+            r#"
+            [[aggregations]]
+            measurement = "temperature"
+            window = 60
+
+            [[aggregations]]
+            measurement = "temperature"
+            window = 600
+            "#,
+        );
+
+        assert_matches!(result, Err(RulesError::DuplicatedAggregation(_)));
+    }
+
+    #[test]
+    fn aggregate_samples() {
+        let aggregate = Aggregate::new(vec![
+            (datetime!(2021-04-23 19:00:01 +05:00), 20.0),
+            (datetime!(2021-04-23 19:00:00 +05:00), 24.0),
+            (datetime!(2021-04-23 19:00:02 +05:00), 19.0),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            aggregate,
+            Aggregate {
+                start: datetime!(2021-04-23 19:00:00 +05:00),
+                min: 19.0,
+                max: 24.0,
+                sum: 63.0,
+                last: 19.0,
+                count: 3,
+            }
+        );
+        assert_eq!(Aggregate::new(vec![]), None);
+    }
+
+    #[test]
+    fn serialize_aggregates() {
+        let aggregate = Aggregate::new(vec![
+            (datetime!(2021-04-23 19:00:00 +05:00), 20.0),
+            (datetime!(2021-04-23 19:00:01 +05:00), 24.0),
+        ])
+        .unwrap();
+        let rule = AggregationRule {
+            measurement: "temperature".into(),
+            window: 60,
+            functions: AggregateFunction::all(),
+        };
+
+        let json = rule.to_thin_edge_json(&aggregate).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            json!({
+                "time": "2021-04-23T19:00:00+05:00",
+                "temperature": {"min": 20.0, "max": 24.0, "mean": 22.0, "last": 24.0, "count": 2.0}
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_aggregates_of_grouped_measurements() {
+        let aggregate =
+            Aggregate::new(vec![(datetime!(2021-04-23 19:00:00 +05:00), 80.0)]).unwrap();
+        let rule = AggregationRule {
+            measurement: "battery/level".into(),
+            window: 60,
+            functions: vec![AggregateFunction::Mean, AggregateFunction::Count],
+        };
+
+        let json = rule.to_thin_edge_json(&aggregate).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            json!({
+                "time": "2021-04-23T19:00:00+05:00",
+                "battery": {"level_mean": 80.0, "level_count": 1.0}
+            })
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use batcher::{BatchConfigBuilder, BatchDriver, BatchDriverInput, BatchDriverOutput, Batcher};
+use futures::SinkExt;
+use mqtt_channel::{Message, Topic, UnboundedSender};
+use thin_edge_json::parser::ThinEdgeJsonParserError;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tracing::error;
+
+use crate::{
+    core::measurements::for_each_numeric_measurement,
+    rules::aggregation::{Aggregate, AggregationRule, AggregationRules},
+};
+
+const AGGREGATION_CHANNEL_SIZE: usize = 100;
+
+/// A sample of an aggregated measurement.
+#[derive(Debug)]
+pub struct Sample {
+    /// Unique to each sample, as the batcher drops the events sharing both a key and a time.
+    /// Two samples received together with the same value are still counted twice.
+    sequence: u64,
+    received_at: OffsetDateTime,
+    time: OffsetDateTime,
+    value: f64,
+}
+
+impl batcher::Batchable for Sample {
+    // All the samples received within a window are aggregated together, none being a duplicate.
+    type Key = u64;
+
+    fn key(&self) -> Self::Key {
+        self.sequence
+    }
+
+    fn event_time(&self) -> OffsetDateTime {
+        self.received_at
+    }
+}
+
+/// Aggregates the samples of measurements over time windows,
+/// publishing the aggregates as thin-edge JSON.
+///
+/// Each aggregated measurement has its own batcher, running in the background,
+/// the aggregates being sent on the output given on spawn.
+#[derive(Debug)]
+pub struct Aggregator {
+    input_topic: String,
+    inputs: HashMap<String, Sender<BatchDriverInput<Sample>>>,
+    sequence: u64,
+}
+
+impl Aggregator {
+    pub fn spawn(rules: AggregationRules, output: UnboundedSender<Message>) -> Self {
+        let output_topic = Topic::new_unchecked(&rules.output_topic);
+        let mut inputs = HashMap::new();
+        for rule in rules.aggregations {
+            // The samples are timestamped on reception, before being queued to the batcher,
+            // which drops the samples older than the delivery jitter.
+            // Accepting samples delayed by up to a window, no sample of a burst is lost,
+            // at the cost of publishing each aggregate one window after its end.
+            let window = u32::try_from(rule.window().as_millis()).unwrap_or(u32::MAX);
+            let batch_config = BatchConfigBuilder::new()
+                .event_jitter(window)
+                .delivery_jitter(window)
+                .message_leap_limit(0)
+                .build();
+            let (input, batch_input) = mpsc::channel(AGGREGATION_CHANNEL_SIZE);
+            let (batch_output, batches) = mpsc::channel(AGGREGATION_CHANNEL_SIZE);
+
+            let driver = BatchDriver::new(Batcher::new(batch_config), batch_input, batch_output);
+            tokio::spawn(async move {
+                if let Err(err) = driver.run().await {
+                    error!("Error in measurement aggregator: {}", err);
+                }
+            });
+
+            inputs.insert(rule.measurement.clone(), input);
+            tokio::spawn(publish_aggregates(
+                rule,
+                batches,
+                output_topic.clone(),
+                output.clone(),
+            ));
+        }
+
+        Aggregator {
+            input_topic: rules.input_topic,
+            inputs,
+            sequence: 0,
+        }
+    }
+
+    pub fn input_topic(&self) -> &str {
+        &self.input_topic
+    }
+
+    /// Add the samples of thin-edge JSON measurements to their aggregation windows.
+    pub async fn add(&mut self, input: &str) -> Result<(), ThinEdgeJsonParserError> {
+        let received_at = OffsetDateTime::now_utc();
+        let mut samples = vec![];
+        let () = for_each_numeric_measurement(input, |measurement, timestamp, value| {
+            if self.inputs.contains_key(measurement) {
+                samples.push((measurement.to_string(), timestamp, value));
+            }
+        })?;
+
+        for (measurement, timestamp, value) in samples {
+            if let Some(input) = self.inputs.get(&measurement) {
+                self.sequence += 1;
+                let sample = Sample {
+                    sequence: self.sequence,
+                    received_at,
+                    time: timestamp.unwrap_or(received_at),
+                    value,
+                };
+                if let Err(err) = input.send(BatchDriverInput::Event(sample)).await {
+                    error!("Failed to aggregate a measurement: {}", err);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn publish_aggregates(
+    rule: AggregationRule,
+    mut batches: Receiver<BatchDriverOutput<Sample>>,
+    output_topic: Topic,
+    mut output: UnboundedSender<Message>,
+) {
+    while let Some(BatchDriverOutput::Batch(mut batch)) = batches.recv().await {
+        batch.sort_by_key(|sample| sample.sequence);
+        let aggregate = match Aggregate::new(batch.into_iter().map(|s| (s.time, s.value))) {
+            Some(aggregate) => aggregate,
+            None => continue,
+        };
+        match rule.to_thin_edge_json(&aggregate) {
+            Ok(payload) => {
+                let _ = output.send(Message::new(&output_topic, payload)).await;
+            }
+            Err(err) => error!(
+                "Failed to publish the aggregates of {}: {}",
+                rule.measurement, err
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn samples_are_aggregated_over_a_window() {
+        let rules = AggregationRules::parse(
+            r#"
+            [[aggregations]]
+            measurement = "temperature"
+            window = 1
+            functions = ["min", "max", "count"]
+            "#,
+        )
+        .unwrap();
+        let (output, mut aggregates) = futures::channel::mpsc::unbounded();
+        let mut aggregator = Aggregator::spawn(rules, output);
+
+        aggregator
+            .add(r#"{"temperature": 20, "pressure": 1000}"#)
+            .await
+            .unwrap();
+        aggregator
+            .add(r#"[{"temperature": 24}, {"temperature": 22}]"#)
+            .await
+            .unwrap();
+
+        let aggregate = tokio::time::timeout(Duration::from_secs(5), aggregates.next())
+            .await
+            .expect("aggregates to be published")
+            .unwrap();
+        assert_eq!(aggregate.topic.name, "tedge/aggregates");
+        let json: serde_json::Value =
+            serde_json::from_str(aggregate.payload_str().unwrap()).unwrap();
+        assert_eq!(
+            json["temperature"],
+            serde_json::json!({"min": 20.0, "max": 24.0, "count": 3.0})
+        );
+    }
+
+    #[tokio::test]
+    async fn no_sample_of_a_burst_is_lost() {
+        let rules = AggregationRules::parse(
+            r#"
+            [[aggregations]]
+            measurement = "temperature"
+            window = 1
+            functions = ["count"]
+            "#,
+        )
+        .unwrap();
+        let (output, mut aggregates) = futures::channel::mpsc::unbounded();
+        let mut aggregator = Aggregator::spawn(rules, output);
+
+        // Far more samples than the channel to the batcher can hold, all with the same value
+        for _ in 0..10 * AGGREGATION_CHANNEL_SIZE {
+            aggregator.add(r#"{"temperature": 20}"#).await.unwrap();
+        }
+
+        let aggregate = tokio::time::timeout(Duration::from_secs(5), aggregates.next())
+            .await
+            .expect("aggregates to be published")
+            .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(aggregate.payload_str().unwrap()).unwrap();
+        assert_eq!(
+            json["temperature"],
+            serde_json::json!({"count": (10 * AGGREGATION_CHANNEL_SIZE) as f64})
+        );
+    }
+}
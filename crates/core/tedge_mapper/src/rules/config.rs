@@ -0,0 +1,400 @@
+use std::{collections::HashSet, convert::TryFrom, fmt, path::Path};
+
+use serde::Deserialize;
+use thin_edge_json::alarm::AlarmSeverity;
+use tracing::warn;
+
+use crate::{
+    core::{config::read_config_file, measurements::is_valid_measurement_path},
+    rules::error::RulesError,
+};
+
+/// The threshold rules, as defined in `/etc/tedge/rules/thresholds.toml`:
+///
+/// ```toml
+/// [[rules]]
+/// measurement = "temperature"
+/// comparator = ">"
+/// threshold = 80.0
+/// hysteresis = 5.0
+/// severity = "major"
+/// type = "temperature_high"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdRules {
+    #[serde(default)]
+    pub rules: Vec<ThresholdRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdRule {
+    /// The name of the measurement, or `group/name` for a measurement of a group.
+    pub measurement: String,
+
+    pub comparator: Comparator,
+
+    pub threshold: f64,
+
+    /// How far the value has to move back past the threshold for the alarm to be cleared.
+    #[serde(default)]
+    pub hysteresis: f64,
+
+    pub severity: String,
+
+    #[serde(rename = "type")]
+    pub alarm_type: String,
+
+    /// The text of the alarm, a description of the crossed threshold by default.
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Comparator {
+    #[serde(rename = ">")]
+    Above,
+    #[serde(rename = ">=")]
+    AboveOrEqual,
+    #[serde(rename = "<")]
+    Below,
+    #[serde(rename = "<=")]
+    BelowOrEqual,
+}
+
+impl ThresholdRules {
+    /// Read the rules from a TOML file.
+    ///
+    /// A missing file is not an error: there are simply no rules.
+    pub fn read_from_file(path: &Path) -> Result<Self, RulesError> {
+        match read_rules_file(path)? {
+            Some(content) => Self::parse(&content),
+            None => {
+                warn!("No threshold rules defined: {} not found", path.display());
+                Ok(Self::default())
+            }
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, RulesError> {
+        let rules: ThresholdRules = toml::from_str(content)?;
+
+        let mut alarms = HashSet::new();
+        for rule in rules.rules.iter() {
+            let () = rule.validate()?;
+            if !alarms.insert((&rule.severity, &rule.alarm_type)) {
+                return Err(RulesError::DuplicatedAlarm {
+                    severity: rule.severity.clone(),
+                    alarm_type: rule.alarm_type.clone(),
+                });
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+impl ThresholdRule {
+    /// The topic on which the alarm of this rule is raised and cleared.
+    pub fn alarm_topic(&self) -> String {
+        format!("tedge/alarms/{}/{}", self.severity, self.alarm_type)
+    }
+
+    /// Check if the value trips the rule, raising the alarm.
+    pub fn is_tripped_by(&self, value: f64) -> bool {
+        self.comparator.compare(value, self.threshold)
+    }
+
+    /// Check if the value is far enough from the threshold to clear the alarm.
+    pub fn is_recovered_by(&self, value: f64) -> bool {
+        let threshold = match self.comparator {
+            Comparator::Above | Comparator::AboveOrEqual => self.threshold - self.hysteresis,
+            Comparator::Below | Comparator::BelowOrEqual => self.threshold + self.hysteresis,
+        };
+        !self.comparator.compare(value, threshold)
+    }
+
+    fn validate(&self) -> Result<(), RulesError> {
+        if self.alarm_type.is_empty() || self.alarm_type.contains(&['/', '+', '#'][..]) {
+            return Err(RulesError::InvalidAlarmType(self.alarm_type.clone()));
+        }
+
+        if AlarmSeverity::try_from(self.severity.as_str()).is_err() {
+            return Err(RulesError::InvalidSeverity {
+                severity: self.severity.clone(),
+                alarm_type: self.alarm_type.clone(),
+            });
+        }
+
+        if !is_valid_measurement_path(&self.measurement) {
+            return Err(RulesError::InvalidMeasurement {
+                measurement: self.measurement.clone(),
+                alarm_type: self.alarm_type.clone(),
+            });
+        }
+
+        if !self.threshold.is_finite() {
+            return Err(RulesError::InvalidThreshold {
+                threshold: self.threshold,
+                alarm_type: self.alarm_type.clone(),
+            });
+        }
+
+        if !self.hysteresis.is_finite() || self.hysteresis < 0.0 {
+            return Err(RulesError::InvalidHysteresis {
+                hysteresis: self.hysteresis,
+                alarm_type: self.alarm_type.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a rules file, returning `None` if there is no such file.
+pub fn read_rules_file(path: &Path) -> Result<Option<String>, RulesError> {
+    read_config_file(path).map_err(|error| RulesError::FromIo {
+        path: path.display().to_string(),
+        error,
+    })
+}
+
+impl Comparator {
+    fn compare(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparator::Above => value > threshold,
+            Comparator::AboveOrEqual => value >= threshold,
+            Comparator::Below => value < threshold,
+            Comparator::BelowOrEqual => value <= threshold,
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Comparator::Above => write!(f, ">"),
+            Comparator::AboveOrEqual => write!(f, ">="),
+            Comparator::Below => write!(f, "<"),
+            Comparator::BelowOrEqual => write!(f, "<="),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use test_case::test_case;
+
+    #[test]
+    fn parse_rules() {
+        let rules = ThresholdRules::parse(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = ">"
+            threshold = 80.0
+            hysteresis = 5.0
+            severity = "major"
+            type = "temperature_high"
+
+            [[rules]]
+            measurement = "battery/level"
+            comparator = "<="
+            threshold = 10
+            severity = "warning"
+            type = "battery_low"
+            text = "Low battery"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rules.rules,
+            vec![
+                ThresholdRule {
+                    measurement: "temperature".into(),
+                    comparator: Comparator::Above,
+                    threshold: 80.0,
+                    hysteresis: 5.0,
+                    severity: "major".into(),
+                    alarm_type: "temperature_high".into(),
+                    text: None,
+                },
+                ThresholdRule {
+                    measurement: "battery/level".into(),
+                    comparator: Comparator::BelowOrEqual,
+                    threshold: 10.0,
+                    hysteresis: 0.0,
+                    severity: "warning".into(),
+                    alarm_type: "battery_low".into(),
+                    text: Some("Low battery".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            rules.rules[1].alarm_topic(),
+            "tedge/alarms/warning/battery_low"
+        );
+    }
+
+    #[test]
+    fn no_rules() {
+        let rules = ThresholdRules::parse("").unwrap();
+
+        assert!(rules.rules.is_empty());
+    }
+
+    #[test_case(">", 80.0, true; "above")]
+    #[test_case(">", 79.0, false; "not above")]
+    #[test_case(">=", 79.0, true; "above or equal")]
+    #[test_case("<", 79.0, false; "not below")]
+    #[test_case("<=", 79.0, true; "below or equal")]
+    fn rules_are_tripped_according_to_the_comparator(comparator: &str, value: f64, tripped: bool) {
+        let rules = ThresholdRules::parse(&format!(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = "{comparator}"
+            threshold = 79.0
+            severity = "major"
+            type = "temperature"
+            "#,
+        ))
+        .unwrap();
+
+        assert_eq!(rules.rules[0].is_tripped_by(value), tripped);
+    }
+
+    #[test_case(">", 80.0, false; "above, still tripped")]
+    #[test_case(">", 77.0, false; "above, within hysteresis")]
+    #[test_case(">", 75.0, true; "above, recovered")]
+    #[test_case("<", 80.0, false; "below, within hysteresis")]
+    #[test_case("<", 85.0, true; "below, recovered")]
+    #[test_case("<=", 84.0, false; "below or equal, within hysteresis")]
+    fn rules_are_recovered_beyond_the_hysteresis(comparator: &str, value: f64, recovered: bool) {
+        let rules = ThresholdRules::parse(&format!(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = "{comparator}"
+            threshold = 80.0
+            hysteresis = 5.0
+            severity = "major"
+            type = "temperature"
+            "#,
+        ))
+        .unwrap();
+
+        assert_eq!(rules.rules[0].is_recovered_by(value), recovered);
+    }
+
+    #[test]
+    fn reject_unknown_comparator() {
+        let result = ThresholdRules::parse(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = "!="
+            threshold = 80.0
+            severity = "major"
+            type = "temperature_high"
+            "#,
+        );
+
+        assert_matches!(result, Err(RulesError::FromToml(_)));
+    }
+
+    #[test]
+    fn reject_unknown_severity() {
+        let result = ThresholdRules::parse(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = ">"
+            threshold = 80.0
+            severity = "fatal"
+            type = "temperature_high"
+            "#,
+        );
+
+        assert_matches!(result, Err(RulesError::InvalidSeverity { severity, .. }) if severity == "fatal");
+    }
+
+    #[test_case("temperature/high"; "slash")]
+    #[test_case("temperature+"; "plus")]
+    #[test_case(""; "empty")]
+    fn reject_invalid_alarm_type(alarm_type: &str) {
+        let result = ThresholdRules::parse(&format!(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = ">"
+            threshold = 80.0
+            severity = "major"
+            type = "{alarm_type}"
+            "#,
+        ));
+
+        assert_matches!(result, Err(RulesError::InvalidAlarmType(_)));
+    }
+
+    #[test_case("a/b/c"; "too deep")]
+    #[test_case("/temperature"; "empty group")]
+    #[test_case(""; "empty")]
+    fn reject_invalid_measurement(measurement: &str) {
+        let result = ThresholdRules::parse(&format!(
+            r#"
+            [[rules]]
+            measurement = "{measurement}"
+            comparator = ">"
+            threshold = 80.0
+            severity = "major"
+            type = "temperature_high"
+            "#,
+        ));
+
+        assert_matches!(result, Err(RulesError::InvalidMeasurement { .. }));
+    }
+
+    #[test]
+    fn reject_negative_hysteresis() {
+        let result = ThresholdRules::parse(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = ">"
+            threshold = 80.0
+            hysteresis = -1.0
+            severity = "major"
+            type = "temperature_high"
+            "#,
+        );
+
+        assert_matches!(result, Err(RulesError::InvalidHysteresis { .. }));
+    }
+
+    #[test]
+    fn reject_rules_raising_the_same_alarm() {
+        let result = ThresholdRules::parse(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = ">"
+            threshold = 80.0
+            severity = "major"
+            type = "temperature"
+
+            [[rules]]
+            measurement = "temperature"
+            comparator = "<"
+            threshold = -10.0
+            severity = "major"
+            type = "temperature"
+            "#,
+        );
+
+        assert_matches!(result, Err(RulesError::DuplicatedAlarm { .. }));
+    }
+}
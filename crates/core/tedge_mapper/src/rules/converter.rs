@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use mqtt_channel::{Message, TopicFilter, UnboundedReceiver};
+use tracing::debug;
+
+use crate::{
+    core::{converter::*, error::*},
+    rules::{aggregation::AggregationRules, aggregator::Aggregator, evaluator::ThresholdEvaluator},
+};
+
+const TEDGE_MEASUREMENTS_TOPIC: &str = "tedge/measurements";
+const TEDGE_ALARMS_TOPIC: &str = "tedge/alarms/";
+
+pub struct RulesConverter {
+    pub(crate) evaluator: ThresholdEvaluator,
+    pub(crate) aggregator: Aggregator,
+    pub(crate) aggregates: Option<UnboundedReceiver<Message>>,
+    pub(crate) mapper_config: MapperConfig,
+}
+
+impl RulesConverter {
+    pub fn new(evaluator: ThresholdEvaluator, aggregations: AggregationRules) -> Self {
+        let mapper_config = MapperConfig {
+            in_topic_filter: Self::in_topic_filter(&aggregations),
+            out_topic: make_valid_topic_or_panic("tedge/alarms"),
+            errors_topic: make_valid_topic_or_panic("tedge/errors"),
+        };
+        let (output, aggregates) = futures::channel::mpsc::unbounded();
+        let aggregator = Aggregator::spawn(aggregations, output);
+        RulesConverter {
+            evaluator,
+            aggregator,
+            aggregates: Some(aggregates),
+            mapper_config,
+        }
+    }
+
+    /// The alarms are watched too, to know which are currently raised,
+    /// notably those retained from a previous run.
+    pub fn in_topic_filter(aggregations: &AggregationRules) -> TopicFilter {
+        let mut topic_filter = make_valid_topic_filter_or_panic(TEDGE_MEASUREMENTS_TOPIC);
+        topic_filter.add_all(make_valid_topic_filter_or_panic("tedge/alarms/+/+"));
+        topic_filter.add_all(make_valid_topic_filter_or_panic(&aggregations.input_topic));
+        topic_filter
+    }
+}
+
+#[async_trait]
+impl Converter for RulesConverter {
+    type Error = ConversionError;
+
+    fn get_mapper_config(&self) -> &MapperConfig {
+        &self.mapper_config
+    }
+
+    async fn try_convert(&mut self, input: &Message) -> Result<Vec<Message>, Self::Error> {
+        if input.topic.name.starts_with(TEDGE_ALARMS_TOPIC) {
+            self.evaluator
+                .update_alarm(&input.topic.name, input.payload_bytes());
+            return Ok(vec![]);
+        }
+
+        // Invalid measurements are already reported on `tedge/errors` by the cloud mappers
+        if input.topic.name == self.aggregator.input_topic() {
+            if let Err(err) = self.aggregator.add(input.payload_str()?).await {
+                debug!("Ignoring invalid measurements: {}", err);
+            }
+        }
+
+        if input.topic.name != TEDGE_MEASUREMENTS_TOPIC {
+            return Ok(vec![]);
+        }
+        match self.evaluator.evaluate(input.payload_str()?) {
+            Ok(alarms) => Ok(alarms),
+            Err(err) => {
+                debug!("Ignoring invalid measurements: {}", err);
+                Ok(vec![])
+            }
+        }
+    }
+
+    fn take_async_messages(&mut self) -> Option<UnboundedReceiver<Message>> {
+        self.aggregates.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::config::ThresholdRules;
+    use mqtt_channel::Topic;
+
+    fn converter() -> RulesConverter {
+        let rules = ThresholdRules::parse(
+            r#"
+            [[rules]]
+            measurement = "temperature"
+            comparator = ">"
+            threshold = 80.0
+            severity = "critical"
+            type = "overheating"
+            "#,
+        )
+        .unwrap();
+        RulesConverter::new(ThresholdEvaluator::new(rules), AggregationRules::default())
+    }
+
+    #[tokio::test]
+    async fn measurements_crossing_a_threshold_raise_and_clear_alarms() {
+        let mut converter = converter();
+        let measurements = Topic::new_unchecked("tedge/measurements");
+
+        let alarms = converter
+            .convert(&Message::new(&measurements, r#"{"temperature": 90}"#))
+            .await;
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].topic.name, "tedge/alarms/critical/overheating");
+
+        // The raised alarm is received back
+        let alarms = converter.convert(&alarms[0]).await;
+        assert!(alarms.is_empty());
+
+        let alarms = converter
+            .convert(&Message::new(&measurements, r#"{"temperature": 20}"#))
+            .await;
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].topic.name, "tedge/alarms/critical/overheating");
+        assert!(alarms[0].payload_bytes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_measurements_are_ignored() {
+        let mut converter = converter();
+
+        let messages = converter
+            .convert(&Message::new(
+                &Topic::new_unchecked("tedge/measurements"),
+                r#"{"temperature": "hot""#,
+            ))
+            .await;
+
+        assert!(messages.is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+#[derive(thiserror::Error, Debug)]
+pub enum RulesError {
+    #[error("Cannot read the rules file {path}: {error}")]
+    FromIo {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[error("Invalid rules file: {0}")]
+    FromToml(#[from] toml::de::Error),
+
+    #[error("Invalid severity {severity:?} for the alarm {alarm_type:?}: expecting critical, major, minor or warning")]
+    InvalidSeverity {
+        severity: String,
+        alarm_type: String,
+    },
+
+    #[error("Invalid alarm type {0:?}: it must be non-empty and contain none of '/', '+' and '#'")]
+    InvalidAlarmType(String),
+
+    #[error("Invalid measurement {measurement:?} for the alarm {alarm_type:?}: expecting a name or a group/name path")]
+    InvalidMeasurement {
+        measurement: String,
+        alarm_type: String,
+    },
+
+    #[error(
+        "Invalid threshold {threshold} for the alarm {alarm_type:?}: it must be a finite number"
+    )]
+    InvalidThreshold { threshold: f64, alarm_type: String },
+
+    #[error("Invalid hysteresis {hysteresis} for the alarm {alarm_type:?}: it must be a non-negative number")]
+    InvalidHysteresis { hysteresis: f64, alarm_type: String },
+
+    #[error("Invalid aggregated measurement {0:?}: expecting a name or a group/name path")]
+    InvalidAggregatedMeasurement(String),
+
+    #[error("Invalid window for the aggregation of {0:?}: it must be at least one second")]
+    InvalidAggregationWindow(String),
+
+    #[error("No aggregate functions given for the aggregation of {0:?}")]
+    NoAggregateFunctions(String),
+
+    #[error("The measurement {0:?} is aggregated by more than one rule")]
+    DuplicatedAggregation(String),
+
+    #[error("Invalid topic {0:?}: expecting a topic name without wildcards")]
+    InvalidTopic(String),
+
+    #[error("The aggregates cannot be published on their input topic {0:?}")]
+    AggregationLoop(String),
+
+    #[error("The alarm {severity}/{alarm_type} is raised by more than one rule")]
+    DuplicatedAlarm {
+        severity: String,
+        alarm_type: String,
+    },
+}
@@ -0,0 +1,211 @@
+use mqtt_channel::{Message, Topic};
+use serde_json::json;
+use thin_edge_json::parser::ThinEdgeJsonParserError;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    core::measurements::for_each_numeric_measurement,
+    rules::config::{ThresholdRule, ThresholdRules},
+};
+
+/// Raises and clears the alarms of threshold rules, as measurements are received.
+pub struct ThresholdEvaluator {
+    rules: Vec<RuleState>,
+}
+
+struct RuleState {
+    rule: ThresholdRule,
+    topic: Topic,
+    raised: bool,
+}
+
+impl ThresholdEvaluator {
+    pub fn new(rules: ThresholdRules) -> Self {
+        let rules = rules
+            .rules
+            .into_iter()
+            .map(|rule| RuleState {
+                topic: Topic::new_unchecked(&rule.alarm_topic()),
+                rule,
+                raised: false,
+            })
+            .collect();
+        ThresholdEvaluator { rules }
+    }
+
+    /// Record the state of an alarm published on `tedge/alarms`,
+    /// notably the alarms retained from a previous run.
+    pub fn update_alarm(&mut self, topic: &str, payload: &[u8]) {
+        for state in self.rules.iter_mut() {
+            if state.topic.name == topic {
+                state.raised = !payload.is_empty();
+            }
+        }
+    }
+
+    /// Evaluate the rules against thin-edge JSON measurements,
+    /// returning the alarms to be raised or cleared.
+    pub fn evaluate(&mut self, input: &str) -> Result<Vec<Message>, ThinEdgeJsonParserError> {
+        let mut alarms = vec![];
+        let () = for_each_numeric_measurement(input, |measurement, timestamp, value| {
+            for state in self.rules.iter_mut() {
+                if state.rule.measurement == measurement {
+                    alarms.extend(state.update(timestamp, value));
+                }
+            }
+        })?;
+        Ok(alarms)
+    }
+}
+
+impl RuleState {
+    fn update(&mut self, timestamp: Option<OffsetDateTime>, value: f64) -> Option<Message> {
+        if !self.raised && self.rule.is_tripped_by(value) {
+            self.raised = true;
+            let text = self.rule.text.clone().unwrap_or_else(|| {
+                format!(
+                    "{} {} {}: the value is {}",
+                    self.rule.measurement, self.rule.comparator, self.rule.threshold, value
+                )
+            });
+            let mut alarm = json!({ "text": text });
+            if let Some(timestamp) = timestamp.and_then(|t| t.format(&Rfc3339).ok()) {
+                alarm["time"] = timestamp.into();
+            }
+            Some(Message::new(&self.topic, alarm.to_string()).with_retain())
+        } else if self.raised && self.rule.is_recovered_by(value) {
+            self.raised = false;
+            Some(Message::new(&self.topic, vec![]).with_retain())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluator() -> ThresholdEvaluator {
+        ThresholdEvaluator::new(
+            ThresholdRules::parse(
+                r#"
+                [[rules]]
+                measurement = "temperature"
+                comparator = ">"
+                threshold = 80.0
+                hysteresis = 5.0
+                severity = "major"
+                type = "temperature_high"
+
+                [[rules]]
+                measurement = "battery/level"
+                comparator = "<"
+                threshold = 10.0
+                severity = "warning"
+                type = "battery_low"
+                text = "Low battery"
+                "#,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn alarms_are_raised_once_and_cleared_beyond_the_hysteresis() {
+        let mut evaluator = evaluator();
+
+        assert!(evaluator
+            .evaluate(r#"{"temperature": 70}"#)
+            .unwrap()
+            .is_empty());
+
+        let alarms = evaluator
+            .evaluate(r#"{"time": "2021-04-23T19:00:00+05:00", "temperature": 85}"#)
+            .unwrap();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].topic.name, "tedge/alarms/major/temperature_high");
+        assert!(alarms[0].retain);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(alarms[0].payload_str().unwrap()).unwrap(),
+            json!({
+                "text": "temperature > 80: the value is 85",
+                "time": "2021-04-23T19:00:00+05:00"
+            })
+        );
+
+        assert!(evaluator
+            .evaluate(r#"{"temperature": 90}"#)
+            .unwrap()
+            .is_empty());
+        assert!(evaluator
+            .evaluate(r#"{"temperature": 78}"#)
+            .unwrap()
+            .is_empty());
+
+        let alarms = evaluator.evaluate(r#"{"temperature": 75}"#).unwrap();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].topic.name, "tedge/alarms/major/temperature_high");
+        assert!(alarms[0].retain);
+        assert!(alarms[0].payload_bytes().is_empty());
+    }
+
+    #[test]
+    fn rules_apply_to_grouped_measurements() {
+        let mut evaluator = evaluator();
+
+        let alarms = evaluator
+            .evaluate(r#"{"level": 5, "battery": {"level": 5, "voltage": 3.1}}"#)
+            .unwrap();
+
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].topic.name, "tedge/alarms/warning/battery_low");
+        assert_eq!(
+            alarms[0].payload_str().unwrap(),
+            r#"{"text":"Low battery"}"#
+        );
+    }
+
+    #[test]
+    fn each_sample_of_an_array_is_evaluated_in_order() {
+        let mut evaluator = evaluator();
+
+        let alarms = evaluator
+            .evaluate(r#"[{"temperature": 85}, {"temperature": 70}, {"temperature": 81}]"#)
+            .unwrap();
+
+        assert_eq!(alarms.len(), 3);
+        assert!(!alarms[0].payload_bytes().is_empty());
+        assert!(alarms[1].payload_bytes().is_empty());
+        assert!(!alarms[2].payload_bytes().is_empty());
+    }
+
+    #[test]
+    fn retained_alarms_are_not_raised_twice() {
+        let mut evaluator = evaluator();
+        evaluator.update_alarm(
+            "tedge/alarms/major/temperature_high",
+            br#"{"text": "temperature > 80: the value is 85"}"#,
+        );
+
+        assert!(evaluator
+            .evaluate(r#"{"temperature": 85}"#)
+            .unwrap()
+            .is_empty());
+
+        let alarms = evaluator.evaluate(r#"{"temperature": 20}"#).unwrap();
+        assert_eq!(alarms.len(), 1);
+        assert!(alarms[0].payload_bytes().is_empty());
+    }
+
+    #[test]
+    fn cleared_alarms_can_be_raised_again() {
+        let mut evaluator = evaluator();
+        let _ = evaluator.evaluate(r#"{"temperature": 85}"#).unwrap();
+        evaluator.update_alarm("tedge/alarms/major/temperature_high", b"");
+
+        let alarms = evaluator.evaluate(r#"{"temperature": 85}"#).unwrap();
+
+        assert_eq!(alarms.len(), 1);
+    }
+}
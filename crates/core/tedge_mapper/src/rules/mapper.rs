@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use crate::{
+    core::{component::TEdgeComponent, mapper::create_mapper},
+    rules::{
+        aggregation::AggregationRules, config::ThresholdRules, converter::RulesConverter,
+        evaluator::ThresholdEvaluator,
+    },
+};
+
+use async_trait::async_trait;
+use tedge_config::{ConfigSettingAccessor, MqttBindAddressSetting, MqttPortSetting, TEdgeConfig};
+use tedge_utils::file::create_directory_with_user_group;
+use tracing::{info, info_span, Instrument};
+
+const RULES_MAPPER_NAME: &str = "tedge-mapper-rules";
+const THRESHOLD_RULES_FILE: &str = "rules/thresholds.toml";
+const AGGREGATION_RULES_FILE: &str = "rules/aggregations.toml";
+
+pub struct RulesMapper {}
+
+impl RulesMapper {
+    pub fn new() -> RulesMapper {
+        RulesMapper {}
+    }
+}
+
+#[async_trait]
+impl TEdgeComponent for RulesMapper {
+    fn session_name(&self) -> &str {
+        RULES_MAPPER_NAME
+    }
+
+    async fn init(&self, cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        info!("Initialize tedge mapper rules");
+        let config_dir = cfg_dir.display().to_string();
+        create_directory_with_user_group(&format!("{config_dir}/rules"), "tedge", "tedge", 0o775)?;
+
+        let aggregations = AggregationRules::read_from_file(&cfg_dir.join(AGGREGATION_RULES_FILE))?;
+        self.init_session(RulesConverter::in_topic_filter(&aggregations))
+            .await?;
+        Ok(())
+    }
+
+    async fn start(&self, tedge_config: TEdgeConfig, cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
+        let mqtt_host = tedge_config.query(MqttBindAddressSetting)?.to_string();
+
+        let rules = ThresholdRules::read_from_file(&cfg_dir.join(THRESHOLD_RULES_FILE))?;
+        let aggregations = AggregationRules::read_from_file(&cfg_dir.join(AGGREGATION_RULES_FILE))?;
+        info!(
+            "{} threshold rules and {} aggregation rules loaded",
+            rules.rules.len(),
+            aggregations.aggregations.len()
+        );
+        let converter = Box::new(RulesConverter::new(
+            ThresholdEvaluator::new(rules),
+            aggregations,
+        ));
+
+        let mut mapper = create_mapper(RULES_MAPPER_NAME, mqtt_host, mqtt_port, converter).await?;
+
+        mapper
+            .run()
+            .instrument(info_span!(RULES_MAPPER_NAME))
+            .await?;
+
+        Ok(())
+    }
+}
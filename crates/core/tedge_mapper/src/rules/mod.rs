@@ -0,0 +1,7 @@
+mod aggregation;
+mod aggregator;
+mod config;
+mod converter;
+mod error;
+mod evaluator;
+pub mod mapper;
@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use clock::Clock;
+use mqtt_channel::{Message, TopicFilter};
+use thin_edge_json::{
+    measurement::MeasurementVisitor,
+    serialize::{ThinEdgeJsonSerializationError, ThinEdgeJsonSerializer},
+};
+use tracing::error;
+
+use crate::{
+    core::{converter::*, error::*},
+    sysmon::sampler::{MetricGroup, SystemSampler},
+};
+
+/// Publishes the system metrics on `tedge/measurements`, every interval.
+///
+/// No messages are received, but the health checks handled by the mapper.
+pub struct SysmonConverter {
+    pub(crate) sampler: SystemSampler,
+    pub(crate) interval: Duration,
+    pub(crate) clock: Box<dyn Clock>,
+    pub(crate) mapper_config: MapperConfig,
+}
+
+impl SysmonConverter {
+    pub fn new(mut sampler: SystemSampler, interval: Duration, clock: Box<dyn Clock>) -> Self {
+        // A first sample is taken right away, so the rates are available on the first tick
+        let _ = sampler.sample(Instant::now());
+
+        let mapper_config = MapperConfig {
+            in_topic_filter: TopicFilter::empty(),
+            out_topic: make_valid_topic_or_panic("tedge/measurements"),
+            errors_topic: make_valid_topic_or_panic("tedge/errors"),
+        };
+        SysmonConverter {
+            sampler,
+            interval,
+            clock,
+            mapper_config,
+        }
+    }
+
+    fn to_thin_edge_json(
+        &self,
+        metrics: &[MetricGroup],
+    ) -> Result<String, ThinEdgeJsonSerializationError> {
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        let () = serializer.visit_timestamp(self.clock.now())?;
+        for group in metrics.iter() {
+            let () = serializer.visit_start_group(&group.name)?;
+            for (name, value) in group.values.iter() {
+                let () = serializer.visit_measurement(name, *value)?;
+            }
+            let () = serializer.visit_end_group()?;
+        }
+        serializer.into_string()
+    }
+}
+
+#[async_trait]
+impl Converter for SysmonConverter {
+    type Error = ConversionError;
+
+    fn get_mapper_config(&self) -> &MapperConfig {
+        &self.mapper_config
+    }
+
+    async fn try_convert(&mut self, _input: &Message) -> Result<Vec<Message>, Self::Error> {
+        Ok(vec![])
+    }
+
+    fn tick_interval(&self) -> Option<Duration> {
+        Some(self.interval)
+    }
+
+    fn tick_messages(&mut self) -> Vec<Message> {
+        let metrics = self.sampler.sample(Instant::now());
+        if metrics.is_empty() {
+            return vec![];
+        }
+
+        match self.to_thin_edge_json(&metrics) {
+            Ok(payload) => vec![Message::new(&self.mapper_config.out_topic, payload)],
+            Err(err) => {
+                error!("Cannot publish the system metrics: {}", err);
+                vec![]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysmon::sampler::MetricGroups;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use time::macros::datetime;
+
+    struct TestClock;
+
+    impl Clock for TestClock {
+        fn now(&self) -> clock::Timestamp {
+            datetime!(2022-04-15 05:20:00 UTC)
+        }
+    }
+
+    #[test]
+    fn the_metrics_are_published_as_grouped_measurements() {
+        let root = TempDir::new().unwrap();
+        std::fs::create_dir_all(root.path().join("proc")).unwrap();
+        std::fs::write(
+            root.path().join("proc/meminfo"),
+            "MemTotal: 1000 kB\nMemFree: 200 kB\nMemAvailable: 250 kB\n",
+        )
+        .unwrap();
+        let groups = MetricGroups {
+            cpu: false,
+            memory: true,
+            disk: false,
+            network: false,
+            temperature: false,
+        };
+        let sampler = SystemSampler::with_root(root.path(), groups);
+        let mut converter =
+            SysmonConverter::new(sampler, Duration::from_secs(60), Box::new(TestClock));
+
+        assert_eq!(converter.tick_interval(), Some(Duration::from_secs(60)));
+        let messages = converter.tick_messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].topic.name, "tedge/measurements");
+        let payload: serde_json::Value =
+            serde_json::from_str(messages[0].payload_str().unwrap()).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "time": "2022-04-15T05:20:00Z",
+                "memory": {
+                    "total": 1024000.0,
+                    "available": 256000.0,
+                    "used": 768000.0,
+                    "usage": 75.0
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn nothing_is_published_when_no_metrics_are_available() {
+        let root = TempDir::new().unwrap();
+        let sampler = SystemSampler::with_root(root.path(), MetricGroups::default());
+        let mut converter =
+            SysmonConverter::new(sampler, Duration::from_secs(60), Box::new(TestClock));
+
+        assert!(converter.tick_messages().is_empty());
+    }
+}
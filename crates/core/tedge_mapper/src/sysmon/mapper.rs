@@ -0,0 +1,90 @@
+use std::{path::Path, time::Duration};
+
+use crate::{
+    core::{component::TEdgeComponent, mapper::create_mapper},
+    sysmon::{
+        converter::SysmonConverter,
+        sampler::{MetricGroups, SystemSampler},
+    },
+};
+
+use async_trait::async_trait;
+use clock::WallClock;
+use mqtt_channel::TopicFilter;
+use tedge_config::*;
+use tracing::{info, info_span, warn, Instrument};
+
+const SYSMON_MAPPER_NAME: &str = "tedge-mapper-sysmon";
+const DEFAULT_SYSMON_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct SysmonMapper {}
+
+impl SysmonMapper {
+    pub fn new() -> SysmonMapper {
+        SysmonMapper {}
+    }
+}
+
+#[async_trait]
+impl TEdgeComponent for SysmonMapper {
+    fn session_name(&self) -> &str {
+        SYSMON_MAPPER_NAME
+    }
+
+    async fn init(&self, _cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        info!("Initialize tedge mapper sysmon");
+        self.init_session(TopicFilter::empty()).await?;
+        Ok(())
+    }
+
+    async fn start(&self, tedge_config: TEdgeConfig, _cfg_dir: &Path) -> Result<(), anyhow::Error> {
+        let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
+        let mqtt_host = tedge_config.query(MqttBindAddressSetting)?.to_string();
+
+        let interval = match tedge_config.query_optional(SysmonIntervalSetting)? {
+            Some(interval) if interval.0 == 0 => {
+                anyhow::bail!(
+                    "Invalid {}: it must be at least one second",
+                    SysmonIntervalSetting::KEY
+                )
+            }
+            Some(interval) => Duration::from(interval),
+            None => DEFAULT_SYSMON_INTERVAL,
+        };
+        let groups = metric_groups(&tedge_config)?;
+        if !groups.any() {
+            warn!("All the system metrics are disabled");
+        }
+        info!(
+            "Sampling the system metrics every {:?}: {:?}",
+            interval, groups
+        );
+
+        let converter = Box::new(SysmonConverter::new(
+            SystemSampler::new(groups),
+            interval,
+            Box::new(WallClock),
+        ));
+
+        let mut mapper = create_mapper(SYSMON_MAPPER_NAME, mqtt_host, mqtt_port, converter).await?;
+
+        mapper
+            .run()
+            .instrument(info_span!(SYSMON_MAPPER_NAME))
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// The groups of metrics enabled in tedge.toml, all the groups being enabled by default.
+fn metric_groups(tedge_config: &TEdgeConfig) -> Result<MetricGroups, anyhow::Error> {
+    let enabled = |flag: Option<Flag>| flag.map(|flag| flag.is_set()).unwrap_or(true);
+    Ok(MetricGroups {
+        cpu: enabled(tedge_config.query_optional(SysmonCpuEnableSetting)?),
+        memory: enabled(tedge_config.query_optional(SysmonMemoryEnableSetting)?),
+        disk: enabled(tedge_config.query_optional(SysmonDiskEnableSetting)?),
+        network: enabled(tedge_config.query_optional(SysmonNetworkEnableSetting)?),
+        temperature: enabled(tedge_config.query_optional(SysmonTemperatureEnableSetting)?),
+    })
+}
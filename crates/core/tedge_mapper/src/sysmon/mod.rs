@@ -0,0 +1,3 @@
+mod converter;
+pub mod mapper;
+mod sampler;
@@ -0,0 +1,676 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use nix::sys::statvfs::statvfs;
+use tracing::{info, warn};
+
+/// The groups of system metrics that can be enabled or disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricGroups {
+    pub cpu: bool,
+    pub memory: bool,
+    pub disk: bool,
+    pub network: bool,
+    pub temperature: bool,
+}
+
+impl Default for MetricGroups {
+    fn default() -> Self {
+        MetricGroups {
+            cpu: true,
+            memory: true,
+            disk: true,
+            network: true,
+            temperature: true,
+        }
+    }
+}
+
+impl MetricGroups {
+    pub fn any(&self) -> bool {
+        self.cpu || self.memory || self.disk || self.network || self.temperature
+    }
+}
+
+/// A group of measurements, published as a thin-edge JSON group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricGroup {
+    pub name: String,
+    pub values: Vec<(String, f64)>,
+}
+
+impl MetricGroup {
+    fn new(name: impl Into<String>) -> Self {
+        MetricGroup {
+            name: name.into(),
+            values: vec![],
+        }
+    }
+
+    fn with_value(mut self, name: impl Into<String>, value: f64) -> Self {
+        self.values.push((name.into(), value));
+        self
+    }
+}
+
+/// Samples the system metrics from `/proc` and `/sys`.
+///
+/// The CPU usage and the network throughput are computed from the counters
+/// read by the previous sample, so nothing is reported for these before the second sample.
+///
+/// A source that cannot be read is reported once, and not at each sample, until it can be read again.
+pub struct SystemSampler {
+    root: PathBuf,
+    groups: MetricGroups,
+    cpu_times: Option<CpuTimes>,
+    network_counters: Option<(Instant, HashMap<String, NetworkCounters>)>,
+    failing_sources: HashSet<String>,
+}
+
+impl SystemSampler {
+    pub fn new(groups: MetricGroups) -> Self {
+        Self::with_root("/", groups)
+    }
+
+    /// A sampler reading `proc` and `sys` under the given root directory, rather than `/`.
+    pub fn with_root(root: impl Into<PathBuf>, groups: MetricGroups) -> Self {
+        SystemSampler {
+            root: root.into(),
+            groups,
+            cpu_times: None,
+            network_counters: None,
+            failing_sources: HashSet::new(),
+        }
+    }
+
+    pub fn sample(&mut self, now: Instant) -> Vec<MetricGroup> {
+        let mut metrics = vec![];
+        if self.groups.cpu {
+            let cpu = self.sample_cpu();
+            metrics.extend(self.report("CPU metrics", cpu).flatten());
+        }
+        if self.groups.memory {
+            let memory = self.sample_memory();
+            metrics.extend(self.report("memory metrics", memory).flatten());
+        }
+        if self.groups.disk {
+            let disks = self.sample_disks();
+            metrics.extend(self.report("disk metrics", disks).into_iter().flatten());
+        }
+        if self.groups.network {
+            let network = self.sample_network(now);
+            metrics.extend(
+                self.report("network metrics", network)
+                    .into_iter()
+                    .flatten(),
+            );
+        }
+        if self.groups.temperature {
+            metrics.extend(self.sample_temperatures());
+        }
+        metrics
+    }
+
+    /// Warn on the first error of a source, and not on the next ones, until the source is read again.
+    fn report<T, E: std::fmt::Display>(&mut self, source: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                if self.failing_sources.remove(source) {
+                    info!("The {} are sampled again", source);
+                }
+                Some(value)
+            }
+            Err(err) => {
+                if self.failing_sources.insert(source.to_string()) {
+                    warn!("Cannot sample the {}: {}", source, err);
+                }
+                None
+            }
+        }
+    }
+
+    fn path(&self, path: &str) -> PathBuf {
+        self.root.join(path)
+    }
+
+    fn sample_cpu(&mut self) -> io::Result<Option<MetricGroup>> {
+        let stat = fs::read_to_string(self.path("proc/stat"))?;
+        let cpu_times = stat
+            .lines()
+            .find_map(CpuTimes::parse)
+            .ok_or_else(|| invalid_data("no cpu line in /proc/stat"))?;
+        let previous = self.cpu_times.replace(cpu_times);
+
+        let mut cpu = MetricGroup::new("cpu");
+        if let Some(previous) = previous {
+            let total = cpu_times.total.saturating_sub(previous.total);
+            if total > 0 {
+                let idle = cpu_times.idle.saturating_sub(previous.idle);
+                let iowait = cpu_times.iowait.saturating_sub(previous.iowait);
+                cpu = cpu
+                    .with_value("usage", percent(total - idle.min(total), total))
+                    .with_value("iowait", percent(iowait, total));
+            }
+        }
+
+        if let Ok(loadavg) = fs::read_to_string(self.path("proc/loadavg")) {
+            let loads: Vec<f64> = loadavg
+                .split_whitespace()
+                .take(3)
+                .filter_map(|load| load.parse().ok())
+                .collect();
+            if let [load1, load5, load15] = loads[..] {
+                cpu = cpu
+                    .with_value("load1", load1)
+                    .with_value("load5", load5)
+                    .with_value("load15", load15);
+            }
+        }
+
+        Ok(Some(cpu).filter(|cpu| !cpu.values.is_empty()))
+    }
+
+    fn sample_memory(&self) -> io::Result<Option<MetricGroup>> {
+        let meminfo = fs::read_to_string(self.path("proc/meminfo"))?;
+        let fields: HashMap<&str, f64> = meminfo
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                let kilobytes: f64 = value.trim().trim_end_matches(" kB").parse().ok()?;
+                Some((name, kilobytes * 1024.0))
+            })
+            .collect();
+
+        let total = match fields.get("MemTotal") {
+            Some(total) if *total > 0.0 => *total,
+            _ => return Err(invalid_data("no MemTotal in /proc/meminfo")),
+        };
+        // MemAvailable is missing on kernels older than 3.14
+        let available = fields.get("MemAvailable").copied().unwrap_or_else(|| {
+            ["MemFree", "Buffers", "Cached"]
+                .iter()
+                .filter_map(|field| fields.get(field))
+                .sum()
+        });
+        let used = (total - available).max(0.0);
+
+        Ok(Some(
+            MetricGroup::new("memory")
+                .with_value("total", total)
+                .with_value("available", available)
+                .with_value("used", used)
+                .with_value("usage", 100.0 * used / total),
+        ))
+    }
+
+    fn sample_disks(&mut self) -> io::Result<Vec<MetricGroup>> {
+        let mounts = fs::read_to_string(self.path("proc/mounts"))?;
+        let mut mount_points: Vec<String> = vec![];
+        for line in mounts.lines() {
+            let mut fields = line.split_whitespace();
+            let (device, mount_point) = match (fields.next(), fields.next()) {
+                (Some(device), Some(mount_point)) => (device, unescape_mount_point(mount_point)),
+                _ => continue,
+            };
+            // Only the block devices, ignoring the pseudo file systems
+            if device.starts_with("/dev/") && !mount_points.contains(&mount_point) {
+                mount_points.push(mount_point);
+            }
+        }
+
+        let mut disks = vec![];
+        for mount_point in mount_points {
+            let path = self.root.join(mount_point.trim_start_matches('/'));
+            let source = format!("disk metrics of {}", mount_point);
+            let stat = match self.report(&source, statvfs(&path)) {
+                Some(stat) => stat,
+                None => continue,
+            };
+            let fragment_size = stat.fragment_size() as f64;
+            let total = stat.blocks() as f64 * fragment_size;
+            let used = (stat.blocks() as f64 - stat.blocks_free() as f64) * fragment_size;
+            let free = stat.blocks_available() as f64 * fragment_size;
+            // As df, the usage is relative to the space available to the users
+            let usage = if used + free > 0.0 {
+                100.0 * used / (used + free)
+            } else {
+                0.0
+            };
+            disks.push(
+                MetricGroup::new(format!("disk-{}", mount_point_name(&mount_point)))
+                    .with_value("total", total)
+                    .with_value("used", used)
+                    .with_value("free", free)
+                    .with_value("usage", usage),
+            );
+        }
+        Ok(disks)
+    }
+
+    fn sample_network(&mut self, now: Instant) -> io::Result<Vec<MetricGroup>> {
+        let dev = fs::read_to_string(self.path("proc/net/dev"))?;
+        let counters: HashMap<String, NetworkCounters> = dev
+            .lines()
+            .filter_map(NetworkCounters::parse)
+            .filter(|(interface, _)| interface != "lo")
+            .collect();
+        let previous = self.network_counters.replace((now, counters.clone()));
+
+        let (previous_time, previous_counters) = match previous {
+            Some(previous) => previous,
+            None => return Ok(vec![]),
+        };
+        let elapsed = now.saturating_duration_since(previous_time).as_secs_f64();
+        if elapsed <= 0.0 {
+            return Ok(vec![]);
+        }
+
+        let mut interfaces: Vec<_> = counters.iter().collect();
+        interfaces.sort_by_key(|&(interface, _)| interface);
+        let mut network = vec![];
+        for (interface, counters) in interfaces {
+            let previous = match previous_counters.get(interface) {
+                Some(previous) => previous,
+                None => continue,
+            };
+            // The counters are reset when an interface is re-created
+            if let (Some(rx), Some(tx)) = (
+                counters.rx_bytes.checked_sub(previous.rx_bytes),
+                counters.tx_bytes.checked_sub(previous.tx_bytes),
+            ) {
+                network.push(
+                    MetricGroup::new(format!("network-{}", metric_name(interface)))
+                        .with_value("rx_rate", rx as f64 / elapsed)
+                        .with_value("tx_rate", tx as f64 / elapsed),
+                );
+            }
+        }
+        Ok(network)
+    }
+
+    fn sample_temperatures(&self) -> Option<MetricGroup> {
+        // There is no thermal zone on most virtual machines
+        let zones = fs::read_dir(self.path("sys/class/thermal")).ok()?;
+        let mut zones: Vec<PathBuf> = zones
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_thermal_zone(path))
+            .collect();
+        zones.sort();
+
+        let mut temperatures = MetricGroup::new("temperature");
+        for zone in zones {
+            let millidegrees: f64 = match fs::read_to_string(zone.join("temp"))
+                .ok()
+                .and_then(|temp| temp.trim().parse().ok())
+            {
+                Some(millidegrees) => millidegrees,
+                None => continue,
+            };
+            let zone_name = zone
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut name = fs::read_to_string(zone.join("type"))
+                .map(|zone_type| metric_name(zone_type.trim()))
+                .ok()
+                .filter(|zone_type| !zone_type.is_empty())
+                .unwrap_or_else(|| zone_name.clone());
+            // Several zones can have the same type
+            if temperatures.values.iter().any(|(known, _)| known == &name) {
+                name = format!("{name}_{}", zone_name.trim_start_matches("thermal_zone"));
+            }
+            temperatures = temperatures.with_value(name, millidegrees / 1000.0);
+        }
+
+        Some(temperatures).filter(|temperatures| !temperatures.values.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CpuTimes {
+    total: u64,
+    idle: u64,
+    iowait: u64,
+}
+
+impl CpuTimes {
+    /// Parse the aggregated `cpu` line of `/proc/stat`.
+    fn parse(line: &str) -> Option<CpuTimes> {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("cpu") {
+            return None;
+        }
+        // user nice system idle iowait irq softirq steal, the guest times being included in user and nice
+        let times: Vec<u64> = fields
+            .take(8)
+            .filter_map(|time| time.parse().ok())
+            .collect();
+        if times.len() < 4 {
+            return None;
+        }
+        let iowait = times.get(4).copied().unwrap_or(0);
+        Some(CpuTimes {
+            total: times.iter().sum(),
+            idle: times[3] + iowait,
+            iowait,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NetworkCounters {
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+impl NetworkCounters {
+    /// Parse an interface line of `/proc/net/dev`.
+    fn parse(line: &str) -> Option<(String, NetworkCounters)> {
+        let (interface, counters) = line.split_once(':')?;
+        let counters: Vec<u64> = counters
+            .split_whitespace()
+            .map(|counter| counter.parse().ok())
+            .collect::<Option<_>>()?;
+        // 8 receive counters followed by 8 transmit counters
+        if counters.len() < 16 {
+            return None;
+        }
+        Some((
+            interface.trim().to_string(),
+            NetworkCounters {
+                rx_bytes: counters[0],
+                tx_bytes: counters[8],
+            },
+        ))
+    }
+}
+
+fn is_thermal_zone(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with("thermal_zone"))
+        .unwrap_or(false)
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    100.0 * part as f64 / total as f64
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The mount points are listed in `/proc/mounts` with octal escapes for the spaces, tabs and backslashes.
+fn unescape_mount_point(mount_point: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = mount_point.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let code: String = chars.by_ref().take(3).collect();
+            match u8::from_str_radix(&code, 8) {
+                Ok(byte) => unescaped.push(byte as char),
+                Err(_) => {
+                    unescaped.push(c);
+                    unescaped.push_str(&code);
+                }
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// The name of the disk group of a mount point: `/` is `root` and `/boot/efi` is `boot-efi`.
+fn mount_point_name(mount_point: &str) -> String {
+    let name = mount_point.trim_matches('/');
+    if name.is_empty() {
+        return "root".into();
+    }
+    name.split('/')
+        .map(metric_name)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A measurement or group name with only alphanumeric characters, `-` and `_`.
+fn metric_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use test_case::test_case;
+
+    fn only(groups: &str) -> MetricGroups {
+        MetricGroups {
+            cpu: groups.contains("cpu"),
+            memory: groups.contains("memory"),
+            disk: groups.contains("disk"),
+            network: groups.contains("network"),
+            temperature: groups.contains("temperature"),
+        }
+    }
+
+    fn write(root: &TempDir, path: &str, content: &str) {
+        let path = root.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn values(group: &MetricGroup) -> Vec<(&str, f64)> {
+        group
+            .values
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn the_cpu_usage_is_computed_from_the_previous_sample() {
+        let root = TempDir::new().unwrap();
+        let mut sampler = SystemSampler::with_root(root.path(), only("cpu"));
+        let now = Instant::now();
+
+        write(
+            &root,
+            "proc/stat",
+            "cpu  100 0 100 700 100 0 0 0 0 0\ncpu0 1 2 3 4\n",
+        );
+        assert_eq!(sampler.sample(now), vec![]);
+
+        write(&root, "proc/stat", "cpu  200 0 200 900 200 0 0 0 0 0\n");
+        write(&root, "proc/loadavg", "0.50 0.25 0.10 1/100 1234\n");
+        let metrics = sampler.sample(now + Duration::from_secs(1));
+
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].name, "cpu");
+        assert_eq!(
+            values(&metrics[0]),
+            vec![
+                ("usage", 40.0),
+                ("iowait", 20.0),
+                ("load1", 0.5),
+                ("load5", 0.25),
+                ("load15", 0.1)
+            ]
+        );
+    }
+
+    #[test]
+    fn the_memory_usage_falls_back_to_the_free_memory_on_old_kernels() {
+        let root = TempDir::new().unwrap();
+        let mut sampler = SystemSampler::with_root(root.path(), only("memory"));
+        write(
+            &root,
+            "proc/meminfo",
+            "MemTotal:  1000 kB\nMemFree:  100 kB\nBuffers:  50 kB\nCached:  100 kB\n",
+        );
+
+        let metrics = sampler.sample(Instant::now());
+
+        assert_eq!(
+            metrics,
+            vec![MetricGroup::new("memory")
+                .with_value("total", 1024000.0)
+                .with_value("available", 256000.0)
+                .with_value("used", 768000.0)
+                .with_value("usage", 75.0)]
+        );
+    }
+
+    #[test]
+    fn the_disk_usage_is_sampled_for_the_block_devices_only() {
+        let root = TempDir::new().unwrap();
+        let mut sampler = SystemSampler::with_root(root.path(), only("disk"));
+        fs::create_dir_all(root.path().join("data disk")).unwrap();
+        write(
+            &root,
+            "proc/mounts",
+            concat!(
+                "/dev/root / ext4 rw 0 0\n",
+                "proc /proc proc rw 0 0\n",
+                "tmpfs /run tmpfs rw 0 0\n",
+                "/dev/sda1 /data\\040disk ext4 rw 0 0\n",
+                "/dev/sda2 /missing ext4 rw 0 0\n",
+            ),
+        );
+
+        let metrics = sampler.sample(Instant::now());
+
+        let names: Vec<&str> = metrics.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, vec!["disk-root", "disk-data_disk"]);
+        let value_names: Vec<&str> = values(&metrics[0]).iter().map(|(name, _)| *name).collect();
+        assert_eq!(value_names, vec!["total", "used", "free", "usage"]);
+        let usage = values(&metrics[0])[3].1;
+        assert!((0.0..=100.0).contains(&usage));
+    }
+
+    #[test]
+    fn the_network_throughput_is_computed_from_the_previous_sample() {
+        let root = TempDir::new().unwrap();
+        let mut sampler = SystemSampler::with_root(root.path(), only("network"));
+        let header = concat!(
+            "Inter-|   Receive                                                |  Transmit\n",
+            " face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n",
+        );
+        let now = Instant::now();
+
+        write(
+            &root,
+            "proc/net/dev",
+            &format!(
+                "{header}{}{}",
+                "    lo: 1000 10 0 0 0 0 0 0 1000 10 0 0 0 0 0 0\n",
+                "  eth0: 1000 10 0 0 0 0 0 0 2000 20 0 0 0 0 0 0\n",
+            ),
+        );
+        assert_eq!(sampler.sample(now), vec![]);
+
+        write(
+            &root,
+            "proc/net/dev",
+            &format!(
+                "{header}{}{}{}",
+                "    lo: 9000 90 0 0 0 0 0 0 9000 90 0 0 0 0 0 0\n",
+                "  eth0: 3000 30 0 0 0 0 0 0 2500 25 0 0 0 0 0 0\n",
+                " wlan0: 100 1 0 0 0 0 0 0 100 1 0 0 0 0 0 0\n",
+            ),
+        );
+        let metrics = sampler.sample(now + Duration::from_secs(2));
+
+        assert_eq!(
+            metrics,
+            vec![MetricGroup::new("network-eth0")
+                .with_value("rx_rate", 1000.0)
+                .with_value("tx_rate", 250.0)]
+        );
+    }
+
+    #[test]
+    fn the_temperatures_are_named_after_the_thermal_zone_types() {
+        let root = TempDir::new().unwrap();
+        let mut sampler = SystemSampler::with_root(root.path(), only("temperature"));
+        write(
+            &root,
+            "sys/class/thermal/thermal_zone0/type",
+            "cpu-thermal\n",
+        );
+        write(&root, "sys/class/thermal/thermal_zone0/temp", "45123\n");
+        write(
+            &root,
+            "sys/class/thermal/thermal_zone1/type",
+            "cpu-thermal\n",
+        );
+        write(&root, "sys/class/thermal/thermal_zone1/temp", "47000\n");
+        write(&root, "sys/class/thermal/thermal_zone2/temp", "30000\n");
+        write(&root, "sys/class/thermal/cooling_device0/type", "fan\n");
+
+        let metrics = sampler.sample(Instant::now());
+
+        assert_eq!(
+            metrics,
+            vec![MetricGroup::new("temperature")
+                .with_value("cpu-thermal", 45.123)
+                .with_value("cpu-thermal_1", 47.0)
+                .with_value("thermal_zone2", 30.0)]
+        );
+    }
+
+    #[test]
+    fn the_disabled_groups_are_not_sampled() {
+        let root = TempDir::new().unwrap();
+        let mut sampler = SystemSampler::with_root(root.path(), only("cpu"));
+        write(
+            &root,
+            "proc/meminfo",
+            "MemTotal: 1000 kB\nMemAvailable: 250 kB\n",
+        );
+        write(&root, "sys/class/thermal/thermal_zone0/temp", "30000\n");
+
+        assert_eq!(sampler.sample(Instant::now()), vec![]);
+    }
+
+    #[test]
+    fn a_missing_source_is_reported_once_until_it_is_back() {
+        let root = TempDir::new().unwrap();
+        let mut sampler = SystemSampler::with_root(root.path(), only("memory"));
+
+        assert_eq!(sampler.sample(Instant::now()), vec![]);
+        assert_eq!(sampler.sample(Instant::now()), vec![]);
+        assert_eq!(
+            sampler.failing_sources,
+            HashSet::from(["memory metrics".to_string()])
+        );
+
+        write(
+            &root,
+            "proc/meminfo",
+            "MemTotal: 1000 kB\nMemAvailable: 250 kB\n",
+        );
+        assert_eq!(sampler.sample(Instant::now()).len(), 1);
+        assert!(sampler.failing_sources.is_empty());
+    }
+
+    #[test_case("/", "root")]
+    #[test_case("/boot/efi", "boot-efi")]
+    #[test_case("/media/usb key", "media-usb_key")]
+    fn mount_point_names(mount_point: &str, name: &str) {
+        assert_eq!(mount_point_name(mount_point), name);
+    }
+}
@@ -1,7 +1,11 @@
-use std::convert::{TryFrom, TryInto};
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+};
 
 use clock::Timestamp;
 use serde::Deserialize;
+use serde_json::Value;
 
 /// In-memory representation of ThinEdge JSON alarm.
 #[derive(Debug, Deserialize, PartialEq)]
@@ -20,6 +24,8 @@ pub enum AlarmSeverity {
 }
 
 /// In-memory representation of ThinEdge JSON alarm payload
+///
+/// Any field other than `text` and `time` is kept as a custom fragment of the alarm.
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct ThinEdgeAlarmData {
     pub text: Option<String>,
@@ -27,6 +33,9 @@ pub struct ThinEdgeAlarmData {
     #[serde(default)]
     #[serde(with = "clock::serde::rfc3339::option")]
     pub time: Option<Timestamp>,
+
+    #[serde(flatten)]
+    pub extras: HashMap<String, Value>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -112,6 +121,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: Some("I raised it".into()),
                 time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+                extras: HashMap::new(),
             }),
         };
         "critical alarm parsing"
@@ -127,6 +137,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: Some("I raised it".into()),
                 time: None,
+                extras: HashMap::new(),
             }),
         };
         "major alarm parsing without timestamp"
@@ -142,6 +153,7 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: None,
                 time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+                extras: HashMap::new(),
             }),
         };
         "minor alarm parsing without text"
@@ -155,10 +167,32 @@ mod tests {
             data: Some(ThinEdgeAlarmData {
                 text: None,
                 time: None,
+                extras: HashMap::new(),
             }),
         };
         "warning alarm parsing without text or timestamp"
     )]
+    #[test_case(
+        "tedge/alarms/major/temperature_alarm",
+        json!({
+            "text": "I raised it",
+            "threshold": 80,
+            "sensor": {"id": "t1", "location": "boiler"},
+        }),
+        ThinEdgeAlarm {
+            name: "temperature_alarm".into(),
+            severity: AlarmSeverity::Major,
+            data: Some(ThinEdgeAlarmData {
+                text: Some("I raised it".into()),
+                time: None,
+                extras: HashMap::from([
+                    ("threshold".to_string(), json!(80)),
+                    ("sensor".to_string(), json!({"id": "t1", "location": "boiler"})),
+                ]),
+            }),
+        };
+        "alarm parsing with custom fragments"
+    )]
     fn parse_thin_edge_alarm_json(
         alarm_topic: &str,
         alarm_payload: Value,
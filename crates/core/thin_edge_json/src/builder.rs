@@ -3,9 +3,12 @@ use time::OffsetDateTime;
 use crate::{data::*, measurement::*};
 
 /// A `MeasurementVisitor` that builds up `ThinEdgeJson`.
+///
+/// Only a single ThinEdge JSON object is accepted, not an array of such objects.
 #[derive(Default)]
 pub struct ThinEdgeJsonBuilder {
     timestamp: Option<OffsetDateTime>,
+    measurement_type: Option<String>,
     inside_group: Option<MultiValueMeasurement>,
     measurements: Vec<ThinEdgeValue>,
 }
@@ -22,6 +25,7 @@ impl ThinEdgeJsonBuilder {
 
         Ok(ThinEdgeJson {
             timestamp: self.timestamp,
+            measurement_type: self.measurement_type,
             values: self.measurements,
         })
     }
@@ -30,6 +34,10 @@ impl ThinEdgeJsonBuilder {
 impl MeasurementVisitor for ThinEdgeJsonBuilder {
     type Error = ThinEdgeJsonBuilderError;
 
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        Err(ThinEdgeJsonBuilderError::UnexpectedFrame)
+    }
+
     fn visit_timestamp(&mut self, value: OffsetDateTime) -> Result<(), Self::Error> {
         match self.timestamp {
             None => {
@@ -40,6 +48,16 @@ impl MeasurementVisitor for ThinEdgeJsonBuilder {
         }
     }
 
+    fn visit_type(&mut self, measurement_type: &str) -> Result<(), Self::Error> {
+        match self.measurement_type {
+            None => {
+                self.measurement_type = Some(measurement_type.into());
+                Ok(())
+            }
+            Some(_) => Err(ThinEdgeJsonBuilderError::DuplicatedType),
+        }
+    }
+
     fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), Self::Error> {
         if let Some(group) = &mut self.inside_group {
             group.values.push((name, value).into());
@@ -49,6 +67,28 @@ impl MeasurementVisitor for ThinEdgeJsonBuilder {
         Ok(())
     }
 
+    fn visit_text_measurement(&mut self, name: &str, value: &str) -> Result<(), Self::Error> {
+        if self.inside_group.is_some() {
+            return Err(ThinEdgeJsonBuilderError::UnexpectedNonNumericValueInGroup {
+                name: name.into(),
+            });
+        }
+
+        self.measurements.push((name, value).into());
+        Ok(())
+    }
+
+    fn visit_bool_measurement(&mut self, name: &str, value: bool) -> Result<(), Self::Error> {
+        if self.inside_group.is_some() {
+            return Err(ThinEdgeJsonBuilderError::UnexpectedNonNumericValueInGroup {
+                name: name.into(),
+            });
+        }
+
+        self.measurements.push((name, value).into());
+        Ok(())
+    }
+
     fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error> {
         if self.inside_group.is_none() {
             self.inside_group = Some(MultiValueMeasurement {
@@ -87,6 +127,15 @@ pub enum ThinEdgeJsonBuilderError {
     #[error("... time stamp within a group")]
     DuplicatedTimestamp,
 
+    #[error("Duplicated measurement type")]
+    DuplicatedType,
+
+    #[error("Unexpected non-numeric value for {name:?}: only numbers are accepted within a group")]
+    UnexpectedNonNumericValueInGroup { name: String },
+
+    #[error("Unexpected array of measurements: a single Thin Edge measurement is expected")]
+    UnexpectedFrame,
+
     #[error("Unexpected open group")]
     UnexpectedOpenGroup,
 
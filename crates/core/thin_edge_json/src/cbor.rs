@@ -0,0 +1,128 @@
+//! A CBOR front-end for ThinEdge measurements.
+//!
+//! The CBOR data items follow the same structure as ThinEdge JSON,
+//! and are yielded to the same `MeasurementVisitor`s as the JSON parser.
+//!
+use crate::measurement::MeasurementVisitor;
+
+/// Parses `input` as CBOR-encoded ThinEdge measurements yielding the parsed measurements to the `visitor`.
+pub fn parse_slice<T: MeasurementVisitor>(
+    mut input: &[u8],
+    visitor: &mut T,
+) -> Result<(), ThinEdgeCborParserError> {
+    let data: serde_json::Value = ciborium::de::from_reader(&mut input)?;
+    if !input.is_empty() {
+        return Err(ThinEdgeCborParserError::TrailingData);
+    }
+
+    let () = crate::parser::parse(&data, visitor)?;
+    Ok(())
+}
+
+/// Encodes `value` as CBOR, e.g. measurements to be published on `tedge/measurements/cbor`.
+pub fn to_vec<T: serde::Serialize>(
+    value: &T,
+) -> Result<Vec<u8>, ciborium::ser::Error<std::io::Error>> {
+    let mut bytes = Vec::new();
+    let () = ciborium::ser::into_writer(value, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// The error returned by `parse_slice`.
+#[derive(Debug, thiserror::Error)]
+pub enum ThinEdgeCborParserError {
+    #[error("Invalid CBOR: {0}")]
+    InvalidCbor(#[from] ciborium::de::Error<std::io::Error>),
+
+    #[error("Invalid CBOR: {0}")]
+    InvalidMeasurement(#[from] serde_json::Error),
+
+    #[error("Invalid CBOR: trailing data after the measurements")]
+    TrailingData,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder::ThinEdgeJsonBuilder, serialize::ThinEdgeJsonSerializer};
+    use serde_json::json;
+    use time::macros::datetime;
+
+    fn cbor(value: serde_json::Value) -> Vec<u8> {
+        to_vec(&value).unwrap()
+    }
+
+    #[test]
+    fn it_deserializes_cbor_measurements() -> anyhow::Result<()> {
+        let input = cbor(json!({
+            "time": "2021-04-30T17:03:14.123+02:00",
+            "type": "c8y_Environment",
+            "temperature": 24,
+            "pressure": 123.4,
+            "coordinate": {"x": 1, "y": -2, "z": 3.5},
+            "status": "running",
+            "door_open": false,
+        }));
+
+        let mut builder = ThinEdgeJsonBuilder::default();
+        let () = parse_slice(&input, &mut builder)?;
+        let output = builder.done()?;
+
+        assert_eq!(
+            output.timestamp,
+            Some(datetime!(2021-04-30 17:03:14.123 +02:00))
+        );
+        assert_eq!(output.measurement_type, Some("c8y_Environment".into()));
+        assert_eq!(
+            output.values,
+            vec![
+                (
+                    "coordinate",
+                    vec![("x", 1.0).into(), ("y", -2.0).into(), ("z", 3.5).into()]
+                )
+                    .into(),
+                ("door_open", false).into(),
+                ("pressure", 123.4).into(),
+                ("status", "running").into(),
+                ("temperature", 24.0).into(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_translates_cbor_arrays_into_json() -> anyhow::Result<()> {
+        let input = cbor(json!([{"temperature": 23}, {"temperature": 24}]));
+
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        let () = parse_slice(&input, &mut serializer)?;
+
+        assert_eq!(
+            serializer.into_string()?,
+            r#"[{"temperature":23.0},{"temperature":24.0}]"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_invalid_measurements() {
+        let input = cbor(json!({"temperature": {"inner": {"x": 1}}}));
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        let error = parse_slice(&input, &mut serializer).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Invalid CBOR: Expect single-value measurement"));
+
+        let input = cbor(json!({"time": "2021-04-30T17:03:14.123+02:00"}));
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        let error = parse_slice(&input, &mut serializer).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Invalid CBOR: Empty Thin Edge measurement"));
+
+        let mut input = cbor(json!({"temperature": 23}));
+        input.push(0);
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        assert!(parse_slice(&input, &mut serializer).is_err());
+    }
+}
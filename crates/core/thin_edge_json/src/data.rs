@@ -6,6 +6,7 @@ use time::OffsetDateTime;
 #[derive(Debug)]
 pub struct ThinEdgeJson {
     pub timestamp: Option<OffsetDateTime>,
+    pub measurement_type: Option<String>,
     pub values: Vec<ThinEdgeValue>,
 }
 
@@ -23,6 +24,8 @@ impl ThinEdgeJson {
 pub enum ThinEdgeValue {
     Single(SingleValueMeasurement),
     Multi(MultiValueMeasurement),
+    Text(TextMeasurement),
+    Bool(BoolMeasurement),
 }
 
 #[derive(Debug, PartialEq)]
@@ -37,6 +40,18 @@ pub struct MultiValueMeasurement {
     pub values: Vec<SingleValueMeasurement>,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct TextMeasurement {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BoolMeasurement {
+    pub name: String,
+    pub value: bool,
+}
+
 impl<T> From<(T, f64)> for SingleValueMeasurement
 where
     T: Into<String>,
@@ -69,3 +84,27 @@ where
         })
     }
 }
+
+impl<T> From<(T, &str)> for ThinEdgeValue
+where
+    T: Into<String>,
+{
+    fn from((name, value): (T, &str)) -> Self {
+        ThinEdgeValue::Text(TextMeasurement {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+}
+
+impl<T> From<(T, bool)> for ThinEdgeValue
+where
+    T: Into<String>,
+{
+    fn from((name, value): (T, bool)) -> Self {
+        ThinEdgeValue::Bool(BoolMeasurement {
+            name: name.into(),
+            value,
+        })
+    }
+}
@@ -98,6 +98,12 @@ pub enum MeasurementGrouperError {
     #[error("Duplicated measurement: {0}.{1}")]
     DuplicatedSubMeasurement(String, String),
 
+    #[error("Unsupported non-numeric measurement: {0}")]
+    UnsupportedNonNumericMeasurement(String),
+
+    #[error("Unsupported array of measurements")]
+    UnsupportedFrames,
+
     #[error("Unexpected end")]
     UnexpectedEnd,
 
@@ -137,6 +143,10 @@ impl Default for MeasurementGrouper {
 impl MeasurementVisitor for MeasurementGrouper {
     type Error = MeasurementGrouperError;
 
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        Err(MeasurementGrouperError::UnsupportedFrames)
+    }
+
     fn visit_timestamp(&mut self, time: OffsetDateTime) -> Result<(), Self::Error> {
         self.measurement_group.timestamp = Some(time);
         Ok(())
@@ -162,6 +172,18 @@ impl MeasurementVisitor for MeasurementGrouper {
         }
     }
 
+    fn visit_text_measurement(&mut self, name: &str, _value: &str) -> Result<(), Self::Error> {
+        Err(MeasurementGrouperError::UnsupportedNonNumericMeasurement(
+            name.into(),
+        ))
+    }
+
+    fn visit_bool_measurement(&mut self, name: &str, _value: bool) -> Result<(), Self::Error> {
+        Err(MeasurementGrouperError::UnsupportedNonNumericMeasurement(
+            name.into(),
+        ))
+    }
+
     fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), Self::Error> {
         let key = name.to_owned();
 
@@ -208,8 +230,11 @@ mod tests {
         impl MeasurementVisitor for GroupedVisitor {
             type Error = TestError;
 
+            fn visit_frame(&mut self) -> Result<(), TestError>;
             fn visit_timestamp(&mut self, value: OffsetDateTime) -> Result<(), TestError>;
             fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), TestError>;
+            fn visit_text_measurement(&mut self, name: &str, value: &str) -> Result<(), TestError>;
+            fn visit_bool_measurement(&mut self, name: &str, value: bool) -> Result<(), TestError>;
             fn visit_start_group(&mut self, group: &str) -> Result<(), TestError>;
             fn visit_end_group(&mut self) -> Result<(), TestError>;
         }
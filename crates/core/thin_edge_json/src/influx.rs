@@ -0,0 +1,289 @@
+//! Serializes ThinEdge measurements into [InfluxDB line protocol](https://docs.influxdata.com/influxdb/v2.2/reference/syntax/line-protocol/).
+//!
+use crate::{measurement::MeasurementVisitor, serialize::MeasurementStreamError};
+use std::fmt::Write;
+use time::OffsetDateTime;
+
+/// Serializes measurements into InfluxDB line protocol, one line per measurement.
+///
+/// A single-valued measurement is serialized as a point with a `value` field,
+/// while a multi-valued measurement is serialized as a point with a field per value:
+///
+/// ```text
+/// temperature,type=c8y_Environment value=23.5 1650000000000000000
+/// current,type=c8y_Environment L1=9.5,L2=10.3 1650000000000000000
+/// ```
+///
+/// The measurement type, if any, is added as a `type` tag, along the tags given on creation.
+/// The timestamps are given in nanoseconds.
+pub struct InfluxLineSerializer {
+    lines: String,
+    tags: Vec<(String, String)>,
+    default_timestamp: Option<OffsetDateTime>,
+    timestamp: Option<OffsetDateTime>,
+    measurement_type: Option<String>,
+    points: Vec<Point>,
+    group: Option<Point>,
+}
+
+struct Point {
+    name: String,
+    fields: Vec<(String, FieldValue)>,
+}
+
+enum FieldValue {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+impl InfluxLineSerializer {
+    pub fn new() -> Self {
+        Self::new_with_timestamp(None)
+    }
+
+    pub fn new_with_timestamp(default_timestamp: Option<OffsetDateTime>) -> Self {
+        InfluxLineSerializer {
+            lines: String::new(),
+            tags: vec![],
+            default_timestamp,
+            timestamp: None,
+            measurement_type: None,
+            points: vec![],
+            group: None,
+        }
+    }
+
+    /// Add a tag to all the points, say the device the measurements are coming from.
+    pub fn with_tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn into_string(mut self) -> Result<String, MeasurementStreamError> {
+        let () = self.end_frame()?;
+        Ok(self.lines)
+    }
+
+    fn end_frame(&mut self) -> Result<(), MeasurementStreamError> {
+        if self.group.is_some() {
+            return Err(MeasurementStreamError::UnexpectedEndOfData);
+        }
+
+        let mut tags = self.tags.clone();
+        if let Some(measurement_type) = self.measurement_type.take() {
+            tags.push(("type".into(), measurement_type));
+        }
+        // Sorting the tags is recommended by InfluxDB for performance
+        tags.sort();
+
+        let timestamp = self.timestamp.take().or(self.default_timestamp);
+        for point in self.points.drain(..) {
+            write_point(&mut self.lines, &point, &tags, timestamp);
+        }
+        Ok(())
+    }
+
+    fn add_field(&mut self, name: &str, value: FieldValue) {
+        match self.group.as_mut() {
+            Some(group) => group.fields.push((name.to_string(), value)),
+            None => self.points.push(Point {
+                name: name.to_string(),
+                fields: vec![("value".into(), value)],
+            }),
+        }
+    }
+}
+
+impl Default for InfluxLineSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MeasurementVisitor for InfluxLineSerializer {
+    type Error = MeasurementStreamError;
+
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        if self.group.is_some() {
+            return Err(MeasurementStreamError::UnexpectedStartOfFrame);
+        }
+        self.end_frame()
+    }
+
+    fn visit_timestamp(&mut self, value: OffsetDateTime) -> Result<(), Self::Error> {
+        if self.group.is_some() {
+            return Err(MeasurementStreamError::UnexpectedTimestamp);
+        }
+        self.timestamp = Some(value);
+        Ok(())
+    }
+
+    fn visit_type(&mut self, measurement_type: &str) -> Result<(), Self::Error> {
+        if self.group.is_some() {
+            return Err(MeasurementStreamError::UnexpectedType);
+        }
+        self.measurement_type = Some(measurement_type.to_string());
+        Ok(())
+    }
+
+    fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), Self::Error> {
+        self.add_field(name, FieldValue::Number(value));
+        Ok(())
+    }
+
+    fn visit_text_measurement(&mut self, name: &str, value: &str) -> Result<(), Self::Error> {
+        self.add_field(name, FieldValue::Text(value.to_string()));
+        Ok(())
+    }
+
+    fn visit_bool_measurement(&mut self, name: &str, value: bool) -> Result<(), Self::Error> {
+        self.add_field(name, FieldValue::Bool(value));
+        Ok(())
+    }
+
+    fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error> {
+        if self.group.is_some() {
+            return Err(MeasurementStreamError::UnexpectedStartOfGroup);
+        }
+        self.group = Some(Point {
+            name: group.to_string(),
+            fields: vec![],
+        });
+        Ok(())
+    }
+
+    fn visit_end_group(&mut self) -> Result<(), Self::Error> {
+        match self.group.take() {
+            Some(group) if group.fields.is_empty() => Ok(()),
+            Some(group) => {
+                self.points.push(group);
+                Ok(())
+            }
+            None => Err(MeasurementStreamError::UnexpectedEndOfGroup),
+        }
+    }
+}
+
+fn write_point(
+    line: &mut String,
+    point: &Point,
+    tags: &[(String, String)],
+    timestamp: Option<OffsetDateTime>,
+) {
+    escape_into(line, &point.name, &[',', ' ']);
+    for (key, value) in tags {
+        line.push(',');
+        escape_into(line, key, &[',', '=', ' ']);
+        line.push('=');
+        escape_into(line, value, &[',', '=', ' ']);
+    }
+
+    let mut separator = ' ';
+    for (key, value) in point.fields.iter() {
+        line.push(separator);
+        separator = ',';
+        escape_into(line, key, &[',', '=', ' ']);
+        line.push('=');
+        match value {
+            FieldValue::Number(value) => {
+                let _ = write!(line, "{}", value);
+            }
+            FieldValue::Bool(value) => {
+                let _ = write!(line, "{}", value);
+            }
+            FieldValue::Text(value) => {
+                line.push('"');
+                escape_into(line, value, &['"', '\\']);
+                line.push('"');
+            }
+        }
+    }
+
+    if let Some(timestamp) = timestamp {
+        let _ = write!(line, " {}", timestamp.unix_timestamp_nanos());
+    }
+    line.push('\n');
+}
+
+fn escape_into(output: &mut String, input: &str, special_chars: &[char]) {
+    for c in input.chars() {
+        if special_chars.contains(&c) {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_str;
+    use time::macros::datetime;
+
+    fn serialize(input: &str, serializer: InfluxLineSerializer) -> String {
+        let mut serializer = serializer;
+        let () = parse_str(input, &mut serializer).unwrap();
+        serializer.into_string().unwrap()
+    }
+
+    #[test]
+    fn single_and_multi_valued_measurements_are_serialized_as_points() {
+        let input = r#"{
+            "type": "c8y_Environment",
+            "temperature": 23.5,
+            "current": {"L1": 9.5, "L2": 10},
+            "time": "2022-04-15T05:20:00Z"
+        }"#;
+
+        assert_eq!(
+            serialize(input, InfluxLineSerializer::new()),
+            concat!(
+                "temperature,type=c8y_Environment value=23.5 1650000000000000000\n",
+                "current,type=c8y_Environment L1=9.5,L2=10 1650000000000000000\n",
+            )
+        );
+    }
+
+    #[test]
+    fn the_default_timestamp_and_tags_are_added() {
+        let input = r#"{"status": "running", "door_open": true}"#;
+        let serializer =
+            InfluxLineSerializer::new_with_timestamp(Some(datetime!(2022-04-15 05:20:00 UTC)))
+                .with_tag("device", "child 1");
+
+        assert_eq!(
+            serialize(input, serializer),
+            concat!(
+                "status,device=child\\ 1 value=\"running\" 1650000000000000000\n",
+                "door_open,device=child\\ 1 value=true 1650000000000000000\n",
+            )
+        );
+    }
+
+    #[test]
+    fn each_sample_of_an_array_has_its_own_timestamp() {
+        let input = r#"[
+            {"temperature": 23, "time": "2022-04-15T05:20:00Z"},
+            {"temperature": 24}
+        ]"#;
+
+        assert_eq!(
+            serialize(input, InfluxLineSerializer::new()),
+            concat!(
+                "temperature value=23 1650000000000000000\n",
+                "temperature value=24\n",
+            )
+        );
+    }
+
+    #[test]
+    fn text_values_are_escaped() {
+        let input = r#"{"message": "a \"quoted\" \\ text"}"#;
+
+        assert_eq!(
+            serialize(input, InfluxLineSerializer::new()),
+            "message value=\"a \\\"quoted\\\" \\\\ text\"\n"
+        );
+    }
+}
@@ -3,10 +3,14 @@
 
 pub mod alarm;
 pub mod builder;
+pub mod cbor;
 pub mod data;
 pub mod event;
 pub mod group;
+pub mod influx;
+pub mod location;
 pub mod measurement;
 pub mod parser;
 pub mod serialize;
+pub mod units;
 pub mod utils;
@@ -0,0 +1,208 @@
+use clock::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use self::error::ThinEdgeJsonLocationError;
+
+const LOCATION_TOPIC: &str = "tedge/location";
+
+/// In-memory representation of a ThinEdge JSON location,
+/// published on `tedge/location` for the device or on `tedge/location/<child-id>` for a child device.
+#[derive(Debug, PartialEq)]
+pub struct ThinEdgeLocation {
+    pub child_id: Option<String>,
+    pub data: ThinEdgeLocationData,
+}
+
+/// In-memory representation of ThinEdge JSON location payload
+///
+/// The latitude and longitude are given in degrees,
+/// the altitude and the accuracy in meters.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ThinEdgeLocationData {
+    pub lat: f64,
+
+    pub lng: f64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "clock::serde::rfc3339::option")]
+    pub time: Option<Timestamp>,
+}
+
+pub mod error {
+    #[derive(thiserror::Error, Debug)]
+    pub enum ThinEdgeJsonLocationError {
+        #[error("Unsupported topic: {0}")]
+        UnsupportedTopic(String),
+
+        #[error("Invalid latitude: {0}. Expecting a value between -90 and 90")]
+        InvalidLatitude(f64),
+
+        #[error("Invalid longitude: {0}. Expecting a value between -180 and 180")]
+        InvalidLongitude(f64),
+
+        #[error("Invalid accuracy: {0}. Expecting a positive value")]
+        InvalidAccuracy(f64),
+
+        #[error(transparent)]
+        SerdeJsonError(#[from] serde_json::error::Error),
+    }
+}
+
+impl ThinEdgeLocation {
+    pub fn try_from(
+        mqtt_topic: &str,
+        mqtt_payload: &str,
+    ) -> Result<Self, ThinEdgeJsonLocationError> {
+        let child_id = match mqtt_topic.strip_prefix(LOCATION_TOPIC) {
+            Some("") => None,
+            Some(child_topic) => match child_topic.strip_prefix('/') {
+                Some(child_id) if !child_id.is_empty() && !child_id.contains('/') => {
+                    Some(child_id.to_string())
+                }
+                _ => {
+                    return Err(ThinEdgeJsonLocationError::UnsupportedTopic(
+                        mqtt_topic.into(),
+                    ))
+                }
+            },
+            None => {
+                return Err(ThinEdgeJsonLocationError::UnsupportedTopic(
+                    mqtt_topic.into(),
+                ))
+            }
+        };
+
+        let data: ThinEdgeLocationData = serde_json::from_str(mqtt_payload)?;
+        let () = data.validate()?;
+
+        Ok(Self { child_id, data })
+    }
+}
+
+impl ThinEdgeLocationData {
+    fn validate(&self) -> Result<(), ThinEdgeJsonLocationError> {
+        if !(-90.0..=90.0).contains(&self.lat) {
+            return Err(ThinEdgeJsonLocationError::InvalidLatitude(self.lat));
+        }
+        if !(-180.0..=180.0).contains(&self.lng) {
+            return Err(ThinEdgeJsonLocationError::InvalidLongitude(self.lng));
+        }
+        if let Some(accuracy) = self.accuracy {
+            if accuracy < 0.0 {
+                return Err(ThinEdgeJsonLocationError::InvalidAccuracy(accuracy));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+    use time::macros::datetime;
+
+    #[test_case(
+        "tedge/location",
+        json!({
+            "lat": 52.52,
+            "lng": 13.405,
+            "alt": 34.0,
+            "accuracy": 5.0,
+            "time": "2021-04-23T19:00:00+05:00",
+        }),
+        ThinEdgeLocation {
+            child_id: None,
+            data: ThinEdgeLocationData {
+                lat: 52.52,
+                lng: 13.405,
+                alt: Some(34.0),
+                accuracy: Some(5.0),
+                time: Some(datetime!(2021-04-23 19:00:00 +05:00)),
+            },
+        };
+        "location parsing"
+    )]
+    #[test_case(
+        "tedge/location/child1",
+        json!({
+            "lat": -33.86,
+            "lng": 151.2,
+        }),
+        ThinEdgeLocation {
+            child_id: Some("child1".into()),
+            data: ThinEdgeLocationData {
+                lat: -33.86,
+                lng: 151.2,
+                alt: None,
+                accuracy: None,
+                time: None,
+            },
+        };
+        "child location parsing without optional fields"
+    )]
+    fn parse_thin_edge_location_json(
+        location_topic: &str,
+        location_payload: Value,
+        expected_location: ThinEdgeLocation,
+    ) {
+        let location =
+            ThinEdgeLocation::try_from(location_topic, location_payload.to_string().as_str())
+                .unwrap();
+
+        assert_eq!(location, expected_location);
+    }
+
+    #[test_case("tedge/locations")]
+    #[test_case("tedge/location/")]
+    #[test_case("tedge/location/child1/more")]
+    #[test_case("tedge/events/location")]
+    fn unsupported_location_topic(topic: &str) {
+        let result = ThinEdgeLocation::try_from(topic, r#"{"lat": 1.0, "lng": 2.0}"#);
+
+        assert_matches!(result, Err(ThinEdgeJsonLocationError::UnsupportedTopic(_)));
+    }
+
+    #[test]
+    fn invalid_latitude() {
+        let result = ThinEdgeLocation::try_from("tedge/location", r#"{"lat": 91.0, "lng": 2.0}"#);
+
+        assert_matches!(result, Err(ThinEdgeJsonLocationError::InvalidLatitude(_)));
+    }
+
+    #[test]
+    fn invalid_longitude() {
+        let result = ThinEdgeLocation::try_from("tedge/location", r#"{"lat": 1.0, "lng": -180.5}"#);
+
+        assert_matches!(result, Err(ThinEdgeJsonLocationError::InvalidLongitude(_)));
+    }
+
+    #[test]
+    fn invalid_accuracy() {
+        let result = ThinEdgeLocation::try_from(
+            "tedge/location",
+            r#"{"lat": 1.0, "lng": 2.0, "accuracy": -1.0}"#,
+        );
+
+        assert_matches!(result, Err(ThinEdgeJsonLocationError::InvalidAccuracy(_)));
+    }
+
+    #[test_case(r#"{"lng": 2.0}"#)]
+    #[test_case(r#"{"lat": "1.0", "lng": 2.0}"#)]
+    #[test_case(r#"{"lat": 1.0, "lng": 2.0, "speed": 12.0}"#)]
+    fn invalid_location_payload(payload: &str) {
+        let result = ThinEdgeLocation::try_from("tedge/location", payload);
+
+        assert_matches!(result, Err(ThinEdgeJsonLocationError::SerdeJsonError(_)));
+    }
+}
@@ -27,6 +27,10 @@ use time::OffsetDateTime;
 /// impl MeasurementVisitor for MeasurementPrinter {
 ///     type Error = MeasurementError;
 ///
+///     fn visit_frame(&mut self) -> Result<(), Self::Error> {
+///         Ok(println!("---"))
+///     }
+///
 ///     fn visit_timestamp(&mut self, timestamp: OffsetDateTime) -> Result<(), Self::Error> {
 ///         let format =
 ///             format_description::parse("[day] [month repr:short] [year] [hour repr:24]:[minute]:[seconds] [offset_hour sign:mandatory]:[offset_minute]").unwrap();
@@ -45,6 +49,14 @@ use time::OffsetDateTime;
 ///         }
 ///     }
 ///
+///     fn visit_text_measurement(&mut self, name: &str, value: &str) -> Result<(), Self::Error> {
+///         Ok(println!("{} = {:?}", name, value))
+///     }
+///
+///     fn visit_bool_measurement(&mut self, name: &str, value: bool) -> Result<(), Self::Error> {
+///         Ok(println!("{} = {}", name, value))
+///     }
+///
 ///     fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error> {
 ///         if self.group.is_none() {
 ///             self.group = Some(group.to_owned());
@@ -68,12 +80,43 @@ pub trait MeasurementVisitor {
     /// Error type specific to this visitor.
     type Error: std::error::Error + std::fmt::Debug;
 
+    /// Start a new frame of measurements, with its own timestamp and type.
+    ///
+    /// Called before each of the objects of an array of thin-edge JSON objects,
+    /// but never for a single thin-edge JSON object.
+    ///
+    /// Ignored by default, for the visitors that process the measurements one by one.
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Set the timestamp shared by all the measurements of this series.
     fn visit_timestamp(&mut self, value: OffsetDateTime) -> Result<(), Self::Error>;
 
+    /// Set the type shared by all the measurements of this series.
+    ///
+    /// Ignored by default, for the visitors that have no use of the measurement type.
+    fn visit_type(&mut self, _measurement_type: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Add a new measurement, attached to the current group if any.
     fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), Self::Error>;
 
+    /// Add a new measurement with a text value, attached to the current group if any.
+    ///
+    /// Ignored by default, for the visitors that only process numeric measurements.
+    fn visit_text_measurement(&mut self, _name: &str, _value: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Add a new measurement with a boolean value, attached to the current group if any.
+    ///
+    /// Ignored by default, for the visitors that only process numeric measurements.
+    fn visit_bool_measurement(&mut self, _name: &str, _value: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Start to gather measurements for a group.
     fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error>;
 
@@ -4,7 +4,7 @@
 //!
 use crate::measurement::MeasurementVisitor;
 use serde::{
-    de::{self, DeserializeSeed, MapAccess},
+    de::{self, DeserializeSeed, MapAccess, SeqAccess},
     Deserializer,
 };
 use std::borrow::Cow;
@@ -13,20 +13,29 @@ use std::fmt;
 use time::{format_description, OffsetDateTime};
 
 /// Parses `input` as ThinEdge JSON yielding the parsed measurements to the `visitor`.
+///
+/// The input is either a single ThinEdge JSON object or an array of such objects,
+/// each of the latter being notified to the visitor as a new frame.
 pub fn parse_str<T: MeasurementVisitor>(
     input: &str,
     visitor: &mut T,
 ) -> Result<(), ThinEdgeJsonParserError> {
     let mut deserializer = serde_json::Deserializer::from_str(input);
 
-    let parser = ThinEdgeJsonParser { visitor };
-
-    let () = deserializer
-        .deserialize_map(parser)
-        .map_err(|error| map_error(error, input))?;
+    let () = parse(&mut deserializer, visitor).map_err(|error| map_error(error, input))?;
     Ok(())
 }
 
+/// Parses ThinEdge measurements from any serde data format, yielding the parsed measurements to the `visitor`.
+pub(crate) fn parse<'de, D, T>(deserializer: D, visitor: &mut T) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+    T: MeasurementVisitor,
+{
+    let parser = ThinEdgeJsonParser { visitor };
+    deserializer.deserialize_any(parser)
+}
+
 /// The error returned by `parse_str`.
 #[derive(Debug, thiserror::Error)]
 #[error("Invalid JSON: {error}: `{input_excerpt}`")]
@@ -40,9 +49,12 @@ pub struct ThinEdgeJsonParserError {
 /// Parses top-level ThinEdge JSON:
 ///
 /// ```grammar
-/// {
+/// frame | [frame]
+///
+/// frame = {
 ///     time?: string,
-///     [key: string]: number | {[key: string]: number},
+///     type?: string,
+///     [key: string]: number | string | boolean | {[key: string]: number},
 /// }
 /// ```
 ///
@@ -53,16 +65,24 @@ where
     visitor: &'vis mut T,
 }
 
-/// Parses a single value (number) or multi-value measurement:
+/// Parses a frame of an array of ThinEdge JSON objects, which can only be an object.
+struct ThinEdgeFrameParser<'vis, T>
+where
+    T: MeasurementVisitor,
+{
+    visitor: &'vis mut T,
+}
+
+/// Parses a single value (number, string or boolean) or multi-value measurement:
 ///
 /// ```grammar
-/// number | {[key: string]: number}
+/// number | string | boolean | {[key: string]: number}
 /// ```
 ///
 struct ThinEdgeValueParser<'key, 'vis, T> {
     /// Recursion depth.
     ///
-    /// When `depth = 0`, we accept both number or multi-value measurements.
+    /// When `depth = 0`, we accept numbers, strings, booleans or multi-value measurements.
     /// When `depth > 0`, we only accept numbers.
     depth: usize,
     /// The associated key of the single or multi-value measurement.
@@ -91,7 +111,17 @@ where
             let key: Cow<str> = key;
 
             match key.as_ref() {
-                "type" => return Err(de::Error::custom(invalid_measurement_name("type"))),
+                "type" => {
+                    let measurement_type: &str = map.next_value()?;
+                    if measurement_type.is_empty() {
+                        return Err(de::Error::custom(invalid_empty_type()));
+                    }
+
+                    let () = self
+                        .visitor
+                        .visit_type(measurement_type)
+                        .map_err(de::Error::custom)?;
+                }
                 "externalSource" => {
                     return Err(de::Error::custom(invalid_measurement_name(
                         "externalSource",
@@ -129,6 +159,45 @@ where
 
         Ok(())
     }
+
+    /// Parses an array of ThinEdge JSON objects, each with its own timestamp and type.
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut frames_count: usize = 0;
+
+        while let Some(()) = seq.next_element_seed(ThinEdgeFrameParser {
+            visitor: self.visitor,
+        })? {
+            frames_count += 1;
+        }
+
+        if frames_count == 0 {
+            return Err(de::Error::custom(invalid_empty_array()));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'vis, 'de, T> DeserializeSeed<'de> for ThinEdgeFrameParser<'vis, T>
+where
+    T: MeasurementVisitor,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let () = self.visitor.visit_frame().map_err(de::Error::custom)?;
+
+        let parser = ThinEdgeJsonParser {
+            visitor: self.visitor,
+        };
+        deserializer.deserialize_map(parser)
+    }
 }
 
 impl<'key, 'vis, 'de, T> de::Visitor<'de> for ThinEdgeValueParser<'key, 'vis, T>
@@ -212,6 +281,40 @@ where
         Ok(())
     }
 
+    /// Parses a single-value text measurement, rejected within a multi-value measurement.
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        if self.depth > 0 {
+            return Err(de::Error::custom(invalid_grouped_value(&self.key)));
+        }
+
+        let () = self
+            .visitor
+            .visit_text_measurement(self.key.as_ref(), value)
+            .map_err(de::Error::custom)?;
+
+        Ok(())
+    }
+
+    /// Parses a single-value boolean measurement, rejected within a multi-value measurement.
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        if self.depth > 0 {
+            return Err(de::Error::custom(invalid_grouped_value(&self.key)));
+        }
+
+        let () = self
+            .visitor
+            .visit_bool_measurement(self.key.as_ref(), value)
+            .map_err(de::Error::custom)?;
+
+        Ok(())
+    }
+
     /// Parses a single-value measurement. See `visit_f64`.
     fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
     where
@@ -272,6 +375,21 @@ fn invalid_timestamp(value: &str, err: impl std::fmt::Display) -> String {
     )
 }
 
+fn invalid_grouped_value(key: &str) -> String {
+    format!(
+        "Invalid value for {:?}: only numbers are accepted within a multi-value measurement",
+        key
+    )
+}
+
+fn invalid_empty_type() -> &'static str {
+    "Invalid measurement type: it must be a non-empty string"
+}
+
+fn invalid_empty_array() -> &'static str {
+    "Empty array of Thin Edge measurements: it must contain at least one measurement"
+}
+
 fn invalid_empty_root() -> &'static str {
     "Empty Thin Edge measurement: it must contain at least one measurement"
 }
@@ -344,6 +462,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_deserializes_the_measurement_type() -> anyhow::Result<()> {
+        use crate::builder::ThinEdgeJsonBuilder;
+        let input = r#"{"temperature": 21.5, "type": "c8y_Environment"}"#;
+
+        let mut builder = ThinEdgeJsonBuilder::default();
+        let () = parse_str(input, &mut builder)?;
+        let output = builder.done()?;
+
+        assert_eq!(output.measurement_type, Some("c8y_Environment".into()));
+        assert_eq!(output.values, vec![("temperature", 21.5).into()]);
+        Ok(())
+    }
+
+    #[test]
+    fn it_deserializes_text_and_bool_values() -> anyhow::Result<()> {
+        use crate::builder::ThinEdgeJsonBuilder;
+        let input = r#"{"status": "running", "door_open": true, "temperature": 21.5}"#;
+
+        let mut builder = ThinEdgeJsonBuilder::default();
+        let () = parse_str(input, &mut builder)?;
+        let output = builder.done()?;
+
+        assert_eq!(
+            output.values,
+            vec![
+                ("status", "running").into(),
+                ("door_open", true).into(),
+                ("temperature", 21.5).into(),
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     fn it_shows_input_excerpt_on_error() -> anyhow::Result<()> {
         use crate::builder::ThinEdgeJsonBuilder;
@@ -2,9 +2,15 @@ use crate::measurement::MeasurementVisitor;
 use json_writer::{JsonWriter, JsonWriterError};
 use time::{format_description, OffsetDateTime};
 
+const DEFAULT_CAPACITY: usize = 1024; // XXX: Choose a capacity based on expected JSON length.
+
+/// Serializes measurements into ThinEdge JSON.
+///
+/// A series of frames is serialized as an array of ThinEdge JSON objects.
 pub struct ThinEdgeJsonSerializer {
     json: JsonWriter,
     is_within_group: bool,
+    is_within_frames: bool,
     default_timestamp: Option<OffsetDateTime>,
     timestamp_present: bool,
 }
@@ -32,6 +38,9 @@ pub enum MeasurementStreamError {
     #[error("Unexpected time stamp within a group")]
     UnexpectedTimestamp,
 
+    #[error("Unexpected measurement type within a group")]
+    UnexpectedType,
+
     #[error("Unexpected end of data")]
     UnexpectedEndOfData,
 
@@ -40,6 +49,9 @@ pub enum MeasurementStreamError {
 
     #[error("Unexpected start of group")]
     UnexpectedStartOfGroup,
+
+    #[error("Unexpected start of frame within a group")]
+    UnexpectedStartOfFrame,
 }
 
 impl ThinEdgeJsonSerializer {
@@ -48,19 +60,19 @@ impl ThinEdgeJsonSerializer {
     }
 
     pub fn new_with_timestamp(default_timestamp: Option<OffsetDateTime>) -> Self {
-        let capa = 1024; // XXX: Choose a capacity based on expected JSON length.
-        let mut json = JsonWriter::with_capacity(capa);
+        let mut json = JsonWriter::with_capacity(DEFAULT_CAPACITY);
         json.write_open_obj();
 
         Self {
             json,
             is_within_group: false,
+            is_within_frames: false,
             default_timestamp,
             timestamp_present: false,
         }
     }
 
-    fn end(&mut self) -> Result<(), ThinEdgeJsonSerializationError> {
+    fn end_object(&mut self) -> Result<(), ThinEdgeJsonSerializationError> {
         if self.is_within_group {
             return Err(MeasurementStreamError::UnexpectedEndOfData.into());
         }
@@ -75,6 +87,14 @@ impl ThinEdgeJsonSerializer {
         Ok(())
     }
 
+    fn end(&mut self) -> Result<(), ThinEdgeJsonSerializationError> {
+        self.end_object()?;
+        if self.is_within_frames {
+            self.json.write_close_array();
+        }
+        Ok(())
+    }
+
     pub fn bytes(mut self) -> Result<Vec<u8>, ThinEdgeJsonSerializationError> {
         Ok(self.into_string()?.into_bytes())
     }
@@ -94,6 +114,25 @@ impl Default for ThinEdgeJsonSerializer {
 impl MeasurementVisitor for ThinEdgeJsonSerializer {
     type Error = ThinEdgeJsonSerializationError;
 
+    fn visit_frame(&mut self) -> Result<(), Self::Error> {
+        if self.is_within_group {
+            return Err(MeasurementStreamError::UnexpectedStartOfFrame.into());
+        }
+
+        if self.is_within_frames {
+            self.end_object()?;
+        } else {
+            // The object opened on creation is replaced by an array of objects
+            self.json = JsonWriter::with_capacity(DEFAULT_CAPACITY);
+            self.json.write_open_array();
+            self.is_within_frames = true;
+        }
+
+        self.json.write_open_obj();
+        self.timestamp_present = false;
+        Ok(())
+    }
+
     fn visit_timestamp(&mut self, timestamp: OffsetDateTime) -> Result<(), Self::Error> {
         if self.is_within_group {
             return Err(MeasurementStreamError::UnexpectedTimestamp.into());
@@ -109,12 +148,34 @@ impl MeasurementVisitor for ThinEdgeJsonSerializer {
         Ok(())
     }
 
+    fn visit_type(&mut self, measurement_type: &str) -> Result<(), Self::Error> {
+        if self.is_within_group {
+            return Err(MeasurementStreamError::UnexpectedType.into());
+        }
+
+        self.json.write_key("type")?;
+        self.json.write_str(measurement_type)?;
+        Ok(())
+    }
+
     fn visit_measurement(&mut self, name: &str, value: f64) -> Result<(), Self::Error> {
         self.json.write_key(name)?;
         self.json.write_f64(value)?;
         Ok(())
     }
 
+    fn visit_text_measurement(&mut self, name: &str, value: &str) -> Result<(), Self::Error> {
+        self.json.write_key(name)?;
+        self.json.write_str(value)?;
+        Ok(())
+    }
+
+    fn visit_bool_measurement(&mut self, name: &str, value: bool) -> Result<(), Self::Error> {
+        self.json.write_key(name)?;
+        self.json.write_bool(value)?;
+        Ok(())
+    }
+
     fn visit_start_group(&mut self, group: &str) -> Result<(), Self::Error> {
         if self.is_within_group {
             return Err(MeasurementStreamError::UnexpectedStartOfGroup.into());
@@ -140,6 +201,7 @@ impl MeasurementVisitor for ThinEdgeJsonSerializer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use time::macros::datetime;
 
     fn test_timestamp() -> OffsetDateTime {
         OffsetDateTime::now_utc()
@@ -203,6 +265,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn serialize_text_and_bool_values() -> anyhow::Result<()> {
+        let mut serializer = ThinEdgeJsonSerializer::new();
+        serializer.visit_text_measurement("status", "running")?;
+        serializer.visit_bool_measurement("door_open", true)?;
+        serializer.visit_measurement("temperature", 25.5)?;
+        let expected_output = r#"{"status":"running","door_open":true,"temperature":25.5}"#;
+        let output = serializer.into_string()?;
+        assert_eq!(expected_output, output);
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_frames_as_an_array() -> anyhow::Result<()> {
+        let timestamp = datetime!(2021-04-08 0:00:0 +05:00);
+        let mut serializer = ThinEdgeJsonSerializer::new_with_timestamp(Some(timestamp));
+        serializer.visit_frame()?;
+        serializer.visit_timestamp(datetime!(2021-04-07 23:59:58 +05:00))?;
+        serializer.visit_measurement("temperature", 25.5)?;
+        serializer.visit_frame()?;
+        serializer.visit_measurement("temperature", 25.7)?;
+        let expected_output = r#"[{"time":"2021-04-07T23:59:58+05:00","temperature":25.5},{"temperature":25.7,"time":"2021-04-08T00:00:00+05:00"}]"#;
+        let output = serializer.into_string()?;
+        assert_eq!(expected_output, output);
+        Ok(())
+    }
+
     #[test]
     fn serialize_empty_message() -> anyhow::Result<()> {
         let mut serializer = ThinEdgeJsonSerializer::new();
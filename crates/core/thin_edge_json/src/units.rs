@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use self::error::ThinEdgeJsonUnitsError;
+
+/// The units of the measurements, as published on `tedge/measurements/meta`.
+///
+/// The units are given using the same structure as the measurements:
+///
+/// ```json
+/// {
+///     "temperature": "°C",
+///     "current": {"L1": "A", "L2": "A", "L3": "A"},
+///     "voltage": "V"
+/// }
+/// ```
+///
+/// A unit given for a group, here `voltage`, applies to all the measurements of this group.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MeasurementUnits {
+    units: HashMap<String, Unit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum Unit {
+    Single(String),
+    Group(HashMap<String, String>),
+}
+
+pub mod error {
+    #[derive(thiserror::Error, Debug)]
+    pub enum ThinEdgeJsonUnitsError {
+        #[error("Invalid unit for {0:?}: expecting a string or an object of strings")]
+        InvalidUnit(String),
+
+        #[error(transparent)]
+        SerdeJsonError(#[from] serde_json::error::Error),
+    }
+}
+
+impl MeasurementUnits {
+    /// Parse the units from a `tedge/measurements/meta` payload.
+    ///
+    /// An empty payload, i.e. a cleared retained message, removes all the units.
+    pub fn try_from(mqtt_payload: &str) -> Result<Self, ThinEdgeJsonUnitsError> {
+        if mqtt_payload.is_empty() {
+            return Ok(MeasurementUnits::default());
+        }
+
+        let values: HashMap<String, serde_json::Value> = serde_json::from_str(mqtt_payload)?;
+        let mut units = HashMap::new();
+        for (name, value) in values {
+            let unit = serde_json::from_value(value)
+                .map_err(|_| ThinEdgeJsonUnitsError::InvalidUnit(name.clone()))?;
+            units.insert(name, unit);
+        }
+
+        Ok(MeasurementUnits { units })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// The unit of a measurement, possibly within a group.
+    pub fn unit(&self, group: Option<&str>, name: &str) -> Option<&str> {
+        match group {
+            Some(group) => match self.units.get(group)? {
+                Unit::Single(unit) => Some(unit),
+                Unit::Group(units) => units.get(name).map(String::as_str),
+            },
+            None => match self.units.get(name)? {
+                Unit::Single(unit) => Some(unit),
+                Unit::Group(_) => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn units_are_given_per_measurement_or_per_group() {
+        let units = MeasurementUnits::try_from(
+            r#"{
+                "temperature": "°C",
+                "current": {"L1": "A", "L2": "mA"},
+                "voltage": "V"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(units.unit(None, "temperature"), Some("°C"));
+        assert_eq!(units.unit(Some("current"), "L1"), Some("A"));
+        assert_eq!(units.unit(Some("current"), "L2"), Some("mA"));
+        assert_eq!(units.unit(Some("current"), "L3"), None);
+        assert_eq!(units.unit(Some("voltage"), "L1"), Some("V"));
+        assert_eq!(units.unit(None, "current"), None);
+        assert_eq!(units.unit(None, "pressure"), None);
+    }
+
+    #[test]
+    fn empty_payload_clears_the_units() {
+        let units = MeasurementUnits::try_from("").unwrap();
+
+        assert!(units.is_empty());
+    }
+
+    #[test]
+    fn units_must_be_strings() {
+        let result = MeasurementUnits::try_from(r#"{"temperature": 42}"#);
+        assert_matches!(result, Err(ThinEdgeJsonUnitsError::InvalidUnit(name)) if name == "temperature");
+
+        let result = MeasurementUnits::try_from(r#"{"current": {"L1": {"unit": "A"}}}"#);
+        assert_matches!(result, Err(ThinEdgeJsonUnitsError::InvalidUnit(name)) if name == "current");
+    }
+}
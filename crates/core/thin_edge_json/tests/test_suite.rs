@@ -35,6 +35,37 @@ fn it_rejects_invalid_thin_edge_json() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_rejects_invalid_arrays_of_thin_edge_json() -> anyhow::Result<()> {
+    let mut had_missing_test_fixtures = false;
+
+    for fixture in fixtures("tests/fixtures/invalid_arrays")?.iter() {
+        let input = std::fs::read_to_string(fixture.path())?;
+        println!("Fixture: {:?}", fixture.path());
+
+        // The builder only accepts single objects, hence use a serializer that accepts arrays
+        let mut serializer = thin_edge_json::serialize::ThinEdgeJsonSerializer::new();
+        let res = thin_edge_json::parser::parse_str(&input, &mut serializer);
+
+        assert!(res.is_err());
+        let err_msg = res.unwrap_err().to_string();
+
+        if let Ok(expected_error) =
+            std::fs::read_to_string(fixture.path().with_extension("expected_error"))
+        {
+            assert_eq!(expected_error, err_msg);
+        } else {
+            // we don't have a test fixture yet. Create one and abort.
+            std::fs::write(fixture.path().with_extension("expected_error"), err_msg)?;
+            had_missing_test_fixtures = true;
+        }
+    }
+
+    assert!(!had_missing_test_fixtures, "Test fixtures were missing.");
+
+    Ok(())
+}
+
 #[test]
 fn it_transforms_valid_thin_edge_json() -> anyhow::Result<()> {
     let mut had_missing_test_fixtures = false;
@@ -1,6 +1,7 @@
 use crate::config::FileEntry;
 use crate::error::ConfigManagementError;
 use crate::{error, PluginConfig, CONFIG_CHANGE_TOPIC};
+use agent_interface::{ConfigUpdateRequest, ConfigUpdateResponse, Jsonify, OperationStatus};
 use c8y_api::http_proxy::C8YHttpProxy;
 use c8y_smartrest::error::SmartRestSerializerError;
 use c8y_smartrest::smartrest_deserializer::SmartRestConfigDownloadRequest;
@@ -70,6 +71,61 @@ pub async fn handle_config_download_request(
     }
 }
 
+/// Update a configuration file on behalf of the tedge agent, as part of a device profile.
+///
+/// The outcome is reported to the agent and not to the cloud.
+pub async fn handle_config_update_request(
+    plugin_config: &PluginConfig,
+    request: ConfigUpdateRequest,
+    tmp_dir: PathBuf,
+    mqtt_client: &mut Connection,
+    http_client: &mut impl C8YHttpProxy,
+) -> Result<(), anyhow::Error> {
+    let response_topic = Topic::new_unchecked(ConfigUpdateResponse::topic_name());
+    let target_config_type = request.config_type.clone();
+
+    let download_result = match plugin_config.get_file_entry_from_type(&target_config_type) {
+        Ok(file_entry) => download_config_file(
+            request.url.url(),
+            PathBuf::from(&file_entry.path),
+            tmp_dir,
+            file_entry.file_permissions.clone(),
+            http_client,
+        )
+        .await
+        .map(|()| file_entry),
+        Err(err) => Err(err.into()),
+    };
+
+    match download_result {
+        Ok(file_entry) => {
+            info!("The configuration update for '{target_config_type}' is successful.");
+
+            let response =
+                ConfigUpdateResponse::new(&request).with_status(OperationStatus::Successful);
+            let () = mqtt_client
+                .published
+                .send(Message::new(&response_topic, response.to_bytes()?))
+                .await?;
+
+            let notification_message =
+                get_file_change_notification_message(&file_entry.path, &target_config_type);
+            let () = mqtt_client.published.send(notification_message).await?;
+            Ok(())
+        }
+        Err(err) => {
+            error!("The configuration update for '{target_config_type}' failed.",);
+
+            let response = ConfigUpdateResponse::new(&request).with_error(&err.to_string());
+            let () = mqtt_client
+                .published
+                .send(Message::new(&response_topic, response.to_bytes()?))
+                .await?;
+            Err(err)
+        }
+    }
+}
+
 async fn download_config_file(
     download_url: &str,
     file_path: PathBuf,
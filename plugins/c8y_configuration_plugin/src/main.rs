@@ -4,8 +4,9 @@ mod error;
 mod upload;
 
 use crate::config::PluginConfig;
-use crate::download::handle_config_download_request;
+use crate::download::{handle_config_download_request, handle_config_update_request};
 use crate::upload::handle_config_upload_request;
+use agent_interface::{ConfigUpdateRequest, Jsonify};
 use anyhow::Result;
 use c8y_api::http_proxy::{C8YHttpProxy, JwtAuthHttpProxy};
 use c8y_smartrest::smartrest_deserializer::{
@@ -30,6 +31,8 @@ pub const CONFIG_CHANGE_TOPIC: &str = "tedge/configuration_change";
 const AFTER_HELP_TEXT: &str = r#"On start, `c8y_configuration_plugin` notifies the cloud tenant of the managed configuration files, listed in the `CONFIG_FILE`, sending this list with a `119` on `c8y/s/us`.
 `c8y_configuration_plugin` subscribes then to `c8y/s/ds` listening for configuration operation requests (messages `524` and `526`).
 notifying the Cumulocity tenant of their progress (messages `501`, `502` and `503`).
+`c8y_configuration_plugin` also updates configuration files on behalf of the tedge agent applying a device profile,
+listening on `tedge/commands/req/config/update` and responding on `tedge/commands/res/config/update`.
 
 The thin-edge `CONFIG_DIR` is used to find where:
   * to store temporary files on download: `tedge config get tmp.path`,
@@ -67,6 +70,7 @@ async fn create_mqtt_client(mqtt_port: u16) -> Result<mqtt_channel::Connection,
         mqtt_channel::TopicFilter::new_unchecked(C8yTopic::SmartRestRequest.as_str());
     let _ = topic_filter
         .add_unchecked(format!("{CONFIG_CHANGE_TOPIC}/{DEFAULT_PLUGIN_CONFIG_TYPE}").as_str());
+    let _ = topic_filter.add_unchecked(ConfigUpdateRequest::topic_name());
 
     let mqtt_config = mqtt_channel::Config::default()
         .with_port(mqtt_port)
@@ -148,6 +152,20 @@ async fn run(
                 let msg = plugin_config.to_supported_config_types_message()?;
                 mqtt_client.published.send(msg).await?;
                 Ok(())
+            } else if message.topic.name == ConfigUpdateRequest::topic_name() {
+                match ConfigUpdateRequest::from_json(payload) {
+                    Ok(config_update_request) => {
+                        handle_config_update_request(
+                            &plugin_config,
+                            config_update_request,
+                            tmp_dir.clone(),
+                            &mut mqtt_client,
+                            http_client,
+                        )
+                        .await
+                    }
+                    Err(err) => Err(err.into()),
+                }
             } else {
                 match payload.split(',').next().unwrap_or_default() {
                     "524" => {
@@ -314,4 +332,49 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[serial_test::serial]
+    async fn test_config_update_request_from_the_agent() -> anyhow::Result<()> {
+        let broker = mqtt_tests::test_mqtt_broker();
+
+        let mut startup = broker.messages_published_on("c8y/s/us").await;
+        let mut messages = broker
+            .messages_published_on("tedge/commands/res/config/update")
+            .await;
+
+        let mut http_client = MockC8YHttpProxy::new();
+        let tmp_dir = tempfile::tempdir()?;
+
+        // Run the plugin's runtime logic in an async task
+        tokio::spawn(async move {
+            let _ = run(
+                broker.port,
+                &mut http_client,
+                tmp_dir.path().to_path_buf(),
+                PathBuf::from("/some/test/config").as_path(),
+            )
+            .await;
+        });
+
+        // Wait for the plugin to be ready, i.e. publishing the supported config types
+        mqtt_tests::assert_received_all_expected(&mut startup, TEST_TIMEOUT_MS, &["119,"]).await;
+
+        // Send a configuration update request for a type unknown to the plugin
+        let _ = broker
+            .publish(
+                "tedge/commands/req/config/update",
+                r#"{"id":"123-0","type":"unknown","url":"http://server/unknown"}"#,
+            )
+            .await?;
+
+        mqtt_tests::assert_received_all_expected(
+            &mut messages,
+            TEST_TIMEOUT_MS,
+            &[r#"{"id":"123-0","status":"failed","reason":"The requested config_type unknown is not defined in the plugin configuration file."}"#],
+        )
+        .await;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,21 @@
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteAccessError {
+    #[error("Failed to connect to {host}:{port}: {reason}")]
+    TargetConnectionFailed {
+        host: String,
+        port: u16,
+        reason: String,
+    },
+
+    #[error("Invalid remote access url {url:?}: {reason}")]
+    InvalidUrl { url: String, reason: String },
+
+    #[error(transparent)]
+    FromWebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+
+    #[error(transparent)]
+    FromIo(#[from] std::io::Error),
+
+    #[error("Failed to retrieve a JWT token: {reason}")]
+    JwtTokenFailed { reason: String },
+}
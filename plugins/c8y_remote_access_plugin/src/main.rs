@@ -0,0 +1,417 @@
+mod error;
+mod tunnel;
+
+use crate::error::RemoteAccessError;
+use anyhow::Result;
+use c8y_api::http_proxy::{C8YHttpProxy, JwtAuthHttpProxy};
+use c8y_smartrest::error::SmartRestSerializerError;
+use c8y_smartrest::smartrest_deserializer::{
+    SmartRestRemoteAccessConnect, SmartRestRequestGeneric,
+};
+use c8y_smartrest::smartrest_serializer::{
+    CumulocitySupportedOperations, SmartRest, SmartRestSerializer,
+    SmartRestSetOperationToExecuting, SmartRestSetOperationToFailed,
+    SmartRestSetOperationToSuccessful, TryIntoOperationStatusMessage,
+};
+use c8y_smartrest::topic::C8yTopic;
+use clap::Parser;
+use mqtt_channel::{Connection, SinkExt, StreamExt};
+use std::path::PathBuf;
+use tedge_config::{
+    C8yUrlSetting, ConfigRepository, ConfigSettingAccessor, ConfigSettingAccessorStringExt,
+    MqttPortSetting, TEdgeConfig, DEFAULT_TEDGE_CONFIG_PATH,
+};
+use tedge_utils::file::{create_directory_with_user_group, create_file_with_user_group};
+use tracing::{debug, error, info};
+
+const AFTER_HELP_TEXT: &str = r#"`c8y_remote_access_plugin` subscribes to `c8y/s/ds` listening for remote access connect operation requests (`530`),
+notifying the Cumulocity tenant of their progress (messages `501`, `502` and `503`).
+
+For each request, the plugin connects the requested local host and port
+to the Cumulocity remote access endpoint over a websocket authenticated with a JWT token,
+and pipes the traffic between the two until either side closes the connection.
+
+The thin-edge `CONFIG_DIR` is used to find:
+  * the Cumulocity tenant url: `tedge config get c8y.url`,
+  * the port to connect the MQTT bus: `tedge config get mqtt.port`."#;
+
+#[derive(Debug, clap::Parser)]
+#[clap(
+name = clap::crate_name!(),
+version = clap::crate_version!(),
+about = clap::crate_description!(),
+after_help = AFTER_HELP_TEXT
+)]
+pub struct RemoteAccessPluginOpt {
+    /// Turn-on the debug log level.
+    ///
+    /// If off only reports ERROR, WARN, and INFO
+    /// If on also reports DEBUG and TRACE
+    #[clap(long)]
+    pub debug: bool,
+
+    /// Create supported operation files
+    #[clap(short, long)]
+    pub init: bool,
+
+    #[clap(long = "config-dir", default_value = DEFAULT_TEDGE_CONFIG_PATH)]
+    pub config_dir: PathBuf,
+}
+
+async fn create_mqtt_client(mqtt_port: u16) -> Result<mqtt_channel::Connection, anyhow::Error> {
+    let mqtt_config = mqtt_channel::Config::default()
+        .with_port(mqtt_port)
+        .with_subscriptions(mqtt_channel::TopicFilter::new_unchecked(
+            C8yTopic::SmartRestRequest.as_str(),
+        ));
+
+    let mqtt_client = mqtt_channel::Connection::new(&mqtt_config).await?;
+    Ok(mqtt_client)
+}
+
+pub async fn create_http_client(
+    tedge_config: &TEdgeConfig,
+) -> Result<JwtAuthHttpProxy, anyhow::Error> {
+    let mut http_proxy = JwtAuthHttpProxy::try_new(tedge_config).await?;
+    let () = http_proxy.init().await?;
+    Ok(http_proxy)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let remote_access_plugin_opt = RemoteAccessPluginOpt::parse();
+    tedge_utils::logging::initialise_tracing_subscriber(remote_access_plugin_opt.debug);
+
+    if remote_access_plugin_opt.init {
+        init(remote_access_plugin_opt.config_dir)?;
+        return Ok(());
+    }
+
+    // Load tedge config from the provided location
+    let tedge_config_location =
+        tedge_config::TEdgeConfigLocation::from_custom_root(remote_access_plugin_opt.config_dir);
+    let config_repository = tedge_config::TEdgeConfigRepository::new(tedge_config_location);
+    let tedge_config = config_repository.load()?;
+
+    let mqtt_port = tedge_config.query(MqttPortSetting)?.into();
+    let mut http_client = create_http_client(&tedge_config).await?;
+    let endpoint = format!("wss://{}", tedge_config.query_string(C8yUrlSetting)?);
+
+    run(mqtt_port, &mut http_client, &endpoint).await
+}
+
+async fn run(
+    mqtt_port: u16,
+    http_client: &mut impl C8YHttpProxy,
+    endpoint: &str,
+) -> Result<(), anyhow::Error> {
+    let mut mqtt_client = create_mqtt_client(mqtt_port).await?;
+
+    // Mqtt message loop
+    while let Some(message) = mqtt_client.received.next().await {
+        debug!("Received {:?}", message);
+        if let Ok(payload) = message.payload_str() {
+            let result = match payload.split(',').next().unwrap_or_default() {
+                "530" => match SmartRestRemoteAccessConnect::from_smartrest(payload) {
+                    Ok(request) => {
+                        handle_remote_access_connect(
+                            request,
+                            endpoint,
+                            &mut mqtt_client,
+                            http_client,
+                        )
+                        .await
+                    }
+                    Err(err) => Err(err.into()),
+                },
+                _ => {
+                    // Ignore operation messages not meant for this plugin
+                    Ok(())
+                }
+            };
+
+            if let Err(err) = result {
+                error!("Handling of operation: '{payload}' failed with {err}");
+            }
+        }
+    }
+
+    mqtt_client.close().await;
+
+    Ok(())
+}
+
+async fn handle_remote_access_connect(
+    request: SmartRestRemoteAccessConnect,
+    endpoint: &str,
+    mqtt_client: &mut Connection,
+    http_client: &mut impl C8YHttpProxy,
+) -> Result<(), anyhow::Error> {
+    let executing_message = RemoteAccessConnectStatusMessage::executing()?;
+    let () = mqtt_client.published.send(executing_message).await?;
+
+    match open_tunnel(&request, endpoint, http_client).await {
+        Ok((websocket, target)) => {
+            info!(
+                "Remote access session opened to {}:{}",
+                request.host, request.port
+            );
+
+            // The session lives on its own, the plugin being ready for new requests
+            tokio::spawn(async move {
+                match tunnel::pipe(websocket, target).await {
+                    Ok(()) => info!("Remote access session closed"),
+                    Err(err) => error!("Remote access session failed with {err}"),
+                }
+            });
+
+            let successful_message = RemoteAccessConnectStatusMessage::successful(None)?;
+            let () = mqtt_client.published.send(successful_message).await?;
+            Ok(())
+        }
+        Err(err) => {
+            let failed_message = RemoteAccessConnectStatusMessage::failed(err.to_string())?;
+            let () = mqtt_client.published.send(failed_message).await?;
+            Err(err.into())
+        }
+    }
+}
+
+async fn open_tunnel(
+    request: &SmartRestRemoteAccessConnect,
+    endpoint: &str,
+    http_client: &mut impl C8YHttpProxy,
+) -> Result<(tunnel::RemoteAccessWebSocket, tokio::net::TcpStream), RemoteAccessError> {
+    // Connect the target first, not to open a websocket for nothing
+    let target = tunnel::connect_target(&request.host, request.port).await?;
+
+    let token =
+        http_client
+            .get_jwt_token()
+            .await
+            .map_err(|err| RemoteAccessError::JwtTokenFailed {
+                reason: err.to_string(),
+            })?;
+    let url = tunnel::remote_access_url(endpoint, &request.key);
+    let websocket = tunnel::connect_websocket(&url, &token.token()).await?;
+
+    Ok((websocket, target))
+}
+
+struct RemoteAccessConnectStatusMessage {}
+
+impl TryIntoOperationStatusMessage for RemoteAccessConnectStatusMessage {
+    fn status_executing() -> Result<SmartRest, SmartRestSerializerError> {
+        SmartRestSetOperationToExecuting::new(CumulocitySupportedOperations::C8yRemoteAccessConnect)
+            .to_smartrest()
+    }
+
+    fn status_successful(
+        _parameter: Option<String>,
+    ) -> Result<SmartRest, SmartRestSerializerError> {
+        SmartRestSetOperationToSuccessful::new(
+            CumulocitySupportedOperations::C8yRemoteAccessConnect,
+        )
+        .to_smartrest()
+    }
+
+    fn status_failed(failure_reason: String) -> Result<SmartRest, SmartRestSerializerError> {
+        SmartRestSetOperationToFailed::new(
+            CumulocitySupportedOperations::C8yRemoteAccessConnect,
+            failure_reason,
+        )
+        .to_smartrest()
+    }
+}
+
+fn init(cfg_dir: PathBuf) -> Result<(), anyhow::Error> {
+    info!("Creating supported operation files");
+    let config_dir = cfg_dir.as_path().display().to_string();
+    create_directory_with_user_group(
+        &format!("{config_dir}/operations/c8y"),
+        "tedge",
+        "tedge",
+        0o775,
+    )?;
+    create_file_with_user_group(
+        &format!("{config_dir}/operations/c8y/c8y_RemoteAccessConnect"),
+        "tedge",
+        "tedge",
+        0o644,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8y_api::http_proxy::MockC8YHttpProxy;
+    use c8y_smartrest::smartrest_deserializer::SmartRestJwtResponse;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+    use tokio_tungstenite::tungstenite::Message;
+
+    const TEST_TIMEOUT_MS: Duration = Duration::from_millis(5000);
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[serial_test::serial]
+    async fn remote_access_traffic_is_piped_to_the_target() -> anyhow::Result<()> {
+        let broker = mqtt_tests::test_mqtt_broker();
+        let mut messages = broker.messages_published_on("c8y/s/us").await;
+
+        // A local echo server stands for the ssh server of the device
+        let target = TcpListener::bind("127.0.0.1:0").await?;
+        let target_port = target.local_addr()?.port();
+        tokio::spawn(async move {
+            let (mut stream, _) = target.accept().await.unwrap();
+            let mut buffer = [0; 64];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(&buffer[..n]).await.unwrap();
+        });
+
+        // A local websocket server stands for the cloud remote access endpoint
+        let cloud = TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("ws://{}", cloud.local_addr()?);
+        let handshake = Arc::new(Mutex::new(None));
+        let handshake_record = handshake.clone();
+        let (echo_sender, echo_receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = cloud.accept().await.unwrap();
+            let mut websocket =
+                tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response| {
+                    let authorization = request.headers().get("Authorization").cloned();
+                    *handshake_record.lock().unwrap() =
+                        Some((request.uri().path().to_string(), authorization));
+                    Ok::<Response, _>(response)
+                })
+                .await
+                .unwrap();
+
+            websocket
+                .send(Message::Binary(b"hello".to_vec()))
+                .await
+                .unwrap();
+            let echo = websocket.next().await.unwrap().unwrap();
+            let _ = echo_sender.send(echo.into_data());
+        });
+
+        let mut http_client = MockC8YHttpProxy::new();
+        http_client
+            .expect_get_jwt_token()
+            .returning(|| Ok(SmartRestJwtResponse::try_new("71,fake-token").unwrap()));
+
+        // Run the plugin's runtime logic in an async task
+        tokio::spawn(async move {
+            let _ = run(broker.port, &mut http_client, &endpoint).await;
+        });
+
+        // Give the plugin the time to subscribe
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let _ = broker
+            .publish(
+                "c8y/s/ds",
+                &format!("530,tedge-device,127.0.0.1,{target_port},connection-key"),
+            )
+            .await?;
+
+        mqtt_tests::assert_received_all_expected(
+            &mut messages,
+            TEST_TIMEOUT_MS,
+            &["501,c8y_RemoteAccessConnect", "503,c8y_RemoteAccessConnect"],
+        )
+        .await;
+
+        let echo = tokio::time::timeout(TEST_TIMEOUT_MS, echo_receiver).await??;
+        assert_eq!(echo, b"hello".to_vec());
+
+        let (path, authorization) = handshake.lock().unwrap().take().unwrap();
+        assert_eq!(path, "/service/remoteaccess/device/connection-key");
+        assert_eq!(authorization.unwrap(), "Bearer fake-token");
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[serial_test::serial]
+    async fn remote_access_fails_when_the_target_is_unreachable() -> anyhow::Result<()> {
+        let broker = mqtt_tests::test_mqtt_broker();
+        let mut messages = broker.messages_published_on("c8y/s/us").await;
+
+        // Get a port on which nothing listens
+        let target_port = TcpListener::bind("127.0.0.1:0").await?.local_addr()?.port();
+
+        let mut http_client = MockC8YHttpProxy::new();
+        tokio::spawn(async move {
+            let _ = run(broker.port, &mut http_client, "ws://127.0.0.1:1").await;
+        });
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let _ = broker
+            .publish(
+                "c8y/s/ds",
+                &format!("530,tedge-device,127.0.0.1,{target_port},connection-key"),
+            )
+            .await?;
+
+        mqtt_tests::assert_received_all_expected(
+            &mut messages,
+            TEST_TIMEOUT_MS,
+            &[
+                "501,c8y_RemoteAccessConnect".to_string(),
+                format!(
+                    "502,c8y_RemoteAccessConnect,\"Failed to connect to 127.0.0.1:{target_port}"
+                ),
+            ],
+        )
+        .await;
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[serial_test::serial]
+    async fn malformed_requests_do_not_stop_the_plugin() -> anyhow::Result<()> {
+        let broker = mqtt_tests::test_mqtt_broker();
+        let mut messages = broker.messages_published_on("c8y/s/us").await;
+
+        // Get a port on which nothing listens
+        let target_port = TcpListener::bind("127.0.0.1:0").await?.local_addr()?.port();
+
+        let mut http_client = MockC8YHttpProxy::new();
+        tokio::spawn(async move {
+            let _ = run(broker.port, &mut http_client, "ws://127.0.0.1:1").await;
+        });
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        broker
+            .publish(
+                "c8y/s/ds",
+                "530,tedge-device,127.0.0.1,not-a-port,connection-key",
+            )
+            .await?;
+        broker
+            .publish(
+                "c8y/s/ds",
+                &format!("530,tedge-device,127.0.0.1,{target_port},connection-key"),
+            )
+            .await?;
+
+        // The next request is still served
+        mqtt_tests::assert_received_all_expected(
+            &mut messages,
+            TEST_TIMEOUT_MS,
+            &[
+                "501,c8y_RemoteAccessConnect".to_string(),
+                format!(
+                    "502,c8y_RemoteAccessConnect,\"Failed to connect to 127.0.0.1:{target_port}"
+                ),
+            ],
+        )
+        .await;
+
+        Ok(())
+    }
+}
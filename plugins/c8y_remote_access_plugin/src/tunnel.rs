@@ -0,0 +1,136 @@
+use crate::error::RemoteAccessError;
+use futures::{SinkExt, StreamExt};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+const BUFFER_SIZE: usize = 4096;
+
+/// The stream carrying a websocket, encrypted or not.
+pub trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> AsyncStream for S {}
+
+pub type RemoteAccessWebSocket = WebSocketStream<Box<dyn AsyncStream>>;
+
+/// The url of the Cumulocity remote access endpoint for a connection key.
+///
+/// The `endpoint` is `wss://<c8y.url>` or, when testing, the url of a local websocket server.
+pub fn remote_access_url(endpoint: &str, key: &str) -> String {
+    format!("{endpoint}/service/remoteaccess/device/{key}")
+}
+
+/// Connect to the local host and port the remote access traffic is for.
+pub async fn connect_target(host: &str, port: u16) -> Result<TcpStream, RemoteAccessError> {
+    TcpStream::connect((host, port)).await.map_err(|err| {
+        RemoteAccessError::TargetConnectionFailed {
+            host: host.to_string(),
+            port,
+            reason: err.to_string(),
+        }
+    })
+}
+
+/// Open a websocket to the remote access endpoint, authenticated with a JWT.
+///
+/// Both `wss://` and `ws://` urls are supported.
+pub async fn connect_websocket(
+    url: &str,
+    token: &str,
+) -> Result<RemoteAccessWebSocket, RemoteAccessError> {
+    let invalid_url = |reason: &str| RemoteAccessError::InvalidUrl {
+        url: url.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut request = url.into_client_request()?;
+    let bearer = HeaderValue::from_str(&format!("Bearer {token}"))
+        .map_err(|_| invalid_url("invalid JWT token"))?;
+    request.headers_mut().insert(AUTHORIZATION, bearer);
+
+    let uri = request.uri().clone();
+    let host = uri.host().ok_or_else(|| invalid_url("no host"))?;
+    let (secure, default_port) = match uri.scheme_str() {
+        Some("wss") => (true, 443),
+        Some("ws") => (false, 80),
+        _ => return Err(invalid_url("expecting a ws:// or wss:// url")),
+    };
+    let port = uri.port_u16().unwrap_or(default_port);
+
+    let tcp_stream = TcpStream::connect((host, port)).await?;
+    let stream: Box<dyn AsyncStream> = if secure {
+        let server_name =
+            ServerName::try_from(host).map_err(|_| invalid_url("invalid host name"))?;
+        let connector = TlsConnector::from(Arc::new(tls_config()));
+        Box::new(connector.connect(server_name, tcp_stream).await?)
+    } else {
+        Box::new(tcp_stream)
+    };
+
+    let (websocket, _response) = tokio_tungstenite::client_async(request, stream).await?;
+    Ok(websocket)
+}
+
+fn tls_config() -> ClientConfig {
+    let mut root_store = RootCertStore::empty();
+    root_store.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+
+    ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store)
+        .with_no_client_auth()
+}
+
+/// Pipe the traffic between the websocket and the target, until either side closes the connection.
+pub async fn pipe(
+    websocket: RemoteAccessWebSocket,
+    target: TcpStream,
+) -> Result<(), RemoteAccessError> {
+    let (mut ws_sink, mut ws_stream) = websocket.split();
+    let (mut target_reader, mut target_writer) = target.into_split();
+
+    let upstream = async {
+        let mut buffer = [0; BUFFER_SIZE];
+        loop {
+            let n = target_reader.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            let () = ws_sink.send(Message::Binary(buffer[..n].to_vec())).await?;
+        }
+        let () = ws_sink.close().await?;
+        Ok::<(), RemoteAccessError>(())
+    };
+
+    let downstream = async {
+        while let Some(message) = ws_stream.next().await {
+            match message? {
+                Message::Binary(data) => target_writer.write_all(&data).await?,
+                Message::Text(text) => target_writer.write_all(text.as_bytes()).await?,
+                Message::Close(_) => break,
+                Message::Ping(_) | Message::Pong(_) => {}
+            }
+        }
+        let () = target_writer.shutdown().await?;
+        Ok::<(), RemoteAccessError>(())
+    };
+
+    tokio::select! {
+        result = upstream => result,
+        result = downstream => result,
+    }
+}